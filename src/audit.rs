@@ -0,0 +1,173 @@
+//! Append-only log of credential use: every read, store and delete of a stored
+//! password or passphrase, and every SSH attempt made with one. Never holds the
+//! secrets themselves, only when, which account and what came of it.
+//!
+//! One JSON object per line in `audit.log` next to the state file, owner-only. Lines
+//! are only ever appended; nothing in the tool rewrites or trims the file.
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Read,
+    Stored,
+    Deleted,
+    SshAttempt,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Action::Read => "read",
+            Action::Stored => "stored",
+            Action::Deleted => "deleted",
+            Action::SshAttempt => "ssh attempt",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Event {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub action: Action,
+    /// Keyring account: a server name, `_default`, `sudo:<server>` or
+    /// `passphrase:<key path>`. The server name for SSH attempts.
+    pub account: String,
+    pub outcome: String,
+}
+
+/// `audit.log` in the directory of the state file.
+pub fn audit_file_path() -> PathBuf {
+    crate::state::state_file_path().with_file_name("audit.log")
+}
+
+/// Appends an event. A log that can't be written is warned about, never fatal:
+/// losing an audit line shouldn't stop a fetch.
+pub fn record(action: Action, account: &str, outcome: &str) {
+    // Unit tests call the credential functions with fake backends; keep their events
+    // out of the real log.
+    if cfg!(test) {
+        return;
+    }
+    let event = Event {
+        at: chrono::Utc::now(),
+        action,
+        account: account.to_string(),
+        outcome: crate::redact::scrub(outcome).into_owned(),
+    };
+    let path = audit_file_path();
+    if let Err(e) = append_to(&path, &event) {
+        log::warn!("Could not write to the audit log {}: {:#}", path.display(), e);
+    }
+}
+
+fn append_to(path: &Path, event: &Event) -> Result<(), anyhow::Error> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    // One write per line, so events from parallel fetches don't interleave.
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Every event in the log, oldest first; empty when nothing was recorded yet.
+/// Lines that don't parse are skipped.
+pub fn read() -> Result<Vec<Event>, anyhow::Error> {
+    read_from(&audit_file_path())
+}
+
+fn read_from(path: &Path) -> Result<Vec<Event>, anyhow::Error> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The outcome to record for a credential lookup.
+pub fn lookup_outcome(result: &crate::credentials::CredentialResult) -> String {
+    match result {
+        crate::credentials::CredentialResult::Found(_) => "found".to_string(),
+        crate::credentials::CredentialResult::NotFound => "not found".to_string(),
+        crate::credentials::CredentialResult::Unavailable(e) => format!("unavailable: {}", e),
+    }
+}
+
+/// The outcome to record for a store or delete.
+pub fn result_outcome<T>(result: &Result<T, String>) -> String {
+    match result {
+        Ok(_) => "ok".to_string(),
+        Err(e) => format!("failed: {}", e),
+    }
+}
+
+/// One line of `audit` output.
+pub fn format_event(event: &Event) -> String {
+    format!(
+        "{}  {:<11}  {:<30}  {}",
+        event.at.format("%Y-%m-%d %H:%M:%S UTC"),
+        event.action.to_string(),
+        event.account,
+        event.outcome
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_read_back() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state").join("audit.log");
+        assert!(read_from(&path).unwrap().is_empty());
+
+        let event = |action, account: &str| Event {
+            at: chrono::Utc::now(),
+            action,
+            account: account.to_string(),
+            outcome: "ok".to_string(),
+        };
+        append_to(&path, &event(Action::Stored, "prod")).unwrap();
+        append_to(&path, &event(Action::SshAttempt, "prod")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+        append_to(&path, &event(Action::Deleted, "sudo:prod")).unwrap();
+
+        let events = read_from(&path).unwrap();
+        let actions: Vec<Action> = events.iter().map(|e| e.action).collect();
+        assert_eq!(actions, [Action::Stored, Action::SshAttempt, Action::Deleted]);
+        assert_eq!(events[2].account, "sudo:prod");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
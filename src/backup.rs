@@ -0,0 +1,120 @@
+use anyhow::Context as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of backup file names; the rest is a UTC timestamp, so names sort by age.
+const BACKUP_PREFIX: &str = "config.";
+
+/// Returns `~/.kube/config-backups/`, where copies of ~/.kube/config are kept before merges.
+pub fn backup_dir() -> Result<PathBuf, anyhow::Error> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".kube")
+        .join("config-backups"))
+}
+
+/// Copies `path` into `dir` under a timestamped name, then deletes all but the newest
+/// `retention` backups. Does nothing when `retention` is 0 or `path` doesn't exist yet.
+pub fn backup_file(path: &Path, dir: &Path, retention: usize) -> Result<Option<PathBuf>, anyhow::Error> {
+    if retention == 0 || !path.exists() {
+        return Ok(None);
+    }
+    fs::create_dir_all(dir).with_context(|| format!("creating backup directory {:?}", dir))?;
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let backup = dir.join(format!("{}{}", BACKUP_PREFIX, stamp));
+    fs::copy(path, &backup).with_context(|| format!("backing up {:?} to {:?}", path, backup))?;
+    log::debug!("Backed up {:?} to {:?}", path, backup);
+
+    for old in list_backups(dir)?.into_iter().skip(retention) {
+        if let Err(e) = fs::remove_file(&old) {
+            log::warn!("Could not remove old backup {:?}: {}", old, e);
+        }
+    }
+    Ok(Some(backup))
+}
+
+/// Backups in `dir`, newest first. A missing directory has no backups.
+pub fn list_backups(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("reading backup directory {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(BACKUP_PREFIX))
+        })
+        .collect();
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// Replaces `target` with `backup`. The current `target` is backed up first, so a
+/// restore can itself be undone.
+pub fn restore(backup: &Path, target: &Path, dir: &Path, retention: usize) -> Result<(), anyhow::Error> {
+    if !backup.is_file() {
+        anyhow::bail!("Backup {:?} does not exist", backup);
+    }
+    backup_file(target, dir, retention.max(1))?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating directory {:?}", parent))?;
+    }
+    let content = fs::read_to_string(backup).with_context(|| format!("reading {:?}", backup))?;
+    crate::kube::write_kubeconfig(target, &content)
+        .with_context(|| format!("restoring {:?} to {:?}", backup, target))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_file_prunes_to_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("config");
+        let backups = dir.path().join("backups");
+        for i in 0..4 {
+            fs::write(&source, format!("version {}", i)).unwrap();
+            backup_file(&source, &backups, 2).unwrap().expect("backup written");
+            // Timestamps have millisecond resolution.
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let kept = list_backups(&backups).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(fs::read_to_string(&kept[0]).unwrap(), "version 3");
+        assert_eq!(fs::read_to_string(&kept[1]).unwrap(), "version 2");
+    }
+
+    #[test]
+    fn test_backup_file_disabled_or_missing_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("config");
+        let backups = dir.path().join("backups");
+        assert!(backup_file(&source, &backups, 5).unwrap().is_none());
+        fs::write(&source, "x").unwrap();
+        assert!(backup_file(&source, &backups, 0).unwrap().is_none());
+        assert!(list_backups(&backups).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_keeps_current_as_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("config");
+        let backups = dir.path().join("backups");
+        fs::write(&target, "good").unwrap();
+        let good = backup_file(&target, &backups, 5).unwrap().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        fs::write(&target, "bad merge").unwrap();
+
+        restore(&good, &target, &backups, 5).unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "good");
+        let newest = &list_backups(&backups).unwrap()[0];
+        assert_eq!(fs::read_to_string(newest).unwrap(), "bad merge");
+    }
+}
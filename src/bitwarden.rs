@@ -5,7 +5,8 @@ use std::process::Command;
 
 /// Parsed from the `[bitwarden]` section in config.toml.
 /// When absent, the tool behaves exactly as before.
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct BitwardenConfig {
     pub enabled: bool,
     pub server_url: Option<String>,
@@ -78,6 +79,7 @@ impl BwItem {
                 file_name: self.field("file_name").map(|s| s.to_string()),
                 context_name: self.field("context_name").map(|s| s.to_string()),
                 identity_file: self.field("identity_file").map(|s| s.to_string()),
+                ..Default::default()
             },
             password: self.login.as_ref().and_then(|l| l.password.clone()),
             vault_item_id: self.id.clone(),
@@ -104,7 +106,7 @@ pub fn merge_servers(
 ) -> (
     Vec<crate::config::Server>,
     HashMap<String, ServerSource>,
-    HashMap<String, String>,
+    HashMap<String, zeroize::Zeroizing<String>>,
 ) {
     let mut merged = Vec::new();
     let mut sources = HashMap::new();
@@ -126,7 +128,8 @@ pub fn merge_servers(
         }
         sources.insert(vs.server.name.clone(), ServerSource::Vault);
         if let Some(pw) = vs.password {
-            passwords.insert(vs.server.name.clone(), pw);
+            crate::redact::register(&pw);
+            passwords.insert(vs.server.name.clone(), zeroize::Zeroizing::new(pw));
         }
         merged.push(vs.server);
     }
@@ -153,8 +156,12 @@ pub struct BwCli {
 
 impl BwCli {
     pub fn new() -> Self {
+        let session = std::env::var("BW_SESSION").ok().filter(|s| !s.is_empty());
+        if let Some(session) = &session {
+            crate::redact::register(session);
+        }
         BwCli {
-            session: std::env::var("BW_SESSION").ok().filter(|s| !s.is_empty()),
+            session,
             server_url: None,
         }
     }
@@ -188,6 +195,7 @@ impl BwCli {
 
     /// Unlock vault with master password (interactive/TUI path).
     pub fn unlock(&mut self, master_password: &str) -> Result<(), String> {
+        crate::redact::register(master_password);
         let output = Command::new("bw")
             .args(["unlock", "--raw", "--passwordenv", "BW_PASSWORD"])
             .env("BW_PASSWORD", master_password)
@@ -200,10 +208,15 @@ impl BwCli {
             return Err(format!("bw unlock failed: {}", stderr));
         }
 
-        self.session = Some(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        self.set_session(String::from_utf8_lossy(&output.stdout).trim().to_string());
         Ok(())
     }
 
+    fn set_session(&mut self, session: String) {
+        crate::redact::register(&session);
+        self.session = Some(session);
+    }
+
     /// Headless login: API key env vars + password file (cron path).
     /// Expects BW_CLIENTID and BW_CLIENTSECRET in the environment.
     pub fn login_headless(&mut self, password_file: &std::path::Path) -> Result<(), String> {
@@ -235,7 +248,7 @@ impl BwCli {
             return Err(format!("bw unlock --passwordfile failed: {}", stderr));
         }
 
-        self.session = Some(String::from_utf8_lossy(&unlock_output.stdout).trim().to_string());
+        self.set_session(String::from_utf8_lossy(&unlock_output.stdout).trim().to_string());
         Ok(())
     }
 
@@ -492,6 +505,7 @@ mod tests {
             file_name: None,
             context_name: None,
             identity_file: None,
+            ..Default::default()
         }];
         let vault = vec![VaultServer {
             server: crate::config::Server {
@@ -503,6 +517,7 @@ mod tests {
                 file_name: None,
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
             password: Some("vault-pw".to_string()),
             vault_item_id: "uuid".to_string(),
@@ -525,6 +540,7 @@ mod tests {
             file_name: None,
             context_name: None,
             identity_file: None,
+            ..Default::default()
         }];
         let vault = vec![VaultServer {
             server: crate::config::Server {
@@ -536,6 +552,7 @@ mod tests {
                 file_name: None,
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
             password: Some("pw123".to_string()),
             vault_item_id: "uuid".to_string(),
@@ -545,7 +562,7 @@ mod tests {
         assert_eq!(merged[0].name, "local-only");
         assert_eq!(merged[1].name, "vault-only");
         assert_eq!(sources[&"vault-only".to_string()], ServerSource::Vault);
-        assert_eq!(passwords["vault-only"], "pw123");
+        assert_eq!(*passwords["vault-only"], "pw123");
     }
 
     #[test]
@@ -559,6 +576,7 @@ mod tests {
             file_name: None,
             context_name: None,
             identity_file: None,
+            ..Default::default()
         }];
         let (merged, sources, passwords) = merge_servers(&local, vec![]);
         assert_eq!(merged.len(), 1);
@@ -0,0 +1,201 @@
+//! `credential export` / `credential import`: every stored password and passphrase in
+//! one age-encrypted file, to set up another machine without typing them again.
+//!
+//! Encryption is left to the `age` CLI (https://age-encryption.org): with no recipients
+//! it asks for a passphrase on the terminal, otherwise it encrypts to the given public
+//! keys and `import --identity` decrypts with the matching private key. The plaintext
+//! is JSON and only ever passes through age's stdin and stdout, never a file.
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    /// Keyring account → password.
+    credentials: BTreeMap<String, String>,
+}
+
+impl Drop for Bundle {
+    fn drop(&mut self) {
+        for password in self.credentials.values_mut() {
+            zeroize::Zeroize::zeroize(password);
+        }
+    }
+}
+
+/// Accounts that may hold a credential: everything in the file store, plus the names
+/// the config implies, since the OS keyring can't be listed. Sorted, no duplicates.
+pub fn candidate_accounts(config: &crate::config::Config) -> Vec<String> {
+    use crate::credentials::{DEFAULT_ACCOUNT, passphrase_account, sudo_account};
+
+    let mut accounts = crate::credentials::file_store_keys();
+    accounts.push(DEFAULT_ACCOUNT.to_string());
+    for server in &config.servers {
+        accounts.push(server.name.clone());
+        accounts.push(sudo_account(&server.name));
+        if let Some(identity_file) = server.identity_file(config) {
+            accounts.push(passphrase_account(identity_file));
+        }
+    }
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
+/// The stored credential of each account that has one.
+fn collect(accounts: &[String], lookup: impl Fn(&str) -> crate::credentials::CredentialResult) -> Bundle {
+    let credentials = accounts
+        .iter()
+        .filter_map(|account| match lookup(account) {
+            crate::credentials::CredentialResult::Found(pw) => Some((account.clone(), pw.to_string())),
+            _ => None,
+        })
+        .collect();
+    Bundle {
+        version: BUNDLE_VERSION,
+        credentials,
+    }
+}
+
+fn parse(json: &[u8]) -> Result<Bundle, anyhow::Error> {
+    let bundle: Bundle = serde_json::from_slice(json).context("the decrypted bundle is not a credential bundle")?;
+    if bundle.version != BUNDLE_VERSION {
+        anyhow::bail!(
+            "unsupported bundle version {} (this build reads version {})",
+            bundle.version,
+            BUNDLE_VERSION
+        );
+    }
+    Ok(bundle)
+}
+
+/// Encrypts every credential found for `config` to `out`. Returns the accounts exported.
+pub fn export(config: &crate::config::Config, out: &Path, recipients: &[String]) -> Result<Vec<String>, anyhow::Error> {
+    let bundle = collect(&candidate_accounts(config), crate::credentials::get_account);
+    if bundle.credentials.is_empty() {
+        anyhow::bail!("No stored credentials to export");
+    }
+    let json = Zeroizing::new(serde_json::to_vec(&bundle)?);
+
+    let mut cmd = Command::new("age");
+    cmd.arg("--encrypt");
+    if recipients.is_empty() {
+        cmd.arg("--passphrase");
+    }
+    for recipient in recipients {
+        cmd.arg("--recipient").arg(recipient);
+    }
+    cmd.arg("--output").arg(out);
+    run_age(cmd, &json)?;
+    Ok(bundle.credentials.keys().cloned().collect())
+}
+
+/// Decrypts `path` and stores each credential in it, replacing what is stored under the
+/// same account. Returns the accounts imported; with `dry_run` nothing is stored.
+pub fn import(path: &Path, identity: Option<&Path>, dry_run: bool) -> Result<Vec<String>, anyhow::Error> {
+    let mut cmd = Command::new("age");
+    cmd.arg("--decrypt");
+    if let Some(identity) = identity {
+        cmd.arg("--identity").arg(identity);
+    }
+    cmd.arg(path);
+    let json = Zeroizing::new(run_age(cmd, &[])?);
+    let bundle = parse(&json)?;
+    for (account, password) in &bundle.credentials {
+        crate::redact::register(password);
+        if !dry_run {
+            store(account, password).with_context(|| format!("storing the credential for '{}'", account))?;
+        }
+    }
+    Ok(bundle.credentials.keys().cloned().collect())
+}
+
+/// Stores in the configured backend, or in the file store when `auto` finds no keyring.
+fn store(account: &str, password: &str) -> Result<(), anyhow::Error> {
+    match crate::credentials::set_credential(account, password) {
+        Err(e)
+            if crate::credentials::keyring_error_is_unavailable(&e) && crate::credentials::file_fallback_allowed() =>
+        {
+            crate::credentials::set_credential_file(account, password).map_err(|e| anyhow::anyhow!(e))
+        }
+        result => result.map_err(|e| anyhow::anyhow!(e)),
+    }
+}
+
+/// Runs `cmd` with `input` on stdin and returns its stdout. The terminal stays attached
+/// to stderr so age can ask for the passphrase.
+fn run_age(mut cmd: Command, input: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                anyhow::anyhow!("age not found; install it from https://age-encryption.org")
+            }
+            _ => anyhow::anyhow!("age failed to start: {}", e),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input).context("writing to age")?;
+    }
+    let output = child.wait_with_output().context("waiting for age")?;
+    if !output.status.success() {
+        anyhow::bail!("age exited with {}", output.status);
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::CredentialResult;
+
+    #[test]
+    fn test_bundle_round_trip_keeps_only_found_accounts() {
+        let accounts = ["_default", "prod", "sudo:prod", "passphrase:/k"].map(str::to_string);
+        let bundle = collect(&accounts, |account| match account {
+            "prod" => CredentialResult::Found(Zeroizing::new("pw-prod".to_string())),
+            "passphrase:/k" => CredentialResult::Found(Zeroizing::new("key-pass".to_string())),
+            "sudo:prod" => CredentialResult::Unavailable("locked".to_string()),
+            _ => CredentialResult::NotFound,
+        });
+        let json = serde_json::to_vec(&bundle).unwrap();
+        let parsed = parse(&json).unwrap();
+        assert_eq!(
+            parsed
+                .credentials
+                .iter()
+                .map(|(a, p)| (a.as_str(), p.as_str()))
+                .collect::<Vec<_>>(),
+            [("passphrase:/k", "key-pass"), ("prod", "pw-prod")]
+        );
+
+        assert!(parse(br#"{"version": 2, "credentials": {}}"#).is_err());
+        assert!(parse(b"prod=pw").is_err());
+    }
+
+    #[test]
+    fn test_candidate_accounts_cover_config() {
+        let config = crate::config::Config {
+            default_identity_file: Some("/keys/id".to_string()),
+            servers: vec![crate::config::Server {
+                name: "prod".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let accounts = candidate_accounts(&config);
+        for expected in ["_default", "prod", "sudo:prod", "passphrase:/keys/id"] {
+            assert!(accounts.iter().any(|a| a == expected), "{} missing", expected);
+        }
+    }
+}
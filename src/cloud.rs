@@ -0,0 +1,251 @@
+//! Kubeconfig source for managed clusters (EKS, GKE, AKS).
+//!
+//! Nothing is fetched from the nodes: the provider's CLI describes the cluster
+//! (endpoint and CA), and the user entry is an exec plugin that gets a fresh token
+//! on every kubectl call, so there are no long-lived credentials to rotate.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::process::Command;
+
+/// Identifies a managed cluster (`[server.cloud]` in config.toml). Which fields are
+/// needed depends on the provider; see `generate_kubeconfig`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CloudCluster {
+    /// Cluster name at the provider. Defaults to the server name.
+    pub cluster: Option<String>,
+    /// AWS region (EKS) or location, i.e. region or zone (GKE).
+    pub region: Option<String>,
+    /// GCP project (GKE).
+    pub project: Option<String>,
+    /// Resource group (AKS).
+    pub resource_group: Option<String>,
+    /// Azure subscription (AKS); the CLI's default when unset.
+    pub subscription: Option<String>,
+    /// AWS profile (EKS), passed to both `aws` calls; the default profile when unset.
+    pub profile: Option<String>,
+}
+
+/// Builds an exec-plugin kubeconfig for the managed cluster. Requires the provider's
+/// CLI (`aws`, `gcloud` or `az`) to be installed and logged in.
+///
+/// * EKS: `region`; `profile` optional. Tokens come from `aws eks get-token`.
+/// * GKE: `region` (location) and `project`. Tokens come from `gke-gcloud-auth-plugin`.
+/// * AKS: `resource_group`; `subscription` optional. Tokens come from `kubelogin`
+///   using the Azure CLI login.
+pub fn generate_kubeconfig(
+    source: crate::config::Source,
+    server_name: &str,
+    cloud: Option<&CloudCluster>,
+) -> Result<Vec<u8>, anyhow::Error> {
+    use crate::config::Source;
+
+    let default = CloudCluster::default();
+    let cloud = cloud.unwrap_or(&default);
+    let cluster = cloud.cluster.as_deref().unwrap_or(server_name);
+    let require = |field: &Option<String>, name: &str| -> Result<String, anyhow::Error> {
+        field
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("[{}] {:?} source needs cloud.{}", server_name, source, name))
+    };
+
+    let kubeconfig = match source {
+        Source::Eks => {
+            let region = require(&cloud.region, "region")?;
+            let mut args = vec!["eks", "describe-cluster", "--name", cluster, "--region", &region];
+            if let Some(profile) = &cloud.profile {
+                args.extend(["--profile", profile]);
+            }
+            args.extend(["--output", "json"]);
+            let described = run_json("aws", &args)?;
+            let endpoint = string_at(&described, "/cluster/endpoint")?;
+            let ca = string_at(&described, "/cluster/certificateAuthority/data")?;
+            eks_kubeconfig(cluster, &endpoint, &ca, &region, cloud.profile.as_deref())
+        }
+        Source::Gke => {
+            let location = require(&cloud.region, "region")?;
+            let project = require(&cloud.project, "project")?;
+            let described = run_json(
+                "gcloud",
+                &[
+                    "container",
+                    "clusters",
+                    "describe",
+                    cluster,
+                    "--location",
+                    &location,
+                    "--project",
+                    &project,
+                    "--format",
+                    "json",
+                ],
+            )?;
+            let endpoint = string_at(&described, "/endpoint")?;
+            let ca = string_at(&described, "/masterAuth/clusterCaCertificate")?;
+            gke_kubeconfig(cluster, &format!("https://{}", endpoint), &ca)
+        }
+        Source::Aks => {
+            let resource_group = require(&cloud.resource_group, "resource_group")?;
+            // `az aks show` has no CA, so read endpoint and CA from the generated
+            // kubeconfig and replace its user with kubelogin.
+            let mut args = vec![
+                "aks",
+                "get-credentials",
+                "--name",
+                cluster,
+                "--resource-group",
+                &resource_group,
+                "--file",
+                "-",
+            ];
+            if let Some(subscription) = &cloud.subscription {
+                args.extend(["--subscription", subscription]);
+            }
+            let output = run("az", &args)?;
+            let generated: crate::kube::KubeConfig = serde_yaml::from_slice(&output)?;
+            let cluster_entry = generated
+                .clusters
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("az aks get-credentials returned no cluster"))?;
+            let ca = cluster_entry
+                .cluster
+                .certificate_authority
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("az aks get-credentials returned no CA"))?;
+            aks_kubeconfig(cluster, &cluster_entry.cluster.server, &ca)
+        }
+        Source::Ssh | Source::Rancher | Source::K3sToken => {
+            anyhow::bail!("{:?} is not a managed-cluster source", source)
+        }
+    };
+    Ok(serde_yaml::to_string(&kubeconfig)?.into_bytes())
+}
+
+fn eks_kubeconfig(cluster: &str, endpoint: &str, ca: &str, region: &str, profile: Option<&str>) -> Value {
+    let mut exec = json!({
+        "apiVersion": "client.authentication.k8s.io/v1beta1",
+        "command": "aws",
+        "args": ["--region", region, "eks", "get-token", "--cluster-name", cluster, "--output", "json"],
+        "interactiveMode": "IfAvailable",
+    });
+    if let Some(profile) = profile {
+        exec["env"] = json!([{ "name": "AWS_PROFILE", "value": profile }]);
+    }
+    exec_kubeconfig(cluster, endpoint, ca, exec)
+}
+
+fn gke_kubeconfig(cluster: &str, endpoint: &str, ca: &str) -> Value {
+    let exec = json!({
+        "apiVersion": "client.authentication.k8s.io/v1beta1",
+        "command": "gke-gcloud-auth-plugin",
+        "installHint": "Install gke-gcloud-auth-plugin: gcloud components install gke-gcloud-auth-plugin",
+        "provideClusterInfo": true,
+        "interactiveMode": "IfAvailable",
+    });
+    exec_kubeconfig(cluster, endpoint, ca, exec)
+}
+
+/// AKS clusters with Entra ID integration all use this server ID for the token audience.
+const AKS_SERVER_ID: &str = "6dae42f8-4368-4678-94ff-3960e28e3630";
+
+fn aks_kubeconfig(cluster: &str, endpoint: &str, ca: &str) -> Value {
+    let exec = json!({
+        "apiVersion": "client.authentication.k8s.io/v1beta1",
+        "command": "kubelogin",
+        "args": ["get-token", "--login", "azurecli", "--server-id", AKS_SERVER_ID],
+        "installHint": "Install kubelogin: az aks install-cli",
+        "interactiveMode": "IfAvailable",
+    });
+    exec_kubeconfig(cluster, endpoint, ca, exec)
+}
+
+/// One cluster, context and user, all named `cluster`; renamed later like any
+/// fetched kubeconfig.
+fn exec_kubeconfig(cluster: &str, endpoint: &str, ca: &str, exec: Value) -> Value {
+    json!({
+        "apiVersion": "v1",
+        "kind": "Config",
+        "current-context": cluster,
+        "clusters": [{
+            "name": cluster,
+            "cluster": { "server": endpoint, "certificate-authority-data": ca },
+        }],
+        "contexts": [{
+            "name": cluster,
+            "context": { "cluster": cluster, "user": cluster },
+        }],
+        "users": [{
+            "name": cluster,
+            "user": { "exec": exec },
+        }],
+    })
+}
+
+/// Runs a provider CLI and returns stdout; a missing binary gets an install hint.
+fn run(program: &str, args: &[&str]) -> Result<Vec<u8>, anyhow::Error> {
+    log::info!("Running {} {}", program, args.join(" "));
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("`{}` is not installed or not on PATH", program)
+        } else {
+            anyhow::anyhow!("Could not run {}: {}", program, e)
+        }
+    })?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} {} failed: {}",
+            program,
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn run_json(program: &str, args: &[&str]) -> Result<Value, anyhow::Error> {
+    Ok(serde_json::from_slice(&run(program, args)?)?)
+}
+
+fn string_at(value: &Value, pointer: &str) -> Result<String, anyhow::Error> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("cluster description has no {}", pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eks_kubeconfig_is_a_valid_exec_kubeconfig() {
+        let yaml = serde_yaml::to_string(&eks_kubeconfig(
+            "prod",
+            "https://ABC.gr7.eu-west-1.eks.amazonaws.com",
+            "Q0E=",
+            "eu-west-1",
+            Some("work"),
+        ))
+        .unwrap();
+        crate::kube::validate_fetched(yaml.as_bytes()).unwrap();
+
+        let kubeconfig: crate::kube::KubeConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(kubeconfig.current_context, "prod");
+        assert_eq!(
+            kubeconfig.clusters[0].cluster.certificate_authority.as_deref(),
+            Some("Q0E=")
+        );
+        let exec = &kubeconfig.users[0].user.extra["exec"];
+        assert_eq!(exec["command"].as_str(), Some("aws"));
+        assert_eq!(exec["args"][5].as_str(), Some("prod"));
+        assert_eq!(exec["env"][0]["value"].as_str(), Some("work"));
+    }
+
+    #[test]
+    fn test_missing_cloud_fields_are_reported() {
+        let err = generate_kubeconfig(crate::config::Source::Gke, "gke-a", None).unwrap_err();
+        assert!(err.to_string().contains("cloud.region"), "{}", err);
+    }
+}
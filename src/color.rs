@@ -0,0 +1,151 @@
+//! Color output control shared by the CLI progress bar and the TUI.
+//!
+//! The choice from `--color` is resolved once at startup against the environment
+//! (`NO_COLOR`, `TERM`, `COLORTERM`) and whether the output is a terminal.
+
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// Value of the `--color` flag.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal that supports it and NO_COLOR is unset.
+    #[default]
+    Auto,
+    /// Always color, even when piped or NO_COLOR is set.
+    Always,
+    /// Never color.
+    Never,
+}
+
+/// How many colors the terminal can show, ordered from least to most capable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub fn enabled(self) -> bool {
+        self != ColorDepth::None
+    }
+}
+
+static DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+
+/// Resolves `choice` for this process and applies it to indicatif's styling.
+/// Must be called before any output is drawn; later calls are ignored.
+pub fn init(choice: ColorChoice) {
+    let depth = *DEPTH.get_or_init(|| detect(choice));
+    console::set_colors_enabled(depth.enabled());
+    console::set_colors_enabled_stderr(depth.enabled());
+}
+
+/// The color depth chosen at startup, or auto-detection if `init` was never called.
+pub fn depth() -> ColorDepth {
+    *DEPTH.get_or_init(|| detect(ColorChoice::Auto))
+}
+
+fn detect(choice: ColorChoice) -> ColorDepth {
+    resolve(
+        choice,
+        std::io::stdout().is_terminal() || std::io::stderr().is_terminal(),
+        std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+        std::env::var("TERM").ok().as_deref(),
+        std::env::var("COLORTERM").ok().as_deref(),
+    )
+}
+
+/// Pure resolution logic, separated from the environment for testing.
+fn resolve(
+    choice: ColorChoice,
+    is_terminal: bool,
+    no_color: bool,
+    term: Option<&str>,
+    colorterm: Option<&str>,
+) -> ColorDepth {
+    let env_depth = if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+        ColorDepth::TrueColor
+    } else if term.is_some_and(|t| t.contains("256color")) {
+        ColorDepth::Ansi256
+    } else if term == Some("dumb") {
+        ColorDepth::None
+    } else {
+        ColorDepth::Ansi16
+    };
+    match choice {
+        ColorChoice::Never => ColorDepth::None,
+        ColorChoice::Always => env_depth.max(ColorDepth::Ansi16),
+        ColorChoice::Auto if no_color || !is_terminal => ColorDepth::None,
+        ColorChoice::Auto => env_depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_never_disables_everything() {
+        let depth = resolve(
+            ColorChoice::Never,
+            true,
+            false,
+            Some("xterm-256color"),
+            Some("truecolor"),
+        );
+        assert_eq!(depth, ColorDepth::None);
+    }
+
+    #[test]
+    fn test_auto_respects_no_color_and_pipes() {
+        assert_eq!(
+            resolve(ColorChoice::Auto, true, true, Some("xterm-256color"), None),
+            ColorDepth::None
+        );
+        assert_eq!(
+            resolve(ColorChoice::Auto, false, false, Some("xterm-256color"), None),
+            ColorDepth::None
+        );
+    }
+
+    #[test]
+    fn test_auto_detects_depth_from_env() {
+        assert_eq!(
+            resolve(
+                ColorChoice::Auto,
+                true,
+                false,
+                Some("xterm-256color"),
+                Some("truecolor")
+            ),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            resolve(ColorChoice::Auto, true, false, Some("screen-256color"), None),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            resolve(ColorChoice::Auto, true, false, Some("xterm"), None),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(
+            resolve(ColorChoice::Auto, true, false, Some("dumb"), None),
+            ColorDepth::None
+        );
+    }
+
+    #[test]
+    fn test_always_overrides_no_color_but_keeps_depth() {
+        assert_eq!(
+            resolve(ColorChoice::Always, false, true, Some("dumb"), None),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(
+            resolve(ColorChoice::Always, false, true, Some("xterm-256color"), None),
+            ColorDepth::Ansi256
+        );
+    }
+}
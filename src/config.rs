@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 use toml_edit::{DocumentMut, Item, value};
 
 /// Represents the main application configuration, loaded from a TOML file.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// The default username to use for SSH connections if not specified per server.
     pub default_user: Option<String>,
@@ -14,37 +15,444 @@ pub struct Config {
     pub default_file_name: Option<String>,
     /// The default SSH identity file to use if not specified per server.
     pub default_identity_file: Option<String>,
+    /// Whether to enable SSH transport compression if not specified per server.
+    pub default_compression: Option<bool>,
+    /// SSH implementation if not specified per server: `libssh2` (built in) or
+    /// `openssh` (the local `ssh` binary).
+    pub default_transport: Option<crate::ssh::Transport>,
+    /// How commands get root if not specified per server: `sudo` (the default), `doas`
+    /// or `none`.
+    pub default_escalation: Option<crate::ssh::Escalation>,
+    /// Command template that replaces the built-in `sudo`/`doas` prefix, with
+    /// `{command}` and optionally `{prompt}` (answered with the sudo password).
+    pub escalation_command: Option<String>,
+    /// How kubeconfigs are read if not specified per server: `exec` (`cat`) or `sftp`.
+    pub default_transfer: Option<crate::ssh::Transfer>,
+    /// With `sftp`, retry over `cat` (using sudo when a password is stored) when the
+    /// file isn't readable by the SSH user. Defaults to `true`.
+    pub sftp_sudo_fallback: Option<bool>,
     /// The local directory where fetched kubeconfig files will be stored.
     pub local_output_dir: String,
+    /// Name of each server's cached kubeconfig in `local_output_dir`, with `{name}`
+    /// replaced by the server name. Defaults to `{name}.yaml`.
+    pub local_file_name: Option<String>,
+    /// Where run state is kept. Defaults to `$XDG_STATE_HOME/kube_config_updater/state.json`.
+    pub state_file: Option<String>,
+    /// Where each CLI or daemon run writes its JSON report (the `--output json` shape).
+    /// Not written when unset.
+    pub report_file: Option<String>,
+    /// Maximum number of servers fetched at once, shared by the CLI and the TUI's fetch queue.
+    pub max_parallel: Option<usize>,
+    /// How often `daemon` mode re-checks each server, in minutes. Defaults to 60.
+    pub daemon_interval_minutes: Option<u64>,
+    /// Renew certs that expire within this many days even though they're still valid.
+    /// Defaults to 0 (only expired certs are renewed).
+    pub renew_before_days: Option<u32>,
+    /// Days before a cert expires from which the TUI shows it in yellow. Defaults to 30.
+    pub cert_warning_days: Option<u32>,
+    /// Days before a cert expires from which the TUI shows it in red. Defaults to 7.
+    pub cert_critical_days: Option<u32>,
+    /// Warn when the cluster CA expires within this many days. Defaults to 180: unlike
+    /// the client cert, a new CA has to reach every client, so it needs a head start.
+    pub ca_warning_days: Option<u32>,
+    /// How the TUI shows cert expiry: `date` (the default) or `relative` (`in 12d`,
+    /// `expired 3d ago`). `T` switches between them.
+    pub cert_display: Option<CertDisplay>,
+    /// The dashboard's columns, in order, e.g. `["name", "user", "cert-expiry", "status"]`.
+    /// Defaults to name, cert expiry and status; REACHABLE and REMOTE are added after
+    /// them when their checks are on.
+    pub dashboard_columns: Option<Vec<Column>>,
+    /// Fetch a server as soon as the TUI's add-server wizard saves it: `true` always
+    /// does, `false` never does. Unset, the wizard asks.
+    pub fetch_after_add: Option<bool>,
+    /// Trust and record host keys not yet in `~/.ssh/known_hosts` instead of failing.
+    /// Also enabled for a single run by `--accept-new`.
+    pub accept_new_host_keys: Option<bool>,
+    /// Read passwords from `KCU_PASSWORD_<SERVER>` / `KCU_PASSWORD_DEFAULT` before the
+    /// keyring, for CI runners that have no keyring at all. Defaults to `false`.
+    pub credentials_from_env: Option<bool>,
+    /// Where credentials are kept: `auto` (the default: OS keyring, else the encrypted
+    /// file), `keyring`, `file` or `command`.
+    pub credential_backend: Option<crate::credentials::Backend>,
+    /// Script for `credential_backend = "command"`, run with `get|set|delete <account>`.
+    pub credential_command: Option<String>,
+    /// Show a REACHABLE column in the TUI, filled by a background TCP connect to each
+    /// server's SSH port. Defaults to `false`.
+    pub reachability_check: Option<bool>,
+    /// Also check the Kubernetes API port (6443 on `target_cluster_ip`) for that column.
+    pub reachability_check_api: Option<bool>,
+    /// Seconds between reachability checks. Defaults to 60.
+    pub reachability_interval_seconds: Option<u64>,
+    /// After each fetch, check that kubectl could reach the API server in the rewritten
+    /// kubeconfig: `tls` (a TLS handshake with its `server` URL) or `version` (a
+    /// `GET /version` with the kubeconfig's CA and client cert). A server that fails
+    /// is recorded as `Unreachable`. Defaults to `off`.
+    pub verify_api: Option<ApiVerification>,
+    /// Seconds a single server's fetch may take end to end (connect, read, rewrite,
+    /// merge) before it is abandoned as failed. Defaults to 120.
+    pub fetch_timeout_seconds: Option<u64>,
+    /// Command the `renew` action runs over SSH to make the node reissue its certs.
+    /// Defaults to `systemctl restart k3s`.
+    pub renew_command: Option<String>,
+    /// Local command the TUI's `o` runs for the selected server, with `{context}`,
+    /// `{kubeconfig}` and `{name}` filled in. Defaults to `k9s --context {context}`.
+    pub open_command: Option<String>,
+    /// Set to `false` to never touch ~/.kube/config; fetched files are still written to
+    /// `local_output_dir`. Also disabled for a single run by `--no-merge`.
+    pub merge: Option<bool>,
+    /// How to merge when a fetched entry's name is already used by a different cluster
+    /// in ~/.kube/config. Defaults to `overwrite`.
+    pub merge_strategy: Option<crate::kube::MergeStrategy>,
+    /// How many versions of each server's cached kubeconfig to keep for `rollback`, in
+    /// `<local_output_dir>/.history/`. Defaults to 5; 0 disables the history.
+    pub history_retention: Option<usize>,
+    /// How many backups of ~/.kube/config to keep in `~/.kube/config-backups/`.
+    /// Defaults to 10; 0 disables backups.
+    pub backup_retention: Option<usize>,
     #[serde(default)]
     pub bitwarden: Option<crate::bitwarden::BitwardenConfig>,
+    #[serde(default)]
+    pub notifications: Option<crate::notify::NotificationsConfig>,
+    /// TUI colors: a preset and per-role overrides. See `tui::theme`.
+    #[serde(default)]
+    pub theme: Option<crate::tui::theme::ThemeConfig>,
+    /// Dashboard and detail view keys to rebind, e.g. `dry-run = "Z"`. See `tui::keys`.
+    #[serde(default)]
+    pub keys: Option<std::collections::BTreeMap<crate::tui::keys::Action, String>>,
+    /// Files whose `[[server]]` entries are appended to `servers` when the config is
+    /// loaded, e.g. `["servers.d/*.toml"]`. Relative paths start at this file's
+    /// directory; `*` and `?` match within the file name.
+    pub include: Option<Vec<String>>,
     /// A list of server configurations to process.
     #[serde(rename = "server", default)]
     pub servers: Vec<Server>,
 }
 
+/// Fetches spend nearly all their time waiting on the network (a dead host holds its
+/// slot for the whole 10-second connect timeout), so the default isn't tied to the
+/// number of CPUs: a few unreachable hosts shouldn't hold up the rest of a large fleet.
+const DEFAULT_MAX_PARALLEL: usize = 32;
+
+/// `local_file_name` unless configured.
+pub const DEFAULT_LOCAL_FILE_NAME: &str = "{name}.yaml";
+
+impl Config {
+    /// Path of the cached kubeconfig of `server_name` in `local_output_dir`. Names not
+    /// in the config (e.g. the new name when renaming) use the global template.
+    pub fn local_path(&self, server_name: &str) -> PathBuf {
+        let file_name = match self.servers.iter().find(|s| s.name == server_name) {
+            Some(server) => server.local_file_name(self),
+            None => self
+                .local_file_name
+                .as_deref()
+                .unwrap_or(DEFAULT_LOCAL_FILE_NAME)
+                .replace("{name}", server_name),
+        };
+        Path::new(&self.local_output_dir).join(file_name)
+    }
+
+    /// The configured server whose cached kubeconfig is called `file_name`.
+    pub fn server_for_local_file(&self, file_name: &str) -> Option<&Server> {
+        self.servers.iter().find(|s| s.local_file_name(self) == file_name)
+    }
+
+    /// Resolves the fetch concurrency limit; `DEFAULT_MAX_PARALLEL` unless configured.
+    pub fn max_parallel(&self) -> usize {
+        self.max_parallel.filter(|&n| n > 0).unwrap_or(DEFAULT_MAX_PARALLEL)
+    }
+
+    /// Number of versions kept per server for `rollback`; 5 unless configured.
+    pub fn history_retention(&self) -> usize {
+        self.history_retention.unwrap_or(5)
+    }
+
+    /// Number of ~/.kube/config backups to keep before each merge; 10 unless configured.
+    pub fn backup_retention(&self) -> usize {
+        self.backup_retention.unwrap_or(10)
+    }
+
+    /// Days left at which a cert turns yellow and red: `cert_warning_days` (30) and
+    /// `cert_critical_days` (7).
+    pub fn cert_thresholds(&self) -> (i64, i64) {
+        (
+            self.cert_warning_days.unwrap_or(30).into(),
+            self.cert_critical_days.unwrap_or(7).into(),
+        )
+    }
+
+    /// `ca_warning_days` as a duration.
+    pub fn ca_warning(&self) -> chrono::Duration {
+        chrono::Duration::days(self.ca_warning_days.unwrap_or(180).into())
+    }
+
+    /// Every tag used by any server, sorted and without duplicates.
+    pub fn tags(&self) -> Vec<String> {
+        let tags: std::collections::BTreeSet<&String> = self.servers.iter().flat_map(|s| &s.tags).collect();
+        tags.into_iter().cloned().collect()
+    }
+
+    /// Whether an SFTP read that is denied access retries over `cat`.
+    pub fn sftp_sudo_fallback(&self) -> bool {
+        self.sftp_sudo_fallback.unwrap_or(true)
+    }
+
+    /// Whether `KCU_PASSWORD_*` environment variables are consulted for passwords.
+    pub fn credentials_from_env(&self) -> bool {
+        self.credentials_from_env.unwrap_or(false)
+    }
+
+    pub fn credential_backend(&self) -> crate::credentials::Backend {
+        self.credential_backend.unwrap_or_default()
+    }
+
+    /// Whether the TUI runs background reachability checks.
+    pub fn reachability_check(&self) -> bool {
+        self.reachability_check.unwrap_or(false)
+    }
+
+    /// Whether reachability checks include the Kubernetes API port.
+    pub fn reachability_check_api(&self) -> bool {
+        self.reachability_check_api.unwrap_or(false)
+    }
+
+    /// What is checked against the API server after each fetch.
+    pub fn verify_api(&self) -> ApiVerification {
+        self.verify_api.unwrap_or_default()
+    }
+
+    /// `open_command`, or k9s on the server's context.
+    pub fn open_command(&self) -> &str {
+        self.open_command.as_deref().unwrap_or("k9s --context {context}")
+    }
+
+    /// The dashboard's columns; name, cert expiry and status unless configured.
+    pub fn dashboard_columns(&self) -> &[Column] {
+        match self.dashboard_columns.as_deref() {
+            Some(columns) if !columns.is_empty() => columns,
+            _ => &[Column::Name, Column::CertExpiry, Column::Status],
+        }
+    }
+
+    /// Time between reachability checks; 60 seconds unless configured, never below 5.
+    pub fn reachability_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.reachability_interval_seconds.unwrap_or(60).max(5))
+    }
+
+    /// Host key policy for unknown hosts; strict unless `accept_new_host_keys` is set.
+    pub fn host_key_policy(&self) -> crate::ssh::HostKeyPolicy {
+        if self.accept_new_host_keys.unwrap_or(false) {
+            crate::ssh::HostKeyPolicy::AcceptNew
+        } else {
+            crate::ssh::HostKeyPolicy::Strict
+        }
+    }
+}
+
+/// How cert expiry is shown in the TUI.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CertDisplay {
+    /// `2025-03-01`.
+    #[default]
+    Date,
+    /// `in 12d`, `expired 3d ago`.
+    Relative,
+}
+
+/// A column `dashboard_columns` can show.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Name,
+    /// The SSH address.
+    Address,
+    /// The SSH user, `default_user` unless the server sets one.
+    User,
+    /// The kubeconfig context the server is merged as.
+    Context,
+    CertExpiry,
+    /// Time since the last run, e.g. `3h ago`.
+    LastUpdated,
+    /// How long the last run took.
+    LastDuration,
+    Status,
+}
+
+/// What `verify_api` checks after a fetch.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiVerification {
+    #[default]
+    Off,
+    /// A TLS handshake with the `server` URL: the address is right and nothing blocks it.
+    Tls,
+    /// `GET /version` trusting only the kubeconfig's CA and presenting its client cert,
+    /// as kubectl would.
+    Version,
+}
+
+/// Where a server's kubeconfig comes from.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Read over SSH from the node at `address`.
+    #[default]
+    Ssh,
+    /// Generated by the Rancher management API at `address` for `rancher_cluster_id`,
+    /// authenticated with an API token stored as the server's credential.
+    Rancher,
+    /// Amazon EKS, described with `aws` and authenticated by `aws eks get-token`.
+    Eks,
+    /// Google GKE, described with `gcloud` and authenticated by `gke-gcloud-auth-plugin`.
+    Gke,
+    /// Azure AKS, read with `az` and authenticated by `kubelogin`.
+    Aks,
+    /// Built locally for the k3s server at `address` from its join token, stored as the
+    /// server's credential. Only the cluster CA is fetched, over TLS.
+    #[serde(rename = "k3s-token")]
+    K3sToken,
+}
+
 /// Represents a single remote server to be processed.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Server {
     /// A unique name for the server, used for local file naming.
     pub name: String,
-    /// The SSH address (e.g., "host.example.com") of the server.
+    /// The SSH address (e.g., "host.example.com") of the server, or the Rancher URL.
+    /// Not used by the managed-cluster sources.
+    #[serde(default)]
     pub address: String,
-    /// The target IP address for the Kubernetes cluster.
+    /// SSH port on `address`. Defaults to 22.
+    pub ssh_port: Option<u16>,
+    /// The IP address or hostname kubectl should use for the cluster, written verbatim
+    /// into the server URL. Required for SSH servers; when empty the kubeconfig's own
+    /// server URL is kept (e.g. Rancher's proxy URL).
+    #[serde(default)]
     pub target_cluster_ip: String,
+    /// API server port written with `target_cluster_ip`. Defaults to 6443.
+    pub target_cluster_port: Option<u16>,
+    /// Full API server URL (e.g. `https://vip.lan:443`), written verbatim; overrides
+    /// `target_cluster_ip` and `target_cluster_port`.
+    pub target_server_url: Option<String>,
+    /// Where the kubeconfig comes from; `ssh` unless set.
+    pub source: Option<Source>,
+    /// Cluster ID (`c-m-xxxxx`) for `source = "rancher"`.
+    pub rancher_cluster_id: Option<String>,
+    /// Managed cluster to describe for `source = "eks" | "gke" | "aks"`.
+    pub cloud: Option<crate::cloud::CloudCluster>,
     /// The username for this specific server, overriding the default.
     pub user: Option<String>,
     /// The remote file path for this server, overriding the default.
     pub file_path: Option<String>,
     /// The remote file name for this server, overriding the default.
     pub file_name: Option<String>,
+    /// Name of this server's cached kubeconfig, overriding `local_file_name`.
+    pub local_file_name: Option<String>,
     /// The desired context name to set in the kubeconfig file.
     pub context_name: Option<String>,
+    /// Namespace written into the context, so kubectl starts there after switching to it.
+    pub namespace: Option<String>,
     /// The SSH identity file for this specific server, overriding the default.
     pub identity_file: Option<String>,
+    /// Answer sudo with a password stored separately (`credential set --server <name>
+    /// --sudo`) instead of the SSH password, e.g. when logging in with a key.
+    pub sudo_password: Option<bool>,
+    /// How commands get root on this server, overriding `default_escalation`.
+    pub escalation: Option<crate::ssh::Escalation>,
+    /// Escalation command template for this server, overriding `escalation_command`.
+    pub escalation_command: Option<String>,
+    /// Enables SSH transport compression for this server, overriding the default.
+    /// Useful on slow links where kubeconfigs with large CA bundles take a while to transfer.
+    pub compression: Option<bool>,
+    /// SSH implementation for this server, overriding `default_transport`.
+    pub transport: Option<crate::ssh::Transport>,
+    /// Transfer mode for this server, overriding `default_transfer`.
+    pub transfer: Option<crate::ssh::Transfer>,
+    /// How often `daemon` mode re-checks this server, in minutes, overriding the default.
+    pub interval_minutes: Option<u64>,
+    /// When `daemon` mode checks this server: an interval (`90s`, `30m`, `6h`, `1d`) or a
+    /// cron expression in local time (`0 6 * * *`). Takes the place of `interval_minutes`.
+    pub schedule: Option<String>,
+    /// Renewal window for this server's cert in days, overriding the default.
+    pub renew_before_days: Option<u32>,
+    /// Renewal command for this server, overriding `renew_command`.
+    pub renew_command: Option<String>,
+    /// Fetch deadline for this server in seconds, overriding `fetch_timeout_seconds`.
+    pub fetch_timeout_seconds: Option<u64>,
+    /// Set to `false` to keep this server out of ~/.kube/config.
+    pub merge: Option<bool>,
+    /// Merge strategy for this server, overriding the default.
+    pub merge_strategy: Option<crate::kube::MergeStrategy>,
+    /// Free-form labels (e.g. "prod", "homelab") for selecting groups of servers
+    /// with `--tag` or the TUI's tag filter.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Server {
+    pub fn source(&self) -> Source {
+        self.source.unwrap_or_default()
+    }
+
+    /// `ssh_port`, or 22.
+    pub fn ssh_port(&self) -> u16 {
+        self.ssh_port.unwrap_or(crate::ssh::SSH_PORT)
+    }
+
+    /// Host kubectl is pointed at: `target_cluster_ip`, or the SSH address when unset.
+    pub fn api_host(&self) -> &str {
+        crate::validate::bare_host(if self.target_cluster_ip.is_empty() {
+            &self.address
+        } else {
+            &self.target_cluster_ip
+        })
+    }
+
+    /// Server URL written into the fetched kubeconfig: `target_server_url`, or
+    /// `https://<target_cluster_ip>:<target_cluster_port>`. `None` keeps the source's URL.
+    pub fn server_url(&self) -> Option<String> {
+        if let Some(url) = &self.target_server_url {
+            return Some(url.clone());
+        }
+        if self.target_cluster_ip.is_empty() {
+            return None;
+        }
+        Some(crate::kube::api_url(
+            crate::validate::bare_host(&self.target_cluster_ip),
+            self.target_cluster_port.unwrap_or(crate::tls::API_PORT),
+        ))
+    }
+
+    /// Host and port of the API server kubectl talks to, for reachability checks and
+    /// the serving-cert probe.
+    pub fn api_endpoint(&self) -> (String, u16) {
+        self.target_server_url
+            .as_deref()
+            .and_then(crate::rancher::host_port)
+            .unwrap_or_else(|| {
+                (
+                    self.api_host().to_string(),
+                    self.target_cluster_port.unwrap_or(crate::tls::API_PORT),
+                )
+            })
+    }
+
+    /// Name of this server's cached kubeconfig in `local_output_dir`: its own
+    /// `local_file_name`, else the global one, with `{name}` replaced.
+    pub fn local_file_name(&self, config: &Config) -> String {
+        self.local_file_name
+            .as_deref()
+            .or(config.local_file_name.as_deref())
+            .unwrap_or(DEFAULT_LOCAL_FILE_NAME)
+            .replace("{name}", &self.name)
+    }
+
+    /// Name of this server's context once merged: `context_name`, or the server name.
+    pub fn kube_context(&self) -> &str {
+        self.context_name.as_deref().unwrap_or(&self.name)
+    }
+
     /// Gets the username for the server, falling back to the default from the main config.
     pub fn user<'a>(&'a self, config: &'a Config) -> Result<&'a str, anyhow::Error> {
         let user = self
@@ -78,9 +486,198 @@ impl Server {
             .as_deref()
             .or(config.default_identity_file.as_deref())
     }
+
+    /// Whether SSH compression is enabled for the server, falling back to the default
+    /// from the main config. Compression is off unless explicitly enabled.
+    pub fn compression(&self, config: &Config) -> bool {
+        self.compression.or(config.default_compression).unwrap_or(false)
+    }
+
+    /// Which SSH implementation connects to the server, falling back to the default
+    /// from the main config and then to libssh2.
+    pub fn transport(&self, config: &Config) -> crate::ssh::Transport {
+        self.transport.or(config.default_transport).unwrap_or_default()
+    }
+
+    /// How commands get root on the server, falling back to the default from the main
+    /// config and then to sudo.
+    pub fn escalation(&self, config: &Config) -> crate::ssh::Escalation {
+        self.escalation.or(config.default_escalation).unwrap_or_default()
+    }
+
+    /// Escalation command template for the server, if one is configured.
+    pub fn escalation_command<'a>(&'a self, config: &'a Config) -> Option<&'a str> {
+        self.escalation_command
+            .as_deref()
+            .or(config.escalation_command.as_deref())
+    }
+
+    /// How the kubeconfig is read from the server, falling back to the default from the
+    /// main config and then to `exec`.
+    pub fn transfer(&self, config: &Config) -> crate::ssh::Transfer {
+        self.transfer.or(config.default_transfer).unwrap_or_default()
+    }
+
+    /// How often daemon mode re-checks the server, falling back to the default from the
+    /// main config and then to one hour. Zero is treated as unset.
+    pub fn interval(&self, config: &Config) -> std::time::Duration {
+        let minutes = self
+            .interval_minutes
+            .or(config.daemon_interval_minutes)
+            .filter(|&m| m > 0)
+            .unwrap_or(60);
+        std::time::Duration::from_secs(minutes * 60)
+    }
+
+    /// How long before expiry a still-valid cert is fetched again, falling back to the
+    /// default from the main config. Zero unless configured.
+    pub fn renew_before(&self, config: &Config) -> chrono::Duration {
+        let days = self.renew_before_days.or(config.renew_before_days).unwrap_or(0);
+        chrono::Duration::days(days.into())
+    }
+
+    /// End-to-end deadline for fetching the server, falling back to the default from
+    /// the main config and then to two minutes. Zero is treated as unset.
+    pub fn fetch_timeout(&self, config: &Config) -> std::time::Duration {
+        let seconds = self
+            .fetch_timeout_seconds
+            .or(config.fetch_timeout_seconds)
+            .filter(|&s| s > 0)
+            .unwrap_or(120);
+        std::time::Duration::from_secs(seconds)
+    }
+
+    /// Whether the fetched kubeconfig is merged into ~/.kube/config. A top-level
+    /// `merge = false` (or `--no-merge`) turns merging off for every server.
+    pub fn merge_enabled(&self, config: &Config) -> bool {
+        config.merge != Some(false) && self.merge.unwrap_or(true)
+    }
+
+    /// Whether the server carries any of `tags`.
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.tags.iter().any(|t| tags.contains(t))
+    }
+
+    /// The merge strategy for the server, falling back to the default from the main config.
+    pub fn merge_strategy(&self, config: &Config) -> crate::kube::MergeStrategy {
+        self.merge_strategy.or(config.merge_strategy).unwrap_or_default()
+    }
+
+    /// Remote command that renews this server's certs; k3s reissues certs close to
+    /// expiry when it restarts.
+    pub fn renew_command<'a>(&'a self, config: &'a Config) -> &'a str {
+        self.renew_command
+            .as_deref()
+            .or(config.renew_command.as_deref())
+            .unwrap_or("systemctl restart k3s")
+    }
 }
 
-/// Loads the application configuration from a specified TOML file path.
+/// File formats the configuration can be written in, picked by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// `.yaml` / `.yml` and `.json` (any case) select YAML and JSON; everything else,
+    /// including no extension, is TOML.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    fn parse<T: serde::de::DeserializeOwned>(self, content: &str) -> Result<T, String> {
+        let message = match self {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        };
+        message.map_err(|e| suggest_field(&e))
+    }
+}
+
+/// Rewrites serde's "unknown field `x`, expected one of `a`, `b`, ..." to name the
+/// closest known key instead of listing all of them. The location the parser added
+/// (line and column) is kept. Other messages pass through unchanged.
+fn suggest_field(message: &str) -> String {
+    const UNKNOWN: &str = "unknown field `";
+    let Some(start) = message.find(UNKNOWN) else {
+        return message.to_string();
+    };
+    let after = &message[start + UNKNOWN.len()..];
+    let Some(name_len) = after.find('`') else {
+        return message.to_string();
+    };
+    let unknown = &after[..name_len];
+    let rest = &after[name_len + 1..];
+    let Some(list) = rest
+        .strip_prefix(", expected one of ")
+        .or_else(|| rest.strip_prefix(", expected "))
+    else {
+        return message.to_string();
+    };
+
+    // The list is `a`, `b` or `a` or `b`; it ends at the first backtick-quoted name
+    // that isn't followed by another separator.
+    let mut expected = Vec::new();
+    let mut tail = list;
+    while let Some(quoted) = tail.strip_prefix('`') {
+        let Some(end) = quoted.find('`') else { break };
+        expected.push(&quoted[..end]);
+        tail = &quoted[end + 1..];
+        match tail.strip_prefix(", ").or_else(|| tail.strip_prefix(" or ")) {
+            Some(next) if next.starts_with('`') => tail = next,
+            _ => break,
+        }
+    }
+    let Some((best, distance)) = expected
+        .iter()
+        .map(|name| (*name, edit_distance(unknown, name)))
+        .min_by_key(|(_, d)| *d)
+    else {
+        return message.to_string();
+    };
+    if distance > (unknown.len() / 3).max(2) {
+        return message.to_string();
+    }
+    format!(
+        "{}unknown field `{}`, did you mean `{}`?{}",
+        &message[..start],
+        unknown,
+        best,
+        tail
+    )
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Loads the application configuration from a specified TOML, YAML or JSON file path.
+/// The format follows the file extension (see `ConfigFormat::from_path`).
 ///
 /// # Arguments
 ///
@@ -112,25 +709,161 @@ pub fn load_config_optional(path: &str) -> Result<Option<Config>, anyhow::Error>
     let config_content = fs::read_to_string(path)?;
     log::debug!("Successfully read config file.");
 
-    let config: Config = toml::from_str(&config_content)
+    let mut config: Config = ConfigFormat::from_path(Path::new(path))
+        .parse(&config_content)
         .map_err(|e| anyhow::anyhow!("Configuration file at '{}' is invalid: {}", path, e))?;
     log::debug!("Successfully parsed configuration.");
 
+    load_includes(&mut config, Path::new(path))?;
+
     Ok(Some(config))
 }
 
-/// Append a new [[server]] entry to config.toml, preserving existing comments and formatting.
-pub fn add_server(config_path: &PathBuf, server: &Server) -> Result<(), anyhow::Error> {
+/// The contents of an included file: server entries only.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fragment {
+    #[serde(rename = "server", default)]
+    servers: Vec<Server>,
+}
+
+/// Appends the servers of every file matched by `config.include`. Patterns are taken in
+/// order and the files each one matches in name order, so the server list is the same
+/// on every load. A server name defined in two files is an error.
+fn load_includes(config: &mut Config, config_path: &Path) -> Result<(), anyhow::Error> {
+    let Some(patterns) = config.include.clone() else {
+        return Ok(());
+    };
+    let base = config_path.parent().unwrap_or(Path::new("."));
+    let mut defined_in: std::collections::HashMap<String, PathBuf> = config
+        .servers
+        .iter()
+        .map(|s| (s.name.clone(), config_path.to_path_buf()))
+        .collect();
+    let mut seen_files = std::collections::HashSet::new();
+
+    for pattern in &patterns {
+        for file in expand_include(base, pattern)? {
+            if !seen_files.insert(file.clone()) {
+                continue;
+            }
+            log::debug!("Including servers from '{}'", file.display());
+            let content = fs::read_to_string(&file)
+                .map_err(|e| anyhow::anyhow!("Could not read included file '{}': {}", file.display(), e))?;
+            let fragment: Fragment = ConfigFormat::from_path(&file)
+                .parse(&content)
+                .map_err(|e| anyhow::anyhow!("Included file '{}' is invalid: {}", file.display(), e))?;
+            for server in fragment.servers {
+                if let Some(first) = defined_in.get(&server.name) {
+                    anyhow::bail!(
+                        "Server '{}' in '{}' is already defined in '{}'",
+                        server.name,
+                        file.display(),
+                        first.display()
+                    );
+                }
+                defined_in.insert(server.name.clone(), file.clone());
+                config.servers.push(server);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files matched by one `include` entry, sorted by name. A pattern without wildcards
+/// must name an existing file; a wildcard pattern may match nothing (an empty or
+/// missing `servers.d` is fine).
+fn expand_include(base: &Path, pattern: &str) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let full = base.join(pattern);
+    let name = full.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        if !full.is_file() {
+            anyhow::bail!("Included file '{}' does not exist", full.display());
+        }
+        return Ok(vec![full]);
+    }
+    let dir = full.parent().unwrap_or(base);
+    if dir.to_string_lossy().contains(['*', '?']) {
+        anyhow::bail!(
+            "include pattern '{}': wildcards are only supported in the file name",
+            pattern
+        );
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        log::debug!("include pattern '{}' matched nothing", pattern);
+        return Ok(Vec::new());
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| wildcard_match(name, n))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Shell-style match of `text` against `pattern`, where `*` is any run of characters
+/// and `?` is exactly one.
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` and the text index it is currently matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Reads the config for editing. Only TOML configs are edited in place, since
+/// toml_edit is what keeps the user's comments and layout intact.
+fn read_toml_document(config_path: &Path) -> Result<DocumentMut, anyhow::Error> {
+    let format = ConfigFormat::from_path(config_path);
+    if format != ConfigFormat::Toml {
+        anyhow::bail!(
+            "{} is a {:?} config; only TOML configs can be edited from here. Edit the file directly.",
+            config_path.display(),
+            format
+        );
+    }
     let content = std::fs::read_to_string(config_path)?;
-    let mut doc: DocumentMut = content
+    content
         .parse()
-        .map_err(|e| anyhow::anyhow!("Failed to parse config.toml: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("Failed to parse config.toml: {}", e))
+}
+
+/// Append a new [[server]] entry to config.toml, preserving existing comments and formatting.
+pub fn add_server(config_path: &PathBuf, server: &Server) -> Result<(), anyhow::Error> {
+    let mut doc = read_toml_document(config_path)?;
 
     // Build the new entry table
     let mut entry = toml_edit::Table::new();
     entry["name"] = value(server.name.as_str());
     entry["address"] = value(server.address.as_str());
+    if let Some(port) = server.ssh_port {
+        entry["ssh_port"] = value(port as i64);
+    }
     entry["target_cluster_ip"] = value(server.target_cluster_ip.as_str());
+    if let Some(port) = server.target_cluster_port {
+        entry["target_cluster_port"] = value(port as i64);
+    }
     if let Some(ref u) = server.user {
         entry["user"] = value(u.as_str());
     }
@@ -140,12 +873,30 @@ pub fn add_server(config_path: &PathBuf, server: &Server) -> Result<(), anyhow::
     if let Some(ref fn_) = server.file_name {
         entry["file_name"] = value(fn_.as_str());
     }
+    if let Some(ref local) = server.local_file_name {
+        entry["local_file_name"] = value(local.as_str());
+    }
     if let Some(ref ctx) = server.context_name {
         entry["context_name"] = value(ctx.as_str());
     }
+    if let Some(ref namespace) = server.namespace {
+        entry["namespace"] = value(namespace.as_str());
+    }
     if let Some(ref id) = server.identity_file {
         entry["identity_file"] = value(id.as_str());
     }
+    if let Some(compression) = server.compression {
+        entry["compression"] = value(compression);
+    }
+    if let Some(minutes) = server.interval_minutes {
+        entry["interval_minutes"] = value(minutes as i64);
+    }
+    if let Some(ref schedule) = server.schedule {
+        entry["schedule"] = value(schedule.as_str());
+    }
+    if !server.tags.is_empty() {
+        entry["tags"] = value(server.tags.iter().collect::<toml_edit::Array>());
+    }
 
     // Get or create the [[server]] array of tables
     if doc.get("server").is_none() {
@@ -172,10 +923,7 @@ pub fn add_server(config_path: &PathBuf, server: &Server) -> Result<(), anyhow::
 /// Update an existing [[server]] entry in config.toml by name.
 /// Fields set to Some("") are written as absent (removing optional fields).
 pub fn update_server(config_path: &PathBuf, updated: &Server) -> Result<(), anyhow::Error> {
-    let content = std::fs::read_to_string(config_path)?;
-    let mut doc: DocumentMut = content
-        .parse()
-        .map_err(|e| anyhow::anyhow!("Failed to parse config.toml: {}", e))?;
+    let mut doc = read_toml_document(config_path)?;
 
     let servers = doc["server"]
         .as_array_of_tables_mut()
@@ -184,14 +932,22 @@ pub fn update_server(config_path: &PathBuf, updated: &Server) -> Result<(), anyh
     let entry = servers
         .iter_mut()
         .find(|t| t["name"].as_str() == Some(&updated.name))
-        .ok_or_else(|| anyhow::anyhow!("Server '{}' not found in config", updated.name))?;
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Server '{}' not found in {} (servers from included files are edited in those files)",
+                updated.name,
+                config_path.display()
+            )
+        })?;
 
-    entry["address"] = value(updated.address.as_str());
-    entry["target_cluster_ip"] = value(updated.target_cluster_ip.as_str());
+    set_value(entry, "address", updated.address.as_str());
+    set_or_remove_value(entry, "ssh_port", updated.ssh_port.map(i64::from));
+    set_value(entry, "target_cluster_ip", updated.target_cluster_ip.as_str());
 
     set_or_remove(entry, "user", updated.user.as_deref());
     set_or_remove(entry, "file_path", updated.file_path.as_deref());
     set_or_remove(entry, "file_name", updated.file_name.as_deref());
+    set_or_remove(entry, "local_file_name", updated.local_file_name.as_deref());
     set_or_remove(entry, "context_name", updated.context_name.as_deref());
     set_or_remove(entry, "identity_file", updated.identity_file.as_deref());
 
@@ -207,21 +963,94 @@ pub fn update_server(config_path: &PathBuf, updated: &Server) -> Result<(), anyh
     Ok(())
 }
 
+/// Sets `key` in `table`, keeping the whitespace and comment around the value it
+/// replaces, so `address = "10.0.0.5"  # lab switch` keeps its comment.
+fn set_value(table: &mut toml_edit::Table, key: &str, new: impl Into<toml_edit::Value>) {
+    let mut new = new.into();
+    if let Some(old) = table.get(key).and_then(Item::as_value) {
+        *new.decor_mut() = old.decor().clone();
+    }
+    table[key] = Item::Value(new);
+}
+
 fn set_or_remove(entry: &mut toml_edit::Table, key: &str, val: Option<&str>) {
     match val {
-        Some(v) if !v.is_empty() => entry[key] = value(v),
+        Some(v) if !v.is_empty() => set_value(entry, key, v),
         _ => {
             entry.remove(key);
         }
     }
 }
 
+fn set_or_remove_value(entry: &mut toml_edit::Table, key: &str, val: Option<impl Into<toml_edit::Value>>) {
+    match val {
+        Some(v) => set_value(entry, key, v),
+        None => {
+            entry.remove(key);
+        }
+    }
+}
+
+/// Writes the global settings the TUI edits (`S`) from `config` to config.toml, keeping
+/// comments and formatting like `update_server`. Unset values are removed, so their
+/// defaults apply again. Nothing else in the file is touched.
+pub fn update_settings(config_path: &PathBuf, config: &Config) -> Result<(), anyhow::Error> {
+    let mut doc = read_toml_document(config_path)?;
+    let root = doc.as_table_mut();
+
+    set_value(root, "local_output_dir", config.local_output_dir.as_str());
+    set_or_remove(root, "default_user", config.default_user.as_deref());
+    set_or_remove(root, "default_file_path", config.default_file_path.as_deref());
+    set_or_remove(root, "default_file_name", config.default_file_name.as_deref());
+    set_or_remove(root, "default_identity_file", config.default_identity_file.as_deref());
+    set_or_remove_value(root, "max_parallel", config.max_parallel.map(|n| n as i64));
+    set_or_remove_value(root, "cert_warning_days", config.cert_warning_days.map(i64::from));
+    set_or_remove_value(root, "cert_critical_days", config.cert_critical_days.map(i64::from));
+    set_or_remove_value(root, "fetch_after_add", config.fetch_after_add);
+
+    let tmp = config_path.with_extension("toml.tmp");
+    std::fs::write(&tmp, doc.to_string()).map_err(|e| {
+        anyhow::anyhow!(
+            "Couldn't save config.toml — check file permissions at {}: {}",
+            config_path.display(),
+            e
+        )
+    })?;
+    std::fs::rename(&tmp, config_path)?;
+    Ok(())
+}
+
+/// Renames the [[server]] entry called `from` in config.toml to `to`.
+pub fn rename_server(config_path: &PathBuf, from: &str, to: &str) -> Result<(), anyhow::Error> {
+    let mut doc = read_toml_document(config_path)?;
+
+    let entry = doc["server"]
+        .as_array_of_tables_mut()
+        .and_then(|servers| servers.iter_mut().find(|t| t["name"].as_str() == Some(from)))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Server '{}' not found in {} (servers from included files are renamed in those files)",
+                from,
+                config_path.display()
+            )
+        })?;
+    set_value(entry, "name", to);
+
+    let tmp = config_path.with_extension("toml.tmp");
+    std::fs::write(&tmp, doc.to_string()).map_err(|e| {
+        anyhow::anyhow!(
+            "Couldn't save config.toml — check file permissions at {}: {}",
+            config_path.display(),
+            e
+        )
+    })?;
+    std::fs::rename(&tmp, config_path)?;
+    Ok(())
+}
+
 /// Remove all [[server]] entries with the given name from config.toml.
 pub fn remove_server(config_path: &PathBuf, name: &str) -> Result<(), anyhow::Error> {
-    let content = std::fs::read_to_string(config_path)?;
-    let mut doc: DocumentMut = content
-        .parse()
-        .map_err(|e| anyhow::anyhow!("Failed to parse config.toml: {}", e))?;
+    let mut doc = read_toml_document(config_path)?;
 
     if let Some(servers) = doc["server"].as_array_of_tables_mut() {
         let len_before = servers.len();
@@ -273,6 +1102,7 @@ mod config_tests {
             file_name: None,
             context_name: None,
             identity_file: None,
+            ..Default::default()
         }
     }
 
@@ -335,6 +1165,28 @@ target_cluster_ip = "10.0.0.1"
         assert_eq!(result.servers[0].name, "existing");
     }
 
+    #[test]
+    fn test_add_and_update_server_write_ssh_port() {
+        let f = write_temp_config("local_output_dir = \"/tmp/kube\"\n");
+        let path = f.path().to_path_buf();
+
+        let mut server = make_server("s1");
+        server.ssh_port = Some(2200);
+        add_server(&path, &server).expect("add_server should succeed");
+        let result = load_config(path.to_str().unwrap()).expect("load should succeed");
+        assert_eq!(result.servers[0].ssh_port(), 2200);
+
+        server.ssh_port = Some(2222);
+        update_server(&path, &server).expect("update_server should succeed");
+        let result = load_config(path.to_str().unwrap()).expect("load should succeed");
+        assert_eq!(result.servers[0].ssh_port(), 2222);
+
+        server.ssh_port = None;
+        update_server(&path, &server).expect("update_server should succeed");
+        let content = std::fs::read_to_string(&path).expect("read");
+        assert!(!content.contains("ssh_port"), "unset port should be removed");
+    }
+
     #[test]
     fn test_add_server_preserves_comments() {
         let initial = "# This is my config\nlocal_output_dir = \"/tmp/kube\"\n";
@@ -347,6 +1199,75 @@ target_cluster_ip = "10.0.0.1"
         assert!(content.contains("# This is my config"), "comment should be preserved");
     }
 
+    #[test]
+    fn test_update_settings_keeps_comments_and_servers() {
+        let initial = r#"# my fleet
+local_output_dir = "/tmp/kube"
+default_user = "ubuntu" # most nodes
+max_parallel = 4
+
+[[server]]
+name = "existing"
+address = "1.2.3.4"
+target_cluster_ip = "10.0.0.1"
+"#;
+        let f = write_temp_config(initial);
+        let path = f.path().to_path_buf();
+        let mut config = load_config(path.to_str().unwrap()).expect("load should succeed");
+        config.default_user = Some("admin".to_string());
+        config.max_parallel = None;
+        config.cert_warning_days = Some(14);
+        config.fetch_after_add = Some(true);
+
+        update_settings(&path, &config).expect("update_settings should succeed");
+
+        let content = std::fs::read_to_string(&path).expect("read");
+        assert!(content.starts_with("# my fleet"), "comment should be preserved");
+        let result = load_config(path.to_str().unwrap()).expect("load should succeed");
+        assert_eq!(result.default_user.as_deref(), Some("admin"));
+        assert_eq!(result.max_parallel, None);
+        assert_eq!(result.cert_warning_days, Some(14));
+        assert_eq!(result.fetch_after_add, Some(true));
+        assert_eq!(result.servers.len(), 1);
+    }
+
+    #[test]
+    fn test_edits_keep_comments_and_key_order() {
+        let initial = r#"# fleet
+local_output_dir = "/tmp/kube"
+
+# first node
+[[server]]
+name = "a"
+address = "1.2.3.4"   # lab switch
+user = "pi"
+target_cluster_ip = "10.0.0.1"
+
+# second node
+[[server]]
+name = "b"
+address = "5.6.7.8"
+target_cluster_ip = "10.0.0.2"
+"#;
+        let f = write_temp_config(initial);
+        let path = f.path().to_path_buf();
+
+        let mut a = load_config(path.to_str().unwrap()).unwrap().servers[0].clone();
+        a.address = "1.2.3.5".to_string();
+        update_server(&path, &a).unwrap();
+        rename_server(&path, "b", "c").unwrap();
+        add_server(&path, &make_server("d")).unwrap();
+        remove_server(&path, "d").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            initial
+                .replace("1.2.3.4", "1.2.3.5")
+                .replace("name = \"b\"", "name = \"c\"")
+        );
+    }
+
     #[test]
     fn test_remove_server_removes_correct_entry() {
         let initial = r#"
@@ -372,6 +1293,100 @@ target_cluster_ip = "10.0.0.2"
         assert_eq!(result.servers[0].name, "keep-me");
     }
 
+    #[test]
+    fn test_rename_server_keeps_fields() {
+        let f = write_temp_config(
+            "local_output_dir = \"/tmp/kube\"\n\n[[server]]\n# lab node\nname = \"old\"\naddress = \"1.2.3.4\"\n",
+        );
+        let path = f.path().to_path_buf();
+
+        rename_server(&path, "old", "new").expect("rename should succeed");
+        assert!(rename_server(&path, "old", "other").is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# lab node"));
+        let result = load_config(path.to_str().unwrap()).expect("load should succeed");
+        assert_eq!(result.servers[0].name, "new");
+        assert_eq!(result.servers[0].address, "1.2.3.4");
+    }
+
+    #[test]
+    fn test_max_parallel_from_config() {
+        let f = write_temp_config("local_output_dir = \"/tmp/kube\"\nmax_parallel = 3\n");
+        let config = load_config(f.path().to_str().unwrap()).expect("should parse");
+        assert_eq!(config.max_parallel(), 3);
+    }
+
+    #[test]
+    fn test_renew_command_server_overrides_default() {
+        let f = write_temp_config(
+            "local_output_dir = \"/tmp/kube\"\nrenew_command = \"k3s certificate rotate\"\n\n\
+             [[server]]\nname = \"a\"\naddress = \"1.1.1.1\"\ntarget_cluster_ip = \"1.1.1.1\"\n\n\
+             [[server]]\nname = \"b\"\naddress = \"2.2.2.2\"\ntarget_cluster_ip = \"2.2.2.2\"\n\
+             renew_command = \"systemctl restart k3s-agent\"\n",
+        );
+        let config = load_config(f.path().to_str().unwrap()).expect("should parse");
+        assert_eq!(config.servers[0].renew_command(&config), "k3s certificate rotate");
+        assert_eq!(config.servers[1].renew_command(&config), "systemctl restart k3s-agent");
+        assert_eq!(
+            config.servers[0].renew_command(&Config::default()),
+            "systemctl restart k3s"
+        );
+    }
+
+    #[test]
+    fn test_transport_and_transfer_fallback() {
+        let f = write_temp_config(
+            "local_output_dir = \"/tmp/kube\"\ndefault_transfer = \"sftp\"\ndefault_transport = \"openssh\"\n\n\
+             [[server]]\nname = \"a\"\naddress = \"1.1.1.1\"\ntarget_cluster_ip = \"1.1.1.1\"\n\n\
+             [[server]]\nname = \"b\"\naddress = \"2.2.2.2\"\ntarget_cluster_ip = \"2.2.2.2\"\n\
+             transfer = \"exec\"\ntransport = \"libssh2\"\n",
+        );
+        let config = load_config(f.path().to_str().unwrap()).expect("should parse");
+        assert_eq!(config.servers[0].transfer(&config), crate::ssh::Transfer::Sftp);
+        assert_eq!(config.servers[1].transfer(&config), crate::ssh::Transfer::Exec);
+        assert_eq!(
+            config.servers[0].transfer(&Config::default()),
+            crate::ssh::Transfer::Exec
+        );
+        assert!(config.sftp_sudo_fallback());
+        assert_eq!(config.servers[0].transport(&config), crate::ssh::Transport::Openssh);
+        assert_eq!(config.servers[1].transport(&config), crate::ssh::Transport::Libssh2);
+    }
+
+    #[test]
+    fn test_fetch_timeout_fallback() {
+        let config = Config {
+            fetch_timeout_seconds: Some(30),
+            ..Default::default()
+        };
+        let mut server = Server::default();
+        assert_eq!(server.fetch_timeout(&config), std::time::Duration::from_secs(30));
+        assert_eq!(
+            server.fetch_timeout(&Config::default()),
+            std::time::Duration::from_secs(120)
+        );
+        server.fetch_timeout_seconds = Some(5);
+        assert_eq!(server.fetch_timeout(&config), std::time::Duration::from_secs(5));
+        server.fetch_timeout_seconds = Some(0);
+        assert_eq!(server.fetch_timeout(&config), std::time::Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_max_parallel_zero_or_missing_uses_default() {
+        let unset = Config::default();
+        assert_eq!(unset.max_parallel(), DEFAULT_MAX_PARALLEL);
+        let zero = Config {
+            max_parallel: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            zero.max_parallel(),
+            unset.max_parallel(),
+            "0 must not stall every fetch"
+        );
+    }
+
     #[test]
     fn test_load_config_with_bitwarden_section() {
         let content = r#"
@@ -7,15 +7,32 @@ use base64::{Engine as _, engine::general_purpose};
 #[cfg(not(target_os = "macos"))]
 use std::collections::HashMap;
 
+#[cfg(not(target_os = "macos"))]
+use chacha20poly1305::{
+    KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+
+use zeroize::Zeroizing;
+
 pub const SERVICE: &str = "kube_config_updater";
 pub const DEFAULT_ACCOUNT: &str = "_default";
+/// Prefix for accounts that hold SSH private-key passphrases rather than server passwords.
+pub const PASSPHRASE_PREFIX: &str = "passphrase:";
+/// Prefix for accounts that hold a server's sudo password when it differs from the SSH one.
+pub const SUDO_PREFIX: &str = "sudo:";
+/// Prefix of the password variables read when `credentials_from_env` is enabled.
+pub const ENV_PASSWORD_PREFIX: &str = "KCU_PASSWORD_";
+/// Shared password variable, the environment counterpart of `DEFAULT_ACCOUNT`.
+pub const ENV_PASSWORD_DEFAULT: &str = "KCU_PASSWORD_DEFAULT";
 
 /// Result of a credential lookup.
 ///
 /// Does NOT derive Debug to prevent passwords from appearing in logs or
 /// debug output. A manual Debug impl is provided that redacts the password.
+/// A found password is zeroed when the result and whatever it is moved into are dropped.
 pub enum CredentialResult {
-    Found(String),
+    Found(Zeroizing<String>),
     NotFound,
     Unavailable(String),
 }
@@ -32,6 +49,79 @@ impl std::fmt::Debug for CredentialResult {
     }
 }
 
+/// Where credentials are kept, from the `credential_backend` config key.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The OS keyring, falling back to the encrypted file when there is none (after
+    /// consent in the TUI).
+    #[default]
+    Auto,
+    /// Only the OS keyring.
+    Keyring,
+    /// Only the encrypted file store. Not available on macOS.
+    File,
+    /// A user-supplied script; see `CommandKeyring`.
+    Command,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Backend::Auto => "auto",
+            Backend::Keyring => "keyring",
+            Backend::File => "file",
+            Backend::Command => "command",
+        })
+    }
+}
+
+/// Set from the config at startup; see `set_backend`.
+static BACKEND: std::sync::OnceLock<(Backend, Option<String>)> = std::sync::OnceLock::new();
+
+/// Uses `backend` (and for `Backend::Command`, `command`) for the rest of the process.
+/// Called at startup, before any lookup; later calls are ignored.
+pub fn set_backend(backend: Backend, command: Option<String>) {
+    let _ = BACKEND.set((backend, command));
+}
+
+fn backend() -> Backend {
+    BACKEND.get().map(|(backend, _)| *backend).unwrap_or_default()
+}
+
+/// Whether a keyring that turns out to be unavailable may be replaced by the file
+/// store. Only `auto` allows it; the other backends stick to what was configured.
+pub fn file_fallback_allowed() -> bool {
+    backend() == Backend::Auto
+}
+
+/// The stores of the configured backend in lookup order. A lookup only moves on to
+/// the next store while the previous one is unavailable; new credentials go to the first.
+fn stores() -> Vec<Box<dyn KeyringBackend>> {
+    match backend() {
+        Backend::Command => vec![Box::new(CommandKeyring {
+            command: BACKEND.get().and_then(|(_, command)| command.clone()),
+        })],
+        #[cfg(not(target_os = "macos"))]
+        Backend::Auto => vec![Box::new(RealKeyring), Box::new(FileKeyring::default_store())],
+        #[cfg(not(target_os = "macos"))]
+        Backend::File => vec![Box::new(FileKeyring::default_store())],
+        _ => vec![Box::new(RealKeyring)],
+    }
+}
+
+/// Asks each store in turn until one isn't unavailable.
+fn lookup(get: impl Fn(&dyn KeyringBackend) -> CredentialResult) -> CredentialResult {
+    let mut result = CredentialResult::Unavailable("no credential store".to_string());
+    for store in stores() {
+        result = get(store.as_ref());
+        if !matches!(result, CredentialResult::Unavailable(_)) {
+            break;
+        }
+    }
+    result
+}
+
 /// Abstraction over the OS keyring, primarily to allow mock injection in tests.
 pub trait KeyringBackend {
     fn get(&self, service: &str, account: &str) -> CredentialResult;
@@ -117,7 +207,7 @@ mod macos_keychain {
 impl KeyringBackend for RealKeyring {
     fn get(&self, service: &str, account: &str) -> CredentialResult {
         match macos_keychain::get(service, account) {
-            Ok(Some(password)) => CredentialResult::Found(password),
+            Ok(Some(password)) => CredentialResult::Found(Zeroizing::new(password)),
             Ok(None) => CredentialResult::NotFound,
             Err(e) => CredentialResult::Unavailable(e),
         }
@@ -138,7 +228,7 @@ impl KeyringBackend for RealKeyring {
         match Entry::new(service, account) {
             Err(e) => CredentialResult::Unavailable(e.to_string()),
             Ok(entry) => match entry.get_password() {
-                Ok(password) => CredentialResult::Found(password),
+                Ok(password) => CredentialResult::Found(Zeroizing::new(password)),
                 Err(KeyringError::NoEntry) => CredentialResult::NotFound,
                 Err(e) => CredentialResult::Unavailable(e.to_string()),
             },
@@ -156,19 +246,127 @@ impl KeyringBackend for RealKeyring {
     }
 }
 
+// ─── Command backend ──────────────────────────────────────────────────────────
+
+/// `credential_backend = "command"`: runs `credential_command` through `sh -c` with
+/// `get <account>`, `set <account>` (password on stdin) or `delete <account>` appended.
+/// `get` prints the password and exits 0, or prints nothing when there is none. Any
+/// other exit status is an error, with stderr as the message.
+pub struct CommandKeyring {
+    command: Option<String>,
+}
+
+impl CommandKeyring {
+    fn run(&self, operation: &str, account: &str, input: Option<&str>) -> Result<String, String> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let command = self
+            .command
+            .as_deref()
+            .ok_or("credential_backend = \"command\" needs credential_command")?;
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", command))
+            .arg("sh")
+            .arg(operation)
+            .arg(account)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("credential_command failed to start: {}", e))?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("writing to credential_command: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("credential_command failed: {}", e))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Err(if stderr.is_empty() {
+                format!("credential_command {} exited with {}", operation, output.status)
+            } else {
+                stderr
+            })
+        }
+    }
+}
+
+impl KeyringBackend for CommandKeyring {
+    fn get(&self, _service: &str, account: &str) -> CredentialResult {
+        match self.run("get", account, None) {
+            Ok(out) => match out.trim_end_matches(['\r', '\n']) {
+                "" => CredentialResult::NotFound,
+                password => CredentialResult::Found(Zeroizing::new(password.to_string())),
+            },
+            Err(e) => CredentialResult::Unavailable(e),
+        }
+    }
+
+    fn set(&self, _service: &str, account: &str, password: &str) -> Result<(), String> {
+        self.run("set", account, Some(password)).map(|_| ())
+    }
+
+    fn delete(&self, _service: &str, account: &str) -> Result<(), String> {
+        self.run("delete", account, None).map(|_| ())
+    }
+}
+
 // ─── File-based fallback keyring (Linux / non-macOS) ──────────────────────────
 //
 // Used when the D-Bus Secret Service daemon is not available.
-// Passwords are stored in a plain-text file with 0600 permissions (owner-read-only).
-// This is the same security model as ~/.kube/config and ~/.ssh/id_rsa.
+// The whole store is encrypted under a master passphrase: the key is derived with
+// argon2id and the entries are sealed with XChaCha20-Poly1305. The file is also kept
+// at 0600, but a copy of it (backup, leaked home directory) reveals nothing without
+// the passphrase.
 //
 // File location: ~/.config/kube_config_updater/credentials
-// Format: one entry per line, tab-separated: account_name <TAB> base64(password)
-// Lines starting with '#' are comments.
+// Format: '#' comment lines, then ENCRYPTED_MAGIC, base64(salt), base64(nonce) and
+// base64(ciphertext), one per line. The plaintext is one entry per line:
+// account_name <TAB> base64(password).
+//
+// Older releases wrote those entry lines unencrypted. Such a file is still read, and
+// is encrypted the first time it is unlocked or written with a master passphrase.
+
+#[cfg(not(target_os = "macos"))]
+const ENCRYPTED_MAGIC: &str = "kcu-encrypted-v1";
+
+/// Environment variable holding the file store's master passphrase, for daemons and
+/// other runs without a terminal to prompt on.
+pub const MASTER_PASSPHRASE_ENV: &str = "KUBE_CONFIG_UPDATER_MASTER_PASSPHRASE";
+
+/// Master passphrase cached for the lifetime of the process once it has unlocked the store.
+#[cfg(not(target_os = "macos"))]
+static MASTER_PASSPHRASE: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// The cached master passphrase, else the one from `MASTER_PASSPHRASE_ENV`.
+#[cfg(not(target_os = "macos"))]
+fn master_passphrase() -> Option<String> {
+    let cached = MASTER_PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    cached.or_else(|| std::env::var(MASTER_PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()))
+}
+
+/// The store key last derived, with the passphrase and salt it came from. argon2id is
+/// slow on purpose, so lookups reuse the key until a write seals the store under a new salt.
+#[cfg(not(target_os = "macos"))]
+static STORE_KEY: std::sync::Mutex<Option<StoreKey>> = std::sync::Mutex::new(None);
+
+#[cfg(not(target_os = "macos"))]
+struct StoreKey {
+    passphrase: String,
+    salt: Vec<u8>,
+    key: [u8; 32],
+}
 
 #[cfg(not(target_os = "macos"))]
 pub struct FileKeyring {
     path: std::path::PathBuf,
+    passphrase: Option<String>,
 }
 
 #[cfg(not(target_os = "macos"))]
@@ -180,29 +378,69 @@ impl FileKeyring {
             .join("credentials")
     }
 
-    fn load(&self) -> HashMap<String, String> {
+    /// The store at its default path, using the session's master passphrase.
+    fn default_store() -> Self {
+        FileKeyring {
+            path: Self::default_path(),
+            passphrase: master_passphrase(),
+        }
+    }
+
+    /// Reads the store. Returns whether it was still in the old plaintext format.
+    fn load_with_format(&self) -> Result<(HashMap<String, String>, bool), String> {
         let content = match std::fs::read_to_string(&self.path) {
             Ok(c) => c,
-            Err(_) => return HashMap::new(),
+            Err(_) => return Ok((HashMap::new(), false)),
         };
-        let mut map = HashMap::new();
-        for line in content.lines() {
-            if line.starts_with('#') || line.trim().is_empty() {
-                continue;
-            }
-            if let Some((account, b64)) = line.split_once('\t')
-                && let Ok(pw_bytes) = general_purpose::STANDARD.decode(b64.trim())
-                && let Ok(pw) = String::from_utf8(pw_bytes)
-            {
-                map.insert(account.to_string(), pw);
-            }
+        let mut lines = content
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty());
+        let mut probe = lines.clone();
+        if probe.next() != Some(ENCRYPTED_MAGIC) {
+            return Ok((parse_entries(&content), true));
+        }
+
+        let passphrase = self.passphrase.as_deref().ok_or_else(|| {
+            format!(
+                "credentials file is encrypted; set {} or run interactively to unlock it",
+                MASTER_PASSPHRASE_ENV
+            )
+        })?;
+        lines.next();
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|l| general_purpose::STANDARD.decode(l.trim()).ok())
+                .ok_or_else(|| format!("credentials file is corrupt (bad {})", name))
+        };
+        let salt = field("salt")?;
+        let nonce = field("nonce")?;
+        let ciphertext = field("ciphertext")?;
+        if nonce.len() != 24 {
+            return Err("credentials file is corrupt (bad nonce)".to_string());
         }
-        map
+
+        let cipher = XChaCha20Poly1305::new(&store_key(passphrase, &salt)?.into());
+        let plaintext = cipher
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| "wrong master passphrase for the credentials file".to_string())?;
+        let plaintext = String::from_utf8(plaintext).map_err(|_| "credentials file is corrupt".to_string())?;
+        Ok((parse_entries(&plaintext), false))
+    }
+
+    fn load(&self) -> Result<HashMap<String, String>, String> {
+        self.load_with_format().map(|(store, _)| store)
     }
 
     fn save(&self, store: &HashMap<String, String>) -> Result<(), String> {
+        use chacha20poly1305::aead::rand_core::RngCore;
         use std::io::Write;
 
+        let passphrase = self
+            .passphrase
+            .as_deref()
+            .ok_or("a master passphrase is required to encrypt the credentials file")?;
+
         let parent = self.path.parent().ok_or("invalid credentials path")?;
         std::fs::create_dir_all(parent).map_err(|e| format!("could not create credentials directory: {}", e))?;
 
@@ -214,16 +452,31 @@ impl FileKeyring {
                 .map_err(|e| format!("could not set directory permissions: {}", e))?;
         }
 
-        let mut content = String::from(
-            "# kube_config_updater credentials\n\
-             # Stored with restricted permissions (0600) — only you can read this file.\n\
-             # Same security model as ~/.kube/config and ~/.ssh/id_rsa.\n",
-        );
+        let mut plaintext = String::new();
         for (account, password) in store {
             let b64 = general_purpose::STANDARD.encode(password.as_bytes());
-            content.push_str(&format!("{}\t{}\n", account, b64));
+            plaintext.push_str(&format!("{}\t{}\n", account, b64));
         }
 
+        // A fresh salt and nonce on every write: nothing is ever sealed twice under one nonce.
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let cipher = XChaCha20Poly1305::new(&store_key(passphrase, &salt)?.into());
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "could not encrypt credentials".to_string())?;
+
+        let content = format!(
+            "# kube_config_updater credentials\n\
+             # Encrypted with your master passphrase (argon2id + XChaCha20-Poly1305).\n\
+             {}\n{}\n{}\n{}\n",
+            ENCRYPTED_MAGIC,
+            general_purpose::STANDARD.encode(salt),
+            general_purpose::STANDARD.encode(nonce),
+            general_purpose::STANDARD.encode(ciphertext),
+        );
+
         // Write to a temp file first, then rename atomically
         let tmp = self.path.with_extension("tmp");
         let mut file = std::fs::OpenOptions::new()
@@ -248,58 +501,171 @@ impl FileKeyring {
 
         Ok(())
     }
+
+    /// Checks the passphrase against the store and encrypts a plaintext store in place.
+    fn unlock(&self) -> Result<(), String> {
+        let (store, legacy) = self.load_with_format()?;
+        if legacy && !store.is_empty() {
+            self.save(&store)?;
+            log::info!("Encrypted the credentials file {:?}", self.path);
+        }
+        Ok(())
+    }
+}
+
+/// Parses `account <TAB> base64(password)` lines, skipping comments and malformed lines.
+#[cfg(not(target_os = "macos"))]
+fn parse_entries(content: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for line in content.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if let Some((account, b64)) = line.split_once('\t')
+            && let Ok(pw_bytes) = general_purpose::STANDARD.decode(b64.trim())
+            && let Ok(pw) = String::from_utf8(pw_bytes)
+        {
+            map.insert(account.to_string(), pw);
+        }
+    }
+    map
+}
+
+/// The store key for `passphrase` and `salt`: the cached one when both match, else a
+/// freshly derived key that replaces it.
+#[cfg(not(target_os = "macos"))]
+fn store_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut cached = STORE_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cached.as_ref().filter(|k| k.passphrase == passphrase && k.salt == salt) {
+        return Ok(cached.key);
+    }
+    let key = derive_key(passphrase, salt)?;
+    *cached = Some(StoreKey {
+        passphrase: passphrase.to_string(),
+        salt: salt.to_vec(),
+        key,
+    });
+    Ok(key)
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+static DERIVATIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Derives the 256-bit store key from the master passphrase with argon2id.
+#[cfg(not(target_os = "macos"))]
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    #[cfg(test)]
+    DERIVATIONS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("could not derive credentials key: {}", e))?;
+    Ok(key)
 }
 
 #[cfg(not(target_os = "macos"))]
 impl KeyringBackend for FileKeyring {
     fn get(&self, _service: &str, account: &str) -> CredentialResult {
-        let store = self.load();
+        let store = match self.load() {
+            Ok(store) => store,
+            Err(e) => return CredentialResult::Unavailable(e),
+        };
         match store.get(account) {
-            Some(pw) => CredentialResult::Found(pw.clone()),
+            Some(pw) => CredentialResult::Found(Zeroizing::new(pw.clone())),
             None => CredentialResult::NotFound,
         }
     }
 
     fn set(&self, _service: &str, account: &str, password: &str) -> Result<(), String> {
-        let mut store = self.load();
+        let mut store = self.load()?;
         store.insert(account.to_string(), password.to_string());
         self.save(&store)
     }
 
     fn delete(&self, _service: &str, account: &str) -> Result<(), String> {
-        let mut store = self.load();
-        store.remove(account);
+        let mut store = self.load()?;
+        if store.remove(account).is_none() {
+            return Ok(());
+        }
         self.save(&store)
     }
 }
 
 // ─── Public API ───────────────────────────────────────────────────────────────
 
-/// Look up a credential for the given server name.
+/// Look up a credential for the given server name in the configured backend.
 ///
-/// On non-macOS: tries the system keyring first; if unavailable (no D-Bus daemon),
-/// falls back to the file store (transparent read — the user already consented when
-/// they stored the credential via `set_credential_file`).
+/// With `auto` on non-macOS: tries the system keyring first; if unavailable (no D-Bus
+/// daemon), falls back to the file store (transparent read — the user already consented
+/// when they stored the credential via `set_credential_file`).
 ///
 /// Falls back to the DEFAULT_ACCOUNT entry when no server-specific entry exists.
-/// Passwords are never written to any log call.
+/// Passwords are never written to any log call. Each lookup goes to the audit log.
 pub fn get_credential(server_name: &str) -> CredentialResult {
-    #[cfg(not(target_os = "macos"))]
-    {
-        let primary = get_credential_with(server_name, &RealKeyring);
-        if matches!(primary, CredentialResult::Unavailable(_)) {
-            let file = FileKeyring {
-                path: FileKeyring::default_path(),
-            };
-            return get_credential_with(server_name, &file);
-        }
-        primary
+    let result = lookup(|store| get_credential_with(server_name, store));
+    crate::audit::record(
+        crate::audit::Action::Read,
+        server_name,
+        &crate::audit::lookup_outcome(&result),
+    );
+    result
+}
+
+/// Like `get_credential`, but when `from_env` is set a `KCU_PASSWORD_<SERVER>` or
+/// `KCU_PASSWORD_DEFAULT` variable takes precedence over the keyring.
+pub fn resolve_credential(server_name: &str, from_env: bool) -> CredentialResult {
+    if from_env && let Some(pw) = env_credential_from(server_name, |var| std::env::var(var).ok()) {
+        crate::redact::register(&pw);
+        crate::audit::record(crate::audit::Action::Read, server_name, "found in the environment");
+        return CredentialResult::Found(Zeroizing::new(pw));
     }
-    #[cfg(target_os = "macos")]
-    get_credential_with(server_name, &RealKeyring)
+    get_credential(server_name)
+}
+
+/// Whether `resolve_credential` would find a password for `server_name`, for status
+/// displays. Nothing is used, so nothing is registered for redaction or audited.
+pub fn has_credential(server_name: &str, from_env: bool) -> bool {
+    if from_env && env_credential_from(server_name, |var| std::env::var(var).ok()).is_some() {
+        return true;
+    }
+    matches!(
+        lookup(|store| find_credential_with(server_name, store)),
+        CredentialResult::Found(_)
+    )
+}
+
+/// Name of the variable holding `server_name`'s password: upper-cased, with anything
+/// other than ASCII letters and digits replaced by `_` (`prod-k3s` → `KCU_PASSWORD_PROD_K3S`).
+pub fn env_var_name(server_name: &str) -> String {
+    let suffix: String = server_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", ENV_PASSWORD_PREFIX, suffix)
+}
+
+fn env_credential_from(server_name: &str, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    [env_var_name(server_name), ENV_PASSWORD_DEFAULT.to_string()]
+        .iter()
+        .find_map(|var| lookup(var).filter(|pw| !pw.is_empty()))
 }
 
 pub fn get_credential_with(server_name: &str, backend: &dyn KeyringBackend) -> CredentialResult {
+    let result = find_credential_with(server_name, backend);
+    if let CredentialResult::Found(pw) = &result {
+        crate::redact::register(pw);
+    }
+    result
+}
+
+/// The server's own entry, else the `DEFAULT_ACCOUNT` one.
+fn find_credential_with(server_name: &str, backend: &dyn KeyringBackend) -> CredentialResult {
     match backend.get(SERVICE, server_name) {
         CredentialResult::NotFound => match backend.get(SERVICE, DEFAULT_ACCOUNT) {
             CredentialResult::Found(pw) => CredentialResult::Found(pw),
@@ -309,59 +675,274 @@ pub fn get_credential_with(server_name: &str, backend: &dyn KeyringBackend) -> C
     }
 }
 
-/// Store a credential for the given server name using the primary keyring backend.
-/// On Linux this requires a running D-Bus Secret Service daemon.
-/// If unavailable, the caller should present a consent dialog and then call
-/// `set_credential_file` instead.
+/// Store a credential for the given server name in the configured backend's first store.
+/// With `auto` on Linux this requires a running D-Bus Secret Service daemon.
+/// If unavailable (and `file_fallback_allowed`), the caller should present a consent
+/// dialog and then call `set_credential_file` instead.
 pub fn set_credential(server_name: &str, password: &str) -> Result<(), String> {
-    set_credential_with(server_name, password, &RealKeyring)
+    let result = set_credential_with(server_name, password, stores()[0].as_ref());
+    crate::audit::record(
+        crate::audit::Action::Stored,
+        server_name,
+        &crate::audit::result_outcome(&result),
+    );
+    result
 }
 
 pub fn set_credential_with(server_name: &str, password: &str, backend: &dyn KeyringBackend) -> Result<(), String> {
+    crate::redact::register(password);
     backend.set(SERVICE, server_name, password)
 }
 
-/// Store a credential in the file-based fallback store with 0600 permissions.
+/// Store a credential in the encrypted file-based fallback store.
 ///
 /// Only call this after the user has explicitly consented to file-based storage
-/// (i.e., accepted the `KeyringFallbackConsent` dialog).
+/// (i.e., accepted the `KeyringFallbackConsent` dialog) and the store has a master
+/// passphrase for this session (see `unlock_credential_file`).
 ///
 /// On macOS the security CLI is always available, so this path is never reached;
 /// it is provided here only to keep the call-site cross-platform.
 pub fn set_credential_file(server_name: &str, password: &str) -> Result<(), String> {
+    #[cfg(not(target_os = "macos"))]
+    let result = set_credential_with(server_name, password, &FileKeyring::default_store());
+    #[cfg(target_os = "macos")]
+    let result = set_credential_with(server_name, password, &RealKeyring);
+    crate::audit::record(
+        crate::audit::Action::Stored,
+        server_name,
+        &crate::audit::result_outcome(&result),
+    );
+    result
+}
+
+/// Whether the file store can be written this session without asking for a master passphrase.
+pub fn credential_file_unlocked() -> bool {
     #[cfg(not(target_os = "macos"))]
     {
-        let file = FileKeyring {
-            path: FileKeyring::default_path(),
-        };
-        set_credential_with(server_name, password, &file)
+        master_passphrase().is_some()
     }
     #[cfg(target_os = "macos")]
-    set_credential_with(server_name, password, &RealKeyring)
+    true
 }
 
-/// Remove the credential for the given server name from the OS keyring.
-/// On non-macOS, also removes from the file store (in case the credential was
-/// stored there as a fallback).
-pub fn delete_credential(server_name: &str) -> Result<(), String> {
+/// Checks `passphrase` against the file store and keeps it for the rest of the process.
+/// When no store exists yet, the passphrase becomes the one it will be encrypted with.
+/// A store still in the old plaintext format is encrypted with it straight away.
+pub fn unlock_credential_file(passphrase: &str) -> Result<(), String> {
     #[cfg(not(target_os = "macos"))]
     {
-        // Try keyring first (may not be available — ignore that specific error)
-        let _ = delete_credential_with(server_name, &RealKeyring);
-        // Always also attempt to remove from file store (idempotent)
-        let file = FileKeyring {
+        if passphrase.is_empty() {
+            return Err("the master passphrase cannot be empty".to_string());
+        }
+        FileKeyring {
             path: FileKeyring::default_path(),
-        };
-        delete_credential_with(server_name, &file)
+            passphrase: Some(passphrase.to_string()),
+        }
+        .unlock()?;
+        *MASTER_PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner()) = Some(passphrase.to_string());
+        Ok(())
     }
     #[cfg(target_os = "macos")]
-    delete_credential_with(server_name, &RealKeyring)
+    {
+        let _ = passphrase;
+        Ok(())
+    }
+}
+
+/// Asks on the terminal for the master passphrase when a file store exists and none
+/// is cached or set in `MASTER_PASSPHRASE_ENV`; with `credential_backend = "file"` also
+/// for a store yet to be created. Without a terminal nothing is asked, and lookups
+/// report the store as locked. Backends without the file store never ask.
+pub fn unlock_credential_file_interactive() {
+    #[cfg(not(target_os = "macos"))]
+    {
+        use std::io::IsTerminal;
+
+        if matches!(backend(), Backend::Keyring | Backend::Command) {
+            return;
+        }
+        if let Some(passphrase) = master_passphrase() {
+            if let Err(e) = unlock_credential_file(&passphrase) {
+                log::warn!("Could not unlock the credentials file: {}", e);
+            }
+            return;
+        }
+        let path = FileKeyring::default_path();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            // The file is the only store, so it needs a passphrase before the first write.
+            Err(_) if backend() == Backend::File => String::new(),
+            Err(_) => return,
+        };
+        if !std::io::stdin().is_terminal() {
+            return;
+        }
+        let prompt = if content.lines().any(|l| l == ENCRYPTED_MAGIC) {
+            format!("Master passphrase for {}: ", path.display())
+        } else if content.is_empty() && backend() == Backend::File {
+            format!("Choose a master passphrase for the new {}: ", path.display())
+        } else if !parse_entries(&content).is_empty() {
+            format!("{} is not encrypted yet. Choose a master passphrase: ", path.display())
+        } else {
+            return;
+        };
+        match rpassword::prompt_password(prompt) {
+            Ok(passphrase) => {
+                if let Err(e) = unlock_credential_file(&passphrase) {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Could not read the master passphrase: {}", e),
+        }
+    }
+}
+
+/// Remove the credential for the given server name from every store of the backend.
+/// With `auto` on non-macOS that is the keyring and the file store (in case the
+/// credential was stored there as a fallback); only the last store's error is reported,
+/// since the keyring may well be unavailable.
+pub fn delete_credential(server_name: &str) -> Result<(), String> {
+    let mut result = Ok(());
+    for store in stores() {
+        result = delete_credential_with(server_name, store.as_ref());
+    }
+    crate::audit::record(
+        crate::audit::Action::Deleted,
+        server_name,
+        &crate::audit::result_outcome(&result),
+    );
+    result
 }
 
 pub fn delete_credential_with(server_name: &str, backend: &dyn KeyringBackend) -> Result<(), String> {
     backend.delete(SERVICE, server_name)
 }
 
+/// Moves `from`'s own credential to `to` in whichever store holds it. Returns whether
+/// there was one to move.
+pub fn rename_credential(from: &str, to: &str) -> Result<bool, String> {
+    let stores = stores();
+    let mut moved = false;
+    for (i, store) in stores.iter().enumerate() {
+        moved |= match rename_credential_with(from, to, store.as_ref()) {
+            Err(e) if i + 1 == stores.len() => return Err(e),
+            result => result.unwrap_or(false),
+        };
+    }
+    if moved {
+        crate::audit::record(crate::audit::Action::Stored, to, &format!("ok, renamed from {}", from));
+        crate::audit::record(crate::audit::Action::Deleted, from, &format!("ok, renamed to {}", to));
+    }
+    Ok(moved)
+}
+
+pub fn rename_credential_with(from: &str, to: &str, backend: &dyn KeyringBackend) -> Result<bool, String> {
+    match backend.get(SERVICE, from) {
+        CredentialResult::Found(pw) => {
+            backend.set(SERVICE, to, &pw)?;
+            backend.delete(SERVICE, from)?;
+            Ok(true)
+        }
+        CredentialResult::NotFound => Ok(false),
+        CredentialResult::Unavailable(e) => Err(e),
+    }
+}
+
+/// Whether `server_name` has a credential of its own in the keyring or file store.
+/// Unlike `get_credential`, the `_default` entry doesn't count.
+pub fn has_own_credential(server_name: &str) -> bool {
+    stores()
+        .iter()
+        .any(|store| matches!(store.get(SERVICE, server_name), CredentialResult::Found(_)))
+}
+
+/// Server names with credentials in the file store, leaving out `_default` and key
+/// passphrases; a sudo password counts for its server.
+/// Empty when the store is absent or locked. The OS keyring can't be listed, so
+/// credentials kept there are only found by name.
+pub fn file_store_accounts() -> Vec<String> {
+    server_accounts(file_store_keys())
+}
+
+/// The server names among stored `accounts`, sorted, each once.
+fn server_accounts(accounts: Vec<String>) -> Vec<String> {
+    let mut accounts: Vec<String> = accounts
+        .into_iter()
+        .filter(|account| account != DEFAULT_ACCOUNT && !account.starts_with(PASSPHRASE_PREFIX))
+        .map(|account| account.strip_prefix(SUDO_PREFIX).map(str::to_string).unwrap_or(account))
+        .collect();
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
+/// Every account in the file store, as stored. Empty when it is absent or locked.
+pub fn file_store_keys() -> Vec<String> {
+    #[cfg(not(target_os = "macos"))]
+    {
+        FileKeyring::default_store()
+            .load()
+            .unwrap_or_default()
+            .into_keys()
+            .collect()
+    }
+    #[cfg(target_os = "macos")]
+    Vec::new()
+}
+
+/// Keyring account holding the passphrase for an encrypted identity file. Keyed by
+/// the key's path so servers that share a key share one stored passphrase.
+pub fn passphrase_account(identity_file: &str) -> String {
+    format!("{}{}", PASSPHRASE_PREFIX, identity_file)
+}
+
+/// Keyring account holding a server's sudo password, used instead of the SSH password
+/// for servers with `sudo_password = true`.
+pub fn sudo_account(server_name: &str) -> String {
+    format!("{}{}", SUDO_PREFIX, server_name)
+}
+
+/// Look up the stored passphrase for an identity file, with the same keyring → file
+/// fallback as `get_credential`. Unlike server passwords there is no default entry.
+pub fn get_key_passphrase(identity_file: &str) -> CredentialResult {
+    let result = lookup(|store| get_key_passphrase_with(identity_file, store));
+    crate::audit::record(
+        crate::audit::Action::Read,
+        &passphrase_account(identity_file),
+        &crate::audit::lookup_outcome(&result),
+    );
+    result
+}
+
+/// Look up a server's separate sudo password, with the same fallback. There is no
+/// default entry either.
+pub fn get_sudo_password(server_name: &str) -> CredentialResult {
+    get_account(&sudo_account(server_name))
+}
+
+pub fn get_sudo_password_with(server_name: &str, backend: &dyn KeyringBackend) -> CredentialResult {
+    backend.get(SERVICE, &sudo_account(server_name))
+}
+
+/// Look up any account as stored (a server, `_default`, `sudo:…` or `passphrase:…`),
+/// with the same keyring → file fallback but no `_default` substitution.
+pub fn get_account(account: &str) -> CredentialResult {
+    let result = lookup(|store| store.get(SERVICE, account));
+    if let CredentialResult::Found(pw) = &result {
+        crate::redact::register(pw);
+    }
+    crate::audit::record(
+        crate::audit::Action::Read,
+        account,
+        &crate::audit::lookup_outcome(&result),
+    );
+    result
+}
+
+pub fn get_key_passphrase_with(identity_file: &str, backend: &dyn KeyringBackend) -> CredentialResult {
+    backend.get(SERVICE, &passphrase_account(identity_file))
+}
+
 /// Check whether credentials are available for a list of server names.
 ///
 /// Returns a map of server name to credential availability status.
@@ -395,6 +976,27 @@ pub fn keyring_error_is_unavailable(err: &str) -> bool {
         || lower.contains("secret service")
 }
 
+/// What the OS keyring is called on this platform, for the setup wizard.
+pub fn keyring_name() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macOS Keychain"
+    } else if cfg!(windows) {
+        "Windows Credential Manager"
+    } else {
+        "Secret Service (GNOME Keyring, KWallet)"
+    }
+}
+
+/// Checks that the OS keyring answers by looking up an account nothing ever stores, so
+/// no credential is read or written. `Err` says why it can't be used, e.g. no Secret
+/// Service on D-Bus.
+pub fn probe_keyring() -> Result<(), String> {
+    match RealKeyring.get(SERVICE, "_probe") {
+        CredentialResult::Unavailable(e) => Err(e),
+        _ => Ok(()),
+    }
+}
+
 /// Returns the path to the file-based credential store (for display in UI messages).
 pub fn credential_file_path() -> String {
     #[cfg(not(target_os = "macos"))]
@@ -429,7 +1031,7 @@ mod tests {
         fn get(&self, service: &str, account: &str) -> CredentialResult {
             let store = self.store.lock().unwrap();
             match store.get(&(service.to_string(), account.to_string())) {
-                Some(pw) => CredentialResult::Found(pw.clone()),
+                Some(pw) => CredentialResult::Found(Zeroizing::new(pw.clone())),
                 None => CredentialResult::NotFound,
             }
         }
@@ -452,7 +1054,7 @@ mod tests {
         let mock = MockKeyring::new();
         mock.set(SERVICE, "my-server", "secret").unwrap();
         let result = get_credential_with("my-server", &mock);
-        assert!(matches!(result, CredentialResult::Found(pw) if pw == "secret"));
+        assert!(matches!(result, CredentialResult::Found(pw) if *pw == "secret"));
     }
 
     #[test]
@@ -460,7 +1062,49 @@ mod tests {
         let mock = MockKeyring::new();
         mock.set(SERVICE, DEFAULT_ACCOUNT, "default-secret").unwrap();
         let result = get_credential_with("unknown-server", &mock);
-        assert!(matches!(result, CredentialResult::Found(pw) if pw == "default-secret"));
+        assert!(matches!(result, CredentialResult::Found(pw) if *pw == "default-secret"));
+    }
+
+    #[test]
+    fn test_key_passphrase_does_not_fall_back_to_default() {
+        let mock = MockKeyring::new();
+        mock.set(SERVICE, DEFAULT_ACCOUNT, "default-secret").unwrap();
+        assert!(matches!(
+            get_key_passphrase_with("/home/u/.ssh/id_ed25519", &mock),
+            CredentialResult::NotFound
+        ));
+        set_credential_with(&passphrase_account("/home/u/.ssh/id_ed25519"), "pp", &mock).unwrap();
+        assert!(matches!(
+            get_key_passphrase_with("/home/u/.ssh/id_ed25519", &mock),
+            CredentialResult::Found(pw) if *pw == "pp"
+        ));
+    }
+
+    #[test]
+    fn test_sudo_password_is_kept_apart_from_the_ssh_password() {
+        let mock = MockKeyring::new();
+        assert_eq!(sudo_account("prod"), "sudo:prod");
+        set_credential_with("prod", "ssh-secret", &mock).unwrap();
+        mock.set(SERVICE, DEFAULT_ACCOUNT, "default-secret").unwrap();
+        assert!(matches!(
+            get_sudo_password_with("prod", &mock),
+            CredentialResult::NotFound
+        ));
+        set_credential_with(&sudo_account("prod"), "sudo-secret", &mock).unwrap();
+        assert!(matches!(
+            get_sudo_password_with("prod", &mock),
+            CredentialResult::Found(pw) if *pw == "sudo-secret"
+        ));
+        assert!(matches!(get_credential_with("prod", &mock), CredentialResult::Found(pw) if *pw == "ssh-secret"));
+    }
+
+    #[test]
+    fn test_server_accounts_counts_a_sudo_password_for_its_server() {
+        let stored = ["prod", "sudo:prod", "sudo:dev", DEFAULT_ACCOUNT, "passphrase:/keys/id"];
+        assert_eq!(
+            server_accounts(stored.iter().map(|a| a.to_string()).collect()),
+            ["dev", "prod"]
+        );
     }
 
     #[test]
@@ -479,6 +1123,16 @@ mod tests {
         assert!(matches!(get_credential_with("srv", &mock), CredentialResult::NotFound));
     }
 
+    #[test]
+    fn test_rename_credential() {
+        let mock = MockKeyring::new();
+        set_credential_with("old", "pw", &mock).unwrap();
+        assert_eq!(rename_credential_with("old", "new", &mock), Ok(true));
+        assert!(matches!(mock.get(SERVICE, "new"), CredentialResult::Found(pw) if *pw == "pw"));
+        assert!(matches!(mock.get(SERVICE, "old"), CredentialResult::NotFound));
+        assert_eq!(rename_credential_with("old", "new", &mock), Ok(false));
+    }
+
     #[test]
     fn test_check_credentials() {
         let mock = MockKeyring::new();
@@ -489,14 +1143,151 @@ mod tests {
         assert!(matches!(&results[1].1, CredentialResult::NotFound));
     }
 
+    #[test]
+    fn test_env_credential_lookup() {
+        assert_eq!(env_var_name("prod-k3s.lab"), "KCU_PASSWORD_PROD_K3S_LAB");
+        let env: HashMap<&str, &str> = [("KCU_PASSWORD_PROD_K3S", "prod-pw"), ("KCU_PASSWORD_DEFAULT", "shared")]
+            .into_iter()
+            .collect();
+        let lookup = |var: &str| env.get(var).map(|v| v.to_string());
+        assert_eq!(env_credential_from("prod-k3s", lookup).as_deref(), Some("prod-pw"));
+        assert_eq!(env_credential_from("staging", lookup).as_deref(), Some("shared"));
+
+        let empty = |var: &str| (var == "KCU_PASSWORD_STAGING").then(String::new);
+        assert_eq!(env_credential_from("staging", empty), None);
+    }
+
+    #[test]
+    fn test_command_keyring_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("store.sh");
+        std::fs::write(
+            &script,
+            format!(
+                "dir={}\ncase \"$1\" in\n  get) cat \"$dir/$2\" 2>/dev/null || true ;;\n  set) cat > \"$dir/$2\" ;;\n  delete) rm -f \"$dir/$2\" ;;\n  *) echo \"unknown $1\" >&2; exit 2 ;;\nesac\n",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+        let store = CommandKeyring {
+            command: Some(format!("sh {}", script.display())),
+        };
+
+        assert!(matches!(
+            get_credential_with("prod", &store),
+            CredentialResult::NotFound
+        ));
+        set_credential_with("prod", "pw with spaces", &store).unwrap();
+        set_credential_with(DEFAULT_ACCOUNT, "shared-pw", &store).unwrap();
+        assert!(matches!(get_credential_with("prod", &store), CredentialResult::Found(pw) if *pw == "pw with spaces"));
+        assert!(matches!(get_credential_with("other", &store), CredentialResult::Found(pw) if *pw == "shared-pw"));
+        assert!(rename_credential_with("prod", "prod-2", &store).unwrap());
+        delete_credential_with("prod-2", &store).unwrap();
+        assert!(matches!(store.get(SERVICE, "prod-2"), CredentialResult::NotFound));
+
+        let failing = CommandKeyring {
+            command: Some("echo 'vault sealed' >&2; exit 1; :".to_string()),
+        };
+        assert!(matches!(failing.get(SERVICE, "prod"), CredentialResult::Unavailable(e) if e == "vault sealed"));
+        let missing = CommandKeyring { command: None };
+        assert!(missing.set(SERVICE, "prod", "pw").is_err());
+    }
+
     #[test]
     fn test_debug_redacts_password() {
-        let found = CredentialResult::Found("super-secret".to_string());
+        let found = CredentialResult::Found(Zeroizing::new("super-secret".to_string()));
         let debug_str = format!("{found:?}");
         assert!(!debug_str.contains("super-secret"));
         assert!(debug_str.contains("redacted"));
     }
 
+    /// The file store tests share the cached store key; run them one at a time so
+    /// derivations can be counted.
+    #[cfg(not(target_os = "macos"))]
+    static FILE_STORE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[cfg(not(target_os = "macos"))]
+    fn file_store(dir: &tempfile::TempDir, passphrase: Option<&str>) -> FileKeyring {
+        FileKeyring {
+            path: dir.path().join("credentials"),
+            passphrase: passphrase.map(str::to_string),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_file_store_is_encrypted() {
+        let _guard = FILE_STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir, Some("correct horse"));
+        store.set(SERVICE, "prod", "super-secret").unwrap();
+
+        let raw = std::fs::read_to_string(dir.path().join("credentials")).unwrap();
+        assert!(raw.contains(ENCRYPTED_MAGIC));
+        assert!(!raw.contains("prod"));
+        assert!(!raw.contains(&general_purpose::STANDARD.encode("super-secret")));
+        assert!(matches!(store.get(SERVICE, "prod"), CredentialResult::Found(pw) if *pw == "super-secret"));
+
+        let wrong = file_store(&dir, Some("battery staple"));
+        assert!(matches!(wrong.get(SERVICE, "prod"), CredentialResult::Unavailable(e) if e.contains("wrong")));
+        assert!(wrong.set(SERVICE, "other", "pw").is_err());
+        let locked = file_store(&dir, None);
+        assert!(matches!(locked.get(SERVICE, "prod"), CredentialResult::Unavailable(_)));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_plaintext_file_store_is_encrypted_on_unlock() {
+        let _guard = FILE_STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials");
+        let b64 = general_purpose::STANDARD.encode("old-secret");
+        std::fs::write(&path, format!("# kube_config_updater credentials\nprod\t{}\n", b64)).unwrap();
+
+        // Still readable without a passphrase, as written by older releases.
+        assert!(matches!(
+            file_store(&dir, None).get(SERVICE, "prod"),
+            CredentialResult::Found(pw) if *pw == "old-secret"
+        ));
+
+        file_store(&dir, Some("pp")).unlock().unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains(&b64));
+        assert!(matches!(
+            file_store(&dir, Some("pp")).get(SERVICE, "prod"),
+            CredentialResult::Found(pw) if *pw == "old-secret"
+        ));
+        assert!(file_store(&dir, Some("nope")).unlock().is_err());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_file_store_derives_key_once_per_salt() {
+        use std::sync::atomic::Ordering;
+
+        let _guard = FILE_STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir, Some("correct horse"));
+        store.set(SERVICE, "prod", "super-secret").unwrap();
+
+        let before = DERIVATIONS.load(Ordering::SeqCst);
+        assert!(matches!(store.get(SERVICE, "prod"), CredentialResult::Found(pw) if *pw == "super-secret"));
+        assert!(matches!(store.get(SERVICE, "prod_default"), CredentialResult::NotFound));
+        assert!(matches!(
+            file_store(&dir, Some("correct horse")).get(SERVICE, "prod"),
+            CredentialResult::Found(_)
+        ));
+        assert_eq!(
+            DERIVATIONS.load(Ordering::SeqCst),
+            before,
+            "lookups should reuse the key"
+        );
+
+        // A write seals under a new salt, which needs one new key; reading it back doesn't.
+        store.set(SERVICE, "staging", "other").unwrap();
+        assert!(matches!(store.get(SERVICE, "staging"), CredentialResult::Found(_)));
+        assert_eq!(DERIVATIONS.load(Ordering::SeqCst), before + 1);
+    }
+
     #[test]
     fn test_keyring_error_is_unavailable_detects_dbus() {
         assert!(keyring_error_is_unavailable(
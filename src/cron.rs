@@ -0,0 +1,228 @@
+//! Five-field cron expressions (`minute hour day-of-month month day-of-week`) for a
+//! server's `schedule` in daemon mode, evaluated in local time.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+/// How far ahead `next_after` looks; far enough for a Feb 29 in the next leap year.
+const SEARCH_YEARS: i64 = 5;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed expression. Each field is a bit set of the values it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    /// Sunday is 0; a 7 in the expression is folded into it.
+    weekdays: u64,
+    /// Whether day-of-month and day-of-week were `*`. As in cron, when both are
+    /// restricted a day matching either one counts.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl std::str::FromStr for Cron {
+    type Err = String;
+
+    /// Fields take `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`,
+    /// `8-18/2`); months and weekdays also take names (`jan`, `mon-fri`). `@hourly`,
+    /// `@daily`, `@weekly`, `@monthly` and `@yearly` stand for the usual expressions.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expanded = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other if other.starts_with('@') => return Err(format!("unknown shorthand '{}'", other)),
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_field(weekday, "day-of-week", 0, 7, &WEEKDAYS)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Cron {
+            minutes: parse_field(minute, "minute", 0, 59, &[])?,
+            hours: parse_field(hour, "hour", 0, 23, &[])?,
+            days: parse_field(day, "day-of-month", 1, 31, &[])?,
+            months: parse_field(month, "month", 1, 12, &MONTHS)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+}
+
+/// One field as a bit set over `min..=max`. `names` spell out the values from `min`.
+fn parse_field(field: &str, what: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        let n = match names.iter().position(|name| *name == lower) {
+            Some(i) => i as u32 + min,
+            None => s.parse().map_err(|_| format!("{} '{}' is not a number", what, s))?,
+        };
+        if !(min..=max).contains(&n) {
+            return Err(format!("{} {} is out of range {}-{}", what, n, min, max));
+        }
+        Ok(n)
+    };
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("{} step '{}' is not a positive number", what, step)),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("{} range '{}' runs backwards", what, range));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+impl Cron {
+    /// The first time after `after` (to the minute) that the expression matches, or
+    /// `None` when it never does, like `0 0 30 2 *`. Local times skipped by a DST change
+    /// are passed over; repeated ones fire on their first occurrence.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(366 * SEARCH_YEARS);
+        let mut t = start;
+        while t < limit {
+            if !has(self.months, t.month()) {
+                t = first_of_next_month(t.date())?;
+            } else if !self.day_matches(t.date()) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                match tz.from_local_datetime(&t) {
+                    LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => return Some(at),
+                    LocalResult::None => t += Duration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+}
+
+fn has(bits: u64, n: u32) -> bool {
+    bits & (1 << n) != 0
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDateTime> {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> Option<String> {
+        let cron: Cron = expr.parse().unwrap();
+        cron.next_after(&at(after)).map(|t| t.to_rfc3339())
+    }
+
+    #[test]
+    fn test_next_after_steps_through_fields() {
+        // Wednesday 2025-01-15
+        assert_eq!(
+            next("0 6 * * *", "2025-01-15T05:59:30Z").as_deref(),
+            Some("2025-01-15T06:00:00+00:00")
+        );
+        assert_eq!(
+            next("0 6 * * *", "2025-01-15T06:00:00Z").as_deref(),
+            Some("2025-01-16T06:00:00+00:00")
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-15T10:07:00Z").as_deref(),
+            Some("2025-01-15T10:15:00+00:00")
+        );
+        assert_eq!(
+            next("30 8 * * mon-fri", "2025-01-17T09:00:00Z").as_deref(),
+            Some("2025-01-20T08:30:00+00:00")
+        );
+        assert_eq!(
+            next("0 0 1 jan *", "2025-01-15T00:00:00Z").as_deref(),
+            Some("2026-01-01T00:00:00+00:00")
+        );
+        assert_eq!(
+            next("@weekly", "2025-12-30T00:00:00Z").as_deref(),
+            Some("2026-01-04T00:00:00+00:00")
+        );
+        // Restricted day-of-month and day-of-week: either one will do, as in cron
+        assert_eq!(
+            next("0 0 20 * 7", "2025-01-15T00:00:00Z").as_deref(),
+            Some("2025-01-19T00:00:00+00:00")
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2025-01-15T00:00:00Z").as_deref(),
+            Some("2028-02-29T00:00:00+00:00")
+        );
+        assert_eq!(next("0 0 30 2 *", "2025-01-15T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_expressions() {
+        for (expr, error) in [
+            ("0 6 * *", "expected 5 fields"),
+            ("60 * * * *", "minute 60 is out of range 0-59"),
+            ("0 6 * * funday", "day-of-week 'funday' is not a number"),
+            ("*/0 * * * *", "step '0'"),
+            ("0 18-8 * * *", "runs backwards"),
+            ("@sometimes", "unknown shorthand"),
+        ] {
+            let err = expr.parse::<Cron>().unwrap_err();
+            assert!(err.contains(error), "{}: {}", expr, err);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Long-running `daemon` mode: re-runs fetches on a per-server interval or cron
+//! `schedule` until SIGINT/SIGTERM.
+
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+
+/// Each run is delayed by up to this fraction of the server's interval so that many
+/// servers (or many hosts running the daemon) don't all connect at the same moment.
+const JITTER_FRACTION: f64 = 0.1;
+
+/// Longest single sleep. Waking up to look at the clock means a machine that was
+/// suspended past a server's time runs it within a minute of resuming.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// When the daemon checks a server.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Schedule {
+    /// Again this long after the last check finished, plus jitter.
+    Every(Duration),
+    /// At the times a cron expression names, without jitter.
+    Cron(crate::cron::Cron),
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = String;
+
+    /// `90s`, `30m`, `6h` or `1d`, else a cron expression.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with('@') || s.contains(char::is_whitespace) {
+            return s.parse().map(Schedule::Cron);
+        }
+        let (count, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86_400,
+            _ => return Err("expected an interval such as 30m, 6h or 1d, or a cron expression".to_string()),
+        };
+        match count.parse::<u64>() {
+            Ok(count) if count > 0 => Ok(Schedule::Every(Duration::from_secs(count * seconds))),
+            _ => Err("an interval needs a positive number, such as 30m".to_string()),
+        }
+    }
+}
+
+impl Schedule {
+    /// The server's `schedule`, else every `interval_minutes` (see `Server::interval`).
+    pub(crate) fn for_server(server: &crate::config::Server, config: &crate::config::Config) -> Result<Self, String> {
+        let Some(text) = &server.schedule else {
+            return Ok(Schedule::Every(server.interval(config)));
+        };
+        let schedule: Schedule = text.parse()?;
+        if let Schedule::Cron(cron) = &schedule
+            && cron.next_after(&Local::now()).is_none()
+        {
+            return Err("the cron expression never matches".to_string());
+        }
+        Ok(schedule)
+    }
+
+    /// When to check again after a run from `started` to `finished`. Cron times that
+    /// passed while the run was going are caught up with a single check right away.
+    fn next(&self, started: DateTime<Utc>, finished: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Every(interval) => finished + *interval + jitter(*interval),
+            Schedule::Cron(cron) => cron
+                .next_after(&started.with_timezone(&Local))
+                .map_or(DateTime::<Utc>::MAX_UTC, |at| at.with_timezone(&Utc).max(finished)),
+        }
+    }
+}
+
+/// Runs the scheduler loop. Every server is checked once at startup and then again
+/// whenever its schedule comes round. Servers due together share one run, and a run
+/// that is still going when a server comes due again isn't doubled: the server is
+/// checked once, as soon as the run ends. A shutdown signal ends the loop after the
+/// current run finishes.
+pub(crate) fn run(
+    config: &crate::config::Config,
+    dry_run: bool,
+    vault_passwords: &HashMap<String, zeroize::Zeroizing<String>>,
+    key_passphrase: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    if config.servers.is_empty() {
+        anyhow::bail!("No servers configured; nothing for the daemon to do.");
+    }
+    let mut schedules = HashMap::new();
+    let mut problems = Vec::new();
+    for server in &config.servers {
+        match Schedule::for_server(server, config) {
+            Ok(schedule) => {
+                schedules.insert(server.name.clone(), schedule);
+            }
+            Err(e) => problems.push(format!("[{}] schedule: {}", server.name, e)),
+        }
+    }
+    if !problems.is_empty() {
+        anyhow::bail!("{}", problems.join("\n"));
+    }
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
+    ctrlc::set_handler(move || {
+        shutdown_tx.send(()).ok();
+    })
+    .map_err(|e| anyhow::anyhow!("Failed to install signal handler: {}", e))?;
+
+    let start = Utc::now();
+    let mut next_due: HashMap<String, DateTime<Utc>> = config.servers.iter().map(|s| (s.name.clone(), start)).collect();
+
+    log::info!("Daemon started with {} server(s)", config.servers.len());
+    loop {
+        let now = Utc::now();
+        let due = due_servers(&next_due, now);
+        if !due.is_empty() {
+            log::info!("Checking {} server(s): {}", due.len(), due.join(", "));
+            // force = false: servers whose certs are still valid are skipped as usual
+            if let Err(e) = crate::fetch::process_servers(
+                config,
+                &due,
+                dry_run,
+                false,
+                false,
+                vault_passwords,
+                key_passphrase,
+                false,
+            ) {
+                log::error!("Run failed: {:#}", e);
+            }
+            let finished = Utc::now();
+            for name in &due {
+                let next = schedules[name].next(now, finished);
+                log::debug!("[{}] next check at {}", name, next.with_timezone(&Local));
+                next_due.insert(name.clone(), next);
+            }
+        }
+
+        let wake_at = next_due.values().min().copied().unwrap_or(now);
+        let sleep_for = (wake_at - Utc::now()).to_std().unwrap_or_default().min(MAX_SLEEP);
+        match shutdown_rx.recv_timeout(sleep_for) {
+            Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+    }
+    log::info!("Daemon shutting down");
+    Ok(())
+}
+
+/// Names of servers whose next run is at or before `now`, in a stable order.
+fn due_servers(next_due: &HashMap<String, DateTime<Utc>>, now: DateTime<Utc>) -> Vec<String> {
+    let mut due: Vec<String> = next_due
+        .iter()
+        .filter(|(_, at)| **at <= now)
+        .map(|(name, _)| name.clone())
+        .collect();
+    due.sort();
+    due
+}
+
+/// A random delay in `[0, interval * JITTER_FRACTION)`. Uses the randomly seeded std
+/// hasher so no RNG dependency is needed.
+fn jitter(interval: Duration) -> Duration {
+    let max = interval.mul_f64(JITTER_FRACTION);
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let random = std::collections::hash_map::RandomState::new().hash_one(std::time::Instant::now());
+    max.mul_f64((random % 10_000) as f64 / 10_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_servers_only_returns_elapsed() {
+        let now = Utc::now();
+        let mut next_due = HashMap::new();
+        next_due.insert("beta".to_string(), now);
+        next_due.insert("alpha".to_string(), now - Duration::from_secs(5));
+        next_due.insert("gamma".to_string(), now + Duration::from_secs(60));
+        assert_eq!(due_servers(&next_due, now), vec!["alpha", "beta"]);
+    }
+
+    #[test]
+    fn test_schedule_parses_intervals_and_cron() {
+        assert_eq!("90s".parse(), Ok(Schedule::Every(Duration::from_secs(90))));
+        assert_eq!("6h".parse(), Ok(Schedule::Every(Duration::from_secs(6 * 3600))));
+        assert_eq!(
+            "0 6 * * *".parse::<Schedule>(),
+            Ok(Schedule::Cron("0 6 * * *".parse().unwrap()))
+        );
+        assert!("0m".parse::<Schedule>().is_err());
+        assert!("6 hours".parse::<Schedule>().is_err());
+        assert!("6w".parse::<Schedule>().is_err());
+
+        let config = crate::config::Config {
+            daemon_interval_minutes: Some(15),
+            ..Default::default()
+        };
+        let mut server = crate::config::Server {
+            interval_minutes: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            Schedule::for_server(&server, &config),
+            Ok(Schedule::Every(Duration::from_secs(300)))
+        );
+        server.schedule = Some("0 0 31 4 *".to_string());
+        assert_eq!(
+            Schedule::for_server(&server, &config),
+            Err("the cron expression never matches".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cron_runs_missed_during_a_run_are_caught_up_once() {
+        let schedule: Schedule = "*/5 * * * *".parse().unwrap();
+        let started = Utc::now();
+        // A run that took half an hour passed six slots; the next check is right away.
+        let finished = started + Duration::from_secs(1800);
+        assert_eq!(schedule.next(started, finished), finished);
+        // A quick run waits for the next slot.
+        let next = schedule.next(started, started + Duration::from_secs(1));
+        assert!(next > started && next <= started + Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_fraction() {
+        let interval = Duration::from_secs(3600);
+        for _ in 0..100 {
+            assert!(jitter(interval) < Duration::from_secs(360));
+        }
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+    }
+}
@@ -0,0 +1,139 @@
+//! Line-based unified diff, used to show what a dry run would change.
+//!
+//! Kubeconfigs are a few hundred lines at most, so a plain LCS table is fast enough
+//! and saves pulling in a diff crate.
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Renders a unified diff (`---`/`+++` header, `@@` hunks) turning `old` into `new`.
+/// Returns an empty string when the two are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old, &new);
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut first = 0;
+    while first < changes.len() {
+        // Changes whose context would touch or overlap share one hunk.
+        let mut last = first;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * CONTEXT + 1 {
+            last += 1;
+        }
+        let from = changes[first].saturating_sub(CONTEXT);
+        let to = (changes[last] + CONTEXT + 1).min(ops.len());
+        push_hunk(&mut out, &ops, from, to);
+        first = last + 1;
+    }
+    out
+}
+
+fn push_hunk(out: &mut String, ops: &[Op], from: usize, to: usize) {
+    let old_count = |ops: &[Op]| ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+    let new_count = |ops: &[Op]| ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+    // An empty side is numbered by the line before it, as diff(1) does.
+    let start = |before: usize, len: usize| if len == 0 { before } else { before + 1 };
+
+    let (old_len, new_len) = (old_count(&ops[from..to]), new_count(&ops[from..to]));
+    out.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        start(old_count(&ops[..from]), old_len),
+        old_len,
+        start(new_count(&ops[..from]), new_len),
+        new_len
+    ));
+    for op in &ops[from..to] {
+        let (sign, line) = match op {
+            Op::Equal(l) => (' ', l),
+            Op::Delete(l) => ('-', l),
+            Op::Insert(l) => ('+', l),
+        };
+        out.push(sign);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    // Only the middle section that differs goes through the quadratic table.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the longest common subsequence of a[i..] and b[j..].
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op> = old[..prefix].iter().copied().map(Op::Equal).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push(Op::Equal(a[i]));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Delete(a[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(b[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().copied().map(Op::Equal));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        assert_eq!(
+            unified_diff(old, new, "a/config", "b/config"),
+            "--- a/config\n+++ b/config\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+
+        assert_eq!(
+            unified_diff("", "x\n", "old", "new"),
+            "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+}
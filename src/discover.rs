@@ -0,0 +1,251 @@
+//! `discover`: scans a network range for Kubernetes nodes (SSH on 22 and an API server
+//! on 6443) and offers them as new servers, named from the API server's certificate.
+
+use crate::config::{Config, Server};
+use crate::import::{Candidate, Skipped};
+use crate::tls::{API_PORT, ServingCert};
+use rayon::prelude::*;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Largest range scanned at once: an IPv4 /20 or IPv6 /116.
+pub const MAX_HOSTS: u128 = 4096;
+
+/// Connections attempted at once. Most addresses in a range don't answer, so the scan
+/// is bound by connect timeouts rather than CPU.
+const SCAN_THREADS: usize = 64;
+
+const SSH_PORT: u16 = 22;
+
+/// What answered at one address.
+#[derive(Debug, Clone)]
+pub struct Host {
+    pub ip: IpAddr,
+    pub ssh: bool,
+    pub api: bool,
+    /// The API server's certificate, when probed.
+    pub cert: Option<ServingCert>,
+}
+
+/// Addresses in `cidr` (`10.0.0.0/24`, `fd00::/120`, or a single address). IPv4 ranges
+/// leave out the network and broadcast addresses, except for /31 and /32.
+pub fn parse_cidr(cidr: &str) -> Result<Vec<IpAddr>, anyhow::Error> {
+    let (addr, prefix) = cidr.split_once('/').unwrap_or((cidr, ""));
+    let ip: IpAddr = addr
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("'{}' is not an IP address or CIDR range", cidr))?;
+    let bits: u32 = if ip.is_ipv4() { 32 } else { 128 };
+    let prefix: u32 = match prefix.trim() {
+        "" => bits,
+        p => p
+            .parse()
+            .ok()
+            .filter(|p| *p <= bits)
+            .ok_or_else(|| anyhow::anyhow!("'{}' has an invalid prefix length", cidr))?,
+    };
+    let size = 1u128.checked_shl(bits - prefix).unwrap_or(u128::MAX);
+    if size > MAX_HOSTS {
+        anyhow::bail!(
+            "{} has {} addresses; scan at most {} at a time (an IPv4 /20)",
+            cidr,
+            size,
+            MAX_HOSTS
+        );
+    }
+    let hosts = match ip {
+        IpAddr::V4(v4) => {
+            let network = u32::from(v4) & (u32::MAX.checked_shl(32 - prefix).unwrap_or(0));
+            let (first, last) = if size <= 2 {
+                (0, size as u32)
+            } else {
+                (1, size as u32 - 1)
+            };
+            (first..last)
+                .map(|i| IpAddr::from(std::net::Ipv4Addr::from(network + i)))
+                .collect()
+        }
+        IpAddr::V6(v6) => {
+            let network = u128::from(v6) & (u128::MAX.checked_shl(128 - prefix).unwrap_or(0));
+            let first = if size <= 2 { 0 } else { 1 };
+            (first..size)
+                .map(|i| IpAddr::from(std::net::Ipv6Addr::from(network + i)))
+                .collect()
+        }
+    };
+    Ok(hosts)
+}
+
+/// Checks ports 22 and 6443 on every address and, with `probe_certs`, reads the API
+/// server's certificate. Returns the addresses where either port answered, in order.
+pub fn scan(hosts: &[IpAddr], timeout: Duration, probe_certs: bool) -> Result<Vec<Host>, anyhow::Error> {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(SCAN_THREADS).build()?;
+    let found = pool.install(|| {
+        hosts
+            .par_iter()
+            .filter_map(|ip| {
+                let host = ip.to_string();
+                let ssh = crate::ssh::tcp_reachable(&host, SSH_PORT, timeout);
+                let api = crate::ssh::tcp_reachable(&host, API_PORT, timeout);
+                if !ssh && !api {
+                    return None;
+                }
+                let cert = (api && probe_certs)
+                    .then(|| crate::tls::probe_serving_cert(&host, API_PORT, timeout * 4).ok())
+                    .flatten();
+                log::debug!("{}: ssh {} api {}", ip, ssh, api);
+                Some(Host {
+                    ip: *ip,
+                    ssh,
+                    api,
+                    cert,
+                })
+            })
+            .collect()
+    });
+    Ok(found)
+}
+
+/// Name a node's API server certificate suggests: its first SAN that isn't one of the
+/// generic names every cluster carries. On k3s that is the node's hostname.
+fn cert_name(cert: &ServingCert) -> Option<&str> {
+    cert.dns_names.iter().map(String::as_str).find(|name| {
+        !name.starts_with("kubernetes")
+            && !name.starts_with('*')
+            && *name != "localhost"
+            && name.parse::<IpAddr>().is_err()
+    })
+}
+
+/// One candidate per host with both SSH and an API server, named from its certificate
+/// (or `node-<ip>`). Hosts already configured by address or cluster IP are skipped.
+pub fn candidates(hosts: &[Host], config: &Config) -> (Vec<Candidate>, Vec<Skipped>) {
+    let mut found: Vec<Candidate> = Vec::new();
+    let mut skipped = Vec::new();
+    for host in hosts {
+        let ip = host.ip.to_string();
+        let mut skip = |reason: &str| {
+            skipped.push(Skipped {
+                name: ip.clone(),
+                reason: reason.to_string(),
+            })
+        };
+        if !host.api {
+            skip("no API server on :6443");
+            continue;
+        }
+        if !host.ssh {
+            skip("no SSH on :22");
+            continue;
+        }
+        if let Some(existing) = config
+            .servers
+            .iter()
+            .find(|s| s.address == ip || s.target_cluster_ip == ip)
+        {
+            skip(&format!("already configured as '{}'", existing.name));
+            continue;
+        }
+        let fallback = format!("node-{}", ip.replace(['.', ':'], "-"));
+        let name = host
+            .cert
+            .as_ref()
+            .and_then(cert_name)
+            .map(crate::import::server_name)
+            .filter(|name| {
+                !name.is_empty()
+                    && !config.servers.iter().any(|s| &s.name == name)
+                    && !found.iter().any(|c| &c.server.name == name)
+            })
+            .unwrap_or(fallback);
+        let server = Server {
+            name,
+            address: ip.clone(),
+            target_cluster_ip: ip,
+            ..Default::default()
+        };
+        found.push(Candidate {
+            url: server.server_url().unwrap_or_default(),
+            server,
+        });
+    }
+    (found, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cidr() {
+        let hosts = parse_cidr("192.168.1.0/30").unwrap();
+        assert_eq!(
+            hosts,
+            vec!["192.168.1.1".parse::<IpAddr>().unwrap(), "192.168.1.2".parse().unwrap()]
+        );
+        assert_eq!(parse_cidr("192.168.1.77/24").unwrap().len(), 254);
+        assert_eq!(
+            parse_cidr("10.0.0.5").unwrap(),
+            vec!["10.0.0.5".parse::<IpAddr>().unwrap()]
+        );
+        assert_eq!(parse_cidr("fd00::/126").unwrap().len(), 3);
+        assert_eq!(parse_cidr("10.0.0.0/20").unwrap().len(), 4094);
+        assert!(parse_cidr("10.0.0.0/16").is_err());
+        assert!(parse_cidr("10.0.0.0/33").is_err());
+        assert!(parse_cidr("node.lan/24").is_err());
+    }
+
+    #[test]
+    fn test_candidates_from_scan() {
+        let cert = |names: &[&str]| ServingCert {
+            not_after: chrono::Utc::now(),
+            dns_names: names.iter().map(|n| n.to_string()).collect(),
+            ips: Vec::new(),
+            details: Default::default(),
+        };
+        let host = |ip: &str, ssh: bool, api: bool, cert: Option<ServingCert>| Host {
+            ip: ip.parse().unwrap(),
+            ssh,
+            api,
+            cert,
+        };
+        let hosts = vec![
+            host(
+                "10.0.0.2",
+                true,
+                true,
+                Some(cert(&["kubernetes", "kubernetes.default", "localhost", "pi-master"])),
+            ),
+            host("10.0.0.3", true, true, None),
+            host("10.0.0.4", true, false, None),
+            host("10.0.0.5", true, true, Some(cert(&["pi-master"]))),
+            host("10.0.0.6", true, true, None),
+        ];
+        let config = Config {
+            servers: vec![Server {
+                name: "old".to_string(),
+                address: "10.0.0.6".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (found, skipped) = candidates(&hosts, &config);
+        let names: Vec<&str> = found.iter().map(|c| c.server.name.as_str()).collect();
+        assert_eq!(names, ["pi-master", "node-10-0-0-3", "node-10-0-0-5"]);
+        assert_eq!(found[0].server.target_cluster_ip, "10.0.0.2");
+        assert_eq!(found[0].url, "https://10.0.0.2:6443");
+        assert_eq!(
+            skipped,
+            [
+                Skipped {
+                    name: "10.0.0.4".to_string(),
+                    reason: "no API server on :6443".to_string(),
+                },
+                Skipped {
+                    name: "10.0.0.6".to_string(),
+                    reason: "already configured as 'old'".to_string(),
+                },
+            ]
+        );
+    }
+}
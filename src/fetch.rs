@@ -1,9 +1,13 @@
 use anyhow::Context;
-use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
 
 pub(crate) enum SkipReason {
     CertValid(chrono::DateTime<chrono::Utc>),
@@ -12,44 +16,198 @@ pub(crate) enum SkipReason {
 
 pub(crate) enum ServerResult {
     Fetched,
+    /// Fetched and merged, but `verify_api` couldn't reach the API server it names.
+    Unreachable(String),
+    /// Dry run: the unified diff a real run would apply, empty when nothing would change.
+    DryRun(String),
     Skipped(SkipReason),
 }
 
+/// The server's `fetch_timeout` passed before its fetch finished.
+#[derive(Debug)]
+pub(crate) struct TimedOut(pub Duration);
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Outcome of a single server in a `process_servers` run.
+#[derive(Serialize, Debug)]
+pub(crate) struct ServerReport {
+    pub name: String,
+    pub status: crate::state::RunStatus,
+    pub error: Option<String>,
+    pub cert_expires: Option<chrono::DateTime<chrono::Utc>>,
+    /// Expiry of the cluster CA in the cached kubeconfig.
+    pub ca_expires: Option<chrono::DateTime<chrono::Utc>>,
+    pub duration_ms: u64,
+    /// Size of the kubeconfig read from the source; 0 when nothing was fetched.
+    pub bytes_fetched: u64,
+    /// Dry runs only: the changes a real run would make.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Version of the `RunReport` JSON. Bumped when a field is renamed or removed; new
+/// fields don't change it.
+pub(crate) const REPORT_VERSION: u32 = 1;
+
+/// Per-server results and totals for a `process_servers` run; printed by `--output json`
+/// and written to `report_file`.
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct RunReport {
+    pub version: u32,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub dry_run: bool,
+    pub servers: Vec<ServerReport>,
+    pub fetched: u32,
+    pub skipped_cert_valid: u32,
+    pub skipped_no_cred: u32,
+    pub failed: u32,
+    /// Fetched, but `verify_api` couldn't reach the API server.
+    pub unreachable: u32,
+    /// Servers left alone because `--fail-fast` stopped the run.
+    pub not_attempted: u32,
+}
+
+impl RunReport {
+    /// `Err` when any server failed or is unreachable, for the process exit code.
+    pub(crate) fn check_failures(&self) -> Result<(), ServersFailed> {
+        let failed = self.failed + self.unreachable;
+        if failed == 0 {
+            return Ok(());
+        }
+        Err(ServersFailed {
+            failed,
+            total: self.servers.len() as u32 + self.not_attempted,
+        })
+    }
+}
+
+/// Some servers in a run failed; `failed == total` when none succeeded.
+#[derive(Debug)]
+pub(crate) struct ServersFailed {
+    pub failed: u32,
+    pub total: u32,
+}
+
+impl std::fmt::Display for ServersFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {} servers failed", self.failed, self.total)
+    }
+}
+
+impl std::error::Error for ServersFailed {}
+
+/// Fetches, rewrites, and merges the kubeconfig for a single server.
+///
+/// `on_progress` receives the running byte count while the remote file is read,
+/// so callers can show transfer progress instead of an indeterminate spinner.
+/// `key_passphrase` overrides the keyring lookup for an encrypted identity file.
+///
+/// The whole pipeline runs against the server's `fetch_timeout`: when it passes, a
+/// watchdog cancels the connection, which unblocks the worker, and the server fails
+/// with `TimedOut`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn process_server(
     server: &crate::config::Server,
     config: &crate::config::Config,
     dry_run: bool,
     force: bool,
     vault_password: Option<&str>,
+    key_passphrase: Option<&str>,
+    on_progress: &(dyn Fn(u64) + Sync),
+    cancel: Option<&crate::ssh::CancelToken>,
 ) -> Result<ServerResult, anyhow::Error> {
-    let user = server.user(config)?;
-    let remote_path_str = server.file_path(config)?;
-    let identity_file = server.identity_file(config);
+    let timeout = server.fetch_timeout(config);
+    let own_token = crate::ssh::CancelToken::default();
+    let token = cancel.unwrap_or(&own_token);
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            if done_rx.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                log::warn!(
+                    "[{}] Fetch timed out after {}s, cancelling",
+                    server.name,
+                    timeout.as_secs()
+                );
+                token.expire();
+            }
+        });
+        let result = run_pipeline(
+            server,
+            config,
+            dry_run,
+            force,
+            vault_password,
+            key_passphrase,
+            on_progress,
+            Some(token),
+        );
+        drop(done_tx);
+        match result {
+            Err(_) if token.timed_out() => Err(TimedOut(timeout).into()),
+            other => other,
+        }
+    })
+}
 
-    let mut local_path = PathBuf::from(&config.local_output_dir);
-    local_path.push(&server.name);
+#[allow(clippy::too_many_arguments)]
+fn run_pipeline(
+    server: &crate::config::Server,
+    config: &crate::config::Config,
+    dry_run: bool,
+    force: bool,
+    vault_password: Option<&str>,
+    key_passphrase: Option<&str>,
+    on_progress: &(dyn Fn(u64) + Sync),
+    cancel: Option<&crate::ssh::CancelToken>,
+) -> Result<ServerResult, anyhow::Error> {
+    let local_path = config.local_path(&server.name);
 
-    // Step 1: Check local cert expiry — skip SSH if cert is still valid (unless force)
+    // Step 1: Check local cert expiry — skip SSH if cert is still valid and not
+    // inside the renew_before_days window (unless force)
     if !force {
+        let renew_before = server.renew_before(config);
         match crate::kube::check_local_cert_expiry(&local_path) {
-            crate::kube::CertStatus::Valid(expiry) => {
+            crate::kube::CertStatus::Valid(expiry) if !renewal_due(expiry, chrono::Utc::now(), renew_before) => {
                 log::debug!("[{}] Cert valid until {}, skipping", server.name, expiry);
                 return Ok(ServerResult::Skipped(SkipReason::CertValid(expiry)));
             }
+            crate::kube::CertStatus::Valid(expiry) => {
+                log::info!(
+                    "[{}] Cert expires {}, within {} day(s), fetching...",
+                    server.name,
+                    expiry,
+                    renew_before.num_days()
+                );
+            }
             crate::kube::CertStatus::Expired(_) => {
                 log::info!("[{}] Cert expired, fetching...", server.name);
             }
             crate::kube::CertStatus::Unknown => {
                 log::info!("[{}] Cert status unknown (no cache), fetching...", server.name);
             }
+            crate::kube::CertStatus::NoClientCert(auth) => {
+                log::info!(
+                    "[{}] User authenticates with {}, no cert expiry; fetching...",
+                    server.name,
+                    auth
+                );
+            }
         }
     }
 
     // Step 2: Look up credential
-    let password: Option<String> = if let Some(pw) = vault_password {
-        Some(pw.to_string())
+    let password: Option<Zeroizing<String>> = if let Some(pw) = vault_password {
+        Some(Zeroizing::new(pw.to_string()))
     } else {
-        match crate::credentials::get_credential(&server.name) {
+        match crate::credentials::resolve_credential(&server.name, config.credentials_from_env()) {
             crate::credentials::CredentialResult::Found(pw) => Some(pw),
             crate::credentials::CredentialResult::NotFound => None,
             crate::credentials::CredentialResult::Unavailable(reason) => {
@@ -59,58 +217,513 @@ pub(crate) fn process_server(
         }
     };
 
-    // Step 3: Fetch the remote kubeconfig
-    let contents = crate::ssh::fetch_remote_file(
-        &server.name,
-        &server.address,
-        user,
-        &remote_path_str,
-        identity_file,
-        password.as_deref(),
+    // Step 3: Fetch the kubeconfig from its source
+    let (contents, origin) = fetch_source(
+        server,
+        config,
+        password.as_ref().map(|pw| pw.as_str()),
+        key_passphrase,
+        on_progress,
+        cancel,
     )?;
+    // Last point to back out: nothing has been written yet.
+    if let Some(token) = cancel {
+        token.check()?;
+    }
 
-    // Step 4: Hash the contents
+    // Step 4: Validate before anything touches the cached copy, so a bad fetch
+    // leaves the last good kubeconfig in place
+    let fetched = crate::kube::validate_fetched(&contents)
+        .with_context(|| format!("fetched {}; keeping the existing local copy", origin))?;
+
+    // Step 5: Hash the contents
     let mut hasher = Sha256::new();
     hasher.update(&contents);
     let source_hash = format!("{:x}", hasher.finalize());
     log::debug!("[{}] Source file SHA256: {}", server.name, source_hash);
 
-    // Step 5: Write local file
+    // Dry run: work out both files in memory and report the diff instead of writing
     if dry_run {
-        log::info!("[{}] DRY-RUN: Would write config to {:?}", server.name, local_path);
-    } else {
-        fs::create_dir_all(&config.local_output_dir)
-            .with_context(|| format!("creating output directory {:?}", config.local_output_dir))?;
-        fs::write(&local_path, &contents).with_context(|| format!("writing config to {:?}", local_path))?;
-        log::info!("[{}] Config written to {:?}", server.name, local_path);
+        let diff = dry_run_diff(server, config, &local_path, fetched, &source_hash)?;
+        return Ok(ServerResult::DryRun(diff));
     }
 
-    // Step 6: Process kubeconfig (update cluster IP, context name, add metadata)
-    crate::kube::process_kubeconfig_file(
-        &local_path,
-        &server.target_cluster_ip,
+    // Step 6: Rewrite in memory (update cluster IP, context name, add metadata), then
+    // write the local file once
+    let updated = crate::kube::rewrite_kubeconfig(
+        fetched,
+        &server.server_url().unwrap_or_default(),
         &source_hash,
         &server.context_name,
         &server.name,
-        dry_run,
+        server.namespace.as_deref(),
     )?;
+    crate::kube::warn_if_source_changed(&local_path, &source_hash);
+    fs::create_dir_all(&config.local_output_dir)
+        .with_context(|| format!("creating output directory {:?}", config.local_output_dir))?;
+    crate::kube::write_kubeconfig(&local_path, &updated).context("writing the local kubeconfig")?;
+    log::info!("[{}] Config written to {:?}", server.name, local_path);
+    if let Err(e) = crate::history::record(config, &server.name, &updated, &source_hash) {
+        log::warn!("[{}] Could not save this version for rollback: {:#}", server.name, e);
+    }
+    warn_if_host_not_in_sans(server);
 
     // Step 7: Merge into ~/.kube/config
-    crate::kube::merge_into_main_kubeconfig(&local_path, &server.name, dry_run)?;
+    if server.merge_enabled(config) {
+        crate::kube::merge_into_main_kubeconfig(
+            &local_path,
+            &server.name,
+            server.merge_strategy(config),
+            config.backup_retention(),
+            false,
+        )?;
+    } else {
+        log::info!("[{}] Merging disabled — ~/.kube/config left unchanged", server.name);
+    }
+
+    // Step 8: Optionally check that kubectl could use what was just written
+    if let Err(e) = verify_api(config.verify_api(), &local_path) {
+        log::warn!("[{}] Fetched, but the API server is unreachable: {:#}", server.name, e);
+        return Ok(ServerResult::Unreachable(format!("{:#}", e)));
+    }
 
     Ok(ServerResult::Fetched)
 }
 
+/// Limit on each `verify_api` check.
+const VERIFY_API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connects to the API server of the cached kubeconfig at `local_path` as configured by
+/// `verify_api`. A fetch can succeed over SSH while the rewritten `server` URL is wrong
+/// or firewalled; this is where that shows.
+pub(crate) fn verify_api(mode: crate::config::ApiVerification, local_path: &Path) -> Result<(), anyhow::Error> {
+    use crate::config::ApiVerification;
+
+    if mode == ApiVerification::Off {
+        return Ok(());
+    }
+    let target = crate::kube::local_api_target(local_path)?;
+    match mode {
+        ApiVerification::Off => {}
+        ApiVerification::Tls => {
+            let (host, port) = crate::rancher::host_port(&target.server)
+                .ok_or_else(|| anyhow::anyhow!("can't parse server URL '{}'", target.server))?;
+            crate::tls::probe_serving_cert(&host, port, VERIFY_API_TIMEOUT)
+                .with_context(|| format!("TLS connection to {}", target.server))?;
+        }
+        ApiVerification::Version => {
+            let version = crate::tls::api_version(&target, VERIFY_API_TIMEOUT)?;
+            log::debug!("{} answered /version with {}", target.server, version);
+        }
+    }
+    Ok(())
+}
+
+/// Limit on the TLS handshake that reads the API server's SANs after a fetch.
+const SAN_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Moves cached kubeconfigs still named after the server alone, as written before
+/// `local_file_name` existed, to their configured name. Never overwrites a file.
+pub(crate) fn migrate_local_files(config: &crate::config::Config) {
+    for server in &config.servers {
+        let legacy = PathBuf::from(&config.local_output_dir).join(&server.name);
+        let path = config.local_path(&server.name);
+        if legacy == path || !legacy.is_file() || path.exists() {
+            continue;
+        }
+        match fs::rename(&legacy, &path) {
+            Ok(()) => log::info!("[{}] Renamed cached kubeconfig {:?} to {:?}", server.name, legacy, path),
+            Err(e) => log::warn!("[{}] Could not rename {:?} to {:?}: {}", server.name, legacy, path, e),
+        }
+    }
+}
+
+/// When kubectl will reach the API server by hostname, checks that the name is in the
+/// serving certificate's SANs. k3s only adds names passed with `--tls-san`, so a
+/// hostname target can fetch fine and still fail in kubectl. Best effort: a probe that
+/// fails is only logged at debug level.
+fn warn_if_host_not_in_sans(server: &crate::config::Server) {
+    let (host, port) = server.api_endpoint();
+    if server.server_url().is_none() || host.parse::<std::net::IpAddr>().is_ok() {
+        return;
+    }
+    match crate::tls::probe_serving_cert(&host, port, SAN_PROBE_TIMEOUT) {
+        Ok(cert) if !cert.covers(&host) => log::warn!(
+            "[{}] {} is not in the API server certificate's SANs ({}); kubectl will reject it. \
+             Add it with k3s --tls-san or use the IP.",
+            server.name,
+            host,
+            cert.sans()
+        ),
+        Ok(_) => {}
+        Err(e) => log::debug!(
+            "[{}] Could not probe {}:{} for its SANs: {:#}",
+            server.name,
+            host,
+            port,
+            e
+        ),
+    }
+}
+
+/// Gets the raw kubeconfig from wherever the server's `source` says, plus a short
+/// description of where it came from for error messages. `password` is the SSH/sudo
+/// password, or the API token for Rancher.
+pub(crate) fn fetch_source(
+    server: &crate::config::Server,
+    config: &crate::config::Config,
+    password: Option<&str>,
+    key_passphrase: Option<&str>,
+    on_progress: &(dyn Fn(u64) + Sync),
+    cancel: Option<&crate::ssh::CancelToken>,
+) -> Result<(Vec<u8>, String), anyhow::Error> {
+    use crate::config::Source;
+
+    let (contents, origin) = match server.source() {
+        Source::Ssh => {
+            let remote_path_str = server.file_path(config)?;
+            let contents = fetch_over_ssh(
+                server,
+                config,
+                &remote_path_str,
+                password,
+                key_passphrase,
+                on_progress,
+                cancel,
+            )?;
+            (contents, format!("{} from {}", remote_path_str, server.address))
+        }
+        Source::Rancher => {
+            let cluster_id = server
+                .rancher_cluster_id
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("[{}] source = \"rancher\" needs rancher_cluster_id", server.name))?;
+            let token = password.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "[{}] No Rancher API token stored. Add it with `credential set --server {}`.",
+                    server.name,
+                    server.name
+                )
+            })?;
+            let contents = crate::rancher::generate_kubeconfig(&server.address, cluster_id, token)?;
+            (
+                contents,
+                format!("cluster {} from Rancher at {}", cluster_id, server.address),
+            )
+        }
+        Source::K3sToken => {
+            let token = password.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "[{}] No k3s token stored. Add it with `credential set --server {}`.",
+                    server.name,
+                    server.name
+                )
+            })?;
+            let contents = crate::k3s::generate_kubeconfig(&server.name, &server.address, token)?;
+            (contents, format!("token and CA from {}", server.address))
+        }
+        source @ (Source::Eks | Source::Gke | Source::Aks) => {
+            let contents = crate::cloud::generate_kubeconfig(source, &server.name, server.cloud.as_ref())?;
+            (contents, format!("{:?} cluster description", source))
+        }
+    };
+    // SSH reads report progress as they go; the other sources report their size here.
+    if server.source() != Source::Ssh {
+        on_progress(contents.len() as u64);
+    }
+    Ok((contents, origin))
+}
+
+/// Reads the kubeconfig from the node over SSH.
+fn fetch_over_ssh(
+    server: &crate::config::Server,
+    config: &crate::config::Config,
+    remote_path: &str,
+    password: Option<&str>,
+    key_passphrase: Option<&str>,
+    on_progress: &(dyn Fn(u64) + Sync),
+    cancel: Option<&crate::ssh::CancelToken>,
+) -> Result<Vec<u8>, anyhow::Error> {
+    if server.address.is_empty() || server.server_url().is_none() {
+        anyhow::bail!(
+            "[{}] address and target_cluster_ip (or target_server_url) are required for SSH servers",
+            server.name
+        );
+    }
+    let identity_file = server.identity_file(config);
+    let key_passphrase = resolve_key_passphrase(identity_file, key_passphrase);
+    let sudo_password = resolve_sudo_password(server)?;
+    let target = crate::ssh::SshTarget {
+        server_name: &server.name,
+        address: &server.address,
+        port: server.ssh_port(),
+        user: server.user(config)?,
+        identity_file,
+        key_passphrase: key_passphrase.as_ref().map(|p| p.as_str()),
+        password,
+        sudo_password: sudo_password.as_ref().map(|pw| pw.as_str()),
+        escalation: server.escalation(config),
+        escalation_command: server.escalation_command(config),
+        compress: server.compression(config),
+        host_key_policy: config.host_key_policy(),
+        transport: server.transport(config),
+        transfer: server.transfer(config),
+        sftp_fallback: config.sftp_sudo_fallback(),
+        cancel,
+    };
+    crate::ssh::fetch_remote_file(&target, remote_path, on_progress).map_err(|e| {
+        // A shut-down socket surfaces as an I/O error from libssh2; report it as what it is.
+        match cancel {
+            Some(token) if token.is_cancelled() => crate::ssh::Cancelled.into(),
+            _ => e,
+        }
+    })
+}
+
+/// How long to wait after a renewal before fetching: k3s rewrites its kubeconfig
+/// only once the restarted service is up again.
+pub(crate) const RENEW_SETTLE: Duration = Duration::from_secs(15);
+
+/// Runs the server's `renew_command` over SSH and returns its output. The caller
+/// fetches afterwards, after waiting `RENEW_SETTLE`.
+pub(crate) fn renew_server(
+    server: &crate::config::Server,
+    config: &crate::config::Config,
+    dry_run: bool,
+    vault_password: Option<&str>,
+    key_passphrase: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    if server.source() != crate::config::Source::Ssh {
+        anyhow::bail!(
+            "[{}] Renewing runs a command over SSH; this server isn't fetched over SSH",
+            server.name
+        );
+    }
+    let command = server.renew_command(config);
+    if dry_run {
+        log::info!(
+            "[{}] DRY-RUN: Would run '{}' on {}",
+            server.name,
+            command,
+            server.address
+        );
+        return Ok(String::new());
+    }
+    let password = match vault_password {
+        Some(pw) => Some(Zeroizing::new(pw.to_string())),
+        None => match crate::credentials::resolve_credential(&server.name, config.credentials_from_env()) {
+            crate::credentials::CredentialResult::Found(pw) => Some(pw),
+            crate::credentials::CredentialResult::NotFound => None,
+            crate::credentials::CredentialResult::Unavailable(reason) => {
+                anyhow::bail!("[{}] Keyring unavailable ({})", server.name, reason)
+            }
+        },
+    };
+    let identity_file = server.identity_file(config);
+    let key_passphrase = resolve_key_passphrase(identity_file, key_passphrase);
+    let sudo_password = resolve_sudo_password(server)?;
+    let target = crate::ssh::SshTarget {
+        server_name: &server.name,
+        address: &server.address,
+        port: server.ssh_port(),
+        user: server.user(config)?,
+        identity_file,
+        key_passphrase: key_passphrase.as_ref().map(|p| p.as_str()),
+        password: password.as_ref().map(|pw| pw.as_str()),
+        sudo_password: sudo_password.as_ref().map(|pw| pw.as_str()),
+        escalation: server.escalation(config),
+        escalation_command: server.escalation_command(config),
+        compress: false,
+        host_key_policy: config.host_key_policy(),
+        transport: server.transport(config),
+        transfer: crate::ssh::Transfer::Exec,
+        sftp_fallback: false,
+        cancel: None,
+    };
+    log::info!("[{}] Running '{}' to renew certificates", server.name, command);
+    crate::ssh::run_remote_command(&target, command)
+}
+
+/// Diff of the per-server kubeconfig and ~/.kube/config between now and after a real
+/// run with the fetched `contents`. Secrets and certificates appear as fingerprints.
+pub(crate) fn dry_run_diff(
+    server: &crate::config::Server,
+    config: &crate::config::Config,
+    local_path: &std::path::Path,
+    fetched: &str,
+    source_hash: &str,
+) -> Result<String, anyhow::Error> {
+    let updated = crate::kube::rewrite_kubeconfig(
+        fetched,
+        &server.server_url().unwrap_or_default(),
+        source_hash,
+        &server.context_name,
+        &server.name,
+        server.namespace.as_deref(),
+    )?;
+    let current = fs::read_to_string(local_path).unwrap_or_default();
+    let label = local_path.display().to_string();
+    let mut diff = crate::diff::unified_diff(
+        &crate::kube::redact_for_diff(&current),
+        &crate::kube::redact_for_diff(&updated),
+        &label,
+        &label,
+    );
+
+    if server.merge_enabled(config)
+        && let Some((main_path, before, after)) =
+            crate::kube::preview_merge(&updated, &server.name, server.merge_strategy(config))?
+    {
+        let label = main_path.display().to_string();
+        diff.push_str(&crate::diff::unified_diff(
+            &crate::kube::redact_for_diff(&before),
+            &crate::kube::redact_for_diff(&after),
+            &label,
+            &label,
+        ));
+    }
+    log::info!(
+        "[{}] DRY-RUN: {} changed line(s) would be written",
+        server.name,
+        count_changes(&diff)
+    );
+    Ok(diff)
+}
+
+/// Number of added or removed lines in a unified diff.
+fn count_changes(diff: &str) -> usize {
+    diff.lines()
+        .filter(|l| (l.starts_with('+') && !l.starts_with("+++")) || (l.starts_with('-') && !l.starts_with("---")))
+        .count()
+}
+
+/// True when a cert expiring at `expiry` is inside the renewal window ending at `now + renew_before`.
+pub(crate) fn renewal_due(
+    expiry: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+    renew_before: chrono::Duration,
+) -> bool {
+    expiry - now <= renew_before
+}
+
+/// The separately stored sudo password of a server with `sudo_password = true`; `None`
+/// for other servers, which answer sudo with the SSH password.
+pub(crate) fn resolve_sudo_password(
+    server: &crate::config::Server,
+) -> Result<Option<Zeroizing<String>>, anyhow::Error> {
+    if !server.sudo_password.unwrap_or(false) {
+        return Ok(None);
+    }
+    sudo_password_from(server, crate::credentials::get_sudo_password(&server.name))
+}
+
+/// The lookup result for a server that needs its own sudo password. Nothing stored is
+/// an error: falling back to the SSH password would only get sudo to reject it.
+pub(crate) fn sudo_password_from(
+    server: &crate::config::Server,
+    lookup: crate::credentials::CredentialResult,
+) -> Result<Option<Zeroizing<String>>, anyhow::Error> {
+    match lookup {
+        crate::credentials::CredentialResult::Found(pw) => Ok(Some(pw)),
+        crate::credentials::CredentialResult::NotFound => anyhow::bail!(
+            "[{}] sudo_password is set but no sudo password is stored. Add it with `credential set --server {} --sudo`.",
+            server.name,
+            server.name
+        ),
+        crate::credentials::CredentialResult::Unavailable(reason) => {
+            anyhow::bail!("[{}] Keyring unavailable ({})", server.name, reason)
+        }
+    }
+}
+
+/// Picks the passphrase for an identity file: an explicit one wins, otherwise the
+/// keyring is consulted, but only when the key is actually encrypted.
+pub(crate) fn resolve_key_passphrase(identity_file: Option<&str>, explicit: Option<&str>) -> Option<Zeroizing<String>> {
+    if let Some(p) = explicit {
+        return Some(Zeroizing::new(p.to_string()));
+    }
+    let path = identity_file?;
+    if !crate::ssh::key_is_encrypted(std::path::Path::new(path)) {
+        return None;
+    }
+    match crate::credentials::get_key_passphrase(path) {
+        crate::credentials::CredentialResult::Found(p) => Some(p),
+        _ => None,
+    }
+}
+
+/// Runs `job` for each item on a thread of its own, at most `limit` at a time, and
+/// starts the next item as soon as any running one finishes. A fetch spends most of its
+/// life blocked on the network, so a host stuck in its connect timeout holds only its
+/// own slot rather than a pool thread with a share of the remaining work queued behind it.
+///
+/// No new item is started once `stop` returns true. Results come back in the order of
+/// `items`, leaving out those never started. A panicking job is re-raised here.
+pub(crate) fn run_bounded<'a, I: Sync, T: Send>(
+    items: &[&'a I],
+    limit: usize,
+    stop: impl Fn() -> bool,
+    job: impl Fn(&'a I) -> T + Sync,
+) -> Vec<(&'a I, T)> {
+    let mut results: Vec<Option<T>> = items.iter().map(|_| None).collect();
+    let (tx, rx) = mpsc::channel();
+    std::thread::scope(|scope| {
+        let mut next = 0;
+        let mut running = 0;
+        loop {
+            while running < limit.max(1) && next < items.len() && !stop() {
+                let (tx, job, item) = (tx.clone(), &job, items[next]);
+                let index = next;
+                scope.spawn(move || {
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job(item)));
+                    let _ = tx.send((index, result));
+                });
+                next += 1;
+                running += 1;
+            }
+            if running == 0 {
+                break;
+            }
+            let Ok((index, result)) = rx.recv() else { break };
+            running -= 1;
+            match result {
+                Ok(result) => results[index] = Some(result),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+    });
+    items
+        .iter()
+        .zip(results)
+        .filter_map(|(item, result)| result.map(|r| (*item, r)))
+        .collect()
+}
+
 /// Iterates through and processes all servers defined in the configuration.
 ///
 /// It ensures the output directory exists and then processes each server in parallel,
-/// logging successes and failures.
+/// logging successes and failures. The returned report lists every processed server.
+/// Progress is an overall bar plus one line per server currently being fetched.
+/// `show_progress` is false for machine-readable output, where the bars would be noise.
+/// `force` fetches even when the cached cert is still valid. With `fail_fast`, no new
+/// server is started once one has failed; fetches already running finish.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn process_servers(
     config: &crate::config::Config,
     servers_to_process: &[String],
     dry_run: bool,
-    vault_passwords: &std::collections::HashMap<String, String>,
-) -> Result<(), anyhow::Error> {
+    force: bool,
+    fail_fast: bool,
+    vault_passwords: &std::collections::HashMap<String, Zeroizing<String>>,
+    key_passphrase: Option<&str>,
+    show_progress: bool,
+) -> Result<RunReport, anyhow::Error> {
+    let mut report = RunReport {
+        version: REPORT_VERSION,
+        started_at: Some(chrono::Utc::now()),
+        dry_run,
+        ..Default::default()
+    };
     fs::create_dir_all(&config.local_output_dir)?;
     log::info!("Using output directory: {}", &config.local_output_dir);
 
@@ -126,71 +739,136 @@ pub(crate) fn process_servers(
 
     if servers.is_empty() {
         log::warn!("No servers found to process. Check your --servers flag or config file.");
-        return Ok(());
+        report.finished_at = Some(chrono::Utc::now());
+        write_report_file(config, &report);
+        return Ok(report);
     }
 
-    let bar = ProgressBar::new(servers.len() as u64);
+    let multi = if show_progress {
+        MultiProgress::new()
+    } else {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    };
+    let bar = multi.add(ProgressBar::new(servers.len() as u64));
     bar.set_style(
         ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")?
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")?
             .progress_chars("#>-"),
     );
+    let server_style = ProgressStyle::default_spinner().template("  {spinner:.green} {prefix:.bold} {msg}")?;
+
+    // Bytes received across all in-flight servers, shown next to the bar.
+    let transferred = AtomicU64::new(0);
+    let failed_once = std::sync::atomic::AtomicBool::new(false);
 
-    let results: Vec<_> = servers
-        .par_iter()
-        .map(|&server| {
+    let results = run_bounded(
+        &servers,
+        config.max_parallel(),
+        || fail_fast && failed_once.load(Ordering::Relaxed),
+        |server| {
+            let started = Instant::now();
+            // Added only once the server's fetch starts, so the lines shown are
+            // exactly the active fetches.
+            let line = multi.add(ProgressBar::new_spinner());
+            line.set_style(server_style.clone());
+            line.set_prefix(server.name.clone());
+            line.set_message("connecting");
+            if show_progress {
+                line.enable_steady_tick(Duration::from_millis(100));
+            }
+            let last_seen = AtomicU64::new(0);
+            let on_progress = |bytes: u64| {
+                let delta = bytes - last_seen.swap(bytes, Ordering::Relaxed);
+                let total = transferred.fetch_add(delta, Ordering::Relaxed) + delta;
+                line.set_message(format!("{} received", HumanBytes(bytes)));
+                bar.set_message(format!("{} received", HumanBytes(total)));
+            };
             let result = process_server(
                 server,
                 config,
                 dry_run,
-                false,
+                force,
                 vault_passwords.get(&server.name).map(|s| s.as_str()),
+                key_passphrase,
+                &on_progress,
+                None,
             );
+            line.finish_and_clear();
+            multi.remove(&line);
             bar.inc(1);
-            (server, result)
-        })
-        .collect();
+            if result.is_err() {
+                failed_once.store(true, Ordering::Relaxed);
+            }
+            (result, started.elapsed(), last_seen.into_inner())
+        },
+    );
 
     bar.finish_and_clear();
 
-    let mut fetched: u32 = 0;
-    let mut skipped_cert_valid: u32 = 0;
-    let mut skipped_no_cred: u32 = 0;
-    let mut failed: u32 = 0;
+    report.not_attempted = (servers.len() - results.len()) as u32;
+    if report.not_attempted > 0 {
+        log::warn!(
+            "--fail-fast: stopped after a failure; {} server(s) not attempted",
+            report.not_attempted
+        );
+    }
 
-    // Load existing state so entries for servers not in this run are preserved
-    let mut state_entries = crate::state::read_state().unwrap_or_default();
+    let mut state_entries = std::collections::HashMap::new();
 
-    for (server, result) in &results {
-        let server_state = match result {
-            Ok(ServerResult::Fetched) => {
-                fetched += 1;
+    for (server, (result, elapsed, bytes_fetched)) in &results {
+        let mut cert_expires = None;
+        let mut server_state = match result {
+            Ok(ServerResult::Fetched | ServerResult::DryRun(_)) => {
+                report.fetched += 1;
+                if let crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) =
+                    crate::kube::check_local_cert_expiry(&config.local_path(&server.name))
+                {
+                    cert_expires = Some(exp);
+                }
                 log::info!("[{}] Successfully fetched and merged.", server.name);
                 crate::state::ServerRunState {
                     status: crate::state::RunStatus::Fetched,
                     last_updated: Some(chrono::Utc::now()),
                     error: None,
+                    duration_ms: None,
+                }
+            }
+            Ok(ServerResult::Unreachable(e)) => {
+                report.unreachable += 1;
+                if let crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) =
+                    crate::kube::check_local_cert_expiry(&config.local_path(&server.name))
+                {
+                    cert_expires = Some(exp);
+                }
+                crate::state::ServerRunState {
+                    status: crate::state::RunStatus::Unreachable,
+                    last_updated: Some(chrono::Utc::now()),
+                    error: Some(e.clone()),
+                    duration_ms: None,
                 }
             }
             Ok(ServerResult::Skipped(SkipReason::CertValid(expiry))) => {
-                skipped_cert_valid += 1;
+                report.skipped_cert_valid += 1;
+                cert_expires = Some(*expiry);
                 log::debug!("[{}] Cert valid until {}, skipping", server.name, expiry);
                 crate::state::ServerRunState {
                     status: crate::state::RunStatus::Skipped,
                     last_updated: Some(chrono::Utc::now()),
                     error: None,
+                    duration_ms: None,
                 }
             }
             Ok(ServerResult::Skipped(SkipReason::KeyringUnavailable)) => {
-                skipped_no_cred += 1;
+                report.skipped_no_cred += 1;
                 crate::state::ServerRunState {
                     status: crate::state::RunStatus::NoCredential,
                     last_updated: Some(chrono::Utc::now()),
                     error: None,
+                    duration_ms: None,
                 }
             }
             Err(e) => {
-                failed += 1;
+                report.failed += 1;
                 log::error!("[{}] FAILED: {}", server.name, e);
                 let e_str = format!("{:#}", e);
                 let status = if crate::state::is_auth_error(&e_str) {
@@ -202,28 +880,101 @@ pub(crate) fn process_servers(
                     status,
                     last_updated: Some(chrono::Utc::now()),
                     error: Some(e_str),
+                    duration_ms: None,
                 }
             }
         };
+        server_state.duration_ms = Some(elapsed.as_millis() as u64);
+        let ca_expires = match result {
+            Ok(
+                ServerResult::Fetched | ServerResult::Unreachable(_) | ServerResult::Skipped(SkipReason::CertValid(_)),
+            ) => crate::kube::local_ca_expiry(&config.local_path(&server.name)),
+            _ => None,
+        };
+        if let Some(ca_expires) = ca_expires {
+            warn_if_ca_expiring(&server.name, ca_expires, config.ca_warning());
+        }
+        report.servers.push(ServerReport {
+            name: server.name.clone(),
+            status: server_state.status.clone(),
+            error: server_state.error.clone(),
+            cert_expires,
+            ca_expires,
+            duration_ms: elapsed.as_millis() as u64,
+            bytes_fetched: *bytes_fetched,
+            diff: match result {
+                Ok(ServerResult::DryRun(diff)) => Some(diff.clone()),
+                _ => None,
+            },
+        });
         state_entries.insert(server.name.clone(), server_state);
     }
 
     // Only emit a summary when something notable happened
     // Total silence when all certs are valid — safe for cron
-    if fetched > 0 || failed > 0 || skipped_no_cred > 0 {
+    if report.fetched > 0 || report.failed > 0 || report.skipped_no_cred > 0 || report.unreachable > 0 {
         log::info!(
-            "Done. fetched={} skipped_cert_valid={} skipped_no_cred={} failed={}",
-            fetched,
-            skipped_cert_valid,
-            skipped_no_cred,
-            failed
+            "Done. fetched={} skipped_cert_valid={} skipped_no_cred={} failed={} unreachable={}",
+            report.fetched,
+            report.skipped_cert_valid,
+            report.skipped_no_cred,
+            report.failed,
+            report.unreachable
         );
     }
 
-    // Write state file for TUI to consume (non-fatal)
-    if let Err(e) = crate::state::write_state(&state_entries) {
+    // Write state file for TUI to consume (non-fatal). Entries for servers not in
+    // this run are preserved.
+    if let Err(e) = crate::state::update_state(|states| states.extend(state_entries)) {
         log::warn!("Could not write state file: {}", e);
     }
 
-    Ok(())
+    if let Some(notifications) = &config.notifications {
+        crate::notify::send_all(notifications, &crate::notify::from_report(&report), dry_run);
+    }
+
+    report.finished_at = Some(chrono::Utc::now());
+    write_report_file(config, &report);
+
+    Ok(report)
+}
+
+/// Warns once the cluster CA is within `warning` of its expiry. Rotating a CA means
+/// handing the new one to every kubeconfig and node, so this starts months ahead.
+fn warn_if_ca_expiring(server_name: &str, expires: chrono::DateTime<chrono::Utc>, warning: chrono::Duration) {
+    let left = expires - chrono::Utc::now();
+    if left <= chrono::Duration::zero() {
+        log::warn!("[{}] Cluster CA expired on {}", server_name, expires.format("%Y-%m-%d"));
+    } else if left <= warning {
+        log::warn!(
+            "[{}] Cluster CA expires on {} ({} days); plan the CA rotation",
+            server_name,
+            expires.format("%Y-%m-%d"),
+            left.num_days()
+        );
+    }
+}
+
+/// Writes the run report to `report_file` when one is configured. Replaced by rename,
+/// so a scraper never reads a half-written file. Failure is logged, not returned: the
+/// fetches themselves already happened.
+fn write_report_file(config: &crate::config::Config, report: &RunReport) {
+    let Some(path) = &config.report_file else {
+        return;
+    };
+    let path = std::path::Path::new(path);
+    let write = || -> Result<(), anyhow::Error> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(report)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        log::warn!("Could not write run report to {}: {:#}", path.display(), e);
+    }
 }
@@ -0,0 +1,189 @@
+//! Earlier versions of each server's cached kubeconfig, so a bad fetch or a broken
+//! certificate rotation on the remote side can be rolled back without reconnecting.
+//!
+//! Versions live in `<local_output_dir>/.history/<server>/`, one file per source hash
+//! named `<UTC timestamp>.<hash>`, so names sort by age and fetching the same remote
+//! file again doesn't add a copy.
+
+use anyhow::Context as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One saved version of a server's cached kubeconfig.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub path: PathBuf,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    /// SHA-256 of the file as read from the source, before it was rewritten.
+    pub source_hash: String,
+    pub cert_expires: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Version {
+    /// First 12 characters of the source hash, enough to pick a version by.
+    pub fn short_hash(&self) -> &str {
+        &self.source_hash[..12.min(self.source_hash.len())]
+    }
+}
+
+/// Where the versions of `server_name` are kept.
+pub fn history_dir(config: &crate::config::Config, server_name: &str) -> PathBuf {
+    Path::new(&config.local_output_dir).join(".history").join(server_name)
+}
+
+/// Saves `content`, the kubeconfig just written for `server_name`, and deletes all
+/// but the newest `history_retention` versions. A version with the same source hash
+/// is replaced, which moves it to the front.
+pub fn record(
+    config: &crate::config::Config,
+    server_name: &str,
+    content: &str,
+    source_hash: &str,
+) -> Result<(), anyhow::Error> {
+    let retention = config.history_retention();
+    if retention == 0 {
+        return Ok(());
+    }
+    let dir = history_dir(config, server_name);
+    fs::create_dir_all(&dir).with_context(|| format!("creating history directory {:?}", dir))?;
+    for version in list_in(&dir)? {
+        if version.source_hash == source_hash {
+            fs::remove_file(&version.path).ok();
+        }
+    }
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let path = dir.join(format!("{}.{}", stamp, source_hash));
+    crate::kube::write_kubeconfig(&path, content)?;
+
+    for old in list_in(&dir)?.into_iter().skip(retention) {
+        if let Err(e) = fs::remove_file(&old.path) {
+            log::warn!("[{}] Could not remove old version {:?}: {}", server_name, old.path, e);
+        }
+    }
+    Ok(())
+}
+
+/// Saved versions of `server_name`, newest first; empty when nothing was saved yet.
+pub fn list(config: &crate::config::Config, server_name: &str) -> Result<Vec<Version>, anyhow::Error> {
+    list_in(&history_dir(config, server_name))
+}
+
+fn list_in(dir: &Path) -> Result<Vec<Version>, anyhow::Error> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut versions: Vec<Version> = fs::read_dir(dir)
+        .with_context(|| format!("reading history directory {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let (stamp, hash) = name.split_once('.').and_then(|(date, rest)| {
+                // The timestamp has its own dot before the milliseconds.
+                let (millis, hash) = rest.split_once('.')?;
+                Some((format!("{}.{}", date, millis), hash.to_string()))
+            })?;
+            let saved_at = chrono::NaiveDateTime::parse_from_str(&stamp, "%Y%m%dT%H%M%S%.3fZ")
+                .ok()?
+                .and_utc();
+            let cert_expires = match crate::kube::check_local_cert_expiry(&path) {
+                crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) => Some(exp),
+                crate::kube::CertStatus::Unknown | crate::kube::CertStatus::NoClientCert(_) => None,
+            };
+            Some(Version {
+                saved_at,
+                source_hash: hash,
+                cert_expires,
+                path,
+            })
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.saved_at));
+    Ok(versions)
+}
+
+/// Picks a version by position (`1` is the one before the newest) or by a prefix of
+/// its source hash. `None` picks the previous version.
+pub fn find<'a>(versions: &'a [Version], wanted: Option<&str>) -> Result<&'a Version, anyhow::Error> {
+    let Some(wanted) = wanted else {
+        return versions
+            .get(1)
+            .ok_or_else(|| anyhow::anyhow!("There is no earlier version to roll back to"));
+    };
+    if let Ok(index) = wanted.parse::<usize>()
+        && wanted.len() < 4
+    {
+        return versions
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("There are only {} versions", versions.len()));
+    }
+    let matches: Vec<&Version> = versions.iter().filter(|v| v.source_hash.starts_with(wanted)).collect();
+    match matches.as_slice() {
+        [one] => Ok(one),
+        [] => anyhow::bail!("No version with hash {}", wanted),
+        _ => anyhow::bail!("Hash {} matches more than one version", wanted),
+    }
+}
+
+/// Puts `version` back as the cached kubeconfig of `server` and merges it into
+/// ~/.kube/config when merging is enabled for the server.
+pub fn rollback(
+    config: &crate::config::Config,
+    server: &crate::config::Server,
+    version: &Version,
+) -> Result<(), anyhow::Error> {
+    let local_path = config.local_path(&server.name);
+    let content = fs::read_to_string(&version.path).with_context(|| format!("reading {:?}", version.path))?;
+    crate::kube::write_kubeconfig(&local_path, &content)
+        .with_context(|| format!("restoring {:?} from {:?}", local_path, version.path))?;
+    log::info!(
+        "[{}] Rolled back to the version saved {} ({})",
+        server.name,
+        version.saved_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        version.short_hash()
+    );
+    if server.merge_enabled(config) {
+        crate::kube::merge_into_main_kubeconfig(
+            &local_path,
+            &server.name,
+            server.merge_strategy(config),
+            config.backup_retention(),
+            false,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_dedups_by_hash_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = crate::config::Config {
+            local_output_dir: dir.path().to_string_lossy().into_owned(),
+            history_retention: Some(2),
+            ..Default::default()
+        };
+        for (content, hash) in [
+            ("one", "aaaa"),
+            ("two", "bbbb"),
+            ("one again", "aaaa"),
+            ("three", "cccc"),
+        ] {
+            record(&config, "s", content, hash).unwrap();
+            // Timestamps have millisecond resolution.
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let versions = list(&config, "s").unwrap();
+        let hashes: Vec<&str> = versions.iter().map(|v| v.source_hash.as_str()).collect();
+        assert_eq!(hashes, ["cccc", "aaaa"]);
+        assert_eq!(fs::read_to_string(&versions[1].path).unwrap(), "one again");
+
+        assert_eq!(find(&versions, None).unwrap().source_hash, "aaaa");
+        assert_eq!(find(&versions, Some("0")).unwrap().source_hash, "cccc");
+        assert_eq!(find(&versions, Some("ccc")).unwrap().source_hash, "cccc");
+        assert!(find(&versions, Some("5")).is_err());
+        assert!(find(&versions, Some("dddd")).is_err());
+    }
+}
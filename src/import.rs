@@ -0,0 +1,413 @@
+//! `import`: turns the clusters of an existing kubeconfig into `[[server]]` entries, so
+//! a fleet already in ~/.kube/config can be onboarded without retyping API addresses.
+
+use crate::config::{Config, Server};
+use crate::kube::KubeConfig;
+use std::path::Path;
+
+/// A cluster from the kubeconfig, ready to become a server once SSH details are known.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Server entry with the name, API host/port and context prefilled.
+    pub server: Server,
+    /// The cluster's `server:` URL, for display.
+    pub url: String,
+}
+
+/// A cluster left out of the import, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Reads the kubeconfig at `path` and splits its clusters into import candidates and
+/// skipped entries.
+pub fn read_candidates(path: &Path, config: &Config) -> Result<(Vec<Candidate>, Vec<Skipped>), anyhow::Error> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Could not read {}: {}", path.display(), e))?;
+    let kubeconfig: KubeConfig =
+        serde_yaml::from_str(&content).map_err(|e| anyhow::anyhow!("{} is not a kubeconfig: {}", path.display(), e))?;
+    Ok(candidates(&kubeconfig, config))
+}
+
+/// One candidate per cluster, named after the first context that uses it (or the
+/// cluster itself when no context does). Clusters already in `config` by name,
+/// context or API endpoint are skipped, as are proxied URLs (with a path, such as
+/// Rancher's `/k8s/clusters/...`) that don't point at a node's API server.
+pub fn candidates(kubeconfig: &KubeConfig, config: &Config) -> (Vec<Candidate>, Vec<Skipped>) {
+    let mut found: Vec<Candidate> = Vec::new();
+    let mut skipped = Vec::new();
+    for cluster in &kubeconfig.clusters {
+        let context = kubeconfig
+            .contexts
+            .iter()
+            .find(|c| c.context.cluster == cluster.name)
+            .map(|c| c.name.as_str());
+        let label = context.unwrap_or(&cluster.name);
+        let mut skip = |reason: String| {
+            skipped.push(Skipped {
+                name: label.to_string(),
+                reason,
+            })
+        };
+
+        let url = cluster.cluster.server.trim_end_matches('/');
+        let Some((host, port)) = crate::rancher::host_port(url) else {
+            skip(format!("server URL '{}' has no host", cluster.cluster.server));
+            continue;
+        };
+        if url.split_once("://").is_some_and(|(_, rest)| rest.contains('/')) {
+            skip(format!("'{}' is a proxy URL, not a node's API server", url));
+            continue;
+        }
+        let name = server_name(label);
+        if let Some(existing) = config.servers.iter().find(|s| {
+            s.name == name
+                || s.kube_context() == label
+                || s.server_url().is_some() && s.api_endpoint() == (host.clone(), port)
+        }) {
+            skip(format!("already configured as '{}'", existing.name));
+            continue;
+        }
+        if found.iter().any(|c| c.server.name == name) {
+            skip(format!("another cluster is already imported as '{}'", name));
+            continue;
+        }
+        found.push(Candidate {
+            server: Server {
+                name: name.clone(),
+                address: host.clone(),
+                target_cluster_ip: host,
+                target_cluster_port: Some(port).filter(|p| *p != crate::tls::API_PORT),
+                context_name: Some(label.to_string()).filter(|c| *c != name),
+                ..Default::default()
+            },
+            url: url.to_string(),
+        });
+    }
+    (found, skipped)
+}
+
+/// What first-run setup offers to add: kubeconfig clusters and `~/.ssh/config` hosts.
+#[derive(Debug, Default)]
+pub struct SetupScan {
+    pub clusters: Vec<Candidate>,
+    /// Hosts that aren't a cluster's API host. Many are no k3s node at all, so the
+    /// setup lists them unmarked.
+    pub hosts: Vec<Candidate>,
+    pub skipped: Vec<Skipped>,
+}
+
+impl SetupScan {
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty() && self.hosts.is_empty()
+    }
+}
+
+/// Scans for first-run setup: the clusters of every kubeconfig in `kube_dir` (`config`
+/// and `*.yaml` / `*.yml`), then `hosts`. A cluster whose API host is also an ssh host
+/// takes that host's SSH settings instead of being listed twice. Files that aren't
+/// kubeconfigs are passed over, and a cluster in several files is listed once.
+pub fn scan_for_setup(kube_dir: &Path, hosts: &[crate::ssh_config::HostEntry], config: &Config) -> SetupScan {
+    let mut scan = SetupScan::default();
+    for path in kubeconfig_files(kube_dir) {
+        let Ok((found, skipped)) = read_candidates(&path, config) else {
+            continue;
+        };
+        for candidate in found {
+            if !scan.clusters.iter().any(|c| c.server.name == candidate.server.name) {
+                scan.clusters.push(candidate);
+            }
+        }
+        for skip in skipped {
+            if !scan.skipped.contains(&skip) {
+                scan.skipped.push(skip);
+            }
+        }
+    }
+
+    for host in hosts {
+        let address = host.address();
+        let ssh_port = host.port.filter(|&port| port != crate::ssh::SSH_PORT);
+        if let Some(cluster) = scan
+            .clusters
+            .iter_mut()
+            .find(|c| c.server.address == address || c.server.address == host.alias)
+        {
+            cluster.server.address = address.to_string();
+            cluster.server.ssh_port = ssh_port;
+            cluster.server.user = host.user.clone();
+            cluster.server.identity_file = host.identity_file.clone();
+            continue;
+        }
+        let name = server_name(&host.alias);
+        if let Some(existing) = config.servers.iter().find(|s| s.name == name || s.address == address) {
+            scan.skipped.push(Skipped {
+                name: host.alias.clone(),
+                reason: format!("already configured as '{}'", existing.name),
+            });
+            continue;
+        }
+        let user = host.user.as_deref().map(|u| format!("{}@", u)).unwrap_or_default();
+        let port = ssh_port.map(|p| format!(":{}", p)).unwrap_or_default();
+        scan.hosts.push(Candidate {
+            server: Server {
+                name,
+                address: address.to_string(),
+                ssh_port,
+                target_cluster_ip: address.to_string(),
+                user: host.user.clone(),
+                identity_file: host.identity_file.clone(),
+                ..Default::default()
+            },
+            url: format!("ssh {}{}{}", user, address, port),
+        });
+    }
+    scan
+}
+
+/// Kubeconfig-looking files directly in `dir`: `config` first, then `*.yaml` and `*.yml`
+/// by name. Empty when `dir` can't be read.
+fn kubeconfig_files(dir: &Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<std::path::PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name().is_some_and(|n| n == "config")
+                || path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    files.sort_by_key(|path| (path.file_name().is_none_or(|n| n != "config"), path.clone()));
+    files
+}
+
+/// Options for `import` and `discover` on the command line.
+pub struct ImportOptions<'a> {
+    /// SSH user for every imported server; prompted for when `None` and interactive.
+    pub user: Option<&'a str>,
+    /// Identity file for every imported server; prompted for when `None` and interactive.
+    pub identity_file: Option<&'a str>,
+    /// Take the prefilled SSH address and the defaults without asking.
+    pub yes: bool,
+    pub dry_run: bool,
+}
+
+/// Imports the clusters of `kubeconfig_path` into the config at `config_path`, asking
+/// on the terminal for each cluster's SSH address, user and identity file.
+pub fn run(
+    config: &Config,
+    config_path: &std::path::PathBuf,
+    kubeconfig_path: &Path,
+    options: &ImportOptions,
+) -> Result<(), anyhow::Error> {
+    let (found, skipped) = read_candidates(kubeconfig_path, config)?;
+    add_candidates(
+        config,
+        config_path,
+        found,
+        &skipped,
+        &kubeconfig_path.display().to_string(),
+        options,
+    )
+}
+
+/// Adds `found` to the config at `config_path`, asking for each one's SSH details
+/// unless `options.yes`. `source` names where the candidates came from, for messages.
+pub fn add_candidates(
+    config: &Config,
+    config_path: &std::path::PathBuf,
+    found: Vec<Candidate>,
+    skipped: &[Skipped],
+    source: &str,
+    options: &ImportOptions,
+) -> Result<(), anyhow::Error> {
+    use std::io::IsTerminal;
+
+    for skip in skipped {
+        println!("Skipping {}: {}", skip.name, skip.reason);
+    }
+    if found.is_empty() {
+        println!("No new clusters in {}", source);
+        return Ok(());
+    }
+    if !options.yes && !std::io::stdin().is_terminal() {
+        anyhow::bail!("Refusing to prompt for SSH details without a terminal; pass --yes to accept the defaults");
+    }
+
+    let mut imported = 0;
+    for candidate in found {
+        let mut server = candidate.server;
+        println!("\n{} ({})", server.name, candidate.url);
+        if !options.yes {
+            if !matches!(prompt("  Add as a server? [Y/n]", "y")?.as_str(), "y" | "Y" | "yes") {
+                continue;
+            }
+            server.address = prompt("  SSH address", &server.address)?;
+        }
+        server.user = match options.user {
+            Some(user) => Some(user.to_string()),
+            None if options.yes => None,
+            None => Some(prompt(
+                "  SSH user (blank for default_user)",
+                config.default_user.as_deref().unwrap_or_default(),
+            )?)
+            .filter(|u| !u.is_empty() && Some(u) != config.default_user.as_ref()),
+        };
+        server.identity_file = match options.identity_file {
+            Some(path) => Some(path.to_string()),
+            None if options.yes => None,
+            None => Some(prompt("  Identity file (blank for default)", "")?).filter(|p| !p.is_empty()),
+        };
+        if options.dry_run {
+            println!("DRY-RUN: Would add server '{}' ({})", server.name, server.address);
+        } else {
+            crate::config::add_server(config_path, &server)?;
+            println!("Added server '{}'", server.name);
+        }
+        imported += 1;
+    }
+    if imported > 0 && !options.dry_run {
+        println!(
+            "\nImported {} server(s). Store passwords with `credential set --server <name>` if they don't use a key.",
+            imported
+        );
+    }
+    Ok(())
+}
+
+/// Asks for a value on stdin; an empty answer takes `default`.
+fn prompt(question: &str, default: &str) -> Result<String, anyhow::Error> {
+    use std::io::{BufRead, Write};
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// A server name from a context name: anything but letters, digits, `-`, `_` and `.`
+/// becomes `-` (EKS contexts are ARNs such as `arn:aws:eks:...:cluster/prod`).
+pub(crate) fn server_name(context: &str) -> String {
+    let name: String = context
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    name.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KUBECONFIG: &str = r#"
+apiVersion: v1
+kind: Config
+current-context: homelab
+clusters:
+- name: homelab
+  cluster:
+    server: https://10.0.0.10:6443
+- name: edge
+  cluster:
+    server: https://edge.lan:16443/
+- name: rancher-proxied
+  cluster:
+    server: https://rancher.lan/k8s/clusters/c-m-abc
+- name: known
+  cluster:
+    server: https://10.0.0.20:6443
+contexts:
+- name: homelab
+  context: { cluster: homelab, user: homelab }
+- name: "admin@edge cluster"
+  context: { cluster: edge, user: edge }
+users: []
+"#;
+
+    #[test]
+    fn test_candidates_from_kubeconfig() {
+        let kubeconfig: KubeConfig = serde_yaml::from_str(KUBECONFIG).unwrap();
+        let config = Config {
+            servers: vec![Server {
+                name: "prod".to_string(),
+                target_cluster_ip: "10.0.0.20".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (found, skipped) = candidates(&kubeconfig, &config);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].server.name, "homelab");
+        assert_eq!(found[0].server.target_cluster_ip, "10.0.0.10");
+        assert_eq!(found[0].server.address, "10.0.0.10");
+        assert_eq!(found[0].server.target_cluster_port, None);
+        assert_eq!(found[0].server.context_name, None);
+        assert_eq!(found[1].server.name, "admin-edge-cluster");
+        assert_eq!(found[1].server.target_cluster_ip, "edge.lan");
+        assert_eq!(found[1].server.target_cluster_port, Some(16443));
+        assert_eq!(found[1].server.context_name.as_deref(), Some("admin@edge cluster"));
+        assert_eq!(found[1].url, "https://edge.lan:16443");
+
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].name, "rancher-proxied");
+        assert_eq!(skipped[1].reason, "already configured as 'prod'");
+    }
+
+    #[test]
+    fn test_scan_for_setup_merges_ssh_hosts_into_clusters() {
+        use crate::ssh_config::HostEntry;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("config"), KUBECONFIG).unwrap();
+        // The same clusters again, as a copy kept next to the main file.
+        std::fs::write(dir.path().join("homelab.yaml"), KUBECONFIG).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not yaml").unwrap();
+        std::fs::write(dir.path().join("broken.yml"), "clusters: [").unwrap();
+
+        let hosts = [
+            HostEntry {
+                alias: "homelab-node".to_string(),
+                host_name: Some("10.0.0.10".to_string()),
+                user: Some("pi".to_string()),
+                port: Some(2222),
+                ..Default::default()
+            },
+            HostEntry {
+                alias: "nas".to_string(),
+                host_name: Some("10.0.0.30".to_string()),
+                user: Some("admin".to_string()),
+                port: Some(22),
+                ..Default::default()
+            },
+        ];
+        let scan = scan_for_setup(dir.path(), &hosts, &Config::default());
+
+        let names: Vec<&str> = scan.clusters.iter().map(|c| c.server.name.as_str()).collect();
+        assert_eq!(names, ["homelab", "admin-edge-cluster", "known"]);
+        assert_eq!(scan.clusters[0].server.user.as_deref(), Some("pi"));
+        assert_eq!(scan.clusters[0].server.ssh_port, Some(2222));
+        assert_eq!(scan.skipped.len(), 1, "each skipped cluster is reported once");
+
+        assert_eq!(scan.hosts.len(), 1);
+        assert_eq!(scan.hosts[0].server.name, "nas");
+        assert_eq!(scan.hosts[0].server.target_cluster_ip, "10.0.0.30");
+        assert_eq!(scan.hosts[0].server.ssh_port, None);
+        assert_eq!(scan.hosts[0].url, "ssh admin@10.0.0.30");
+    }
+}
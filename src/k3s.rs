@@ -0,0 +1,191 @@
+//! Kubeconfig source that needs no shell on the node: given a k3s join token and the
+//! server address, the kubeconfig is built locally. The only request made is for the
+//! cluster CA (`/cacerts`), which k3s serves without authentication and which the token
+//! pins by hash, so neither SSH nor sudo is involved.
+
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Limit on the `/cacerts` request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// User k3s assigns to short (password-only) server tokens.
+const DEFAULT_TOKEN_USER: &str = "server";
+
+/// A parsed k3s token.
+#[derive(Debug, PartialEq, Eq)]
+struct Token<'a> {
+    /// Hex SHA-256 of the CA bundle served at `/cacerts`. Only full `K10` tokens carry it.
+    ca_hash: Option<&'a str>,
+    user: &'a str,
+    password: &'a str,
+}
+
+/// Splits a k3s token. Full tokens look like `K10<ca-sha256>::<user>:<password>`; short
+/// tokens are just the password and authenticate as `server`.
+fn parse_token(token: &str) -> Result<Token<'_>, anyhow::Error> {
+    let token = token.trim();
+    let Some(rest) = token.strip_prefix("K10") else {
+        if token.is_empty() || token.contains("::") {
+            anyhow::bail!("not a k3s token (expected K10<hash>::<user>:<password>)");
+        }
+        return Ok(Token {
+            ca_hash: None,
+            user: DEFAULT_TOKEN_USER,
+            password: token,
+        });
+    };
+    let (ca_hash, credentials) = rest
+        .split_once("::")
+        .ok_or_else(|| anyhow::anyhow!("k3s token has no '::' after the CA hash"))?;
+    if ca_hash.len() != 64 || !ca_hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!("k3s token CA hash is not a SHA-256 hex digest");
+    }
+    let (user, password) = credentials
+        .split_once(':')
+        .filter(|(user, password)| !user.is_empty() && !password.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("k3s token has no <user>:<password> part"))?;
+    Ok(Token {
+        ca_hash: Some(ca_hash),
+        user,
+        password,
+    })
+}
+
+/// `https://host:6443`, bracketing IPv6 literals.
+fn api_url(host: &str) -> String {
+    crate::kube::api_url(crate::validate::bare_host(host), crate::tls::API_PORT)
+}
+
+/// Builds a kubeconfig for the k3s server at `host` from its join token. The CA is
+/// fetched from the server and checked against the hash in the token; short tokens
+/// carry no hash, so their CA is trusted as served and a warning is logged.
+pub fn generate_kubeconfig(server_name: &str, host: &str, token: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let token = parse_token(token)?;
+    let url = api_url(host);
+    let ca = fetch_cacerts(&url)?;
+    match token.ca_hash {
+        Some(expected) => {
+            let actual = sha256_hex(&ca);
+            if !actual.eq_ignore_ascii_case(expected) {
+                anyhow::bail!(
+                    "[{}] CA served by {} does not match the token (sha256 {}, token expects {})",
+                    server_name,
+                    url,
+                    actual,
+                    expected
+                );
+            }
+        }
+        None => log::warn!(
+            "[{}] Short k3s token has no CA hash; trusting the CA served by {}",
+            server_name,
+            url
+        ),
+    }
+    let kubeconfig = token_kubeconfig(server_name, &url, &ca, &token);
+    Ok(serde_yaml::to_string(&kubeconfig)?.into_bytes())
+}
+
+/// Reads the CA bundle. Verification is off for this one request because the CA is
+/// what we are asking for; the token's hash is what makes the result trustworthy.
+fn fetch_cacerts(url: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let cacerts_url = format!("{}/cacerts", url);
+    log::info!("Requesting cluster CA from {}", cacerts_url);
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .tls_config(ureq::tls::TlsConfig::builder().disable_verification(true).build())
+        .build()
+        .into();
+    let ca = agent
+        .get(&cacerts_url)
+        .call()
+        .map_err(|e| anyhow::anyhow!("Could not read the cluster CA from {}: {}", cacerts_url, e))?
+        .body_mut()
+        .read_to_vec()?;
+    if !String::from_utf8_lossy(&ca).contains("-----BEGIN CERTIFICATE-----") {
+        anyhow::bail!("{} did not return a PEM certificate", cacerts_url);
+    }
+    Ok(ca)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// One cluster, context and user, all named `name`. The user authenticates with the
+/// token's user and password.
+fn token_kubeconfig(name: &str, url: &str, ca: &[u8], token: &Token) -> serde_json::Value {
+    use base64::Engine as _;
+
+    json!({
+        "apiVersion": "v1",
+        "kind": "Config",
+        "current-context": name,
+        "clusters": [{
+            "name": name,
+            "cluster": {
+                "server": url,
+                "certificate-authority-data": base64::engine::general_purpose::STANDARD.encode(ca),
+            },
+        }],
+        "contexts": [{
+            "name": name,
+            "context": { "cluster": name, "user": name },
+        }],
+        "users": [{
+            "name": name,
+            "user": { "username": token.user, "password": token.password },
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9";
+
+    #[test]
+    fn test_parse_token() {
+        assert_eq!(
+            parse_token(&format!("K10{}::server:s3cret\n", HASH)).unwrap(),
+            Token {
+                ca_hash: Some(HASH),
+                user: "server",
+                password: "s3cret",
+            }
+        );
+        assert_eq!(
+            parse_token("s3cret").unwrap(),
+            Token {
+                ca_hash: None,
+                user: "server",
+                password: "s3cret",
+            }
+        );
+        assert!(parse_token("K10abc::server:x").is_err());
+        assert!(parse_token(&format!("K10{}::server", HASH)).is_err());
+        assert!(parse_token("").is_err());
+    }
+
+    #[test]
+    fn test_token_kubeconfig_is_valid() {
+        let raw = format!("K10{}::node:pw", HASH);
+        let token = parse_token(&raw).unwrap();
+        let url = api_url("fd00::10");
+        assert_eq!(url, "https://[fd00::10]:6443");
+        let yaml = serde_yaml::to_string(&token_kubeconfig("edge", &url, b"CA", &token)).unwrap();
+        crate::kube::validate_fetched(yaml.as_bytes()).unwrap();
+
+        let kubeconfig: crate::kube::KubeConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(kubeconfig.clusters[0].cluster.server, "https://[fd00::10]:6443");
+        assert_eq!(
+            kubeconfig.clusters[0].cluster.certificate_authority.as_deref(),
+            Some("Q0E=")
+        );
+        assert_eq!(kubeconfig.users[0].user.extra["username"].as_str(), Some("node"));
+        assert_eq!(sha256_hex(b"").len(), 64);
+    }
+}
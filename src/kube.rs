@@ -6,8 +6,15 @@ use std::fs;
 use std::path::Path;
 use x509_parser::prelude::parse_x509_pem;
 
+/// Keys the typed model doesn't know about, kept so a rewrite never drops them.
+pub type ExtraFields = IndexMap<String, serde_yaml::Value>;
+
 /// Represents the top-level structure of a Kubernetes config file.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Every struct in this model carries an `extra` map flattened from unknown keys
+/// (`insecure-skip-tls-verify`, `namespace`, exec users, extensions, ...), so a
+/// read-modify-write round trip preserves fields this tool doesn't manage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct KubeConfig {
     /// The API version of the kubeconfig file format.
     #[serde(rename = "apiVersion")]
@@ -15,17 +22,22 @@ pub struct KubeConfig {
     /// The kind of configuration file, typically "Config".
     pub kind: String,
     /// The name of the context that is currently active.
-    #[serde(rename = "current-context")]
+    #[serde(rename = "current-context", default)]
     pub current_context: String,
     /// A list of all clusters defined in the configuration.
+    #[serde(default)]
     pub clusters: Vec<ClusterInfo>,
     /// A list of all contexts defined in the configuration.
+    #[serde(default)]
     pub contexts: Vec<ContextInfo>,
     /// A list of all users defined in the configuration.
+    #[serde(default)]
     pub users: Vec<UserInfo>,
     /// A map for storing arbitrary, non-standard data.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub preferences: Option<IndexMap<String, serde_yaml::Value>>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// Represents the validity state of a locally cached certificate.
@@ -37,40 +49,100 @@ pub enum CertStatus {
     Expired(chrono::DateTime<chrono::Utc>),
     /// No local file, missing field, parse error — treat as unknown, fetch to be safe
     Unknown,
+    /// The current user authenticates without a client cert, so there is no expiry to
+    /// go by — fetch every time
+    NoClientCert(UserAuth),
 }
 
 /// Checks the local cached kubeconfig to determine if the certificate is still valid.
 /// Returns CertStatus::Unknown when the answer cannot be determined (missing file,
 /// missing field, parse error) — callers should treat Unknown as "needs fetch".
 pub fn check_local_cert_expiry(path: &std::path::Path) -> CertStatus {
-    if !path.exists() {
-        return CertStatus::Unknown;
+    match expiry_preference(path, "certificate-expires-at") {
+        None => match local_user_auth(path) {
+            Some(auth) if auth != UserAuth::ClientCert => CertStatus::NoClientCert(auth),
+            _ => CertStatus::Unknown,
+        },
+        Some(expiry) if expiry <= chrono::Utc::now() => CertStatus::Expired(expiry),
+        Some(expiry) => CertStatus::Valid(expiry),
     }
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return CertStatus::Unknown,
-    };
-    let kubeconfig: KubeConfig = match serde_yaml::from_str(&content) {
-        Ok(k) => k,
-        Err(_) => return CertStatus::Unknown,
-    };
-    let prefs = match kubeconfig.preferences {
-        Some(p) => p,
-        None => return CertStatus::Unknown,
-    };
-    let expiry_str = match prefs.get("certificate-expires-at").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return CertStatus::Unknown,
-    };
-    let expiry = match chrono::DateTime::parse_from_rfc3339(&expiry_str) {
-        Ok(dt) => dt.with_timezone(&chrono::Utc),
-        Err(_) => return CertStatus::Unknown,
+}
+
+/// How the current context's user of the local cached kubeconfig authenticates.
+pub fn local_user_auth(path: &std::path::Path) -> Option<UserAuth> {
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    Some(current_user(&kubeconfig)?.auth())
+}
+
+/// Where kubectl connects with the current context of a cached kubeconfig, and what it
+/// trusts and presents there.
+pub struct ApiTarget {
+    /// The cluster's `server` URL.
+    pub server: String,
+    /// PEM of `certificate-authority-data`, when the cluster has it.
+    pub ca_pem: Option<Vec<u8>>,
+    /// PEM client cert and key, when the user has them inline.
+    pub client_pem: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The `ApiTarget` of the local cached kubeconfig at `path`.
+pub fn local_api_target(path: &std::path::Path) -> Result<ApiTarget, anyhow::Error> {
+    let content = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&content)?;
+    let context = kubeconfig
+        .contexts
+        .iter()
+        .find(|c| c.name == kubeconfig.current_context)
+        .ok_or_else(|| anyhow::anyhow!("no context named '{}'", kubeconfig.current_context))?;
+    let cluster = kubeconfig
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .ok_or_else(|| anyhow::anyhow!("no cluster named '{}'", context.context.cluster))?;
+    let decode = |b64: &str| general_purpose::STANDARD.decode(b64);
+    let ca_pem = cluster
+        .cluster
+        .certificate_authority
+        .as_deref()
+        .map(decode)
+        .transpose()?;
+    let client_pem = match current_user(&kubeconfig) {
+        Some(User {
+            certificate_data: Some(cert),
+            key_data: Some(key),
+            ..
+        }) => Some((decode(cert)?, decode(key)?)),
+        _ => None,
     };
-    if expiry <= chrono::Utc::now() {
-        CertStatus::Expired(expiry)
-    } else {
-        CertStatus::Valid(expiry)
-    }
+    Ok(ApiTarget {
+        server: cluster.cluster.server.clone(),
+        ca_pem,
+        client_pem,
+    })
+}
+
+/// Expiry of the cluster CA recorded in the local cached kubeconfig, or `None` when
+/// the file has none (no `certificate-authority-data`, or fetched by an older version).
+pub fn local_ca_expiry(path: &std::path::Path) -> Option<chrono::DateTime<chrono::Utc>> {
+    expiry_preference(path, "ca-expires-at")
+}
+
+/// SHA-256 of the source file the local cached kubeconfig was written from.
+pub fn local_source_hash(path: &std::path::Path) -> Option<String> {
+    string_preference(path, "source-file-sha256")
+}
+
+/// Reads an RFC 3339 timestamp stored under `key` in a kubeconfig's preferences.
+fn expiry_preference(path: &std::path::Path, key: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(&string_preference(path, key)?)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+fn string_preference(path: &std::path::Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&content).ok()?;
+    Some(kubeconfig.preferences?.get(key)?.as_str()?.to_string())
 }
 
 /// A named cluster entry in the kubeconfig.
@@ -80,6 +152,8 @@ pub struct ClusterInfo {
     pub name: String,
     /// The detailed configuration for the cluster.
     pub cluster: Cluster,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// Contains the connection details for a Kubernetes cluster.
@@ -87,9 +161,16 @@ pub struct ClusterInfo {
 pub struct Cluster {
     /// The URL of the Kubernetes API server.
     pub server: String,
-    /// The base64-encoded certificate authority data for the cluster.
-    #[serde(rename = "certificate-authority-data")]
-    pub certificate_authority: String,
+    /// The base64-encoded certificate authority data for the cluster. Absent when the
+    /// cluster uses `certificate-authority` (a file path) or `insecure-skip-tls-verify`.
+    #[serde(
+        rename = "certificate-authority-data",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub certificate_authority: Option<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// A named context entry in the kubeconfig.
@@ -99,6 +180,8 @@ pub struct ContextInfo {
     pub name: String,
     /// The detailed configuration for the context.
     pub context: Context,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// Defines a context by linking a cluster, a user, and an optional namespace.
@@ -108,6 +191,11 @@ pub struct Context {
     pub user: String,
     /// The name of the cluster for this context.
     pub cluster: String,
+    /// The namespace kubectl uses when none is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// A named user entry in the kubeconfig.
@@ -117,17 +205,71 @@ pub struct UserInfo {
     pub name: String,
     /// The detailed configuration for the user.
     pub user: User,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
-/// Contains the authentication credentials for a user.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Contains the authentication credentials for a user. Client-cert fields are
+/// optional because token and exec-plugin users don't have them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct User {
     /// The base64-encoded client certificate data.
-    #[serde(rename = "client-certificate-data")]
-    pub certificate_data: String,
+    #[serde(rename = "client-certificate-data", default, skip_serializing_if = "Option::is_none")]
+    pub certificate_data: Option<String>,
     /// The base64-encoded client key data.
-    #[serde(rename = "client-key-data")]
-    pub key_data: String,
+    #[serde(rename = "client-key-data", default, skip_serializing_if = "Option::is_none")]
+    pub key_data: Option<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// How a kubeconfig user proves who it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserAuth {
+    /// `client-certificate-data` or a `client-certificate` file.
+    ClientCert,
+    /// A bearer `token` or `tokenFile`, e.g. a service account or k3s `--token` user.
+    Token,
+    /// An `exec` credential plugin such as `aws eks get-token` or `kubelogin`.
+    Exec,
+    /// A legacy `auth-provider` (oidc, gcp, azure).
+    AuthProvider,
+    /// `username` and `password`.
+    Basic,
+    /// None of the above, e.g. an empty `user: {}`.
+    Anonymous,
+}
+
+impl std::fmt::Display for UserAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UserAuth::ClientCert => "client cert",
+            UserAuth::Token => "token",
+            UserAuth::Exec => "exec plugin",
+            UserAuth::AuthProvider => "auth provider",
+            UserAuth::Basic => "basic auth",
+            UserAuth::Anonymous => "no credentials",
+        })
+    }
+}
+
+impl User {
+    pub fn auth(&self) -> UserAuth {
+        let has = |key: &str| self.extra.contains_key(key);
+        if self.certificate_data.is_some() || has("client-certificate") {
+            UserAuth::ClientCert
+        } else if has("token") || has("tokenFile") {
+            UserAuth::Token
+        } else if has("exec") {
+            UserAuth::Exec
+        } else if has("auth-provider") {
+            UserAuth::AuthProvider
+        } else if has("username") {
+            UserAuth::Basic
+        } else {
+            UserAuth::Anonymous
+        }
+    }
 }
 
 /// Adds a timestamp to the kubeconfig preferences indicating when it was last updated.
@@ -161,7 +303,15 @@ fn add_cert_expiration(kubeconfig: &mut KubeConfig) -> Result<(), anyhow::Error>
         return Ok(());
     };
 
-    let pem_data = general_purpose::STANDARD.decode(&user_info.user.certificate_data)?;
+    let Some(cert_b64) = &user_info.user.certificate_data else {
+        log::debug!(
+            "User '{}' authenticates with {} — no cert expiry to record",
+            user_name,
+            user_info.user.auth()
+        );
+        return Ok(());
+    };
+    let pem_data = general_purpose::STANDARD.decode(cert_b64)?;
     match parse_x509_pem(&pem_data) {
         Ok((_, pem)) => {
             let cert = pem.parse_x509()?;
@@ -189,6 +339,44 @@ fn add_cert_expiration(kubeconfig: &mut KubeConfig) -> Result<(), anyhow::Error>
     Ok(())
 }
 
+/// Parses the current context's cluster CA and records when it expires. The CA usually
+/// lives for years, but replacing it means handing the new one to every client.
+fn add_ca_expiration(kubeconfig: &mut KubeConfig) -> Result<(), anyhow::Error> {
+    let Some(ca_b64) = kubeconfig
+        .contexts
+        .iter()
+        .find(|c| c.name == kubeconfig.current_context)
+        .and_then(|context| kubeconfig.clusters.iter().find(|c| c.name == context.context.cluster))
+        .and_then(|cluster| cluster.cluster.certificate_authority.as_deref())
+    else {
+        log::debug!("No certificate-authority-data for the current context — no CA expiry to record");
+        return Ok(());
+    };
+    // Best effort: kubectl is the judge of the CA, an unreadable one only loses the date.
+    let Some(expiry) = pem_not_after(ca_b64) else {
+        log::warn!("Failed to parse the cluster CA certificate. Skipping...");
+        return Ok(());
+    };
+    log::debug!("Cluster CA expires on : {}", expiry);
+    let preferences = kubeconfig.preferences.get_or_insert_with(IndexMap::new);
+    preferences.insert("ca-expires-at".to_string(), serde_yaml::to_value(expiry.to_rfc3339())?);
+    Ok(())
+}
+
+/// `notAfter` of a base64-encoded PEM certificate.
+fn pem_not_after(cert_b64: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let pem_data = general_purpose::STANDARD.decode(cert_b64).ok()?;
+    let (_, pem) = parse_x509_pem(&pem_data).ok()?;
+    let timestamp = pem
+        .parse_x509()
+        .ok()?
+        .validity()
+        .not_after
+        .to_datetime()
+        .unix_timestamp();
+    chrono::DateTime::from_timestamp(timestamp, 0)
+}
+
 /// Adds the SHA256 hash of the original source file to the kubeconfig preferences.
 fn add_source_hash(kubeconfig: &mut KubeConfig, source_hash: &str) -> Result<(), anyhow::Error> {
     let preferences = kubeconfig.preferences.get_or_insert_with(IndexMap::new);
@@ -202,20 +390,35 @@ fn add_metadata(kubeconfig: &mut KubeConfig, source_hash: &str) -> Result<(), an
     add_source_hash(kubeconfig, source_hash)?;
     add_last_updated_timestamp(kubeconfig)?;
     add_cert_expiration(kubeconfig)?;
+    add_ca_expiration(kubeconfig)?;
     Ok(())
 }
 
+/// `https://host:port`, bracketing IPv6 literals as URLs require.
+pub(crate) fn api_url(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("https://[{}]:{}", host, port)
+    } else {
+        format!("https://{}:{}", host, port)
+    }
+}
+
 /// Updates the cluster's server URL and renames the cluster entry to `unique_name`
-/// so that each server's cluster is independently addressable after merging.
-fn update_cluster_info(kubeconfig: &mut KubeConfig, target_ip: &str, unique_name: &str) -> Result<(), anyhow::Error> {
-    if let Some(cluster_info) = kubeconfig.clusters.get_mut(0) {
+/// so that each server's cluster is independently addressable after merging. An
+/// empty `server_url` keeps the URL the source wrote.
+fn update_cluster_info(kubeconfig: &mut KubeConfig, server_url: &str, unique_name: &str) -> Result<(), anyhow::Error> {
+    if let Some(cluster_info) = kubeconfig.clusters.get_mut(0)
+        && server_url.is_empty()
+    {
+        cluster_info.name = unique_name.to_string();
+    } else if let Some(cluster_info) = kubeconfig.clusters.get_mut(0) {
         log::info!(
-            "Updating cluster '{}' server from '{}' to 'https://{}:6443'",
+            "Updating cluster '{}' server from '{}' to '{}'",
             cluster_info.name,
             cluster_info.cluster.server,
-            target_ip
+            server_url
         );
-        cluster_info.cluster.server = format!("https://{}:6443", target_ip);
+        cluster_info.cluster.server = server_url.to_string();
         cluster_info.name = unique_name.to_string();
     } else {
         anyhow::bail!("No clusters found in the kubeconfig file.")
@@ -227,7 +430,11 @@ fn update_cluster_info(kubeconfig: &mut KubeConfig, target_ip: &str, unique_name
 /// Renames the context, user, and all cross-references to `unique_name` so that
 /// multiple servers whose k3s configs all default to "default" can coexist in
 /// a merged ~/.kube/config without overwriting each other's entries.
-fn update_context_info(kubeconfig: &mut KubeConfig, unique_name: &str) -> Result<(), anyhow::Error> {
+fn update_context_info(
+    kubeconfig: &mut KubeConfig,
+    unique_name: &str,
+    namespace: Option<&str>,
+) -> Result<(), anyhow::Error> {
     if let Some(user) = kubeconfig.users.get_mut(0) {
         user.name = unique_name.to_string();
     }
@@ -241,6 +448,9 @@ fn update_context_info(kubeconfig: &mut KubeConfig, unique_name: &str) -> Result
         context_info.name = unique_name.to_string();
         context_info.context.cluster = unique_name.to_string();
         context_info.context.user = unique_name.to_string();
+        if let Some(namespace) = namespace {
+            context_info.context.namespace = Some(namespace.to_string());
+        }
     } else {
         anyhow::bail!("No contexts found in the kubeconfig file.");
     }
@@ -251,65 +461,90 @@ fn update_context_info(kubeconfig: &mut KubeConfig, unique_name: &str) -> Result
     Ok(())
 }
 
-/// Reads a local kubeconfig file, applies modifications, and writes it back.
-///
-/// This is the main function for processing a fetched kubeconfig. It reads the file,
-/// adds metadata, updates cluster and context information, and then saves the file.
-pub fn process_kubeconfig_file(
-    local_path: &Path,
-    target_ip: &str,
-    source_hash: &str,
-    target_context: &Option<String>,
-    server_name: &str,
-    dry_run: bool,
-) -> Result<(), anyhow::Error> {
-    log::debug!("Processing file {:?}...", local_path);
-
-    if !dry_run && local_path.exists() {
-        let old_content = fs::read_to_string(local_path)?;
-        if let Ok(old_kubeconfig) = serde_yaml::from_str::<KubeConfig>(&old_content)
-            && let Some(prefs) = old_kubeconfig.preferences
-            && let Some(old_hash) = prefs.get("source-file-sha256").and_then(|v| v.as_str())
-            && old_hash != source_hash
-        {
-            log::warn!(
-                "[{:?}] Source file on remote has changed since last run (SHA256: {} -> {})",
-                local_path.file_name().unwrap_or_default(),
-                &old_hash[..8],
-                &source_hash[..8]
-            );
-        }
+/// Logs a warning when the cached copy at `local_path` was built from a different
+/// remote file than `source_hash`.
+pub fn warn_if_source_changed(local_path: &Path, source_hash: &str) {
+    if let Ok(old_content) = fs::read_to_string(local_path)
+        && let Ok(old_kubeconfig) = serde_yaml::from_str::<KubeConfig>(&old_content)
+        && let Some(prefs) = old_kubeconfig.preferences
+        && let Some(old_hash) = prefs.get("source-file-sha256").and_then(|v| v.as_str())
+        && old_hash != source_hash
+    {
+        log::warn!(
+            "[{:?}] Source file on remote has changed since last run (SHA256: {} -> {})",
+            local_path.file_name().unwrap_or_default(),
+            &old_hash[..8.min(old_hash.len())],
+            &source_hash[..8.min(source_hash.len())]
+        );
     }
+}
 
-    if dry_run && !local_path.exists() {
-        log::info!(
-            "DRY-RUN: No local file at {:?} — skipping kubeconfig processing (would write on a real run)",
-            local_path
+/// Checks that bytes read from a server are a usable kubeconfig before anything is
+/// written: non-empty UTF-8 YAML of `kind: Config` with at least one cluster (with a
+/// server URL), context and user. Returns the content as text.
+///
+/// `cat` output is taken at face value by the SSH layer, so a sudo password prompt,
+/// an empty file or an error message would otherwise replace a good cached copy.
+pub fn validate_fetched(content: &[u8]) -> Result<&str, anyhow::Error> {
+    let text = std::str::from_utf8(content).context("remote file is not valid UTF-8")?;
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("remote file is empty");
+    }
+    let first = trimmed.lines().next().unwrap_or_default().trim_end();
+    if first.starts_with("[sudo]") || (first.contains("password for ") && first.ends_with(':')) {
+        anyhow::bail!(
+            "remote output looks like a sudo password prompt, not a kubeconfig: {:?}",
+            first_line(trimmed)
         );
-        return Ok(());
     }
+    let kubeconfig: KubeConfig = serde_yaml::from_str(text).with_context(|| {
+        format!(
+            "remote file is not a kubeconfig (starts with {:?})",
+            first_line(trimmed)
+        )
+    })?;
+    if kubeconfig.kind != "Config" {
+        anyhow::bail!("remote file has kind {:?}, expected \"Config\"", kubeconfig.kind);
+    }
+    if kubeconfig.clusters.is_empty() || kubeconfig.contexts.is_empty() || kubeconfig.users.is_empty() {
+        anyhow::bail!(
+            "remote kubeconfig is incomplete ({} cluster(s), {} context(s), {} user(s))",
+            kubeconfig.clusters.len(),
+            kubeconfig.contexts.len(),
+            kubeconfig.users.len()
+        );
+    }
+    if let Some(cluster) = kubeconfig.clusters.iter().find(|c| c.cluster.server.trim().is_empty()) {
+        anyhow::bail!("cluster {:?} in remote kubeconfig has no server URL", cluster.name);
+    }
+    Ok(text)
+}
 
-    let content = fs::read_to_string(local_path)?;
-    let mut kubeconfig: KubeConfig = serde_yaml::from_str(&content)?;
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    line.chars().take(60).collect()
+}
+
+/// Applies the metadata, cluster address and context renames to a fetched kubeconfig
+/// and returns the YAML to write to the per-server file.
+pub fn rewrite_kubeconfig(
+    content: &str,
+    server_url: &str,
+    source_hash: &str,
+    target_context: &Option<String>,
+    server_name: &str,
+    namespace: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let mut kubeconfig: KubeConfig = serde_yaml::from_str(content)?;
 
     let unique_name = target_context.as_deref().unwrap_or(server_name);
 
     add_metadata(&mut kubeconfig, source_hash)?;
-    update_cluster_info(&mut kubeconfig, target_ip, unique_name)?;
-    update_context_info(&mut kubeconfig, unique_name)?;
+    update_cluster_info(&mut kubeconfig, server_url, unique_name)?;
+    update_context_info(&mut kubeconfig, unique_name, namespace)?;
 
-    let updated_content = serde_yaml::to_string(&kubeconfig)?;
-
-    if dry_run {
-        log::info!("DRY-RUN: Would have updated kubeconfig file at {:?}", local_path);
-        // Optionally, you could print the diff or the would-be content here
-        // log::info!("---\n{}---", updated_content);
-    } else {
-        fs::write(local_path, updated_content)?;
-        log::info!("Successfully updated and saved kubeconfig file");
-    }
-
-    Ok(())
+    Ok(serde_yaml::to_string(&kubeconfig)?)
 }
 
 /// Parse the client certificate expiry directly from raw kubeconfig bytes.
@@ -318,91 +553,406 @@ pub fn process_kubeconfig_file(
 pub fn parse_cert_expiry_from_bytes(content: &[u8]) -> Option<chrono::DateTime<chrono::Utc>> {
     let content_str = std::str::from_utf8(content).ok()?;
     let kubeconfig: KubeConfig = serde_yaml::from_str(content_str).ok()?;
+    pem_not_after(current_client_cert(&kubeconfig)?)
+}
 
+/// Details of the client cert in the local cached kubeconfig, for the detail view.
+/// `None` when there is no file or its current user has no readable cert.
+pub fn local_client_cert_details(path: &std::path::Path) -> Option<crate::tls::CertDetails> {
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let pem_data = general_purpose::STANDARD
+        .decode(current_client_cert(&kubeconfig)?)
+        .ok()?;
+    let (_, pem) = parse_x509_pem(&pem_data).ok()?;
+    crate::tls::cert_details(&pem.contents).ok()
+}
+
+/// The base64 client cert of the current context's user.
+fn current_client_cert(kubeconfig: &KubeConfig) -> Option<&str> {
+    current_user(kubeconfig)?.certificate_data.as_deref()
+}
+
+/// The user of the current context.
+fn current_user(kubeconfig: &KubeConfig) -> Option<&User> {
     let context_entry = kubeconfig
         .contexts
         .iter()
         .find(|c| c.name == kubeconfig.current_context)?;
     let user_name = &context_entry.context.user;
-    let user_info = kubeconfig.users.iter().find(|u| u.name == *user_name)?;
+    Some(&kubeconfig.users.iter().find(|u| u.name == *user_name)?.user)
+}
 
-    let pem_data = general_purpose::STANDARD
-        .decode(&user_info.user.certificate_data)
-        .ok()?;
-    let (_, pem) = parse_x509_pem(&pem_data).ok()?;
-    let cert = pem.parse_x509().ok()?;
-    let timestamp = cert.validity().not_after.to_datetime().unix_timestamp();
-    chrono::DateTime::from_timestamp(timestamp, 0)
+/// How `merge_into_main_kubeconfig` handles fetched entries whose names are already
+/// used in ~/.kube/config by a different cluster.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Replace the existing entries (the behaviour before strategies existed).
+    #[default]
+    Overwrite,
+    /// Merge under `<name>-2`, `<name>-3`, ... instead, leaving the existing entries alone.
+    Rename,
+    /// Leave ~/.kube/config untouched for this server.
+    Skip,
+    /// Ask in the TUI; CLI and daemon runs report the conflict as a failure.
+    Prompt,
 }
 
-/// Merges cluster, context, and user entries from a fetched per-server kubeconfig
-/// into the main ~/.kube/config file. Existing entries with the same name are replaced.
-/// Preferences and current_context in the main config are never modified.
-pub fn merge_into_main_kubeconfig(fetched_path: &Path, server_name: &str, dry_run: bool) -> Result<(), anyhow::Error> {
-    if dry_run && !fetched_path.exists() {
-        log::info!(
-            "[{}] DRY-RUN: Would merge processed config into ~/.kube/config",
-            server_name
-        );
-        return Ok(());
+/// What a merge did to ~/.kube/config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Merged,
+    /// Merged under a new name because the original one was taken.
+    Renamed(String),
+    /// Not merged because of a conflict and the `skip` strategy.
+    Skipped,
+}
+
+/// Fetched entries clash with entries in ~/.kube/config that belong to another cluster,
+/// and the strategy is `prompt`.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    pub server_name: String,
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] ~/.kube/config already has a different cluster named {}. \
+             Resolve it in the TUI or set merge_strategy to overwrite, rename, or skip.",
+            self.server_name,
+            self.names.join(", ")
+        )
     }
+}
 
-    let content = fs::read_to_string(fetched_path)?;
-    let fetched: KubeConfig = serde_yaml::from_str(&content)?;
+impl std::error::Error for MergeConflict {}
 
-    let main_config_path = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-        .join(".kube")
-        .join("config");
+/// Names of fetched entries that would replace something other than this server's own
+/// earlier merge: a cluster with the same name but a different API server, or a context
+/// with the same name pointing at a different cluster. Re-merging the same server is
+/// never a conflict, so renewed certificates replace the old ones in place.
+pub fn merge_conflicts(main: &KubeConfig, fetched: &KubeConfig) -> Vec<String> {
+    let mut names = Vec::new();
+    for cluster in &fetched.clusters {
+        if main
+            .clusters
+            .iter()
+            .any(|c| c.name == cluster.name && c.cluster.server != cluster.cluster.server)
+        {
+            names.push(cluster.name.clone());
+        }
+    }
+    for context in &fetched.contexts {
+        if !names.contains(&context.name)
+            && main
+                .contexts
+                .iter()
+                .any(|c| c.name == context.name && c.context.cluster != context.context.cluster)
+        {
+            names.push(context.name.clone());
+        }
+    }
+    names
+}
 
-    let mut main_config = if main_config_path.exists() {
-        let main_content = fs::read_to_string(&main_config_path)?;
-        serde_yaml::from_str::<KubeConfig>(&main_content)?
-    } else {
-        KubeConfig {
-            api_version: "v1".to_string(),
-            kind: "Config".to_string(),
-            current_context: String::new(),
-            clusters: Vec::new(),
-            contexts: Vec::new(),
-            users: Vec::new(),
-            preferences: None,
+/// Renames every cluster, context, and user called `from` to `to`, including the
+/// context's references and `current-context`.
+pub(crate) fn rename_entries(kubeconfig: &mut KubeConfig, from: &str, to: &str) {
+    for cluster in kubeconfig.clusters.iter_mut().filter(|c| c.name == from) {
+        cluster.name = to.to_string();
+    }
+    for user in kubeconfig.users.iter_mut().filter(|u| u.name == from) {
+        user.name = to.to_string();
+    }
+    for context in &mut kubeconfig.contexts {
+        if context.name == from {
+            context.name = to.to_string();
         }
-    };
+        if context.context.cluster == from {
+            context.context.cluster = to.to_string();
+        }
+        if context.context.user == from {
+            context.context.user = to.to_string();
+        }
+    }
+    if kubeconfig.current_context == from {
+        kubeconfig.current_context = to.to_string();
+    }
+}
+
+/// Applies `strategy` and upserts the fetched entries into `main`.
+/// Preferences and current_context in `main` are never modified.
+pub fn merge_kubeconfigs(
+    main: &mut KubeConfig,
+    mut fetched: KubeConfig,
+    strategy: MergeStrategy,
+    server_name: &str,
+) -> Result<MergeOutcome, MergeConflict> {
+    let conflicts = merge_conflicts(main, &fetched);
+    let mut outcome = MergeOutcome::Merged;
+    if !conflicts.is_empty() {
+        match strategy {
+            MergeStrategy::Overwrite => {
+                log::warn!(
+                    "[{}] Overwriting existing ~/.kube/config entries: {}",
+                    server_name,
+                    conflicts.join(", ")
+                );
+            }
+            MergeStrategy::Skip => {
+                log::warn!(
+                    "[{}] Not merging: ~/.kube/config already has a different cluster named {}",
+                    server_name,
+                    conflicts.join(", ")
+                );
+                return Ok(MergeOutcome::Skipped);
+            }
+            MergeStrategy::Prompt => {
+                return Err(MergeConflict {
+                    server_name: server_name.to_string(),
+                    names: conflicts,
+                });
+            }
+            MergeStrategy::Rename => {
+                for name in &conflicts {
+                    // The first free suffix, or one this server already owns from an
+                    // earlier renamed merge, so repeated fetches don't pile up copies.
+                    let new_name = (2..)
+                        .map(|n| format!("{}-{}", name, n))
+                        .find(|candidate| {
+                            let mut renamed = fetched.clone();
+                            rename_entries(&mut renamed, name, candidate);
+                            !merge_conflicts(main, &renamed).contains(candidate)
+                        })
+                        .expect("unbounded suffix search always finds a name");
+                    log::info!(
+                        "[{}] '{}' is taken in ~/.kube/config; merging as '{}'",
+                        server_name,
+                        name,
+                        new_name
+                    );
+                    rename_entries(&mut fetched, name, &new_name);
+                    outcome = MergeOutcome::Renamed(new_name);
+                }
+            }
+        }
+    }
 
     // Upsert clusters
     for cluster in &fetched.clusters {
-        main_config.clusters.retain(|c| c.name != cluster.name);
-        main_config.clusters.push(cluster.clone());
+        main.clusters.retain(|c| c.name != cluster.name);
+        main.clusters.push(cluster.clone());
     }
-    // Upsert contexts
+    // Upsert contexts. A namespace picked with `kubectl config set-context --namespace`
+    // stays unless the server config sets one.
     for context in &fetched.contexts {
-        main_config.contexts.retain(|c| c.name != context.name);
-        main_config.contexts.push(context.clone());
+        let mut context = context.clone();
+        if context.context.namespace.is_none()
+            && let Some(existing) = main
+                .contexts
+                .iter()
+                .find(|c| c.name == context.name && c.context.cluster == context.context.cluster)
+        {
+            context.context.namespace = existing.context.namespace.clone();
+        }
+        main.contexts.retain(|c| c.name != context.name);
+        main.contexts.push(context);
     }
     // Upsert users
     for user in &fetched.users {
-        main_config.users.retain(|u| u.name != user.name);
-        main_config.users.push(user.clone());
+        main.users.retain(|u| u.name != user.name);
+        main.users.push(user.clone());
+    }
+
+    Ok(outcome)
+}
+
+/// Writes a kubeconfig so that `path` holds either the old or the new content, never
+/// a partial file: the content goes to `<path>.tmp`, owner-only (0600) from the
+/// start, is synced to disk and then renamed over `path`.
+pub fn write_kubeconfig(path: &Path, content: &str) -> Result<(), anyhow::Error> {
+    use std::io::Write as _;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // `mode` only applies to new files; a temp file left by a crash keeps its own.
+        if tmp.exists() {
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600)).ok();
+        }
+    }
+    let written = options.open(&tmp).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        fs::remove_file(&tmp).ok();
+        return Err(anyhow::Error::new(e).context(format!("writing {:?}", tmp)));
+    }
+    fs::rename(&tmp, path).with_context(|| format!("replacing {:?}", path))?;
+    // Make the rename itself durable.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty())
+        && let Ok(dir) = fs::File::open(dir)
+    {
+        dir.sync_all().ok();
+    }
+    Ok(())
+}
+
+/// Returns the path of the main kubeconfig, `~/.kube/config`.
+pub fn main_kubeconfig_path() -> Result<std::path::PathBuf, anyhow::Error> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".kube")
+        .join("config"))
+}
+
+/// Makes `context` the `current-context` of the kubeconfig at `path`, like
+/// `kubectl config use-context`. Fails when no context of that name exists there.
+pub fn use_context(path: &Path, context: &str) -> Result<(), anyhow::Error> {
+    let content = fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+    let mut kubeconfig: KubeConfig = serde_yaml::from_str(&content)?;
+    if !kubeconfig.contexts.iter().any(|c| c.name == context) {
+        anyhow::bail!(
+            "context '{}' is not in {} — fetch the server first",
+            context,
+            path.display()
+        );
+    }
+    if kubeconfig.current_context != context {
+        kubeconfig.current_context = context.to_string();
+        write_kubeconfig(path, &serde_yaml::to_string(&kubeconfig)?)?;
+    }
+    Ok(())
+}
+
+/// Merges cluster, context, and user entries from a fetched per-server kubeconfig
+/// into the main ~/.kube/config file. Entries this server merged before are replaced;
+/// name clashes with other clusters are handled according to `strategy`.
+/// Preferences and current_context in the main config are never modified.
+/// The existing file is backed up first, keeping the newest `backup_retention` copies.
+pub fn merge_into_main_kubeconfig(
+    fetched_path: &Path,
+    server_name: &str,
+    strategy: MergeStrategy,
+    backup_retention: usize,
+    dry_run: bool,
+) -> Result<MergeOutcome, anyhow::Error> {
+    if dry_run && !fetched_path.exists() {
+        log::info!(
+            "[{}] DRY-RUN: Would merge processed config into ~/.kube/config",
+            server_name
+        );
+        return Ok(MergeOutcome::Merged);
+    }
+
+    let content = fs::read_to_string(fetched_path)?;
+    let fetched: KubeConfig = serde_yaml::from_str(&content)?;
+    let (clusters, contexts, users) = (fetched.clusters.len(), fetched.contexts.len(), fetched.users.len());
+
+    let main_config_path = main_kubeconfig_path()?;
+    let (_, mut main_config) = load_main_kubeconfig(&main_config_path)?;
+
+    let outcome = merge_kubeconfigs(&mut main_config, fetched, strategy, server_name)?;
+    if outcome == MergeOutcome::Skipped {
+        return Ok(outcome);
     }
 
     if dry_run {
         log::info!(
             "[{}] DRY-RUN: Would merge {} cluster(s), {} context(s), {} user(s) into {:?}",
             server_name,
-            fetched.clusters.len(),
-            fetched.contexts.len(),
-            fetched.users.len(),
+            clusters,
+            contexts,
+            users,
             main_config_path
         );
     } else {
         let updated = serde_yaml::to_string(&main_config)?;
+        crate::backup::backup_file(&main_config_path, &crate::backup::backup_dir()?, backup_retention)?;
         if let Some(parent) = main_config_path.parent() {
             fs::create_dir_all(parent).with_context(|| format!("creating directory {:?}", parent))?;
         }
-        fs::write(&main_config_path, updated).with_context(|| format!("writing {:?}", main_config_path))?;
+        write_kubeconfig(&main_config_path, &updated)?;
         log::info!("[{}] Merged cluster/context/user into ~/.kube/config", server_name);
     }
 
-    Ok(())
+    Ok(outcome)
+}
+
+/// Returns ~/.kube/config as it is now and as it would be after merging `fetched_content`,
+/// without writing anything. `None` when the strategy leaves the file untouched.
+pub fn preview_merge(
+    fetched_content: &str,
+    server_name: &str,
+    strategy: MergeStrategy,
+) -> Result<Option<(std::path::PathBuf, String, String)>, anyhow::Error> {
+    let fetched: KubeConfig = serde_yaml::from_str(fetched_content)?;
+    let main_config_path = main_kubeconfig_path()?;
+    let (before, mut main_config) = load_main_kubeconfig(&main_config_path)?;
+    if merge_kubeconfigs(&mut main_config, fetched, strategy, server_name)? == MergeOutcome::Skipped {
+        return Ok(None);
+    }
+    Ok(Some((main_config_path, before, serde_yaml::to_string(&main_config)?)))
+}
+
+/// Reads ~/.kube/config with its raw text, or an empty `v1` config when it doesn't exist yet.
+fn load_main_kubeconfig(path: &Path) -> Result<(String, KubeConfig), anyhow::Error> {
+    if !path.exists() {
+        let empty = KubeConfig {
+            api_version: "v1".to_string(),
+            kind: "Config".to_string(),
+            ..Default::default()
+        };
+        return Ok((String::new(), empty));
+    }
+    let content = fs::read_to_string(path)?;
+    let config = serde_yaml::from_str(&content)?;
+    Ok((content, config))
+}
+
+/// Keys whose values are credentials and are never shown.
+pub const SECRET_KEYS: [&str; 4] = ["client-key-data", "token", "password", "client-key"];
+/// Keys holding base64 certificates: not secret, but too long to be worth reading.
+pub const CERT_KEYS: [&str; 2] = ["certificate-authority-data", "client-certificate-data"];
+
+/// Splits `  - key: value` into (`  - `, `key`, `value`).
+pub fn split_key_value(line: &str) -> Option<(&str, &str, &str)> {
+    let body = line.trim_start().trim_start_matches("- ");
+    let prefix = &line[..line.len() - body.len()];
+    let (key, value) = body.split_once(':')?;
+    if key.is_empty() || key.contains(' ') {
+        return None;
+    }
+    Some((prefix, key, value.trim()))
+}
+
+/// Replaces secret and certificate values with a short SHA-256 fingerprint, so a diff
+/// of two redacted kubeconfigs still shows a rotated key or cert as a changed line.
+pub fn redact_for_diff(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines() {
+        match split_key_value(line) {
+            Some((prefix, key, value))
+                if (SECRET_KEYS.contains(&key) || CERT_KEYS.contains(&key)) && !value.is_empty() =>
+            {
+                let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+                out.push_str(&format!("{}{}: <redacted sha256:{}>", prefix, key, &digest[..12]));
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out
 }
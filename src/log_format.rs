@@ -0,0 +1,81 @@
+//! Log line formats. `--log-format json` writes one JSON object per log record, for
+//! log shippers (Loki, Elasticsearch) that filter on fields rather than free text.
+//! Both formats mask known credential values (see `redact`).
+
+use flexi_logger::DeferredNow;
+use log::Record;
+use serde_json::json;
+
+/// flexi_logger's default `LEVEL [module] message`, with secrets masked.
+pub fn text(w: &mut dyn std::io::Write, _now: &mut DeferredNow, record: &Record) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "{} [{}] {}",
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>"),
+        crate::redact::scrub(&record.args().to_string())
+    )
+}
+
+/// Writes `record` as a single line:
+/// `{"timestamp", "level", "server", "event", "message"}`.
+///
+/// `server` comes from the `[name] ` prefix this crate puts on per-server messages and
+/// is null otherwise. `event` is the module that logged the record (`main`, `ssh`,
+/// `fetch`, `kube`, ...), or the full target for other crates.
+pub fn json(w: &mut dyn std::io::Write, now: &mut DeferredNow, record: &Record) -> Result<(), std::io::Error> {
+    let message = record.args().to_string();
+    let message = crate::redact::scrub(&message);
+    let (server, message) = split_server(&message);
+    let line = json!({
+        "timestamp": now.now_utc_owned().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "level": record.level().as_str().to_ascii_lowercase(),
+        "server": server,
+        "event": event(record.target()),
+        "message": message,
+    });
+    write!(w, "{}", line)
+}
+
+/// Splits a leading `[server] ` off a message.
+fn split_server(message: &str) -> (Option<&str>, &str) {
+    message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+        .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+        .map_or((None, message), |(name, rest)| (Some(name), rest))
+}
+
+fn event(target: &str) -> &str {
+    if target == env!("CARGO_CRATE_NAME") {
+        return "main";
+    }
+    target
+        .strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::"))
+        .unwrap_or(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_server_and_event() {
+        assert_eq!(
+            split_server("[prod-k3s] Handshake complete"),
+            (Some("prod-k3s"), "Handshake complete")
+        );
+        assert_eq!(
+            split_server("Using output directory: /tmp"),
+            (None, "Using output directory: /tmp")
+        );
+        assert_eq!(split_server("[a b] not a name"), (None, "[a b] not a name"));
+        assert_eq!(event("kube_config_updater"), "main");
+        assert_eq!(event("kube_config_updater::ssh"), "ssh");
+        assert_eq!(
+            event("kube_config_updater::tui::features::detail"),
+            "tui::features::detail"
+        );
+        assert_eq!(event("rustls::client"), "rustls::client");
+    }
+}
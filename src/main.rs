@@ -3,14 +3,36 @@ use flexi_logger::{FileSpec, Logger, WriteMode};
 use std::fs;
 use std::path::PathBuf;
 
+mod audit;
+mod backup;
 mod bitwarden;
+mod bundle;
+mod cloud;
+mod color;
 mod config;
 mod credentials;
+mod cron;
+mod daemon;
+mod diff;
+mod discover;
 mod fetch;
+mod history;
+mod import;
+mod k3s;
 mod kube;
+mod log_format;
+mod notify;
+mod prune;
+mod rancher;
+mod redact;
+mod rename;
+mod schedule;
 mod ssh;
+mod ssh_config;
 mod state;
+mod tls;
 pub mod tui;
+mod validate;
 
 #[derive(Subcommand, Debug)]
 enum Commands {
@@ -21,6 +43,107 @@ enum Commands {
     },
     /// Launch the interactive TUI dashboard
     Tui,
+    /// Keep running and re-check servers on their configured interval
+    Daemon,
+    /// Run each selected server's `renew_command` over SSH, then fetch its new kubeconfig
+    Renew {
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Roll ~/.kube/config back to a backup taken before a merge
+    RestoreBackup {
+        /// Backup file name or path to restore (defaults to the newest backup)
+        backup: Option<PathBuf>,
+        /// List available backups, newest first, instead of restoring
+        #[arg(long)]
+        list: bool,
+    },
+    /// Check the config for unknown keys, bad IPs and missing files without connecting
+    Validate,
+    /// Add a server for each cluster in an existing kubeconfig, asking only for SSH details
+    Import {
+        /// Kubeconfig to read (defaults to ~/.kube/config)
+        kubeconfig: Option<PathBuf>,
+        /// SSH user for every imported server instead of asking
+        #[arg(long)]
+        user: Option<String>,
+        /// SSH identity file for every imported server instead of asking
+        #[arg(long)]
+        identity_file: Option<String>,
+        /// Use each cluster's API host as the SSH address and the config defaults, without asking
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Scan a network for nodes with SSH and a Kubernetes API server, and add them as servers
+    Discover {
+        /// Range to scan, e.g. 192.168.1.0/24 (at most 4096 addresses)
+        cidr: String,
+        /// Don't read each API server's certificate for a name; servers are named node-<ip>
+        #[arg(long)]
+        no_probe: bool,
+        /// How long to wait for each port to answer, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        timeout_ms: u64,
+        /// SSH user for every added server instead of asking
+        #[arg(long)]
+        user: Option<String>,
+        /// SSH identity file for every added server instead of asking
+        #[arg(long)]
+        identity_file: Option<String>,
+        /// Add every node found with the config defaults, without asking
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Remove cached kubeconfigs, state entries, credentials and merged contexts of servers
+    /// no longer in the config
+    Prune {
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Rename a server, moving its state, credential, cached kubeconfig and merged context along
+    Rename {
+        /// Current server name
+        from: String,
+        /// New server name
+        to: String,
+    },
+    /// List the saved versions of a server's kubeconfig, newest first
+    History {
+        /// Server name
+        server: String,
+    },
+    /// Put an earlier version of a server's kubeconfig back and merge it again
+    Rollback {
+        /// Server name
+        server: String,
+        /// Position from `history` (1 is the previous version) or a source hash prefix;
+        /// defaults to the previous version
+        version: Option<String>,
+    },
+    /// Show when stored credentials were read, stored, deleted or used to log in, oldest first
+    Audit {
+        /// Only events for this server (including its sudo password)
+        #[arg(long)]
+        server: Option<String>,
+        /// Only the last N events
+        #[arg(long)]
+        last: Option<usize>,
+    },
+    /// Schedule periodic fetch runs with the current config via a systemd user timer,
+    /// cron or launchd
+    InstallSchedule {
+        /// Minutes between runs (defaults to `daemon_interval_minutes`, else 60)
+        #[arg(long)]
+        interval: Option<u64>,
+        /// Scheduler to install into (defaults to launchd on macOS, systemd elsewhere)
+        #[arg(long, value_enum)]
+        scheduler: Option<schedule::Scheduler>,
+        /// Print the unit files or crontab line instead of installing them
+        #[arg(long)]
+        print: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -31,6 +154,18 @@ enum CredentialAction {
         server: Option<String>,
         #[arg(long, group = "target")]
         default: bool,
+        /// Store the passphrase for an encrypted SSH private key instead
+        #[arg(long, group = "target")]
+        key_file: Option<String>,
+        /// Store the same password for every server in the config
+        #[arg(long, group = "target")]
+        all: bool,
+        /// Store the same password for every server with this tag
+        #[arg(long, group = "target", value_name = "TAG")]
+        group: Option<String>,
+        /// Store the server's sudo password, for servers with `sudo_password = true`
+        #[arg(long, conflicts_with_all = ["default", "key_file"])]
+        sudo: bool,
         #[arg(long)]
         password: Option<String>,
     },
@@ -40,9 +175,52 @@ enum CredentialAction {
         server: Option<String>,
         #[arg(long, group = "target")]
         default: bool,
+        /// Remove the stored passphrase for an SSH private key
+        #[arg(long, group = "target")]
+        key_file: Option<String>,
+        /// Remove the server's sudo password instead of its SSH password
+        #[arg(long, requires = "server")]
+        sudo: bool,
     },
     /// Show which servers have a stored credential (never shows passwords)
     List,
+    /// Write every stored password and passphrase to an age-encrypted bundle
+    Export {
+        /// Bundle to write, e.g. credentials.age
+        #[arg(long)]
+        out: PathBuf,
+        /// age public key to encrypt to, repeatable; without one age asks for a passphrase
+        #[arg(long)]
+        recipient: Vec<String>,
+    },
+    /// Store every credential from a bundle written by `credential export`
+    Import {
+        /// Bundle to read
+        path: PathBuf,
+        /// age identity file to decrypt with, when the bundle was encrypted to a recipient
+        #[arg(long)]
+        identity: Option<PathBuf>,
+    },
+}
+
+/// Format of the fetch run's result on stdout.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable log lines and a progress bar.
+    #[default]
+    Text,
+    /// A JSON report of every processed server; logs move to stderr.
+    Json,
+}
+
+/// Format of log lines.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum LogFormat {
+    /// flexi_logger's default: `LEVEL [module] message`.
+    #[default]
+    Text,
+    /// One JSON object per line with timestamp, level, server, event and message.
+    Json,
 }
 
 /// Command-line arguments for the kube_config_updater application.
@@ -59,16 +237,80 @@ struct Cli {
     #[arg(short, long)]
     log_dir: Option<PathBuf>,
 
+    /// More log detail: -v for debug (SSH negotiation, auth steps), -vv for trace.
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Log errors only.
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Log level or flexi_logger spec (e.g. `warn`, `info,kube_config_updater::ssh=debug`).
+    /// Overrides -v and -q.
+    #[arg(long)]
+    log_level: Option<String>,
+
+    /// Log line format; `json` suits log shippers such as Loki or Elasticsearch.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
     /// A list of specific server names to process.
     /// If not provided, all servers in the config will be processed.
     #[arg(short, long)]
     servers: Vec<String>,
 
+    /// Process every server carrying this tag (repeatable; combines with --servers).
+    #[arg(long, visible_alias = "group")]
+    tag: Vec<String>,
+
+    /// Maximum number of servers fetched at once (overrides `max_parallel`).
+    /// Lower it to limit concurrent SSH sessions through a small bastion.
+    #[arg(short, long, value_parser = clap::value_parser!(usize).range(1..))]
+    jobs: Option<usize>,
+
+    /// Fetch only the servers whose last run failed or had its credentials rejected,
+    /// ignoring cached cert expiry. Combines with --servers and --tag.
+    #[arg(long)]
+    retry_failed: bool,
+
+    /// Start no new server once one has failed (fetches already running finish).
+    #[arg(long)]
+    fail_fast: bool,
+
     /// If set, the application will run in dry-run mode,
     /// printing actions instead of executing them.
     #[arg(long)]
     dry_run: bool,
 
+    /// When to use colors in the progress bar and TUI.
+    /// `auto` honours NO_COLOR and disables color when not writing to a terminal.
+    #[arg(long, value_enum, default_value_t = color::ColorChoice::Auto)]
+    color: color::ColorChoice,
+
+    /// Trust host keys that are not yet in ~/.ssh/known_hosts and record them.
+    /// Without this, unknown hosts fail in CLI and daemon mode (the TUI asks instead).
+    #[arg(long)]
+    accept_new: bool,
+
+    /// Write fetched kubeconfigs to the output directory only; leave ~/.kube/config alone.
+    #[arg(long)]
+    no_merge: bool,
+
+    /// Read the passphrase for encrypted SSH private keys from the first line of stdin.
+    /// Overrides passphrases stored with `credential set --key-file`.
+    #[arg(long)]
+    key_passphrase_stdin: bool,
+
+    /// Prompt on the terminal for one-time codes when a server asks for them
+    /// (keyboard-interactive 2FA such as Duo or TOTP).
+    #[arg(long)]
+    ask_otp: bool,
+
+    /// Output format for fetch runs. `json` prints a per-server result document
+    /// (status, error, cert expiry, duration) on stdout for scripts and CI.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -81,13 +323,29 @@ struct Cli {
 /// - Determining the configuration file path.
 /// - Loading the configuration.
 /// - Initiating the server processing.
-fn main() -> Result<(), anyhow::Error> {
+///
+/// Errors are printed to stderr and mapped to the exit codes from `exit_code`.
+fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            std::process::ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+fn run() -> Result<(), anyhow::Error> {
     let cli = Cli::parse();
+    color::init(cli.color);
 
     // --- Logger Setup ---
     let is_tui = matches!(cli.command, Some(Commands::Tui));
     let has_log_dir = cli.log_dir.is_some();
-    let mut logger = Logger::try_with_str("info")?;
+    let mut logger = Logger::try_with_str(log_spec(&cli))?.format(log_format::text);
+    if cli.log_format == LogFormat::Json {
+        logger = logger.format(log_format::json);
+    }
     if let Some(log_dir) = cli.log_dir {
         // If a log directory is provided, log to a file.
         fs::create_dir_all(&log_dir).map_err(|e| {
@@ -98,6 +356,9 @@ fn main() -> Result<(), anyhow::Error> {
             )
         })?;
         logger = logger.log_to_file(FileSpec::default().directory(&log_dir));
+    } else if cli.output == OutputFormat::Json {
+        // Keep stdout clean for the JSON document.
+        logger = logger.log_to_stderr();
     } else {
         // Otherwise, log to stdout.
         logger = logger.log_to_stdout();
@@ -131,46 +392,122 @@ fn main() -> Result<(), anyhow::Error> {
 
     // TUI handles its own config loading (setup wizard on first run)
     if matches!(cli.command, Some(Commands::Tui)) {
-        match config::load_config_optional(config_path.to_str().unwrap_or_default())? {
+        match config::load_config_optional(config_path.to_str().unwrap_or_default()).map_err(ConfigInvalid)? {
             None => tui::run_tui_setup(config_path, cli.dry_run)?,
-            Some(config) => {
+            Some(mut config) => {
                 log::info!("Found {} servers in config", config.servers.len());
+                apply_cli_overrides(&cli, &mut config);
+                if let Some(path) = &config.state_file {
+                    state::set_state_file(PathBuf::from(path));
+                }
+                credentials::set_backend(config.credential_backend(), config.credential_command.clone());
+                if !cli.dry_run {
+                    fetch::migrate_local_files(&config);
+                }
+                credentials::unlock_credential_file_interactive();
                 tui::run_tui(config, config_path, cli.dry_run)?;
             }
         }
         return Ok(());
     }
 
+    // First run from the command line: offer the setup wizard rather than failing.
+    if !config_path.exists() && offer_setup(&config_path, cli.output)? {
+        _logger_handler.flush();
+        let log_level = log::max_level();
+        if !has_log_dir {
+            log::set_max_level(log::LevelFilter::Off);
+        }
+        tui::run_tui_setup(config_path.clone(), cli.dry_run)?;
+        log::set_max_level(log_level);
+        if !config_path.exists() {
+            println!("No configuration was saved, so the command was not run.");
+            return Ok(());
+        }
+        // The wizard wrote a config: carry on with the command that was run.
+    }
+
     // CLI and credential commands require a valid config
-    let mut config = config::load_config(config_path.to_str().unwrap_or_default())?;
+    let mut config = config::load_config(config_path.to_str().unwrap_or_default()).map_err(ConfigInvalid)?;
     log::info!("Found {} servers in config", config.servers.len());
+    apply_cli_overrides(&cli, &mut config);
+    if let Some(path) = &config.state_file {
+        state::set_state_file(PathBuf::from(path));
+    }
+    credentials::set_backend(config.credential_backend(), config.credential_command.clone());
+    if !cli.dry_run {
+        fetch::migrate_local_files(&config);
+    }
+    if !matches!(
+        cli.command,
+        Some(
+            Commands::RestoreBackup { .. }
+                | Commands::Validate
+                | Commands::Import { .. }
+                | Commands::Discover { .. }
+                | Commands::InstallSchedule { .. }
+                | Commands::History { .. }
+                | Commands::Rollback { .. }
+                | Commands::Audit { .. }
+        )
+    ) {
+        credentials::unlock_credential_file_interactive();
+    }
+    let key_passphrase = if cli.key_passphrase_stdin {
+        Some(read_passphrase_from_stdin()?)
+    } else {
+        None
+    };
+    if cli.ask_otp {
+        // Servers are fetched in parallel; one prompt at a time.
+        let asking = std::sync::Mutex::new(());
+        ssh::set_otp_prompt(Box::new(move |server, prompt| {
+            let _turn = asking.lock().unwrap_or_else(|e| e.into_inner());
+            rpassword::prompt_password(format!("[{}] {} ", server, prompt.trim())).ok()
+        }));
+    }
 
     match cli.command {
         Some(Commands::Credential { action }) => match action {
             CredentialAction::Set {
                 server,
                 default,
+                key_file,
+                all,
+                group,
+                sudo,
                 password,
             } => {
-                let account = if default {
-                    credentials::DEFAULT_ACCOUNT.to_string()
+                let accounts = if all || group.is_some() {
+                    bulk_credential_accounts(&config, group.as_deref(), sudo)?
                 } else {
-                    server.ok_or_else(|| anyhow::anyhow!("Specify --server <name> or --default"))?
+                    vec![credential_account(server, default, key_file, sudo)?]
                 };
                 let pw = match password {
                     Some(p) => p,
                     None => rpassword::prompt_password("Password: ")
                         .map_err(|e| anyhow::anyhow!("Failed to read password: {}", e))?,
                 };
-                credentials::set_credential(&account, &pw).map_err(|e| anyhow::anyhow!("{}", e))?;
-                println!("Credential stored for '{}'.", account);
+                for account in &accounts {
+                    credentials::set_credential(account, &pw)
+                        .map_err(|e| anyhow::anyhow!("Storing the credential for '{}': {}", account, e))?;
+                }
+                match accounts.as_slice() {
+                    [account] => println!("Credential stored for '{}'.", account),
+                    _ => println!(
+                        "Credential stored for {} accounts: {}.",
+                        accounts.len(),
+                        accounts.join(", ")
+                    ),
+                }
             }
-            CredentialAction::Delete { server, default } => {
-                let account = if default {
-                    credentials::DEFAULT_ACCOUNT.to_string()
-                } else {
-                    server.ok_or_else(|| anyhow::anyhow!("Specify --server <name> or --default"))?
-                };
+            CredentialAction::Delete {
+                server,
+                default,
+                key_file,
+                sudo,
+            } => {
+                let account = credential_account(server, default, key_file, sudo)?;
                 credentials::delete_credential(&account).map_err(|e| anyhow::anyhow!("{}", e))?;
                 println!("Credential deleted for '{}'.", account);
             }
@@ -195,56 +532,599 @@ fn main() -> Result<(), anyhow::Error> {
                         "[NOT SET]"
                     };
                     println!("{:<30} {}", name, status);
+                    if config
+                        .servers
+                        .iter()
+                        .any(|s| s.name == *name && s.sudo_password == Some(true))
+                    {
+                        let status = match credentials::get_sudo_password(name) {
+                            credentials::CredentialResult::Found(_) => "[SET]",
+                            _ => "[NOT SET]",
+                        };
+                        println!("{:<30} {}", format!("{} (sudo)", name), status);
+                    }
+                }
+            }
+            CredentialAction::Export { out, recipient } => {
+                let accounts = bundle::export(&config, &out, &recipient)?;
+                println!("Exported {} credential(s) to {}:", accounts.len(), out.display());
+                for account in &accounts {
+                    println!("  {}", account);
+                }
+            }
+            CredentialAction::Import { path, identity } => {
+                let accounts = bundle::import(&path, identity.as_deref(), cli.dry_run)?;
+                if cli.dry_run {
+                    println!("DRY-RUN: Would store {} credential(s):", accounts.len());
+                } else {
+                    println!("Imported {} credential(s):", accounts.len());
+                }
+                for account in &accounts {
+                    println!("  {}", account);
                 }
             }
         },
         Some(Commands::Tui) => unreachable!("handled above"),
-        None => {
-            let vault_passwords = if let Some(bw_config) = config.bitwarden.clone() {
-                if bw_config.enabled {
-                    if !bitwarden::BwCli::is_available() {
-                        anyhow::bail!(
-                            "Bitwarden CLI (bw) not found but [bitwarden] is enabled in config. \
-                             Install: npm i -g @bitwarden/cli"
-                        );
+        Some(Commands::Validate) => {
+            let problems = validate::check(&config);
+            if !problems.is_empty() {
+                for problem in &problems {
+                    eprintln!("{}", problem);
+                }
+                return Err(ConfigInvalid(anyhow::anyhow!(
+                    "{} problem(s) in {}",
+                    problems.len(),
+                    config_path.display()
+                ))
+                .into());
+            }
+            println!("{} is valid ({} servers)", config_path.display(), config.servers.len());
+        }
+        Some(Commands::Import {
+            kubeconfig,
+            user,
+            identity_file,
+            yes,
+        }) => {
+            let kubeconfig = match kubeconfig {
+                Some(path) => path,
+                None => kube::main_kubeconfig_path()?,
+            };
+            import::run(
+                &config,
+                &config_path,
+                &kubeconfig,
+                &import::ImportOptions {
+                    user: user.as_deref(),
+                    identity_file: identity_file.as_deref(),
+                    yes,
+                    dry_run: cli.dry_run,
+                },
+            )?;
+        }
+        Some(Commands::Prune { yes }) => {
+            // Vault servers are configured too; their artifacts aren't orphans.
+            load_vault_servers(&mut config)?;
+            let orphans = prune::find_orphans(&config)?;
+            if orphans.is_empty() {
+                println!("Nothing to prune.");
+                return Ok(());
+            }
+            for orphan in &orphans {
+                println!("  {}: {}", orphan.name, orphan.describe());
+            }
+            if cli.dry_run {
+                println!("DRY-RUN: Would remove the above.");
+                return Ok(());
+            }
+            if !yes && !confirm("Remove these?", "prune")? {
+                println!("Aborted.");
+                return Ok(());
+            }
+            prune::remove(&orphans, &config)?;
+            println!("Pruned {} server name(s).", orphans.len());
+        }
+        Some(Commands::Rename { from, to }) => {
+            let rename = rename::plan(&config, &from, &to)?;
+            if cli.dry_run {
+                println!("DRY-RUN: Would rename '{}' to '{}':", from, to);
+            }
+            for change in rename.describe() {
+                println!("  {}", change);
+            }
+            if !cli.dry_run {
+                rename::apply(&rename, &config, &config_path)?;
+                println!("Renamed '{}' to '{}'.", from, to);
+            }
+        }
+        Some(Commands::History { server }) => {
+            let versions = history::list(&config, &server)?;
+            if versions.is_empty() {
+                println!(
+                    "No saved versions of '{}' in {}",
+                    server,
+                    history::history_dir(&config, &server).display()
+                );
+            }
+            let current = kube::local_source_hash(&config.local_path(&server));
+            for (i, version) in versions.iter().enumerate() {
+                println!(
+                    "{:>3}  {}  {}  cert expires {}{}",
+                    i,
+                    version.saved_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    version.short_hash(),
+                    version
+                        .cert_expires
+                        .map(|t| t.format("%Y-%m-%d").to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    if current.as_deref() == Some(version.source_hash.as_str()) {
+                        "  (current)"
+                    } else {
+                        ""
                     }
+                );
+            }
+        }
+        Some(Commands::Rollback { server, version }) => {
+            let entry = config
+                .servers
+                .iter()
+                .find(|s| s.name == server)
+                .ok_or_else(|| anyhow::anyhow!("No server named '{}' in the config", server))?;
+            let versions = history::list(&config, &server)?;
+            let chosen = history::find(&versions, version.as_deref())?;
+            if cli.dry_run {
+                println!(
+                    "DRY-RUN: Would roll '{}' back to the version saved {} ({})",
+                    server,
+                    chosen.saved_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    chosen.short_hash()
+                );
+            } else {
+                history::rollback(&config, entry, chosen)?;
+                println!(
+                    "Rolled '{}' back to the version saved {} ({})",
+                    server,
+                    chosen.saved_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    chosen.short_hash()
+                );
+            }
+        }
+        Some(Commands::Audit { server, last }) => {
+            let mut events = audit::read()?;
+            if let Some(server) = &server {
+                let sudo = credentials::sudo_account(server);
+                events.retain(|e| e.account == *server || e.account == sudo);
+            }
+            if let Some(last) = last {
+                events.drain(..events.len().saturating_sub(last));
+            }
+            if events.is_empty() {
+                println!("No credential use recorded in {}", audit::audit_file_path().display());
+            }
+            for event in &events {
+                println!("{}", audit::format_event(event));
+            }
+        }
+        Some(Commands::Discover {
+            cidr,
+            no_probe,
+            timeout_ms,
+            user,
+            identity_file,
+            yes,
+        }) => {
+            let addresses = discover::parse_cidr(&cidr)?;
+            log::info!("Scanning {} addresses in {}", addresses.len(), cidr);
+            let hosts = discover::scan(&addresses, std::time::Duration::from_millis(timeout_ms), !no_probe)?;
+            for host in &hosts {
+                let mark = |open: bool| if open { "open" } else { "-" };
+                println!(
+                    "{:<40} ssh {:<4}  api {:<4}  {}",
+                    host.ip,
+                    mark(host.ssh),
+                    mark(host.api),
+                    host.cert.as_ref().map(|c| c.sans()).unwrap_or_default()
+                );
+            }
+            let (found, skipped) = discover::candidates(&hosts, &config);
+            import::add_candidates(
+                &config,
+                &config_path,
+                found,
+                &skipped,
+                &cidr,
+                &import::ImportOptions {
+                    user: user.as_deref(),
+                    identity_file: identity_file.as_deref(),
+                    yes,
+                    dry_run: cli.dry_run,
+                },
+            )?;
+        }
+        Some(Commands::InstallSchedule {
+            interval,
+            scheduler,
+            print,
+        }) => {
+            schedule::install(
+                scheduler.unwrap_or_else(schedule::Scheduler::platform_default),
+                interval.or(config.daemon_interval_minutes).unwrap_or(60),
+                &config_path,
+                print || cli.dry_run,
+            )?;
+        }
+        Some(Commands::RestoreBackup { backup, list }) => {
+            let dir = backup::backup_dir()?;
+            let backups = backup::list_backups(&dir)?;
+            if list {
+                if backups.is_empty() {
+                    println!("No backups in {}", dir.display());
+                }
+                for path in &backups {
+                    println!("{}", path.display());
+                }
+                return Ok(());
+            }
+            let chosen = match backup {
+                Some(path) if path.is_file() => path,
+                Some(name) => dir.join(name),
+                None => backups
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("No backups found in {}", dir.display()))?,
+            };
+            let target = kube::main_kubeconfig_path()?;
+            if cli.dry_run {
+                println!("DRY-RUN: Would restore {} from {}", target.display(), chosen.display());
+            } else {
+                backup::restore(&chosen, &target, &dir, config.backup_retention())?;
+                println!("Restored {} from {}", target.display(), chosen.display());
+            }
+        }
+        Some(Commands::Renew { yes }) => {
+            let vault_passwords = load_vault_servers(&mut config)?;
+            let selected = select_servers(&config, &cli.servers, &cli.tag)?;
+            if selected.is_empty() {
+                anyhow::bail!("Name the servers to renew with --servers or --tag");
+            }
+            if !yes && !cli.dry_run && !confirm_renew(&config, &selected)? {
+                println!("Aborted.");
+                return Ok(());
+            }
+            let mut renewed = Vec::new();
+            for server in config.servers.iter().filter(|s| selected.contains(&s.name)) {
+                match fetch::renew_server(
+                    server,
+                    &config,
+                    cli.dry_run,
+                    vault_passwords.get(&server.name).map(|s| s.as_str()),
+                    key_passphrase.as_deref(),
+                ) {
+                    Ok(_) => renewed.push(server.name.clone()),
+                    Err(e) => log::error!("[{}] Renewal failed: {:#}", server.name, e),
+                }
+            }
+            if renewed.is_empty() {
+                anyhow::bail!("No server was renewed");
+            }
+            if !cli.dry_run {
+                log::info!(
+                    "Waiting {}s for the services to come back",
+                    fetch::RENEW_SETTLE.as_secs()
+                );
+                std::thread::sleep(fetch::RENEW_SETTLE);
+            }
+            fetch::process_servers(
+                &config,
+                &renewed,
+                cli.dry_run,
+                true,
+                cli.fail_fast,
+                &vault_passwords,
+                key_passphrase.as_deref(),
+                true,
+            )?
+            .check_failures()?;
+        }
+        Some(Commands::Daemon) => {
+            let vault_passwords = load_vault_servers(&mut config)?;
+            let selected = select_servers(&config, &cli.servers, &cli.tag)?;
+            if !selected.is_empty() {
+                config.servers.retain(|s| selected.contains(&s.name));
+            }
+            daemon::run(&config, cli.dry_run, &vault_passwords, key_passphrase.as_deref())?;
+        }
+        None => {
+            let vault_passwords = load_vault_servers(&mut config)?;
+            let json = cli.output == OutputFormat::Json;
+            let mut selected = select_servers(&config, &cli.servers, &cli.tag)?;
+            if cli.retry_failed {
+                selected = failed_servers(&config, &selected, &state::read_state()?);
+                if selected.is_empty() {
+                    log::info!("No servers failed on their last run; nothing to retry.");
+                    return Ok(());
+                }
+                log::info!("Retrying {}", selected.join(", "));
+            }
+            let report = fetch::process_servers(
+                &config,
+                &selected,
+                cli.dry_run,
+                cli.retry_failed,
+                cli.fail_fast,
+                &vault_passwords,
+                key_passphrase.as_deref(),
+                !json,
+            )?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if cli.dry_run {
+                print_dry_run_diffs(&report);
+            }
+            report.check_failures()?;
+        }
+    }
 
-                    if let Some(ref pf) = bw_config.password_file
-                        && let Err(warning) = bitwarden::check_password_file_permissions(pf)
-                    {
-                        log::warn!("{}", warning);
-                    }
+    Ok(())
+}
 
-                    let mut bw_cli = bitwarden::BwCli::new().with_server_url(bw_config.server_url.as_deref());
+/// Applies the flags that override config keys for this run: `--accept-new`,
+/// `--no-merge` and `--jobs`.
+fn apply_cli_overrides(cli: &Cli, config: &mut config::Config) {
+    if cli.accept_new {
+        config.accept_new_host_keys = Some(true);
+    }
+    if cli.no_merge {
+        config.merge = Some(false);
+    }
+    if cli.jobs.is_some() {
+        config.max_parallel = cli.jobs;
+    }
+}
 
-                    bw_cli
-                        .ensure_session(bw_config.password_file.as_deref())
-                        .map_err(|e| anyhow::anyhow!("Bitwarden: {}", e))?;
+/// The logger spec from `--log-level`, `-q` or `-v`/`-vv`; `info` by default.
+fn log_spec(cli: &Cli) -> &str {
+    match (&cli.log_level, cli.quiet, cli.verbose) {
+        (Some(spec), _, _) => spec,
+        (None, true, _) => "error",
+        (None, false, 0) => "info",
+        (None, false, 1) => "debug",
+        (None, false, _) => "trace",
+    }
+}
 
-                    let prefix = bw_config.item_prefix.as_deref().unwrap_or("k3s:");
-                    let (vault_servers, skipped) = bw_cli
-                        .fetch_servers(prefix, bw_config.collection.as_deref())
-                        .map_err(|e| anyhow::anyhow!("Bitwarden fetch: {}", e))?;
+/// The config couldn't be loaded or failed `validate`; exits with status 2.
+#[derive(Debug)]
+struct ConfigInvalid(anyhow::Error);
 
-                    for s in &skipped {
-                        log::warn!("Vault item skipped: {}", s);
-                    }
-                    let (merged, _sources, passwords) = bitwarden::merge_servers(&config.servers, vault_servers);
-                    config.servers = merged;
-                    log::info!("Loaded {} vault server(s), {} skipped", passwords.len(), skipped.len());
-                    passwords
-                } else {
-                    std::collections::HashMap::new()
+impl std::fmt::Display for ConfigInvalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigInvalid {}
+
+/// Exit status for a failed run: 1 when some servers failed or for any other error,
+/// 2 for a config error, 3 when every server failed.
+fn exit_code(e: &anyhow::Error) -> u8 {
+    if e.downcast_ref::<ConfigInvalid>().is_some() {
+        2
+    } else if let Some(failed) = e.downcast_ref::<fetch::ServersFailed>() {
+        if failed.failed == failed.total { 3 } else { 1 }
+    } else {
+        1
+    }
+}
+
+/// Lists what `renew` is about to run and asks for a y/N answer on the terminal.
+fn confirm_renew(config: &config::Config, selected: &[String]) -> Result<bool, anyhow::Error> {
+    for server in config.servers.iter().filter(|s| selected.contains(&s.name)) {
+        println!(
+            "  {} ({}): {}",
+            server.name,
+            server.address,
+            server.renew_command(config)
+        );
+    }
+    confirm("Run these commands?", "renew")
+}
+
+/// Asks a yes/no `question` on the terminal; no is the default. Fails when stdin isn't a
+/// terminal, naming `action` so the user knows to pass --yes.
+fn confirm(question: &str, action: &str) -> Result<bool, anyhow::Error> {
+    use std::io::{BufRead, IsTerminal, Write};
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Refusing to {} without confirmation; pass --yes when not on a terminal",
+            action
+        );
+    }
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Asks whether to run the setup wizard when there is no config yet. Only on a
+/// terminal and without `--output json`; elsewhere the missing config is an error.
+fn offer_setup(config_path: &std::path::Path, output: OutputFormat) -> Result<bool, anyhow::Error> {
+    use std::io::IsTerminal;
+    if !can_offer_setup(output, std::io::stdin().is_terminal(), std::io::stdout().is_terminal()) {
+        return Ok(false);
+    }
+    println!("No configuration file at {}.", config_path.display());
+    confirm("Set one up now in the setup wizard?", "set up")
+}
+
+/// The wizard needs someone at a terminal to answer it, and a JSON caller expects a
+/// report or an error on stdout, never a prompt.
+fn can_offer_setup(output: OutputFormat, stdin_is_tty: bool, stdout_is_tty: bool) -> bool {
+    output != OutputFormat::Json && stdin_is_tty && stdout_is_tty
+}
+
+/// Prints each server's dry-run diff, colored like `git diff` when color is on.
+fn print_dry_run_diffs(report: &fetch::RunReport) {
+    for server in &report.servers {
+        match server.diff.as_deref() {
+            Some("") => println!("{}: no changes", server.name),
+            Some(diff) => {
+                for line in diff.lines() {
+                    let styled = if line.starts_with("+++") || line.starts_with("---") {
+                        console::style(line).bold()
+                    } else if line.starts_with('+') {
+                        console::style(line).green()
+                    } else if line.starts_with('-') {
+                        console::style(line).red()
+                    } else if line.starts_with("@@") {
+                        console::style(line).cyan()
+                    } else {
+                        console::style(line)
+                    };
+                    println!("{}", styled);
                 }
-            } else {
-                std::collections::HashMap::new()
-            };
+            }
+            None => {}
+        }
+    }
+}
 
-            fetch::process_servers(&config, &cli.servers, cli.dry_run, &vault_passwords)?;
+/// Combines `--servers` names with every server carrying one of the `--tag` values.
+/// An empty result means "all servers", so a tag that matches nothing is an error.
+fn select_servers(config: &config::Config, names: &[String], tags: &[String]) -> Result<Vec<String>, anyhow::Error> {
+    let mut selected = names.to_vec();
+    if tags.is_empty() {
+        return Ok(selected);
+    }
+    selected.extend(
+        config
+            .servers
+            .iter()
+            .filter(|s| s.has_any_tag(tags) && !names.contains(&s.name))
+            .map(|s| s.name.clone()),
+    );
+    if selected.is_empty() {
+        anyhow::bail!("No servers are tagged {}", tags.join(", "));
+    }
+    Ok(selected)
+}
+
+/// Servers among `selected` (all configured servers when empty) whose last recorded
+/// run was `Failed` or `AuthRejected`, in config order.
+fn failed_servers(
+    config: &config::Config,
+    selected: &[String],
+    states: &std::collections::HashMap<String, state::ServerRunState>,
+) -> Vec<String> {
+    config
+        .servers
+        .iter()
+        .filter(|s| selected.is_empty() || selected.contains(&s.name))
+        .filter(|s| {
+            states
+                .get(&s.name)
+                .is_some_and(|st| matches!(st.status, state::RunStatus::Failed | state::RunStatus::AuthRejected))
+        })
+        .map(|s| s.name.clone())
+        .collect()
+}
+
+/// Maps the mutually exclusive `credential` targets to a keyring account name.
+fn credential_account(
+    server: Option<String>,
+    default: bool,
+    key_file: Option<String>,
+    sudo: bool,
+) -> Result<String, anyhow::Error> {
+    if default {
+        Ok(credentials::DEFAULT_ACCOUNT.to_string())
+    } else if let Some(path) = key_file {
+        Ok(credentials::passphrase_account(&path))
+    } else if let Some(server) = server.as_deref().filter(|_| sudo) {
+        Ok(credentials::sudo_account(server))
+    } else {
+        server.ok_or_else(|| anyhow::anyhow!("Specify --server <name>, --default or --key-file <path>"))
+    }
+}
+
+/// Accounts `credential set --all` / `--group <tag>` writes: every server in the config,
+/// or those tagged `group`, as their sudo accounts with `sudo`.
+fn bulk_credential_accounts(
+    config: &config::Config,
+    group: Option<&str>,
+    sudo: bool,
+) -> Result<Vec<String>, anyhow::Error> {
+    let accounts: Vec<String> = config
+        .servers
+        .iter()
+        .filter(|s| group.is_none_or(|tag| s.tags.iter().any(|t| t == tag)))
+        .map(|s| {
+            if sudo {
+                credentials::sudo_account(&s.name)
+            } else {
+                s.name.clone()
+            }
+        })
+        .collect();
+    if accounts.is_empty() {
+        match group {
+            Some(tag) => anyhow::bail!("No server in the config is tagged '{}'", tag),
+            None => anyhow::bail!("No servers in the config"),
         }
     }
+    Ok(accounts)
+}
 
-    Ok(())
+/// Reads a single line from stdin, without the trailing newline.
+fn read_passphrase_from_stdin() -> Result<String, anyhow::Error> {
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| anyhow::anyhow!("Failed to read key passphrase from stdin: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// When `[bitwarden]` is enabled, merges vault servers into `config.servers` and returns
+/// their passwords keyed by server name. Returns an empty map otherwise.
+fn load_vault_servers(
+    config: &mut config::Config,
+) -> Result<std::collections::HashMap<String, zeroize::Zeroizing<String>>, anyhow::Error> {
+    let Some(bw_config) = config.bitwarden.clone().filter(|bw| bw.enabled) else {
+        return Ok(std::collections::HashMap::new());
+    };
+    if !bitwarden::BwCli::is_available() {
+        anyhow::bail!(
+            "Bitwarden CLI (bw) not found but [bitwarden] is enabled in config. \
+             Install: npm i -g @bitwarden/cli"
+        );
+    }
+
+    if let Some(ref pf) = bw_config.password_file
+        && let Err(warning) = bitwarden::check_password_file_permissions(pf)
+    {
+        log::warn!("{}", warning);
+    }
+
+    let mut bw_cli = bitwarden::BwCli::new().with_server_url(bw_config.server_url.as_deref());
+
+    bw_cli
+        .ensure_session(bw_config.password_file.as_deref())
+        .map_err(|e| anyhow::anyhow!("Bitwarden: {}", e))?;
+
+    let prefix = bw_config.item_prefix.as_deref().unwrap_or("k3s:");
+    let (vault_servers, skipped) = bw_cli
+        .fetch_servers(prefix, bw_config.collection.as_deref())
+        .map_err(|e| anyhow::anyhow!("Bitwarden fetch: {}", e))?;
+
+    for s in &skipped {
+        log::warn!("Vault item skipped: {}", s);
+    }
+    let (merged, _sources, passwords) = bitwarden::merge_servers(&config.servers, vault_servers);
+    config.servers = merged;
+    log::info!("Loaded {} vault server(s), {} skipped", passwords.len(), skipped.len());
+    Ok(passwords)
 }
 
 #[cfg(test)]
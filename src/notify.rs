@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a single webhook request may take before it's abandoned.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parsed from the `[notifications]` section in config.toml.
+/// When absent, no webhooks are called.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationsConfig {
+    /// Events sent to webhooks that don't list their own. Defaults to all of them.
+    pub events: Option<Vec<NotifyEvent>>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+/// A single `[[notifications.webhooks]]` entry.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Events this webhook receives, overriding `notifications.events`.
+    pub events: Option<Vec<NotifyEvent>>,
+}
+
+/// Payload shape: Slack and Discord incoming webhooks expect a message field,
+/// `generic` posts the structured event for custom receivers.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    #[default]
+    Generic,
+    Slack,
+    Discord,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    /// A server was fetched, i.e. its certificate was renewed locally.
+    Renewed,
+    Failed,
+    AuthRejected,
+}
+
+impl NotifyEvent {
+    const ALL: [NotifyEvent; 3] = [NotifyEvent::Renewed, NotifyEvent::Failed, NotifyEvent::AuthRejected];
+}
+
+/// One event about one server, as sent by the `generic` format.
+#[derive(Serialize, Debug, Clone)]
+pub struct Notification {
+    pub event: NotifyEvent,
+    pub server: String,
+    pub error: Option<String>,
+    pub cert_expires: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Notification {
+    /// A one-line summary for chat webhooks.
+    fn text(&self) -> String {
+        match self.event {
+            NotifyEvent::Renewed => match self.cert_expires {
+                Some(exp) => format!(
+                    "kube_config_updater: {} renewed, cert valid until {}",
+                    self.server,
+                    exp.format("%Y-%m-%d")
+                ),
+                None => format!("kube_config_updater: {} renewed", self.server),
+            },
+            NotifyEvent::Failed => format!(
+                "kube_config_updater: {} failed: {}",
+                self.server,
+                self.error.as_deref().unwrap_or("unknown error")
+            ),
+            NotifyEvent::AuthRejected => format!(
+                "kube_config_updater: {} rejected the SSH credentials: {}",
+                self.server,
+                self.error.as_deref().unwrap_or("authentication failed")
+            ),
+        }
+    }
+}
+
+/// Turns a run report into the events worth announcing. Skipped servers never notify.
+pub(crate) fn from_report(report: &crate::fetch::RunReport) -> Vec<Notification> {
+    report
+        .servers
+        .iter()
+        .filter_map(|s| {
+            let event = match s.status {
+                crate::state::RunStatus::Fetched => NotifyEvent::Renewed,
+                crate::state::RunStatus::Failed | crate::state::RunStatus::Unreachable => NotifyEvent::Failed,
+                crate::state::RunStatus::AuthRejected => NotifyEvent::AuthRejected,
+                _ => return None,
+            };
+            Some(Notification {
+                event,
+                server: s.name.clone(),
+                error: s.error.clone(),
+                cert_expires: s.cert_expires,
+            })
+        })
+        .collect()
+}
+
+/// Builds the JSON body for `format`.
+pub fn payload(format: WebhookFormat, notification: &Notification) -> serde_json::Value {
+    match format {
+        WebhookFormat::Slack => serde_json::json!({ "text": notification.text() }),
+        WebhookFormat::Discord => serde_json::json!({ "content": notification.text() }),
+        WebhookFormat::Generic => serde_json::json!({
+            "event": notification.event,
+            "server": notification.server,
+            "error": notification.error,
+            "cert_expires": notification.cert_expires,
+            "message": notification.text(),
+        }),
+    }
+}
+
+/// Posts every notification to each webhook subscribed to its event. Failures are
+/// logged and never fail the run; in dry-run mode nothing is sent.
+pub fn send_all(config: &NotificationsConfig, notifications: &[Notification], dry_run: bool) {
+    if notifications.is_empty() || config.webhooks.is_empty() {
+        return;
+    }
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(WEBHOOK_TIMEOUT))
+        .build()
+        .into();
+
+    for webhook in &config.webhooks {
+        let events = webhook
+            .events
+            .as_deref()
+            .or(config.events.as_deref())
+            .unwrap_or(&NotifyEvent::ALL);
+        for notification in notifications.iter().filter(|n| events.contains(&n.event)) {
+            if dry_run {
+                log::info!(
+                    "DRY-RUN: Would notify {} about {:?} for {}",
+                    webhook.url,
+                    notification.event,
+                    notification.server
+                );
+                continue;
+            }
+            if let Err(e) = agent
+                .post(&webhook.url)
+                .send_json(payload(webhook.format, notification))
+            {
+                log::warn!("Webhook {} failed: {}", webhook.url, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(server: &str) -> Notification {
+        Notification {
+            event: NotifyEvent::Failed,
+            server: server.to_string(),
+            error: Some("connection refused".to_string()),
+            cert_expires: None,
+        }
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let n = failed("prod");
+        assert_eq!(
+            payload(WebhookFormat::Slack, &n)["text"],
+            "kube_config_updater: prod failed: connection refused"
+        );
+        assert!(payload(WebhookFormat::Discord, &n)["content"].is_string());
+        let generic = payload(WebhookFormat::Generic, &n);
+        assert_eq!(generic["event"], "failed");
+        assert_eq!(generic["server"], "prod");
+        assert_eq!(generic["error"], "connection refused");
+    }
+
+    #[test]
+    fn test_notifications_config_parses() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"
+events = ["failed", "auth_rejected"]
+
+[[webhooks]]
+url = "https://hooks.slack.com/services/x"
+format = "slack"
+
+[[webhooks]]
+url = "https://example.com/hook"
+events = ["renewed"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.webhooks.len(), 2);
+        assert_eq!(config.webhooks[0].format, WebhookFormat::Slack);
+        assert_eq!(config.webhooks[1].format, WebhookFormat::Generic);
+        assert_eq!(config.webhooks[1].events, Some(vec![NotifyEvent::Renewed]));
+        assert_eq!(config.events.as_deref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_from_report_skips_unremarkable_servers() {
+        use crate::fetch::{RunReport, ServerReport};
+        use crate::state::RunStatus;
+        let server = |name: &str, status| ServerReport {
+            name: name.to_string(),
+            status,
+            error: None,
+            cert_expires: None,
+            ca_expires: None,
+            duration_ms: 0,
+            bytes_fetched: 0,
+            diff: None,
+        };
+        let report = RunReport {
+            servers: vec![
+                server("a", RunStatus::Fetched),
+                server("b", RunStatus::Skipped),
+                server("c", RunStatus::AuthRejected),
+                server("d", RunStatus::NoCredential),
+            ],
+            ..Default::default()
+        };
+        let events: Vec<_> = from_report(&report).into_iter().map(|n| (n.server, n.event)).collect();
+        assert_eq!(
+            events,
+            vec![
+                ("a".to_string(), NotifyEvent::Renewed),
+                ("c".to_string(), NotifyEvent::AuthRejected)
+            ]
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! `prune`: removes what a server leaves behind once it's gone from the config, such as
+//! the cached kubeconfig, the state entry, the stored credential and the entries merged
+//! into ~/.kube/config.
+
+use crate::config::Config;
+use crate::kube::KubeConfig;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// Leftovers of one server name that is no longer configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Orphan {
+    pub name: String,
+    /// Cached kubeconfig in `local_output_dir`.
+    pub cached_file: Option<PathBuf>,
+    pub state_entry: bool,
+    pub credential: bool,
+    /// Contexts in ~/.kube/config merged from the cached kubeconfig.
+    pub merged_contexts: Vec<String>,
+}
+
+impl Orphan {
+    /// What would be removed, e.g. `cached kubeconfig, state entry, context 'old'`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.cached_file.is_some() {
+            parts.push("cached kubeconfig".to_string());
+        }
+        if self.state_entry {
+            parts.push("state entry".to_string());
+        }
+        if self.credential {
+            parts.push("credential".to_string());
+        }
+        for context in &self.merged_contexts {
+            parts.push(format!("context '{}'", context));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Finds names that have artifacts but no server in `config`. Candidate names come from
+/// the state file, the files in `local_output_dir` and the credential file store;
+/// only files that parse as kubeconfigs count, so other files in the directory stay.
+pub fn find_orphans(config: &Config) -> Result<Vec<Orphan>, anyhow::Error> {
+    let configured: BTreeSet<&str> = config.servers.iter().map(|s| s.name.as_str()).collect();
+    let states = crate::state::read_state()?;
+
+    let mut cached = std::collections::BTreeMap::new();
+    if let Ok(entries) = std::fs::read_dir(&config.local_output_dir) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with('.')
+                || file_name.ends_with(".tmp")
+                || config.server_for_local_file(&file_name).is_some()
+            {
+                continue;
+            }
+            let name = server_name_of(config, &file_name);
+            if configured.contains(name.as_str()) {
+                continue;
+            }
+            if let Ok(content) = std::fs::read_to_string(entry.path())
+                && let Ok(kubeconfig) = serde_yaml::from_str::<KubeConfig>(&content)
+            {
+                cached.insert(name, (entry.path(), kubeconfig));
+            }
+        }
+    }
+
+    let names: BTreeSet<String> = states
+        .keys()
+        .cloned()
+        .chain(cached.keys().cloned())
+        .chain(crate::credentials::file_store_accounts())
+        .filter(|name| !configured.contains(name.as_str()))
+        .collect();
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let main = read_main_kubeconfig()?;
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let (cached_file, merged_contexts) = match (cached.get(&name), &main) {
+                (Some((path, kubeconfig)), Some(main)) => {
+                    (Some(path.clone()), remove_merged(&mut main.clone(), kubeconfig))
+                }
+                (Some((path, _)), None) => (Some(path.clone()), Vec::new()),
+                (None, _) => (None, Vec::new()),
+            };
+            Orphan {
+                state_entry: states.contains_key(&name),
+                credential: crate::credentials::has_own_credential(&name)
+                    || crate::credentials::has_own_credential(&crate::credentials::sudo_account(&name)),
+                cached_file,
+                merged_contexts,
+                name,
+            }
+        })
+        .collect())
+}
+
+/// Removes every artifact listed in `orphans`. ~/.kube/config is backed up before it
+/// changes. Keeps going after a failure and returns the first error.
+pub fn remove(orphans: &[Orphan], config: &Config) -> Result<(), anyhow::Error> {
+    let mut first_error = None;
+    let mut fail = |e: anyhow::Error| {
+        log::warn!("{:#}", e);
+        first_error.get_or_insert(e);
+    };
+
+    if orphans.iter().any(|o| !o.merged_contexts.is_empty())
+        && let Err(e) = remove_from_main_kubeconfig(orphans, config)
+    {
+        fail(e);
+    }
+    for orphan in orphans {
+        if orphan.credential {
+            for account in [orphan.name.clone(), crate::credentials::sudo_account(&orphan.name)] {
+                if let Err(e) = crate::credentials::delete_credential(&account) {
+                    fail(anyhow::anyhow!("[{}] Could not delete credential: {}", orphan.name, e));
+                }
+            }
+        }
+        if let Some(path) = &orphan.cached_file
+            && let Err(e) = std::fs::remove_file(path)
+        {
+            fail(anyhow::anyhow!(
+                "[{}] Could not remove {}: {}",
+                orphan.name,
+                path.display(),
+                e
+            ));
+        }
+        let history = crate::history::history_dir(config, &orphan.name);
+        if history.exists()
+            && let Err(e) = std::fs::remove_dir_all(&history)
+        {
+            fail(anyhow::anyhow!(
+                "[{}] Could not remove {}: {}",
+                orphan.name,
+                history.display(),
+                e
+            ));
+        }
+    }
+    if orphans.iter().any(|o| o.state_entry)
+        && let Err(e) = crate::state::update_state(|states| {
+            states.retain(|name, _| !orphans.iter().any(|o| o.state_entry && &o.name == name))
+        })
+    {
+        fail(e);
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Removes the entries each orphan's cached kubeconfig merged, read before the cached
+/// files themselves go.
+fn remove_from_main_kubeconfig(orphans: &[Orphan], config: &Config) -> Result<(), anyhow::Error> {
+    let path = crate::kube::main_kubeconfig_path()?;
+    let Some(mut main) = read_main_kubeconfig()? else {
+        return Ok(());
+    };
+    let mut removed = 0;
+    for orphan in orphans.iter().filter(|o| !o.merged_contexts.is_empty()) {
+        let Some(cached) = &orphan.cached_file else {
+            continue;
+        };
+        let kubeconfig: KubeConfig = serde_yaml::from_str(&std::fs::read_to_string(cached)?)?;
+        removed += remove_merged(&mut main, &kubeconfig).len();
+    }
+    if removed > 0 {
+        crate::backup::backup_file(&path, &crate::backup::backup_dir()?, config.backup_retention())?;
+        crate::kube::write_kubeconfig(&path, &serde_yaml::to_string(&main)?)?;
+        log::info!("Removed {} context(s) from {}", removed, path.display());
+    }
+    Ok(())
+}
+
+/// The server name a cached kubeconfig called `file_name` was written for: the part
+/// matching `{name}` in `local_file_name`, or the whole name for files written before
+/// the template existed.
+fn server_name_of(config: &Config, file_name: &str) -> String {
+    let template = config
+        .local_file_name
+        .as_deref()
+        .unwrap_or(crate::config::DEFAULT_LOCAL_FILE_NAME);
+    template
+        .split_once("{name}")
+        .and_then(|(prefix, suffix)| file_name.strip_prefix(prefix)?.strip_suffix(suffix))
+        .filter(|name| !name.is_empty())
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+/// ~/.kube/config, or `None` when it doesn't exist.
+pub(crate) fn read_main_kubeconfig() -> Result<Option<KubeConfig>, anyhow::Error> {
+    let path = crate::kube::main_kubeconfig_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_yaml::from_str(&std::fs::read_to_string(&path)?)?))
+}
+
+/// Removes from `main` the entries `cached` would have merged: contexts with the same
+/// name and cluster, clusters with the same name and API server, and users with the
+/// same name that no remaining context refers to. Returns the removed context names.
+/// Entries that were since replaced by another cluster's don't match and are kept.
+fn remove_merged(main: &mut KubeConfig, cached: &KubeConfig) -> Vec<String> {
+    let mut removed = Vec::new();
+    main.contexts.retain(|context| {
+        let merged = cached
+            .contexts
+            .iter()
+            .any(|c| c.name == context.name && c.context.cluster == context.context.cluster);
+        if merged {
+            removed.push(context.name.clone());
+        }
+        !merged
+    });
+    if removed.is_empty() {
+        return removed;
+    }
+    main.clusters.retain(|cluster| {
+        !cached
+            .clusters
+            .iter()
+            .any(|c| c.name == cluster.name && c.cluster.server == cluster.cluster.server)
+    });
+    let in_use: BTreeSet<String> = main.contexts.iter().map(|c| c.context.user.clone()).collect();
+    main.users
+        .retain(|user| in_use.contains(&user.name) || !cached.users.iter().any(|u| u.name == user.name));
+    if removed.contains(&main.current_context) {
+        main.current_context.clear();
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kubeconfig(entries: &[(&str, &str)], current: &str) -> KubeConfig {
+        let yaml = format!(
+            "apiVersion: v1\nkind: Config\ncurrent-context: {}\nclusters:\n{}contexts:\n{}users:\n{}",
+            current,
+            entries
+                .iter()
+                .map(|(name, server)| format!("- name: {name}\n  cluster:\n    server: {server}\n"))
+                .collect::<String>(),
+            entries
+                .iter()
+                .map(|(name, _)| format!("- name: {name}\n  context:\n    cluster: {name}\n    user: {name}\n"))
+                .collect::<String>(),
+            entries
+                .iter()
+                .map(|(name, _)| format!("- name: {name}\n  user: {{}}\n"))
+                .collect::<String>(),
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_remove_merged_only_takes_matching_entries() {
+        let mut main = kubeconfig(
+            &[("old", "https://10.0.0.1:6443"), ("keep", "https://10.0.0.2:6443")],
+            "old",
+        );
+        let cached = kubeconfig(&[("old", "https://10.0.0.1:6443")], "old");
+        assert_eq!(remove_merged(&mut main, &cached), ["old"]);
+        assert_eq!(main.contexts.len(), 1);
+        assert_eq!(main.clusters[0].name, "keep");
+        assert_eq!(main.users[0].name, "keep");
+        assert_eq!(main.current_context, "");
+
+        // The name now belongs to a different cluster: nothing is removed.
+        let mut main = kubeconfig(&[("old", "https://10.9.9.9:6443")], "old");
+        let mut moved = kubeconfig(&[("other", "https://10.0.0.1:6443")], "old");
+        moved.contexts[0].name = "old".to_string();
+        assert!(remove_merged(&mut main, &moved).is_empty());
+        assert_eq!(main.clusters.len(), 1);
+    }
+
+    #[test]
+    fn test_server_name_of_cached_file() {
+        let config = Config::default();
+        assert_eq!(server_name_of(&config, "old.yaml"), "old");
+        assert_eq!(server_name_of(&config, "old"), "old");
+        let config = Config {
+            local_file_name: Some("k3s-{name}.conf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(server_name_of(&config, "k3s-old.conf"), "old");
+        assert_eq!(server_name_of(&config, "k3s-.conf"), "k3s-.conf");
+    }
+
+    #[test]
+    fn test_describe_orphan() {
+        let orphan = Orphan {
+            name: "old".to_string(),
+            cached_file: Some(PathBuf::from("/tmp/old")),
+            state_entry: true,
+            merged_contexts: vec!["old".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(orphan.describe(), "cached kubeconfig, state entry, context 'old'");
+    }
+}
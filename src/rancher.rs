@@ -0,0 +1,95 @@
+//! Kubeconfig source for clusters managed by Rancher: the management API generates
+//! the kubeconfig, so no SSH access to the nodes is needed.
+
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Limit on the whole API request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct GenerateKubeconfigOutput {
+    config: String,
+}
+
+/// URL of the `generateKubeconfig` action for `cluster_id` on the Rancher server at
+/// `base_url` (e.g. `https://rancher.example.com`).
+fn action_url(base_url: &str, cluster_id: &str) -> String {
+    format!(
+        "{}/v3/clusters/{}?action=generateKubeconfig",
+        base_url.trim_end_matches('/'),
+        cluster_id
+    )
+}
+
+/// Asks Rancher to generate a kubeconfig for `cluster_id`, authenticating with an API
+/// token (`token-xxxxx:secret`, as shown by Rancher when the key is created).
+pub fn generate_kubeconfig(base_url: &str, cluster_id: &str, token: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let url = action_url(base_url, cluster_id);
+    log::info!("Requesting kubeconfig from {}", url);
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+    let output: GenerateKubeconfigOutput = agent
+        .post(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/json")
+        .send_empty()
+        .map_err(|e| match e {
+            ureq::Error::StatusCode(401 | 403) => {
+                anyhow::anyhow!(
+                    "Rancher rejected the API token ({}). Check it with `credential set`.",
+                    e
+                )
+            }
+            ureq::Error::StatusCode(404) => anyhow::anyhow!("Rancher has no cluster '{}'", cluster_id),
+            e => anyhow::anyhow!("Rancher request to {} failed: {}", url, e),
+        })?
+        .body_mut()
+        .read_json()?;
+    Ok(output.config.into_bytes())
+}
+
+/// Host and port of the Rancher server, for reachability checks.
+pub fn host_port(base_url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = base_url.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let default_port = if scheme == "http" { 80 } else { 443 };
+    let (host, port) = match authority.strip_prefix('[') {
+        // IPv6 literal: [::1]:8443
+        Some(bracketed) => {
+            let (host, after) = bracketed.split_once(']')?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(p) => p.parse().ok()?,
+        None => default_port,
+    };
+    Some((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_url_and_host_port() {
+        assert_eq!(
+            action_url("https://rancher.lan/", "c-m-abc"),
+            "https://rancher.lan/v3/clusters/c-m-abc?action=generateKubeconfig"
+        );
+        assert_eq!(host_port("https://rancher.lan"), Some(("rancher.lan".to_string(), 443)));
+        assert_eq!(
+            host_port("https://rancher.lan:8443/dashboard"),
+            Some(("rancher.lan".to_string(), 8443))
+        );
+        assert_eq!(host_port("http://[::1]"), Some(("::1".to_string(), 80)));
+        assert_eq!(host_port("rancher.lan"), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! Keeps credential values out of logs, the state file and reports. Every password,
+//! passphrase or token the process reads or is given is registered here, and text on
+//! its way out passes through `scrub`, which masks any registered value it contains.
+//! A remote sudo that echoes its input or an error that quotes a URL with a token in
+//! it then can't leak the secret.
+
+use std::borrow::Cow;
+use std::sync::{Mutex, MutexGuard};
+use zeroize::Zeroizing;
+
+/// What a secret is replaced with.
+pub const MASK: &str = "[REDACTED]";
+
+/// Shorter values match too much ordinary text to be worth masking.
+const MIN_LEN: usize = 4;
+
+/// Registered values, longest first so one that contains another is masked whole.
+/// This is a copy of every secret for the life of the process: nothing is ever
+/// removed, so `Zeroizing` only clears them if the list itself is dropped, which
+/// doesn't happen before exit.
+static SECRETS: Mutex<Vec<Zeroizing<String>>> = Mutex::new(Vec::new());
+
+fn secrets() -> MutexGuard<'static, Vec<Zeroizing<String>>> {
+    SECRETS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Remembers `secret` so `scrub` masks it from now on.
+pub fn register(secret: &str) {
+    if secret.chars().count() < MIN_LEN {
+        return;
+    }
+    let mut secrets = secrets();
+    if secrets.iter().any(|known| known.as_str() == secret) {
+        return;
+    }
+    secrets.push(Zeroizing::new(secret.to_string()));
+    secrets.sort_by_key(|known| std::cmp::Reverse(known.len()));
+}
+
+/// `text` with every registered secret replaced by `MASK`. Borrows when there is
+/// nothing to mask, which is almost always.
+pub fn scrub(text: &str) -> Cow<'_, str> {
+    let secrets = secrets();
+    if !secrets.iter().any(|secret| text.contains(secret.as_str())) {
+        return Cow::Borrowed(text);
+    }
+    let mut scrubbed = text.to_string();
+    for secret in secrets.iter() {
+        scrubbed = scrubbed.replace(secret.as_str(), MASK);
+    }
+    Cow::Owned(scrubbed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_masks_registered_values() {
+        register("hunter2-sudo");
+        register("hunter2");
+        register("abc");
+        assert_eq!(
+            scrub("sudo echoed hunter2-sudo, then hunter2"),
+            format!("sudo echoed {}, then {}", MASK, MASK)
+        );
+        // Too short to register.
+        assert_eq!(scrub("abc"), "abc");
+        // Other tests register secrets concurrently; text shorter than any of them can't match.
+        assert!(matches!(scrub("ok"), Cow::Borrowed(_)));
+    }
+}
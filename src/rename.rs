@@ -0,0 +1,284 @@
+//! `rename`: gives a server a new name and moves everything keyed by the old one along:
+//! the config entry, the state entry, the stored credential, the cached kubeconfig with its
+//! saved versions and, when the context is named after the server, the entries merged
+//! into ~/.kube/config.
+
+use crate::config::Config;
+use crate::kube::KubeConfig;
+use std::path::{Path, PathBuf};
+
+/// What renaming one server touches, worked out before anything changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+    /// Cached kubeconfig in `local_output_dir`, moved to the new name.
+    pub cached_file: Option<PathBuf>,
+    /// The context is named after the server (no `context_name`), so it is renamed too.
+    pub context: bool,
+    /// ~/.kube/config holds the entries merged from the cached kubeconfig.
+    pub merged: bool,
+    pub state_entry: bool,
+    pub credential: bool,
+}
+
+impl Rename {
+    /// One line per change, e.g. `cached kubeconfig`, `context 'old' → 'new'`.
+    pub fn describe(&self) -> Vec<String> {
+        let mut changes = vec![format!("config entry '{}' → '{}'", self.from, self.to)];
+        if self.cached_file.is_some() {
+            changes.push("cached kubeconfig".to_string());
+        }
+        if self.context && self.cached_file.is_some() {
+            let place = if self.merged {
+                "in the cached kubeconfig and ~/.kube/config"
+            } else {
+                "in the cached kubeconfig"
+            };
+            changes.push(format!("context '{}' → '{}' {}", self.from, self.to, place));
+        }
+        if self.state_entry {
+            changes.push("state entry".to_string());
+        }
+        if self.credential {
+            changes.push("credential".to_string());
+        }
+        changes
+    }
+}
+
+/// Checks that `from` can become `to` and lists what would change. Fails when `to` is
+/// taken by a server, by leftovers of a removed one (see `prune`) or by another
+/// cluster's entries in ~/.kube/config.
+pub fn plan(config: &Config, from: &str, to: &str) -> Result<Rename, anyhow::Error> {
+    let server = config
+        .servers
+        .iter()
+        .find(|s| s.name == from)
+        .ok_or_else(|| anyhow::anyhow!("No server named '{}' in the config", from))?;
+    if to.is_empty() || to.starts_with('.') || crate::import::server_name(to) != to {
+        anyhow::bail!(
+            "'{}' isn't a valid server name; use letters, digits, '-', '_' and '.'",
+            to
+        );
+    }
+    if from == to {
+        anyhow::bail!("'{}' already has that name", from);
+    }
+    if config.servers.iter().any(|s| s.name == to) {
+        anyhow::bail!("A server named '{}' already exists", to);
+    }
+
+    let states = crate::state::read_state()?;
+    let new_file = renamed_path(config, from, to);
+    // A `local_file_name` without `{name}` keeps the file where it is.
+    if states.contains_key(to)
+        || (new_file.exists() && new_file != config.local_path(from))
+        || crate::history::history_dir(config, to).exists()
+        || has_credential(to)
+    {
+        anyhow::bail!("'{}' still has artifacts from a removed server; run `prune` first", to);
+    }
+
+    let old_file = config.local_path(from);
+    let cached_file = old_file.exists().then_some(old_file);
+    let context = server.context_name.is_none();
+    let mut merged = false;
+    if context && let Some(cached) = &cached_file {
+        let cached: KubeConfig = serde_yaml::from_str(&std::fs::read_to_string(cached)?)?;
+        if let Some(main) = crate::prune::read_main_kubeconfig()? {
+            if main.clusters.iter().any(|c| c.name == to)
+                || main.contexts.iter().any(|c| c.name == to)
+                || main.users.iter().any(|u| u.name == to)
+            {
+                anyhow::bail!("~/.kube/config already has an entry named '{}'", to);
+            }
+            merged = main.contexts.iter().any(|c| {
+                c.name == from
+                    && cached
+                        .contexts
+                        .iter()
+                        .any(|cc| cc.name == c.name && cc.context.cluster == c.context.cluster)
+            });
+        }
+    }
+
+    Ok(Rename {
+        from: from.to_string(),
+        to: to.to_string(),
+        cached_file,
+        context,
+        merged,
+        state_entry: states.contains_key(from),
+        credential: has_credential(from),
+    })
+}
+
+type Undo = Box<dyn FnOnce() -> Result<(), anyhow::Error>>;
+
+/// Carries out `rename`. Each step is undone again when a later one fails, so the
+/// server ends up either fully renamed or as it was.
+pub fn apply(rename: &Rename, config: &Config, config_path: &PathBuf) -> Result<(), anyhow::Error> {
+    let mut done: Vec<Undo> = Vec::new();
+    let result = apply_steps(rename, config, config_path, &mut done);
+    if let Err(e) = result {
+        for undo in done.into_iter().rev() {
+            if let Err(undo_error) = undo() {
+                log::error!("[{}] Could not undo part of the rename: {:#}", rename.from, undo_error);
+            }
+        }
+        return Err(e.context(format!("Renaming '{}' failed; nothing was changed", rename.from)));
+    }
+    log::info!("Renamed server '{}' to '{}'", rename.from, rename.to);
+    Ok(())
+}
+
+fn apply_steps(
+    rename: &Rename,
+    config: &Config,
+    config_path: &PathBuf,
+    done: &mut Vec<Undo>,
+) -> Result<(), anyhow::Error> {
+    let (from, to) = (rename.from.clone(), rename.to.clone());
+
+    crate::config::rename_server(config_path, &from, &to)?;
+    {
+        let (config_path, from, to) = (config_path.clone(), from.clone(), to.clone());
+        done.push(Box::new(move || crate::config::rename_server(&config_path, &to, &from)));
+    }
+
+    if let Some(old_file) = &rename.cached_file {
+        let new_file = renamed_path(config, &from, &to);
+        let original = std::fs::read_to_string(old_file)?;
+        if rename.context {
+            let mut cached: KubeConfig = serde_yaml::from_str(&original)?;
+            crate::kube::rename_entries(&mut cached, &from, &to);
+            crate::kube::write_kubeconfig(&new_file, &serde_yaml::to_string(&cached)?)?;
+            if &new_file != old_file {
+                std::fs::remove_file(old_file)?;
+            }
+        } else {
+            std::fs::rename(old_file, &new_file)?;
+        }
+        let old_file = old_file.clone();
+        done.push(Box::new(move || {
+            if new_file != old_file {
+                std::fs::remove_file(&new_file)?;
+            }
+            crate::kube::write_kubeconfig(&old_file, &original)?;
+            Ok(())
+        }));
+    }
+
+    let old_history = crate::history::history_dir(config, &from);
+    if old_history.exists() {
+        let new_history = crate::history::history_dir(config, &to);
+        std::fs::rename(&old_history, &new_history)?;
+        done.push(Box::new(move || Ok(std::fs::rename(&new_history, &old_history)?)));
+    }
+
+    if rename.merged {
+        let path = crate::kube::main_kubeconfig_path()?;
+        let original = std::fs::read_to_string(&path)?;
+        let mut main: KubeConfig = serde_yaml::from_str(&original)?;
+        crate::kube::rename_entries(&mut main, &from, &to);
+        crate::backup::backup_file(&path, &crate::backup::backup_dir()?, config.backup_retention())?;
+        crate::kube::write_kubeconfig(&path, &serde_yaml::to_string(&main)?)?;
+        done.push(Box::new(move || crate::kube::write_kubeconfig(&path, &original)));
+    }
+
+    if rename.state_entry {
+        crate::state::update_state(|states| move_key(states, &from, &to))?;
+        let (from, to) = (from.clone(), to.clone());
+        done.push(Box::new(move || {
+            crate::state::update_state(|states| move_key(states, &to, &from))
+        }));
+    }
+
+    if rename.credential {
+        crate::credentials::rename_credential(&from, &to)
+            .map_err(|e| anyhow::anyhow!("Could not move the credential: {}", e))?;
+        crate::credentials::rename_credential(
+            &crate::credentials::sudo_account(&from),
+            &crate::credentials::sudo_account(&to),
+        )
+        .map_err(|e| anyhow::anyhow!("Could not move the sudo password: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Where the cached kubeconfig of `from` goes once it is called `to`, following its
+/// `local_file_name`.
+fn renamed_path(config: &Config, from: &str, to: &str) -> PathBuf {
+    match config.servers.iter().find(|s| s.name == from) {
+        Some(server) => Path::new(&config.local_output_dir).join(
+            crate::config::Server {
+                name: to.to_string(),
+                ..server.clone()
+            }
+            .local_file_name(config),
+        ),
+        None => config.local_path(to),
+    }
+}
+
+/// A password or separate sudo password stored for `name`.
+fn has_credential(name: &str) -> bool {
+    crate::credentials::has_own_credential(name)
+        || crate::credentials::has_own_credential(&crate::credentials::sudo_account(name))
+}
+
+fn move_key<V>(map: &mut std::collections::HashMap<String, V>, from: &str, to: &str) {
+    if let Some(value) = map.remove(from) {
+        map.insert(to.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Server;
+
+    #[test]
+    fn test_plan_rejects_bad_names() {
+        let config = Config {
+            local_output_dir: "/nonexistent/kube".to_string(),
+            servers: ["a", "b"]
+                .iter()
+                .map(|name| Server {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        assert!(plan(&config, "missing", "c").is_err());
+        assert!(plan(&config, "a", "b").is_err());
+        assert!(plan(&config, "a", "a").is_err());
+        assert!(plan(&config, "a", "with space").is_err());
+        assert!(plan(&config, "a", "../c").is_err());
+        assert!(plan(&config, "a", "..").is_err());
+    }
+
+    #[test]
+    fn test_describe_rename() {
+        let rename = Rename {
+            from: "old".to_string(),
+            to: "new".to_string(),
+            cached_file: Some(PathBuf::from("/tmp/old")),
+            context: true,
+            merged: true,
+            state_entry: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            rename.describe(),
+            [
+                "config entry 'old' → 'new'",
+                "cached kubeconfig",
+                "context 'old' → 'new' in the cached kubeconfig and ~/.kube/config",
+                "state entry",
+            ]
+        );
+    }
+}
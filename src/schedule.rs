@@ -0,0 +1,303 @@
+//! `install-schedule`: sets up periodic CLI runs with the platform's scheduler, a
+//! user-level systemd timer, a crontab line or a launchd agent.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name of the systemd units and the launchd label's suffix.
+const UNIT: &str = "kube-config-updater";
+const LAUNCHD_LABEL: &str = "com.github.derpy4me.kube-config-updater";
+/// Marks the crontab line this tool owns, so reinstalling replaces it.
+const CRON_MARKER: &str = "# kube_config_updater install-schedule";
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheduler {
+    /// User-level systemd service and timer.
+    Systemd,
+    /// A line in the user's crontab.
+    Cron,
+    /// A launchd agent in ~/Library/LaunchAgents (macOS).
+    Launchd,
+}
+
+impl Scheduler {
+    /// launchd on macOS, systemd elsewhere.
+    pub fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            Scheduler::Launchd
+        } else {
+            Scheduler::Systemd
+        }
+    }
+}
+
+/// The command each scheduled run executes: this binary with the config in use,
+/// logging errors only.
+fn run_args(exe: &Path, config_path: &Path) -> Vec<String> {
+    vec![
+        exe.display().to_string(),
+        "--config-path".to_string(),
+        config_path.display().to_string(),
+        "-q".to_string(),
+    ]
+}
+
+/// Quotes an argument for a shell command line (crontab) or a systemd `ExecStart=`,
+/// both of which split on whitespace.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_./=:@".contains(&b))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+fn systemd_service(args: &[String]) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Refresh kubeconfigs with kube_config_updater\n\
+         Wants=network-online.target\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart={}\n",
+        args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" ")
+    )
+}
+
+fn systemd_timer(interval_minutes: u64) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Run kube_config_updater every {interval_minutes} minutes\n\
+         \n\
+         [Timer]\n\
+         OnBootSec=2min\n\
+         OnUnitActiveSec={interval_minutes}min\n\
+         RandomizedDelaySec=60\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n"
+    )
+}
+
+/// Crontab schedule for `interval_minutes`. Cron can only repeat on divisors of an
+/// hour or of a day.
+fn cron_schedule(interval_minutes: u64) -> Result<String, anyhow::Error> {
+    match interval_minutes {
+        1 => Ok("* * * * *".to_string()),
+        m if m < 60 && 60 % m == 0 => Ok(format!("*/{} * * * *", m)),
+        60 => Ok("0 * * * *".to_string()),
+        1440 => Ok("0 0 * * *".to_string()),
+        m if m % 60 == 0 && 24 % (m / 60) == 0 => Ok(format!("0 */{} * * *", m / 60)),
+        m => anyhow::bail!(
+            "cron can't run every {} minutes; use a divisor of 60, or whole hours that divide 24",
+            m
+        ),
+    }
+}
+
+fn cron_line(interval_minutes: u64, args: &[String]) -> Result<String, anyhow::Error> {
+    Ok(format!(
+        "{} {} {}",
+        cron_schedule(interval_minutes)?,
+        args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" "),
+        CRON_MARKER
+    ))
+}
+
+/// `existing` crontab with this tool's line replaced (or appended).
+fn replace_cron_line(existing: &str, line: &str) -> String {
+    let mut lines: Vec<&str> = existing.lines().filter(|l| !l.ends_with(CRON_MARKER)).collect();
+    lines.push(line);
+    lines.join("\n") + "\n"
+}
+
+fn launchd_plist(interval_minutes: u64, args: &[String]) -> String {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let program_args: String = args
+        .iter()
+        .map(|a| format!("        <string>{}</string>\n", escape(a)))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{LAUNCHD_LABEL}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {program_args}\
+         \x20   </array>\n\
+         \x20   <key>StartInterval</key>\n\
+         \x20   <integer>{}</integer>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         </dict>\n\
+         </plist>\n",
+        interval_minutes * 60
+    )
+}
+
+/// Installs (or, with `print_only`, prints) a schedule running this binary against
+/// `config_path` every `interval_minutes`. Reinstalling replaces the previous schedule.
+pub fn install(
+    scheduler: Scheduler,
+    interval_minutes: u64,
+    config_path: &Path,
+    print_only: bool,
+) -> Result<(), anyhow::Error> {
+    if interval_minutes == 0 {
+        anyhow::bail!("The interval must be at least one minute");
+    }
+    let exe = std::env::current_exe()?;
+    let config_path = std::fs::canonicalize(config_path).unwrap_or_else(|_| config_path.to_path_buf());
+    let args = run_args(&exe, &config_path);
+
+    match scheduler {
+        Scheduler::Systemd => {
+            let dir = dirs::config_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine the config directory"))?
+                .join("systemd/user");
+            let files = [
+                (dir.join(format!("{}.service", UNIT)), systemd_service(&args)),
+                (dir.join(format!("{}.timer", UNIT)), systemd_timer(interval_minutes)),
+            ];
+            if print_only {
+                print_files(&files);
+                return Ok(());
+            }
+            write_files(&dir, &files)?;
+            let timer = format!("{}.timer", UNIT);
+            run_steps(&[
+                &["systemctl", "--user", "daemon-reload"],
+                &["systemctl", "--user", "enable", "--now", &timer],
+            ])?;
+            println!(
+                "Enabled {}; check it with `systemctl --user list-timers {}`",
+                timer, timer
+            );
+        }
+        Scheduler::Cron => {
+            let line = cron_line(interval_minutes, &args)?;
+            if print_only {
+                println!("{}", line);
+                return Ok(());
+            }
+            // `crontab -l` fails when the user has no crontab yet.
+            let existing = Command::new("crontab")
+                .arg("-l")
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+                .unwrap_or_default();
+            let mut child = Command::new("crontab")
+                .arg("-")
+                .stdin(std::process::Stdio::piped())
+                .spawn()
+                .map_err(|e| anyhow::anyhow!("Could not run crontab: {}", e))?;
+            if let Some(stdin) = child.stdin.as_mut() {
+                use std::io::Write as _;
+                stdin.write_all(replace_cron_line(&existing, &line).as_bytes())?;
+            }
+            if !child.wait()?.success() {
+                anyhow::bail!("crontab rejected the new schedule");
+            }
+            println!("Installed crontab entry:\n{}", line);
+        }
+        Scheduler::Launchd => {
+            let dir = dirs::home_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine the home directory"))?
+                .join("Library/LaunchAgents");
+            let plist = dir.join(format!("{}.plist", LAUNCHD_LABEL));
+            let files = [(plist.clone(), launchd_plist(interval_minutes, &args))];
+            if print_only {
+                print_files(&files);
+                return Ok(());
+            }
+            write_files(&dir, &files)?;
+            let plist = plist.display().to_string();
+            // Unloading first makes a reinstall pick up the new interval; it fails
+            // harmlessly when the agent wasn't loaded.
+            Command::new("launchctl").args(["unload", &plist]).output().ok();
+            run_steps(&[&["launchctl", "load", "-w", &plist]])?;
+            println!("Loaded {}", plist);
+        }
+    }
+    Ok(())
+}
+
+fn print_files(files: &[(PathBuf, String)]) {
+    for (path, content) in files {
+        println!("# {}\n{}", path.display(), content);
+    }
+}
+
+fn write_files(dir: &Path, files: &[(PathBuf, String)]) -> Result<(), anyhow::Error> {
+    std::fs::create_dir_all(dir)?;
+    for (path, content) in files {
+        std::fs::write(path, content)?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Runs each command in turn, stopping at the first failure.
+fn run_steps(steps: &[&[&str]]) -> Result<(), anyhow::Error> {
+    for step in steps {
+        let status = Command::new(step[0])
+            .args(&step[1..])
+            .status()
+            .map_err(|e| anyhow::anyhow!("Could not run {}: {}", step[0], e))?;
+        if !status.success() {
+            anyhow::bail!("`{}` failed ({})", step.join(" "), status);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cron_schedule_and_line() {
+        assert_eq!(cron_schedule(15).unwrap(), "*/15 * * * *");
+        assert_eq!(cron_schedule(60).unwrap(), "0 * * * *");
+        assert_eq!(cron_schedule(360).unwrap(), "0 */6 * * *");
+        assert_eq!(cron_schedule(1440).unwrap(), "0 0 * * *");
+        assert!(cron_schedule(45).is_err());
+        assert!(cron_schedule(420).is_err());
+
+        let args = run_args(
+            Path::new("/usr/local/bin/kube_config_updater"),
+            Path::new("/home/a b/config.toml"),
+        );
+        let line = cron_line(30, &args).unwrap();
+        assert_eq!(
+            line,
+            "*/30 * * * * /usr/local/bin/kube_config_updater --config-path '/home/a b/config.toml' -q \
+             # kube_config_updater install-schedule"
+        );
+        assert_eq!(
+            replace_cron_line(&format!("MAILTO=me\n0 * * * * old {}\n", CRON_MARKER), &line),
+            format!("MAILTO=me\n{}\n", line)
+        );
+    }
+
+    #[test]
+    fn test_systemd_and_launchd_units() {
+        let args = run_args(Path::new("/usr/bin/kcu"), Path::new("/etc/kcu.toml"));
+        assert!(systemd_service(&args).contains("ExecStart=/usr/bin/kcu --config-path /etc/kcu.toml -q\n"));
+        assert!(systemd_timer(90).contains("OnUnitActiveSec=90min\n"));
+        let plist = launchd_plist(60, &args);
+        assert!(plist.contains("<integer>3600</integer>"));
+        assert!(plist.contains("        <string>/etc/kcu.toml</string>\n"));
+    }
+}
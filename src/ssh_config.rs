@@ -0,0 +1,203 @@
+//! Reads `Host` entries from `~/.ssh/config` so the add-server wizard can offer them.
+//!
+//! Only what the wizard prefills is kept: `HostName`, `User`, `Port` and the first
+//! `IdentityFile`. Wildcard patterns aren't offered as hosts, but a `Host *` block
+//! supplies defaults to the others, as ssh would. `Match` blocks and `Include` are
+//! skipped.
+
+use std::path::{Path, PathBuf};
+
+/// One concrete host alias and the settings ssh would use for it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostEntry {
+    /// The name after `Host`, e.g. `nas` for `ssh nas`.
+    pub alias: String,
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    /// With `~` expanded.
+    pub identity_file: Option<String>,
+}
+
+impl HostEntry {
+    /// The address to connect to: `HostName`, else the alias itself.
+    pub fn address(&self) -> &str {
+        self.host_name.as_deref().unwrap_or(&self.alias)
+    }
+}
+
+pub fn ssh_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+/// Entries of `~/.ssh/config` in file order; empty when there is no such file.
+pub fn read() -> Vec<HostEntry> {
+    let Some(path) = ssh_config_path() else {
+        return Vec::new();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => parse(&content, dirs::home_dir().as_deref()),
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Could not read {}: {}", path.display(), e);
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// Which entries the lines being read apply to.
+enum Block {
+    /// Indexes into the entries of the current `Host` line.
+    Hosts(Vec<usize>),
+    /// `Host *`.
+    Defaults,
+    /// A `Match` block, or a `Host` line with only other patterns.
+    Ignored,
+}
+
+fn parse(content: &str, home: Option<&Path>) -> Vec<HostEntry> {
+    let mut entries: Vec<HostEntry> = Vec::new();
+    let mut defaults = HostEntry::default();
+    let mut block = Block::Ignored;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((keyword, value)) => (
+                keyword,
+                value.trim_start_matches(|c: char| c.is_whitespace() || c == '='),
+            ),
+            None => (line, ""),
+        };
+        let keyword = keyword.to_ascii_lowercase();
+        match keyword.as_str() {
+            "host" => {
+                let patterns: Vec<&str> = value.split_whitespace().map(unquote).collect();
+                block = if patterns == ["*"] {
+                    Block::Defaults
+                } else {
+                    let concrete: Vec<usize> = patterns
+                        .iter()
+                        .filter(|p| !p.contains(['*', '?']) && !p.starts_with('!'))
+                        .map(|alias| {
+                            entries.push(HostEntry {
+                                alias: alias.to_string(),
+                                ..Default::default()
+                            });
+                            entries.len() - 1
+                        })
+                        .collect();
+                    if concrete.is_empty() {
+                        Block::Ignored
+                    } else {
+                        Block::Hosts(concrete)
+                    }
+                };
+                continue;
+            }
+            "match" => {
+                block = Block::Ignored;
+                continue;
+            }
+            _ => {}
+        }
+        let targets: Vec<&mut HostEntry> = match &block {
+            Block::Hosts(indexes) => entries
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| indexes.contains(i))
+                .map(|(_, entry)| entry)
+                .collect(),
+            Block::Defaults => vec![&mut defaults],
+            Block::Ignored => continue,
+        };
+        let value = unquote(value);
+        for entry in targets {
+            set(entry, &keyword, value, home);
+        }
+    }
+
+    // As in ssh, the first value obtained wins, so `Host *` only fills what is unset.
+    for entry in &mut entries {
+        entry.host_name = entry.host_name.take().or_else(|| defaults.host_name.clone());
+        entry.user = entry.user.take().or_else(|| defaults.user.clone());
+        entry.port = entry.port.or(defaults.port);
+        entry.identity_file = entry.identity_file.take().or_else(|| defaults.identity_file.clone());
+    }
+    entries
+}
+
+/// Records `keyword value` on `entry` unless it already has a value for it.
+fn set(entry: &mut HostEntry, keyword: &str, value: &str, home: Option<&Path>) {
+    match keyword {
+        "hostname" if entry.host_name.is_none() => entry.host_name = Some(value.to_string()),
+        "user" if entry.user.is_none() => entry.user = Some(value.to_string()),
+        "port" if entry.port.is_none() => entry.port = value.parse().ok(),
+        "identityfile" if entry.identity_file.is_none() => {
+            entry.identity_file = Some(match (value.strip_prefix("~/"), home) {
+                (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+                _ => value.to_string(),
+            })
+        }
+        _ => {}
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hosts_with_defaults() {
+        let content = r#"
+# homelab
+Host nas backup
+    HostName 192.168.1.20
+    User admin
+    Port 2222
+
+Host k3s-*
+    User ops
+
+Host pi
+    hostname=10.0.0.5
+    IdentityFile "~/.ssh/id_pi"
+    IdentityFile ~/.ssh/id_other
+
+Match host *.corp
+    User corp
+
+Host *
+    User fallback
+    IdentityFile ~/.ssh/id_ed25519
+"#;
+        let entries = parse(content, Some(Path::new("/home/me")));
+        let aliases: Vec<&str> = entries.iter().map(|e| e.alias.as_str()).collect();
+        assert_eq!(aliases, ["nas", "backup", "pi"]);
+
+        assert_eq!(entries[0].address(), "192.168.1.20");
+        assert_eq!(entries[1].user.as_deref(), Some("admin"));
+        assert_eq!(entries[1].port, Some(2222));
+        assert_eq!(
+            entries[0].identity_file.as_deref(),
+            Some("/home/me/.ssh/id_ed25519"),
+            "Host * fills in what the entry doesn't set"
+        );
+
+        assert_eq!(entries[2].address(), "10.0.0.5");
+        assert_eq!(entries[2].user.as_deref(), Some("fallback"));
+        assert_eq!(entries[2].port, None);
+        assert_eq!(entries[2].identity_file.as_deref(), Some("/home/me/.ssh/id_pi"));
+    }
+}
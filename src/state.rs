@@ -2,27 +2,55 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Legacy path written by older versions. Migrated automatically on first read.
 const STATE_FILE_LEGACY: &str = "/tmp/kube_config_updater_state.json";
 
-/// Returns `~/.local/share/kube_config_updater/` (or `/tmp/` as fallback).
+/// Set from the `state_file` config key; see `set_state_file`.
+static STATE_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Uses `path` as the state file for the rest of the process instead of the default.
+/// Called at startup, before anything reads state; later calls are ignored.
+pub fn set_state_file(path: PathBuf) {
+    let _ = STATE_FILE_OVERRIDE.set(path);
+}
+
+/// Returns `$XDG_STATE_HOME/kube_config_updater/` (`~/.local/state/...` when unset).
+/// Platforms without a state dir use the local data dir instead.
 fn state_dir() -> PathBuf {
-    dirs::data_local_dir()
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
         .unwrap_or_else(|| PathBuf::from("/tmp"))
         .join("kube_config_updater")
 }
 
 /// Returns the path to the persistent state file.
 pub fn state_file_path() -> PathBuf {
-    state_dir().join("state.json")
+    STATE_FILE_OVERRIDE
+        .get()
+        .cloned()
+        .unwrap_or_else(|| state_dir().join("state.json"))
+}
+
+/// Locations older versions wrote state to, newest first: the local data dir, then `/tmp`.
+fn legacy_state_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(data_dir) = dirs::data_local_dir() {
+        files.push(data_dir.join("kube_config_updater").join("state.json"));
+    }
+    files.push(PathBuf::from(STATE_FILE_LEGACY));
+    files
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ServerRunState {
     pub status: RunStatus,
     pub last_updated: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// How long the last run took; absent in state written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -32,25 +60,18 @@ pub enum RunStatus {
     NoCredential,
     AuthRejected,
     Failed,
+    /// Fetched, but the API server in the kubeconfig couldn't be reached (`verify_api`).
+    Unreachable,
 }
 
-/// Read the persistent state file. Migrates from the legacy `/tmp` path on first run.
-/// Returns an empty map if neither file exists.
+/// Read the persistent state file. Migrates from legacy locations on first run.
+/// Returns an empty map if no state file exists.
 pub fn read_state() -> Result<HashMap<String, ServerRunState>, anyhow::Error> {
     let path = state_file_path();
 
-    // One-time migration: if persistent file is absent but legacy file exists, adopt it.
+    // One-time migration: if persistent file is absent but a legacy file exists, adopt it.
     if !path.exists() {
-        let legacy = Path::new(STATE_FILE_LEGACY);
-        if legacy.exists()
-            && let Ok(content) = std::fs::read_to_string(legacy)
-            && let Ok(map) = serde_json::from_str::<HashMap<String, ServerRunState>>(&content)
-        {
-            // Best-effort write to new location; ignore errors (will retry on next fetch).
-            let _ = write_state(&map);
-            return Ok(map);
-        }
-        return Ok(HashMap::new());
+        return Ok(migrate_state(&path, &legacy_state_files()));
     }
 
     let content = std::fs::read_to_string(&path)?;
@@ -58,15 +79,78 @@ pub fn read_state() -> Result<HashMap<String, ServerRunState>, anyhow::Error> {
     Ok(map)
 }
 
-/// Write state file atomically to the persistent data dir.
-pub fn write_state(states: &HashMap<String, ServerRunState>) -> Result<(), anyhow::Error> {
-    let dir = state_dir();
-    std::fs::create_dir_all(&dir)?;
-    let tmp = dir.join("state.json.tmp");
-    let dest = dir.join("state.json");
-    let json = serde_json::to_string_pretty(states)?;
+/// Adopts the first readable file in `legacy` as the state at `dest`. Once `dest` is
+/// written, the legacy copies are removed so stale state doesn't linger in `/tmp`.
+fn migrate_state(dest: &Path, legacy: &[PathBuf]) -> HashMap<String, ServerRunState> {
+    let legacy: Vec<&PathBuf> = legacy.iter().filter(|p| p.as_path() != dest).collect();
+    for old in &legacy {
+        if let Ok(content) = std::fs::read_to_string(old)
+            && let Ok(map) = serde_json::from_str::<HashMap<String, ServerRunState>>(&content)
+        {
+            // Best-effort write to new location; on failure the legacy file stays and
+            // migration is retried on the next read.
+            if write_state_to(dest, &map).is_ok() {
+                log::info!("Moved state file from {} to {}", old.display(), dest.display());
+                for stale in &legacy {
+                    let _ = std::fs::remove_file(stale);
+                }
+            }
+            return map;
+        }
+    }
+    HashMap::new()
+}
+
+/// Applies `f` to the current state and writes the result, holding the state lock
+/// across the read-modify-write so concurrent CLI, daemon, and TUI updates don't
+/// overwrite each other's entries.
+pub fn update_state(f: impl FnOnce(&mut HashMap<String, ServerRunState>)) -> Result<(), anyhow::Error> {
+    update_state_at(&state_file_path(), f)
+}
+
+fn update_state_at(path: &Path, f: impl FnOnce(&mut HashMap<String, ServerRunState>)) -> Result<(), anyhow::Error> {
+    let _lock = lock_state(path)?;
+    let mut states = if path.exists() {
+        serde_json::from_str(&std::fs::read_to_string(path)?)?
+    } else {
+        migrate_state(path, &legacy_state_files())
+    };
+    f(&mut states);
+    write_state_to(path, &states)
+}
+
+/// Takes an exclusive advisory lock on `<state file>.lock`, released when the returned
+/// handle is dropped. Readers don't lock: writes replace the file by rename, so a read
+/// always sees a complete file.
+fn lock_state(path: &Path) -> Result<std::fs::File, anyhow::Error> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut lock_name = path.file_name().unwrap_or_default().to_os_string();
+    lock_name.push(".lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_file_name(lock_name))?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Writes `states` to `dest` via a temp file and rename, readable only by the owner.
+fn write_state_to(dest: &Path, states: &HashMap<String, ServerRunState>) -> Result<(), anyhow::Error> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = dest.with_extension("json.tmp");
+    let json = crate::redact::scrub(&serde_json::to_string_pretty(states)?).into_owned();
     std::fs::write(&tmp, &json)?;
-    std::fs::rename(&tmp, &dest)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&tmp, dest)?;
     Ok(())
 }
 
@@ -78,11 +162,12 @@ pub fn is_auth_error(msg: &str) -> bool {
     lower.contains("authentication failed") || lower.contains("auth rejected")
 }
 
-/// Read the current state, update one entry, write back.
+/// Read the current state, update one entry, write back, atomically with respect to
+/// other writers.
 pub fn update_server_state(name: &str, state: ServerRunState) -> Result<(), anyhow::Error> {
-    let mut states = read_state()?;
-    states.insert(name.to_string(), state);
-    write_state(&states)
+    update_state(|states| {
+        states.insert(name.to_string(), state);
+    })
 }
 
 #[cfg(test)]
@@ -91,7 +176,7 @@ mod tests {
     use chrono::Utc;
     use std::sync::Mutex;
 
-    // Serialize all state-file tests — they share /tmp/kube_config_updater_state.json
+    // Serialize all state-file tests — they share the real state file
     static STATE_FILE_LOCK: Mutex<()> = Mutex::new(());
 
     fn make_state(status: RunStatus) -> ServerRunState {
@@ -99,6 +184,7 @@ mod tests {
             status,
             last_updated: Some(Utc::now()),
             error: None,
+            duration_ms: None,
         }
     }
 
@@ -107,7 +193,9 @@ mod tests {
         let _guard = STATE_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         // Remove both the persistent and legacy files so we get a clean "missing" state.
         let _ = std::fs::remove_file(state_file_path());
-        let _ = std::fs::remove_file(STATE_FILE_LEGACY);
+        for legacy in legacy_state_files() {
+            let _ = std::fs::remove_file(legacy);
+        }
         let result = read_state();
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -124,10 +212,11 @@ mod tests {
                 status: RunStatus::Failed,
                 last_updated: Some(Utc::now()),
                 error: Some("Connection refused".to_string()),
+                duration_ms: None,
             },
         );
 
-        write_state(&states).expect("write should succeed");
+        update_state(|s| *s = states.clone()).expect("write should succeed");
         let loaded = read_state().expect("read should succeed");
 
         assert_eq!(loaded.len(), 2);
@@ -136,12 +225,65 @@ mod tests {
         assert_eq!(loaded["server2"].error.as_deref(), Some("Connection refused"));
     }
 
+    #[test]
+    fn test_migrate_state_moves_legacy_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("state").join("state.json");
+        let older = dir.path().join("data-state.json");
+        let oldest = dir.path().join("tmp-state.json");
+        let mut states = HashMap::new();
+        states.insert("server1".to_string(), make_state(RunStatus::Fetched));
+        std::fs::write(&oldest, serde_json::to_string(&states).unwrap()).unwrap();
+
+        let migrated = migrate_state(&dest, &[older.clone(), oldest.clone()]);
+
+        assert!(migrated.contains_key("server1"));
+        assert!(dest.exists());
+        assert!(!oldest.exists(), "legacy file should be removed after migration");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&dest).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_updates_keep_every_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::thread::scope(|scope| {
+            for t in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    for i in 0..10 {
+                        update_state_at(path, |states| {
+                            states.insert(format!("server-{}-{}", t, i), make_state(RunStatus::Fetched));
+                        })
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        let states: HashMap<String, ServerRunState> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(states.len(), 80);
+    }
+
+    #[test]
+    fn test_migrate_state_without_legacy_files_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("state.json");
+        assert!(migrate_state(&dest, &[dir.path().join("missing.json")]).is_empty());
+        assert!(!dest.exists());
+    }
+
     #[test]
     fn test_update_server_state_merges() {
         let _guard = STATE_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let mut initial = HashMap::new();
         initial.insert("existing".to_string(), make_state(RunStatus::Skipped));
-        write_state(&initial).expect("write should succeed");
+        update_state(|s| *s = initial).expect("write should succeed");
 
         // Update should add server2 without removing server1
         update_server_state("new_server", make_state(RunStatus::Fetched)).expect("update should succeed");
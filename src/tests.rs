@@ -1,5 +1,7 @@
 use super::config::{Config, Server, load_config};
-use super::kube::{KubeConfig, merge_into_main_kubeconfig, process_kubeconfig_file};
+use super::kube::{
+    KubeConfig, MergeStrategy, merge_into_main_kubeconfig, rewrite_kubeconfig, warn_if_source_changed, write_kubeconfig,
+};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
@@ -62,6 +64,190 @@ fn test_load_valid_config() {
     assert_eq!(config.servers[0].name, "server1");
 }
 
+#[test]
+fn test_load_config_formats_match() {
+    let toml_file = Builder::new().suffix(".toml").tempfile().unwrap();
+    fs::write(
+        toml_file.path(),
+        "local_output_dir = \"/tmp/kube_configs\"\ndefault_user = \"ubuntu\"\n\n\
+         [[server]]\nname = \"server1\"\naddress = \"1.1.1.1\"\ntarget_cluster_ip = \"10.0.0.1\"\ntags = [\"lab\"]\n",
+    )
+    .unwrap();
+    let yaml_file = Builder::new().suffix(".yml").tempfile().unwrap();
+    fs::write(
+        yaml_file.path(),
+        "local_output_dir: /tmp/kube_configs\ndefault_user: ubuntu\nserver:\n  \
+         - name: server1\n    address: 1.1.1.1\n    target_cluster_ip: 10.0.0.1\n    tags: [lab]\n",
+    )
+    .unwrap();
+    let json_file = Builder::new().suffix(".JSON").tempfile().unwrap();
+    fs::write(
+        json_file.path(),
+        r#"{"local_output_dir": "/tmp/kube_configs", "default_user": "ubuntu",
+            "server": [{"name": "server1", "address": "1.1.1.1", "target_cluster_ip": "10.0.0.1", "tags": ["lab"]}]}"#,
+    )
+    .unwrap();
+
+    let load = |file: &NamedTempFile| format!("{:?}", load_config(file.path().to_str().unwrap()).unwrap());
+    assert_eq!(load(&yaml_file), load(&toml_file));
+    assert_eq!(load(&json_file), load(&toml_file));
+
+    fs::write(yaml_file.path(), "server: [name: x\n").unwrap();
+    let err = load_config(yaml_file.path().to_str().unwrap()).unwrap_err().to_string();
+    assert!(err.contains("is invalid") && err.contains("line"), "{}", err);
+
+    let server = Server::default();
+    assert!(super::config::add_server(&json_file.path().to_path_buf(), &server).is_err());
+}
+
+#[test]
+fn test_config_includes() {
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        "local_output_dir = \"/tmp\"\ninclude = [\"servers.d/*.toml\", \"extra.yaml\"]\n\n\
+         [[server]]\nname = \"main\"\naddress = \"1.1.1.1\"\ntarget_cluster_ip = \"10.0.0.1\"\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("servers.d")).unwrap();
+    fs::write(
+        dir.path().join("servers.d/bob.toml"),
+        "[[server]]\nname = \"bob\"\naddress = \"b\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("servers.d/alice.toml"),
+        "[[server]]\nname = \"alice-1\"\naddress = \"a\"\n\n[[server]]\nname = \"alice-2\"\naddress = \"a2\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("servers.d/notes.txt"), "not a fragment").unwrap();
+    fs::write(
+        dir.path().join("extra.yaml"),
+        "server:\n  - name: extra\n    address: e\n",
+    )
+    .unwrap();
+
+    let config = load_config(config_path.to_str().unwrap()).unwrap();
+    let names: Vec<&str> = config.servers.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["main", "alice-1", "alice-2", "bob", "extra"]);
+
+    fs::write(
+        dir.path().join("servers.d/carol.toml"),
+        "[[server]]\nname = \"alice-2\"\naddress = \"c\"\n",
+    )
+    .unwrap();
+    let err = load_config(config_path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(err.contains("'alice-2'") && err.contains("alice.toml"), "{}", err);
+
+    fs::write(dir.path().join("servers.d/carol.toml"), "default_user = \"x\"\n").unwrap();
+    assert!(load_config(config_path.to_str().unwrap()).is_err());
+
+    assert!(super::config::wildcard_match("*.toml", "a.toml"));
+    assert!(super::config::wildcard_match("team-?.t*", "team-a.toml"));
+    assert!(!super::config::wildcard_match("*.toml", "a.toml.bak"));
+}
+
+#[test]
+fn test_unknown_config_keys_are_rejected_with_a_suggestion() {
+    let config_file = create_test_config("local_output_dir = \"/tmp\"\ndefualt_user = \"ubuntu\"\n");
+    let err = load_config(config_file.path().to_str().unwrap())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("line 2, column 1"), "{}", err);
+    assert!(
+        err.contains("unknown field `defualt_user`, did you mean `default_user`?"),
+        "{}",
+        err
+    );
+    assert!(!err.contains("expected one of"), "{}", err);
+
+    let yaml_file = Builder::new().suffix(".yaml").tempfile().unwrap();
+    fs::write(
+        yaml_file.path(),
+        "local_output_dir: /tmp\nserver:\n  - name: a\n    adress: 10.0.0.1\n",
+    )
+    .unwrap();
+    let err = load_config(yaml_file.path().to_str().unwrap()).unwrap_err().to_string();
+    assert!(
+        err.contains("did you mean `address`?") && err.contains("line 4"),
+        "{}",
+        err
+    );
+
+    // Nothing close enough: serde's list of keys is kept.
+    let config_file = create_test_config("local_output_dir = \"/tmp\"\nzzz = 1\n");
+    let err = load_config(config_file.path().to_str().unwrap())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unknown field `zzz`, expected one of"), "{}", err);
+}
+
+#[test]
+fn test_dashboard_columns_are_parsed_in_order() {
+    use super::config::Column;
+
+    let config_file = create_test_config(
+        "local_output_dir = \"/tmp\"\ndashboard_columns = [\"status\", \"name\", \"last-duration\", \"user\"]\n",
+    );
+    let config = load_config(config_file.path().to_str().unwrap()).unwrap();
+    assert_eq!(
+        config.dashboard_columns(),
+        [Column::Status, Column::Name, Column::LastDuration, Column::User]
+    );
+
+    // Unset or empty, the dashboard keeps its usual three columns.
+    let default = [Column::Name, Column::CertExpiry, Column::Status];
+    assert_eq!(Config::default().dashboard_columns(), default);
+    let config_file = create_test_config("local_output_dir = \"/tmp\"\ndashboard_columns = []\n");
+    let config = load_config(config_file.path().to_str().unwrap()).unwrap();
+    assert_eq!(config.dashboard_columns(), default);
+
+    let config_file = create_test_config("local_output_dir = \"/tmp\"\ndashboard_columns = [\"uptime\"]\n");
+    assert!(load_config(config_file.path().to_str().unwrap()).is_err());
+}
+
+#[test]
+fn test_server_url_from_target_fields() {
+    let mut server = Server {
+        name: "s".to_string(),
+        address: "node1".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(server.server_url(), None);
+    assert_eq!(server.api_endpoint(), ("node1".to_string(), 6443));
+
+    server.target_cluster_ip = "10.0.0.1".to_string();
+    assert_eq!(server.server_url().as_deref(), Some("https://10.0.0.1:6443"));
+    server.target_cluster_port = Some(443);
+    assert_eq!(server.server_url().as_deref(), Some("https://10.0.0.1:443"));
+    assert_eq!(server.api_endpoint(), ("10.0.0.1".to_string(), 443));
+
+    server.target_server_url = Some("https://127.0.0.1:16443".to_string());
+    assert_eq!(server.server_url().as_deref(), Some("https://127.0.0.1:16443"));
+    assert_eq!(server.api_endpoint(), ("127.0.0.1".to_string(), 16443));
+
+    let updated = rewrite_kubeconfig(TEST_KUBECONFIG_CONTENT, "https://vip.lan:443", "hash", &None, "s", None).unwrap();
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&updated).unwrap();
+    assert_eq!(kubeconfig.clusters[0].cluster.server, "https://vip.lan:443");
+
+    let server = Server {
+        target_cluster_ip: "k3s.internal.lan".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(server.server_url().as_deref(), Some("https://k3s.internal.lan:6443"));
+    assert_eq!(server.api_endpoint(), ("k3s.internal.lan".to_string(), 6443));
+
+    for ip in ["fd00::10", "[fd00::10]"] {
+        let server = Server {
+            target_cluster_ip: ip.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(server.server_url().as_deref(), Some("https://[fd00::10]:6443"));
+        assert_eq!(server.api_endpoint(), ("fd00::10".to_string(), 6443));
+    }
+}
+
 #[test]
 fn test_load_non_existent_config() {
     let result = load_config("/tmp/non_existent_config.toml");
@@ -87,6 +273,7 @@ fn test_server_user_fallback() {
                 file_name: None,
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
             Server {
                 name: "server2".to_string(),
@@ -97,8 +284,10 @@ fn test_server_user_fallback() {
                 file_name: None,
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     assert_eq!(config.servers[0].user(&config).unwrap(), "default_user");
@@ -124,6 +313,7 @@ fn test_server_identity_file_fallback() {
                 file_name: None,
                 context_name: None,
                 identity_file: None, // Should use default
+                ..Default::default()
             },
             Server {
                 name: "server2".to_string(),
@@ -134,14 +324,145 @@ fn test_server_identity_file_fallback() {
                 file_name: None,
                 context_name: None,
                 identity_file: Some("server_key".to_string()), // Should use its own
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     assert_eq!(config.servers[0].identity_file(&config).unwrap(), "default_key");
     assert_eq!(config.servers[1].identity_file(&config).unwrap(), "server_key");
 }
 
+#[test]
+fn test_server_compression_fallback() {
+    let config = Config {
+        default_compression: Some(true),
+        servers: vec![
+            Server {
+                name: "server1".to_string(),
+                compression: None, // Should use default
+                ..Default::default()
+            },
+            Server {
+                name: "server2".to_string(),
+                compression: Some(false), // Should use its own
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    assert!(config.servers[0].compression(&config));
+    assert!(!config.servers[1].compression(&config));
+    assert!(
+        !Server::default().compression(&Config::default()),
+        "compression is off by default"
+    );
+}
+
+#[test]
+fn test_select_servers_by_tag() {
+    let tagged = |name: &str, tags: &[&str]| Server {
+        name: name.to_string(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    };
+    let config = Config {
+        servers: vec![
+            tagged("a", &["prod"]),
+            tagged("b", &["prod", "homelab"]),
+            tagged("c", &["homelab"]),
+            tagged("d", &[]),
+        ],
+        ..Default::default()
+    };
+    let s = |v: &[&str]| v.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+    assert!(super::select_servers(&config, &[], &[]).unwrap().is_empty());
+    assert_eq!(
+        super::select_servers(&config, &[], &s(&["prod"])).unwrap(),
+        s(&["a", "b"])
+    );
+    assert_eq!(
+        super::select_servers(&config, &s(&["d", "b"]), &s(&["homelab"])).unwrap(),
+        s(&["d", "b", "c"])
+    );
+    assert!(super::select_servers(&config, &[], &s(&["missing"])).is_err());
+    assert_eq!(config.tags(), s(&["homelab", "prod"]));
+}
+
+#[test]
+fn test_missing_sudo_password_is_an_error() {
+    use super::credentials::CredentialResult;
+    use super::fetch::{resolve_sudo_password, sudo_password_from};
+    let server = Server {
+        name: "prod".to_string(),
+        sudo_password: Some(true),
+        ..Default::default()
+    };
+    let err = sudo_password_from(&server, CredentialResult::NotFound).unwrap_err();
+    assert!(
+        err.to_string().contains("credential set --server prod --sudo"),
+        "{}",
+        err
+    );
+
+    let plain = Server {
+        name: "dev".to_string(),
+        ..Default::default()
+    };
+    assert!(resolve_sudo_password(&plain).unwrap().is_none());
+}
+
+#[test]
+fn test_renew_before_window() {
+    use super::fetch::renewal_due;
+    let mut server = Server::default();
+    let mut config = Config::default();
+    assert_eq!(server.renew_before(&config), chrono::Duration::zero());
+    config.renew_before_days = Some(14);
+    assert_eq!(server.renew_before(&config), chrono::Duration::days(14));
+    server.renew_before_days = Some(3);
+    assert_eq!(server.renew_before(&config), chrono::Duration::days(3));
+
+    let now = chrono::Utc::now();
+    let tomorrow = now + chrono::Duration::days(1);
+    assert!(!renewal_due(tomorrow, now, chrono::Duration::zero()));
+    assert!(renewal_due(tomorrow, now, server.renew_before(&config)));
+    assert!(!renewal_due(
+        now + chrono::Duration::days(30),
+        now,
+        server.renew_before(&config)
+    ));
+}
+
+#[test]
+fn test_server_interval_fallback() {
+    use std::time::Duration;
+
+    let mut server = Server {
+        name: "test".to_string(),
+        interval_minutes: Some(15),
+        ..Default::default()
+    };
+    let mut config = Config {
+        daemon_interval_minutes: Some(240),
+        ..Default::default()
+    };
+
+    // Case 1: Server override wins
+    assert_eq!(server.interval(&config), Duration::from_secs(15 * 60));
+
+    // Case 2: Falls back to daemon_interval_minutes
+    server.interval_minutes = None;
+    assert_eq!(server.interval(&config), Duration::from_secs(240 * 60));
+
+    // Case 3: Neither set, or zero, uses one hour
+    config.daemon_interval_minutes = Some(0);
+    assert_eq!(server.interval(&config), Duration::from_secs(3600));
+}
+
 #[test]
 fn test_server_file_path_fallback() {
     let config = Config {
@@ -161,6 +482,7 @@ fn test_server_file_path_fallback() {
                 file_name: None, // Should use default
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
             Server {
                 name: "server2".to_string(),
@@ -171,8 +493,10 @@ fn test_server_file_path_fallback() {
                 file_name: Some("server_name".to_string()),  // Should use its own
                 context_name: None,
                 identity_file: None,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     assert_eq!(
@@ -221,15 +545,10 @@ fn test_process_kubeconfig_file_updates_content() {
     let source_hash = "test_hash_123";
     let target_context = Some("new-context-name".to_string());
 
-    process_kubeconfig_file(
-        &kubeconfig_path,
-        target_ip,
-        source_hash,
-        &target_context,
-        "test-server",
-        false,
-    )
-    .unwrap();
+    let content = fs::read_to_string(&kubeconfig_path).unwrap();
+    let server_url = format!("https://{}:6443", target_ip);
+    let updated = rewrite_kubeconfig(&content, &server_url, source_hash, &target_context, "test-server", None).unwrap();
+    write_kubeconfig(&kubeconfig_path, &updated).unwrap();
 
     let updated_content = fs::read_to_string(kubeconfig_path).unwrap();
     let updated_kubeconfig: super::kube::KubeConfig = serde_yaml::from_str(&updated_content).unwrap();
@@ -249,26 +568,118 @@ fn test_process_kubeconfig_file_updates_content() {
 }
 
 #[test]
-fn test_process_kubeconfig_file_dry_run() {
+fn test_dry_run_diff_leaves_the_local_copy_alone() {
     let temp_dir = Builder::new().prefix("test_kube_dry_run").tempdir().unwrap();
     let kubeconfig_path = setup_test_kubeconfig(&temp_dir, TEST_KUBECONFIG_CONTENT);
+    let server = Server {
+        name: "test-server".to_string(),
+        target_cluster_ip: "9.9.9.9".to_string(),
+        context_name: Some("new-context".to_string()),
+        merge: Some(false),
+        ..Default::default()
+    };
 
-    let original_content = fs::read_to_string(&kubeconfig_path).unwrap();
-
-    process_kubeconfig_file(
+    let diff = super::fetch::dry_run_diff(
+        &server,
+        &Config::default(),
         &kubeconfig_path,
-        "9.9.9.9",
+        TEST_KUBECONFIG_CONTENT,
+        "test_hash_456",
+    )
+    .unwrap();
+
+    assert!(diff.contains("+    server: https://9.9.9.9:6443"), "{}", diff);
+    assert!(diff.contains("+current-context: new-context"), "{}", diff);
+    assert_eq!(fs::read_to_string(&kubeconfig_path).unwrap(), TEST_KUBECONFIG_CONTENT);
+}
+
+#[test]
+fn test_dry_run_diff_of_rewritten_kubeconfig_hides_secrets() {
+    use super::kube::{redact_for_diff, rewrite_kubeconfig};
+    let rewritten = rewrite_kubeconfig(
+        TEST_KUBECONFIG_CONTENT,
+        "https://9.9.9.9:6443",
         "test_hash_456",
         &Some("new-context".to_string()),
         "test-server",
-        true,
+        None,
     )
     .unwrap();
+    // A rotated key must still show up as a changed line.
+    let rotated = rewritten.replace("client-key-data: ", "client-key-data: bmV3");
+    let diff = super::diff::unified_diff(
+        &redact_for_diff(TEST_KUBECONFIG_CONTENT),
+        &redact_for_diff(&rotated),
+        "before",
+        "after",
+    );
+
+    assert!(diff.contains("-    server: https://1.2.3.4:6443"), "{}", diff);
+    assert!(diff.contains("+    server: https://9.9.9.9:6443"), "{}", diff);
+    assert!(
+        diff.lines()
+            .any(|l| l.starts_with("-    client-key-data: <redacted sha256:"))
+    );
+    assert!(
+        diff.lines()
+            .any(|l| l.starts_with("+    client-key-data: <redacted sha256:"))
+    );
+    assert!(!diff.contains("bmV3"));
+}
+
+#[test]
+fn test_validate_fetched_rejects_non_kubeconfig_output() {
+    use super::kube::validate_fetched;
+
+    assert!(validate_fetched(TEST_KUBECONFIG_CONTENT.as_bytes()).is_ok());
+
+    let empty = validate_fetched(b"  \n").unwrap_err();
+    assert!(empty.to_string().contains("empty"), "{}", empty);
+
+    let prompt = validate_fetched(b"[sudo] password for admin: ").unwrap_err();
+    assert!(prompt.to_string().contains("sudo"), "{}", prompt);
+    let prompt = validate_fetched(b"\npassword for admin: \n").unwrap_err();
+    assert!(prompt.to_string().contains("sudo"), "{}", prompt);
+    // Only the first line is taken for a prompt; the words can appear in a real file.
+    let commented = format!(
+        "# rotate the password for admin: see runbook\n{}",
+        TEST_KUBECONFIG_CONTENT
+    );
+    assert!(validate_fetched(commented.as_bytes()).is_ok());
 
-    let content_after_dry_run = fs::read_to_string(kubeconfig_path).unwrap();
+    assert!(validate_fetched(b"cat: /etc/rancher/k3s/k3s.yaml: Permission denied\n").is_err());
+    assert!(validate_fetched(&[0xff, 0xfe, 0x00]).is_err());
 
-    // Verify the file content has not changed
-    assert_eq!(original_content, content_after_dry_run);
+    let no_users = TEST_KUBECONFIG_CONTENT.split("users:").next().unwrap();
+    let incomplete = validate_fetched(no_users.as_bytes()).unwrap_err();
+    assert!(incomplete.to_string().contains("0 user(s)"), "{}", incomplete);
+}
+
+#[test]
+fn test_use_context_sets_current_context() {
+    let temp_dir = Builder::new().prefix("test_use_context").tempdir().unwrap();
+    let content = TEST_KUBECONFIG_CONTENT.replace(
+        "users:",
+        "- name: prod\n  context:\n    cluster: old-cluster\n    user: old-user\nusers:",
+    );
+    let path = setup_test_kubeconfig(&temp_dir, &content);
+
+    super::kube::use_context(&path, "prod").unwrap();
+    let updated: KubeConfig = serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(updated.current_context, "prod");
+    assert_eq!(updated.contexts.len(), 2);
+
+    let err = super::kube::use_context(&path, "missing").unwrap_err();
+    assert!(err.to_string().contains("'missing'"), "{}", err);
+}
+
+#[test]
+fn test_rewrite_kubeconfig_without_target_ip_keeps_server_url() {
+    let updated = rewrite_kubeconfig(TEST_KUBECONFIG_CONTENT, "", "hash", &None, "downstream", None).unwrap();
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&updated).unwrap();
+    assert_eq!(kubeconfig.clusters[0].cluster.server, "https://1.2.3.4:6443");
+    assert_eq!(kubeconfig.clusters[0].name, "downstream");
+    assert_eq!(kubeconfig.contexts[0].context.cluster, "downstream");
 }
 
 #[test]
@@ -276,13 +687,19 @@ fn test_process_kubeconfig_file_hash_change_warning() {
     let temp_dir = Builder::new().prefix("test_kube_hash_change").tempdir().unwrap();
     let kubeconfig_path = setup_test_kubeconfig(&temp_dir, TEST_KUBECONFIG_CONTENT);
 
+    let run = |hash: &str| {
+        let content = fs::read_to_string(&kubeconfig_path).unwrap();
+        let updated = rewrite_kubeconfig(&content, "https://9.9.9.9:6443", hash, &None, "test-server", None).unwrap();
+        write_kubeconfig(&kubeconfig_path, &updated).unwrap();
+    };
+
     // First run, should just write the file
-    process_kubeconfig_file(&kubeconfig_path, "9.9.9.9", "first_hash", &None, "test-server", false).unwrap();
+    run("first_hash");
 
     // Second run with a different hash, should trigger a warning
     // (We can't easily check for logs here, but we're ensuring it runs without panic)
-    let result = process_kubeconfig_file(&kubeconfig_path, "9.9.9.9", "second_hash", &None, "test-server", false);
-    assert!(result.is_ok());
+    warn_if_source_changed(&kubeconfig_path, "second_hash");
+    run("second_hash");
 }
 
 #[test]
@@ -291,15 +708,16 @@ fn test_process_kubeconfig_no_context_update() {
     let kubeconfig_path = setup_test_kubeconfig(&temp_dir, TEST_KUBECONFIG_CONTENT);
 
     // When no target_context is set, server_name is used as the unique_name
-    process_kubeconfig_file(
-        &kubeconfig_path,
-        "8.8.8.8",
+    let updated = rewrite_kubeconfig(
+        &fs::read_to_string(&kubeconfig_path).unwrap(),
+        "https://8.8.8.8:6443",
         "some_hash",
         &None, // No target context — server_name becomes the unique_name
         "my-server",
-        false,
+        None,
     )
     .unwrap();
+    write_kubeconfig(&kubeconfig_path, &updated).unwrap();
 
     let updated_content = fs::read_to_string(kubeconfig_path).unwrap();
     let updated_kubeconfig: super::kube::KubeConfig = serde_yaml::from_str(&updated_content).unwrap();
@@ -310,6 +728,93 @@ fn test_process_kubeconfig_no_context_update() {
     assert_eq!(updated_kubeconfig.clusters[0].name, "my-server");
 }
 
+#[test]
+fn test_process_kubeconfig_preserves_unknown_fields() {
+    let temp_dir = Builder::new().prefix("test_kube_unknown").tempdir().unwrap();
+    let kubeconfig_path = setup_test_kubeconfig(
+        &temp_dir,
+        r#"apiVersion: v1
+kind: Config
+current-context: default
+clusters:
+- name: default
+  cluster:
+    server: https://127.0.0.1:6443
+    insecure-skip-tls-verify: true
+contexts:
+- name: default
+  context:
+    cluster: default
+    user: default
+    namespace: kube-system
+users:
+- name: default
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1beta1
+      command: rke2-auth
+extensions:
+- name: vendor
+"#,
+    );
+
+    let content = fs::read_to_string(&kubeconfig_path).unwrap();
+    let updated = rewrite_kubeconfig(&content, "https://9.9.9.9:6443", "hash", &None, "rke2", None).unwrap();
+    write_kubeconfig(&kubeconfig_path, &updated).unwrap();
+
+    let updated: KubeConfig = serde_yaml::from_str(&fs::read_to_string(&kubeconfig_path).unwrap()).unwrap();
+    let cluster = &updated.clusters[0].cluster;
+    assert_eq!(cluster.server, "https://9.9.9.9:6443");
+    assert!(cluster.certificate_authority.is_none());
+    assert_eq!(cluster.extra["insecure-skip-tls-verify"], serde_yaml::Value::Bool(true));
+    assert_eq!(updated.contexts[0].context.namespace.as_deref(), Some("kube-system"));
+    let user = &updated.users[0].user;
+    assert!(user.certificate_data.is_none());
+    assert_eq!(user.extra["exec"]["command"].as_str(), Some("rke2-auth"));
+    assert!(updated.extra.contains_key("extensions"));
+}
+
+/// Self-signed test CA (`CN=k3s-server-ca`) valid until 2036-10-12T08:47:45Z.
+const TEST_CA_DATA: &str = "LS0tLS1CRUdJTiBDRVJUSUZJQ0FURS0tLS0tCk1JSUJoakNDQVN1Z0F3SUJBZ0lVVjVROU5zV1QwQWxUa0dXa1JXOHY4QnJHWEM0d0NnWUlLb1pJemowRUF3SXcKR0RFV01CUUdBMVVFQXd3TmF6TnpMWE5sY25abGNpMWpZVEFlRncweU5qRXdNVFV3T0RRM05EVmFGdzB6TmpFdwpNVEl3T0RRM05EVmFNQmd4RmpBVUJnTlZCQU1NRFdzemN5MXpaWEoyWlhJdFkyRXdXVEFUQmdjcWhrak9QUUlCCkJnZ3Foa2pPUFFNQkJ3TkNBQVNhV1ZxUmw4OWNzQ1p4d3RBc1A0SDZiMlJUdS84N3FFUy9YZEZvM0hEUmt6Q24Kd2VCTGZuRjZHVUpkVktNdXIzWE5Ea0tGOVM2L2xtd0JYd3d0OE1BWm8xTXdVVEFkQmdOVkhRNEVGZ1FVSnZ1SgpZTVF2ZFllM3NzbDNZM055MUE4c2VwY3dId1lEVlIwakJCZ3dGb0FVSnZ1SllNUXZkWWUzc3NsM1kzTnkxQThzCmVwY3dEd1lEVlIwVEFRSC9CQVV3QXdFQi96QUtCZ2dxaGtqT1BRUURBZ05KQURCR0FpRUF1UGRRYUNJc2dKRlMKejlzR25uUXJoZitHK2MyaWl5bWNmQjRRUUNTendkd0NJUUNmcTNqVzV1L2taNkJaNUdiVmoxK1JBRkd3REVFWQpGc2tzQW9iVnJsSFdRQT09Ci0tLS0tRU5EIENFUlRJRklDQVRFLS0tLS0K";
+
+#[test]
+fn test_ca_expiry_recorded_on_rewrite() {
+    let content = TEST_KUBECONFIG_CONTENT.replace("FAKECERT", TEST_CA_DATA);
+    let updated = rewrite_kubeconfig(&content, "", "hash", &None, "s", None).unwrap();
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(updated.as_bytes()).unwrap();
+    assert_eq!(
+        super::kube::local_ca_expiry(file.path())
+            .map(|t| t.to_rfc3339())
+            .as_deref(),
+        Some("2036-10-12T08:47:45+00:00")
+    );
+
+    // An unreadable CA only loses the date.
+    let updated = rewrite_kubeconfig(TEST_KUBECONFIG_CONTENT, "", "hash", &None, "s", None).unwrap();
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(updated.as_bytes()).unwrap();
+    assert_eq!(super::kube::local_ca_expiry(file.path()), None);
+}
+
+#[test]
+fn test_local_client_cert_details() {
+    let content = TEST_KUBECONFIG_CONTENT.replace("aGVsbG8gd29ybGQ=", TEST_CA_DATA);
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    let details = super::kube::local_client_cert_details(file.path()).unwrap();
+    assert_eq!(details.subject, "CN=k3s-server-ca");
+    assert_eq!(details.issuer, "CN=k3s-server-ca");
+    assert_eq!(details.key_algorithm, "EC 256-bit");
+    assert_eq!(details.not_after.to_rfc3339(), "2036-10-12T08:47:45+00:00");
+    assert!(details.not_before < details.not_after);
+    assert!(details.serial.contains(':'));
+
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(TEST_KUBECONFIG_CONTENT.as_bytes()).unwrap();
+    assert!(super::kube::local_client_cert_details(file.path()).is_none());
+}
+
 #[test]
 fn test_cert_expiry_no_file() {
     let path = std::path::Path::new("/tmp/this_file_does_not_exist_xyz123");
@@ -390,6 +895,57 @@ preferences:
     assert!(matches!(result, super::kube::CertStatus::Unknown));
 }
 
+#[test]
+fn test_write_kubeconfig_replaces_file_owner_only() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("s.yaml");
+    fs::write(&path, "old").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    super::kube::write_kubeconfig(&path, "new").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "temp file left behind");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_token_and_exec_users_have_no_cert_expiry() {
+    use super::kube::{CertStatus, UserAuth};
+    for (user, auth) in [
+        ("token: K10abc::server:def", UserAuth::Token),
+        (
+            "exec:\n      apiVersion: client.authentication.k8s.io/v1beta1\n      command: kubelogin",
+            UserAuth::Exec,
+        ),
+    ] {
+        let content = format!(
+            "apiVersion: v1\nkind: Config\ncurrent-context: default\nclusters:\n- name: default\n  cluster:\n    \
+             server: https://127.0.0.1:6443\ncontexts:\n- name: default\n  context:\n    cluster: default\n    \
+             user: default\nusers:\n- name: default\n  user:\n    {}\n",
+            user
+        );
+        super::kube::validate_fetched(content.as_bytes()).unwrap();
+        let updated = rewrite_kubeconfig(&content, "https://10.0.0.1:6443", "hash", &None, "s", None).unwrap();
+        assert!(!updated.contains("certificate-expires-at"));
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(updated.as_bytes()).unwrap();
+        assert_eq!(super::kube::local_user_auth(file.path()), Some(auth));
+        assert!(matches!(
+            super::kube::check_local_cert_expiry(file.path()),
+            CertStatus::NoClientCert(found) if found == auth
+        ));
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers for merge_into_main_kubeconfig tests
 // ---------------------------------------------------------------------------
@@ -470,7 +1026,7 @@ fn test_merge_dry_run() {
     };
     let mtime_before = main_path.metadata().ok().and_then(|m| m.modified().ok());
 
-    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-dryrun", true);
+    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-dryrun", MergeStrategy::Overwrite, 0, true);
     assert!(result.is_ok(), "dry_run merge returned error: {:?}", result);
 
     // File must not have been modified
@@ -522,7 +1078,7 @@ preferences:
     let fetched_path = temp_dir.path().join("fetched_kubeconfig");
     fs::write(&fetched_path, &yaml).unwrap();
 
-    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-noprefs", false);
+    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-noprefs", MergeStrategy::Overwrite, 0, false);
     assert!(result.is_ok(), "merge returned error: {:?}", result);
 
     let main_path = main_kubeconfig_path();
@@ -556,7 +1112,7 @@ fn test_merge_replaces_existing() {
 
     // First merge with IP A
     let fetched_path = write_fetched_file(&temp_dir, context_name, "10.99.0.10");
-    merge_into_main_kubeconfig(&fetched_path, "test-server-replace", false).unwrap();
+    merge_into_main_kubeconfig(&fetched_path, "test-server-replace", MergeStrategy::Overwrite, 0, false).unwrap();
 
     // Second merge with IP B (overwrite)
     let fetched_path2 = {
@@ -564,7 +1120,14 @@ fn test_merge_replaces_existing() {
         fs::write(&p, make_kubeconfig_yaml(context_name, "10.99.0.20")).unwrap();
         p
     };
-    merge_into_main_kubeconfig(&fetched_path2, "test-server-replace", false).unwrap();
+    merge_into_main_kubeconfig(
+        &fetched_path2,
+        "test-server-replace",
+        MergeStrategy::Overwrite,
+        0,
+        false,
+    )
+    .unwrap();
 
     let main_path = main_kubeconfig_path();
     let content = fs::read_to_string(&main_path).unwrap();
@@ -612,7 +1175,14 @@ fn test_merge_preserves_other_contexts() {
     };
 
     let fetched_path = write_fetched_file(&temp_dir, context_name, "10.99.0.30");
-    merge_into_main_kubeconfig(&fetched_path, "test-server-preserve", false).unwrap();
+    merge_into_main_kubeconfig(
+        &fetched_path,
+        "test-server-preserve",
+        MergeStrategy::Overwrite,
+        0,
+        false,
+    )
+    .unwrap();
 
     let content_after = fs::read_to_string(&main_path).unwrap();
     let config_after: KubeConfig = serde_yaml::from_str(&content_after).unwrap();
@@ -644,6 +1214,8 @@ fn test_merge_dry_run_nonexistent_fetched_returns_ok() {
     let result = merge_into_main_kubeconfig(
         std::path::Path::new("/tmp/this_does_not_exist_kube_test_xyz"),
         "test-server-nonexistent",
+        MergeStrategy::Overwrite,
+        0,
         true,
     );
     assert!(
@@ -659,6 +1231,8 @@ fn test_merge_non_dry_run_returns_err_for_nonexistent_fetched() {
     let result = merge_into_main_kubeconfig(
         std::path::Path::new("/tmp/this_does_not_exist_kube_test_xyz"),
         "test-server-nonexistent",
+        MergeStrategy::Overwrite,
+        0,
         false,
     );
     assert!(
@@ -681,7 +1255,7 @@ fn test_merge_dry_run_valid_file_leaves_main_unchanged() {
         None
     };
 
-    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-dryrun2", true);
+    let result = merge_into_main_kubeconfig(&fetched_path, "test-server-dryrun2", MergeStrategy::Overwrite, 0, true);
     assert!(result.is_ok(), "dry_run merge returned error: {:?}", result);
 
     // Main config content must be byte-for-byte identical
@@ -691,6 +1265,132 @@ fn test_merge_dry_run_valid_file_leaves_main_unchanged() {
     }
 }
 
+/// A fetched kubeconfig as `rewrite_kubeconfig` leaves it: cluster, context, and
+/// user all share one name.
+fn parse_kubeconfig(context_name: &str, server_ip: &str) -> KubeConfig {
+    let mut config: KubeConfig = serde_yaml::from_str(&make_kubeconfig_yaml(context_name, server_ip)).unwrap();
+    config.users[0].name = context_name.to_string();
+    config.contexts[0].context.user = context_name.to_string();
+    config
+}
+
+#[test]
+fn test_merge_same_server_is_not_a_conflict() {
+    use super::kube::{MergeOutcome, merge_kubeconfigs};
+    let mut main = parse_kubeconfig("prod", "10.0.0.1");
+    let outcome = merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.1"),
+        MergeStrategy::Prompt,
+        "prod",
+    );
+    assert_eq!(outcome.unwrap(), MergeOutcome::Merged);
+    assert_eq!(main.clusters.len(), 1);
+}
+
+#[test]
+fn test_merge_strategy_skip_and_prompt_leave_main_unchanged() {
+    use super::kube::{MergeOutcome, merge_kubeconfigs};
+    let mut main = parse_kubeconfig("prod", "10.0.0.1");
+
+    let outcome = merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.2"),
+        MergeStrategy::Skip,
+        "prod",
+    );
+    assert_eq!(outcome.unwrap(), MergeOutcome::Skipped);
+
+    let conflict = merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.2"),
+        MergeStrategy::Prompt,
+        "prod",
+    )
+    .unwrap_err();
+    assert_eq!(conflict.names, vec!["prod".to_string()]);
+
+    assert_eq!(main.clusters.len(), 1);
+    assert_eq!(main.clusters[0].cluster.server, "https://10.0.0.1:6443");
+}
+
+#[test]
+fn test_merge_strategy_rename_reuses_own_suffix() {
+    use super::kube::{MergeOutcome, merge_kubeconfigs};
+    let mut main = parse_kubeconfig("prod", "10.0.0.1");
+
+    let outcome = merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.2"),
+        MergeStrategy::Rename,
+        "other",
+    );
+    assert_eq!(outcome.unwrap(), MergeOutcome::Renamed("prod-2".to_string()));
+    let ctx = main.contexts.iter().find(|c| c.name == "prod-2").unwrap();
+    assert_eq!(ctx.context.cluster, "prod-2");
+    assert!(main.users.iter().any(|u| u.name == "prod-2"));
+    assert_eq!(main.clusters[0].cluster.server, "https://10.0.0.1:6443");
+
+    // A second fetch of the same cluster lands on the same renamed entries.
+    let outcome = merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.2"),
+        MergeStrategy::Rename,
+        "other",
+    );
+    assert_eq!(outcome.unwrap(), MergeOutcome::Renamed("prod-2".to_string()));
+    assert_eq!(main.clusters.len(), 2);
+}
+
+#[test]
+fn test_namespace_written_into_context_and_kept_on_merge() {
+    use super::kube::merge_kubeconfigs;
+    let updated = rewrite_kubeconfig(TEST_KUBECONFIG_CONTENT, "", "hash", &None, "s", Some("apps")).unwrap();
+    let kubeconfig: KubeConfig = serde_yaml::from_str(&updated).unwrap();
+    assert_eq!(kubeconfig.contexts[0].context.namespace.as_deref(), Some("apps"));
+    assert!(updated.contains("namespace: apps"));
+
+    // Without a configured namespace, one chosen in ~/.kube/config survives a refetch.
+    let mut main = parse_kubeconfig("prod", "10.0.0.1");
+    main.contexts[0].context.namespace = Some("monitoring".to_string());
+    merge_kubeconfigs(
+        &mut main,
+        parse_kubeconfig("prod", "10.0.0.1"),
+        MergeStrategy::Overwrite,
+        "prod",
+    )
+    .unwrap();
+    assert_eq!(main.contexts[0].context.namespace.as_deref(), Some("monitoring"));
+
+    let mut fetched = parse_kubeconfig("prod", "10.0.0.1");
+    fetched.contexts[0].context.namespace = Some("apps".to_string());
+    merge_kubeconfigs(&mut main, fetched, MergeStrategy::Overwrite, "prod").unwrap();
+    assert_eq!(main.contexts[0].context.namespace.as_deref(), Some("apps"));
+}
+
+#[test]
+fn test_server_merge_settings_fallback() {
+    let mut config = Config {
+        merge_strategy: Some(MergeStrategy::Skip),
+        ..Default::default()
+    };
+    let mut server = Server {
+        merge_strategy: Some(MergeStrategy::Rename),
+        ..Default::default()
+    };
+    assert_eq!(server.merge_strategy(&config), MergeStrategy::Rename);
+    server.merge_strategy = None;
+    assert_eq!(server.merge_strategy(&config), MergeStrategy::Skip);
+    assert_eq!(server.merge_strategy(&Config::default()), MergeStrategy::Overwrite);
+
+    assert!(server.merge_enabled(&config));
+    server.merge = Some(false);
+    assert!(!server.merge_enabled(&config));
+    server.merge = Some(true);
+    config.merge = Some(false);
+    assert!(!server.merge_enabled(&config), "a global merge = false wins");
+}
+
 // ---------------------------------------------------------------------------
 // process_server early-return tests
 // ---------------------------------------------------------------------------
@@ -706,7 +1406,7 @@ fn test_process_server_cert_valid_skips_ssh() {
     let server_name = "test-proc-cert-valid";
 
     // Write a cached kubeconfig whose cert expires far in the future
-    let local_path = temp_dir.path().join(server_name);
+    let local_path = temp_dir.path().join(format!("{}.yaml", server_name));
     fs::write(
         &local_path,
         r#"apiVersion: v1
@@ -743,6 +1443,7 @@ preferences:
         file_name: Some("admin.conf".to_string()),
         context_name: None,
         identity_file: None,
+        ..Default::default()
     };
 
     let cfg = Config {
@@ -753,12 +1454,385 @@ preferences:
         local_output_dir: temp_dir.path().to_string_lossy().into_owned(),
         bitwarden: None,
         servers: vec![],
+        ..Default::default()
     };
 
-    let result = process_server(&server, &cfg, false, false, None);
+    let result = process_server(&server, &cfg, false, false, None, None, &|_| {}, None);
     assert!(result.is_ok(), "expected Ok, got Err: {:?}", result.err());
     assert!(
         matches!(result.unwrap(), ServerResult::Skipped(SkipReason::CertValid(_))),
         "expected Skipped(CertValid), got something else"
     );
 }
+
+#[test]
+fn test_migrate_local_files_renames_legacy_names() {
+    let dir = TempDir::new().unwrap();
+    let server = |name: &str| Server {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let config = Config {
+        local_output_dir: dir.path().to_string_lossy().into_owned(),
+        servers: vec![
+            server("old"),
+            server("both"),
+            Server {
+                local_file_name: Some("custom.conf".to_string()),
+                ..server("custom")
+            },
+        ],
+        ..Default::default()
+    };
+    for name in ["old", "both", "both.yaml", "custom"] {
+        fs::write(dir.path().join(name), name).unwrap();
+    }
+
+    super::fetch::migrate_local_files(&config);
+    assert_eq!(fs::read_to_string(dir.path().join("old.yaml")).unwrap(), "old");
+    assert!(!dir.path().join("old").exists());
+    // An existing file under the new name is never overwritten.
+    assert_eq!(fs::read_to_string(dir.path().join("both.yaml")).unwrap(), "both.yaml");
+    assert!(dir.path().join("both").exists());
+    assert_eq!(fs::read_to_string(config.local_path("custom")).unwrap(), "custom");
+    assert_eq!(config.server_for_local_file("custom.conf").unwrap().name, "custom");
+}
+
+// ---------------------------------------------------------------------------
+// --output json report shape
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_report_file_is_written_when_configured() {
+    let dir = TempDir::new().unwrap();
+    let report_path = dir.path().join("reports/last-run.json");
+    let config = Config {
+        local_output_dir: dir.path().join("out").to_string_lossy().to_string(),
+        report_file: Some(report_path.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    let report = super::fetch::process_servers(
+        &config,
+        &["missing".to_string()],
+        true,
+        false,
+        false,
+        &Default::default(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert!(report.servers.is_empty());
+
+    let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(written["version"], super::fetch::REPORT_VERSION);
+    assert_eq!(written["dry_run"], true);
+    assert!(written["finished_at"].is_string());
+}
+
+#[test]
+fn test_run_bounded_keeps_slow_items_from_holding_up_the_rest() {
+    use super::fetch::run_bounded;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // Item 0 stands in for a dead host: it holds its slot until every other item has
+    // gone through the remaining one, and only then is it released.
+    let items: Vec<usize> = (0..6).collect();
+    let refs: Vec<&usize> = items.iter().collect();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let release_rx = Mutex::new(release_rx);
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+    let fast_done = AtomicUsize::new(0);
+    let finished = Mutex::new(Vec::new());
+    let results = run_bounded(
+        &refs,
+        2,
+        || false,
+        |&item| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            let released = if item == 0 {
+                // The timeout only keeps a broken scheduler from hanging the test.
+                let rx = release_rx.lock().unwrap();
+                rx.recv_timeout(Duration::from_secs(10)).is_ok()
+            } else {
+                true
+            };
+            running.fetch_sub(1, Ordering::SeqCst);
+            finished.lock().unwrap().push(item);
+            if item != 0 && fast_done.fetch_add(1, Ordering::SeqCst) + 1 == items.len() - 1 {
+                release_tx.send(()).unwrap();
+            }
+            (item * 2, released)
+        },
+    );
+    assert!(peak.load(Ordering::SeqCst) <= 2);
+    assert_eq!(finished.into_inner().unwrap().last(), Some(&0));
+    let returned: Vec<(usize, bool)> = results.iter().map(|(_, r)| *r).collect();
+    assert_eq!(
+        returned,
+        vec![(0, true), (2, true), (4, true), (6, true), (8, true), (10, true)],
+        "results in item order, and the blocked item was released rather than timed out"
+    );
+
+    // Nothing new starts once `stop` says so; what already ran is still returned.
+    let failed = AtomicBool::new(false);
+    let results = run_bounded(
+        &refs,
+        1,
+        || failed.load(Ordering::SeqCst),
+        |&item| failed.store(item == 1, Ordering::SeqCst),
+    );
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn test_failed_servers_selects_failed_and_auth_rejected() {
+    use super::state::{RunStatus, ServerRunState};
+    let config = Config {
+        servers: ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| Server {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let states = [
+        ("a", RunStatus::Failed),
+        ("b", RunStatus::Fetched),
+        ("c", RunStatus::AuthRejected),
+        ("d", RunStatus::Failed),
+    ]
+    .into_iter()
+    .map(|(name, status)| {
+        (
+            name.to_string(),
+            ServerRunState {
+                status,
+                last_updated: None,
+                error: None,
+                duration_ms: None,
+            },
+        )
+    })
+    .collect();
+
+    assert_eq!(super::failed_servers(&config, &[], &states), ["a", "c", "d"]);
+    assert_eq!(
+        super::failed_servers(&config, &["c".to_string(), "e".to_string()], &states),
+        ["c"]
+    );
+}
+
+#[test]
+fn test_bulk_credential_accounts() {
+    let server = |name: &str, tags: &[&str]| Server {
+        name: name.to_string(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    };
+    let config = Config {
+        servers: vec![server("a", &["prod"]), server("b", &[]), server("c", &["prod", "edge"])],
+        ..Default::default()
+    };
+    assert_eq!(
+        super::bulk_credential_accounts(&config, None, false).unwrap(),
+        ["a", "b", "c"]
+    );
+    assert_eq!(
+        super::bulk_credential_accounts(&config, Some("prod"), true).unwrap(),
+        ["sudo:a", "sudo:c"]
+    );
+    assert!(super::bulk_credential_accounts(&config, Some("staging"), false).is_err());
+
+    use clap::Parser;
+    let parse =
+        |args: &[&str]| super::Cli::try_parse_from([&["kube_config_updater", "credential", "set"], args].concat());
+    assert!(parse(&["--group", "prod", "--sudo"]).is_ok());
+    assert!(parse(&["--all", "--server", "a"]).is_err());
+    assert!(parse(&["--default", "--sudo"]).is_err());
+}
+
+#[test]
+fn test_log_spec_from_flags() {
+    use clap::Parser;
+    let spec = |args: &[&str]| {
+        let cli = super::Cli::try_parse_from([&["kube_config_updater"], args].concat()).unwrap();
+        super::log_spec(&cli).to_string()
+    };
+    assert_eq!(spec(&[]), "info");
+    assert_eq!(spec(&["-q"]), "error");
+    assert_eq!(spec(&["-v"]), "debug");
+    assert_eq!(spec(&["-vv"]), "trace");
+    assert_eq!(spec(&["-v", "--log-level", "warn"]), "warn");
+    assert!(super::Cli::try_parse_from(["kube_config_updater", "-q", "-v"]).is_err());
+}
+
+#[test]
+fn test_jobs_flag_overrides_max_parallel() {
+    use clap::Parser;
+    let parse = |args: &[&str]| super::Cli::try_parse_from([&["kube_config_updater"], args].concat());
+    let mut config = Config {
+        max_parallel: Some(8),
+        ..Default::default()
+    };
+
+    super::apply_cli_overrides(&parse(&[]).unwrap(), &mut config);
+    assert_eq!(config.max_parallel(), 8);
+    super::apply_cli_overrides(&parse(&["--jobs", "2"]).unwrap(), &mut config);
+    assert_eq!(config.max_parallel(), 2);
+    // 0 would otherwise fall back to the default and fetch more at once, not fewer.
+    assert!(parse(&["--jobs", "0"]).is_err());
+}
+
+#[test]
+fn test_setup_is_offered_only_on_a_terminal_without_json() {
+    use super::{OutputFormat, can_offer_setup};
+    assert!(can_offer_setup(OutputFormat::Text, true, true));
+    assert!(!can_offer_setup(OutputFormat::Json, true, true));
+    assert!(!can_offer_setup(OutputFormat::Text, false, true), "stdin piped");
+    assert!(!can_offer_setup(OutputFormat::Text, true, false), "stdout redirected");
+}
+
+#[test]
+fn test_exit_codes() {
+    use super::fetch::{RunReport, ServerReport};
+    use super::state::RunStatus;
+
+    let server = |status| ServerReport {
+        name: "s".to_string(),
+        status,
+        error: None,
+        cert_expires: None,
+        ca_expires: None,
+        duration_ms: 0,
+        bytes_fetched: 0,
+        diff: None,
+    };
+    let code = |report: &RunReport| match report.check_failures() {
+        Ok(()) => 0,
+        Err(e) => super::exit_code(&e.into()),
+    };
+
+    let mut report = RunReport {
+        servers: vec![server(RunStatus::Fetched), server(RunStatus::Skipped)],
+        ..Default::default()
+    };
+    assert_eq!(code(&report), 0);
+
+    report.servers.push(server(RunStatus::Failed));
+    report.failed = 1;
+    assert_eq!(code(&report), 1);
+
+    report.servers = vec![server(RunStatus::Failed)];
+    assert_eq!(code(&report), 3);
+    report.not_attempted = 2;
+    assert_eq!(code(&report), 1);
+
+    let report = RunReport {
+        servers: vec![server(RunStatus::Fetched), server(RunStatus::Unreachable)],
+        fetched: 1,
+        unreachable: 1,
+        ..Default::default()
+    };
+    assert_eq!(code(&report), 1);
+
+    assert_eq!(
+        super::exit_code(&super::ConfigInvalid(anyhow::anyhow!("bad")).into()),
+        2
+    );
+    assert_eq!(super::exit_code(&anyhow::anyhow!("other")), 1);
+}
+
+#[test]
+fn test_verify_api_flags_unreachable_server() {
+    use super::config::ApiVerification;
+
+    // A port nothing listens on once the listener is dropped.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("prod.yaml");
+    fs::write(
+        &path,
+        TEST_KUBECONFIG_CONTENT
+            .replace("https://1.2.3.4:6443", &format!("https://127.0.0.1:{}", port))
+            .replace("FAKEKEY", "a2V5"),
+    )
+    .unwrap();
+
+    let target = super::kube::local_api_target(&path).unwrap();
+    assert_eq!(target.server, format!("https://127.0.0.1:{}", port));
+    assert!(target.ca_pem.is_some());
+    assert!(target.client_pem.is_some());
+
+    assert!(super::fetch::verify_api(ApiVerification::Off, &dir.path().join("missing.yaml")).is_ok());
+    let err = super::fetch::verify_api(ApiVerification::Tls, &path).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains(&format!("127.0.0.1:{}", port)),
+        "{:#}",
+        err
+    );
+    assert!(super::fetch::verify_api(ApiVerification::Version, &path).is_err());
+}
+
+/// Scripts consume these keys directly, so renaming a field is a breaking change.
+#[test]
+fn test_run_report_json_shape() {
+    use super::fetch::{RunReport, ServerReport};
+    use super::state::RunStatus;
+
+    let expiry = chrono::DateTime::parse_from_rfc3339("2099-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let report = RunReport {
+        servers: vec![
+            ServerReport {
+                name: "alpha".to_string(),
+                status: RunStatus::Skipped,
+                error: None,
+                cert_expires: Some(expiry),
+                ca_expires: None,
+                duration_ms: 3,
+                bytes_fetched: 0,
+                diff: None,
+            },
+            ServerReport {
+                name: "beta".to_string(),
+                status: RunStatus::Failed,
+                error: Some("Connection refused".to_string()),
+                cert_expires: None,
+                ca_expires: None,
+                duration_ms: 1200,
+                bytes_fetched: 0,
+                diff: None,
+            },
+        ],
+        skipped_cert_valid: 1,
+        failed: 1,
+        version: super::fetch::REPORT_VERSION,
+        ..Default::default()
+    };
+
+    let value = serde_json::to_value(&report).unwrap();
+    assert_eq!(value["version"], 1);
+    assert!(value["started_at"].is_null());
+    assert_eq!(value["dry_run"], false);
+    assert_eq!(value["servers"][0]["bytes_fetched"], 0);
+    assert_eq!(value["failed"], 1);
+    assert_eq!(value["skipped_cert_valid"], 1);
+    assert_eq!(value["servers"][0]["name"], "alpha");
+    assert_eq!(value["servers"][0]["status"], "Skipped");
+    assert_eq!(value["servers"][0]["cert_expires"], "2099-01-01T00:00:00Z");
+    assert_eq!(value["servers"][1]["error"], "Connection refused");
+    assert_eq!(value["servers"][1]["duration_ms"], 1200);
+    assert!(value["servers"][1]["cert_expires"].is_null());
+}
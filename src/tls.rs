@@ -0,0 +1,285 @@
+//! Reads the serving certificate of a Kubernetes API server.
+//!
+//! The handshake accepts any certificate on purpose: the point is to inspect what the
+//! server presents (expiry, SANs), including certs a verifying client would reject.
+//! `api_version` is the exception: it connects the way kubectl would.
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use std::io::Write as _;
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+
+/// Port the k3s / kubeadm API server listens on.
+pub const API_PORT: u16 = 6443;
+
+/// What the API server presented during the TLS handshake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServingCert {
+    pub not_after: chrono::DateTime<chrono::Utc>,
+    /// DNS names from the subjectAltName extension.
+    pub dns_names: Vec<String>,
+    /// IP addresses from the subjectAltName extension.
+    pub ips: Vec<IpAddr>,
+    pub details: CertDetails,
+}
+
+/// Identity and validity of a certificate, as shown in the detail view.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CertDetails {
+    pub subject: String,
+    pub issuer: String,
+    /// Hex bytes separated by colons, as `openssl x509 -serial` would show them.
+    pub serial: String,
+    /// SANs joined for display (IPs first); empty when there are none.
+    pub sans: String,
+    /// e.g. `EC 256-bit`, `RSA 2048-bit`, `Ed25519`.
+    pub key_algorithm: String,
+    pub not_before: chrono::DateTime<chrono::Utc>,
+    pub not_after: chrono::DateTime<chrono::Utc>,
+}
+
+impl ServingCert {
+    /// Whether a client connecting to `host` (an IP or DNS name) would accept this cert's
+    /// SANs. Wildcards cover a single leftmost label, as in RFC 6125.
+    pub fn covers(&self, host: &str) -> bool {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self.ips.contains(&ip);
+        }
+        let host = host.to_ascii_lowercase();
+        self.dns_names.iter().any(|name| {
+            let name = name.to_ascii_lowercase();
+            match name.strip_prefix("*.") {
+                Some(suffix) => host.split_once('.').is_some_and(|(_, rest)| rest == suffix),
+                None => name == host,
+            }
+        })
+    }
+
+    /// SANs joined for display, IPs first.
+    pub fn sans(&self) -> String {
+        join_sans(&self.ips, &self.dns_names)
+    }
+}
+
+/// Connects to `host:port`, completes a TLS handshake and returns the leaf certificate's
+/// expiry and SANs. No request is sent and no client certificate is offered.
+pub fn probe_serving_cert(host: &str, port: u16, timeout: Duration) -> Result<ServingCert, anyhow::Error> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("could not resolve {}", host))?;
+    let mut sock = TcpStream::connect_timeout(&addr, timeout)?;
+    sock.set_read_timeout(Some(timeout))?;
+    sock.set_write_timeout(Some(timeout))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(InspectOnly(provider)))
+        .with_no_client_auth();
+    let server_name = ServerName::try_from(host.to_string())?;
+    let mut conn = rustls::ClientConnection::new(Arc::new(config), server_name)?;
+    while conn.is_handshaking() {
+        conn.complete_io(&mut sock)?;
+    }
+    let leaf = conn
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .ok_or_else(|| anyhow::anyhow!("{}:{} presented no certificate", host, port))?
+        .clone();
+    conn.send_close_notify();
+    conn.complete_io(&mut sock).ok();
+    sock.flush().ok();
+
+    parse_serving_cert(&leaf)
+}
+
+/// `GET /version` on the API server of `target`, trusting only its CA (the bundled
+/// roots when it has none) and presenting its client cert. Returns the server's
+/// `gitVersion`.
+pub fn api_version(target: &crate::kube::ApiTarget, timeout: Duration) -> Result<String, anyhow::Error> {
+    use ureq::tls::{ClientCert, PemItem, PrivateKey, RootCerts, TlsConfig, parse_pem};
+
+    let mut tls = TlsConfig::builder();
+    if let Some(ca_pem) = &target.ca_pem {
+        let roots: Vec<_> = parse_pem(ca_pem)
+            .filter_map(|item| match item {
+                Ok(PemItem::Certificate(cert)) => Some(cert),
+                _ => None,
+            })
+            .collect();
+        if roots.is_empty() {
+            anyhow::bail!("certificate-authority-data holds no certificate");
+        }
+        tls = tls.root_certs(RootCerts::new_with_certs(&roots));
+    }
+    if let Some((cert_pem, key_pem)) = &target.client_pem {
+        let chain: Vec<_> = parse_pem(cert_pem)
+            .filter_map(|item| match item {
+                Ok(PemItem::Certificate(cert)) => Some(cert),
+                _ => None,
+            })
+            .collect();
+        let key = PrivateKey::from_pem(key_pem).map_err(|e| anyhow::anyhow!("client-key-data: {}", e))?;
+        tls = tls.client_cert(Some(ClientCert::new_with_certs(&chain, key)));
+    }
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .tls_config(tls.build())
+        .build()
+        .into();
+    let url = format!("{}/version", target.server.trim_end_matches('/'));
+    let version: serde_json::Value = agent
+        .get(&url)
+        .call()
+        .map_err(|e| anyhow::anyhow!("{}: {}", url, e))?
+        .body_mut()
+        .read_json()?;
+    Ok(version["gitVersion"].as_str().unwrap_or("unknown").to_string())
+}
+
+fn parse_serving_cert(der: &[u8]) -> Result<ServingCert, anyhow::Error> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)?;
+    let (dns_names, ips) = san_entries(&cert);
+    let details = details_of(&cert, join_sans(&ips, &dns_names))?;
+    Ok(ServingCert {
+        not_after: details.not_after,
+        dns_names,
+        ips,
+        details,
+    })
+}
+
+/// Subject, issuer, serial, SANs, key and validity of a DER-encoded certificate.
+pub fn cert_details(der: &[u8]) -> Result<CertDetails, anyhow::Error> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)?;
+    let (dns_names, ips) = san_entries(&cert);
+    details_of(&cert, join_sans(&ips, &dns_names))
+}
+
+fn details_of(cert: &X509Certificate, sans: String) -> Result<CertDetails, anyhow::Error> {
+    let time = |t: x509_parser::time::ASN1Time| {
+        chrono::DateTime::from_timestamp(t.timestamp(), 0)
+            .ok_or_else(|| anyhow::anyhow!("certificate validity out of range"))
+    };
+    Ok(CertDetails {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: cert.raw_serial_as_string(),
+        sans,
+        key_algorithm: key_algorithm(cert),
+        not_before: time(cert.validity().not_before)?,
+        not_after: time(cert.validity().not_after)?,
+    })
+}
+
+fn key_algorithm(cert: &X509Certificate) -> String {
+    use x509_parser::public_key::PublicKey;
+    let spki = cert.public_key();
+    match spki.parsed() {
+        Ok(PublicKey::RSA(rsa)) => format!("RSA {}-bit", rsa.key_size()),
+        Ok(PublicKey::EC(ec)) => format!("EC {}-bit", ec.key_size()),
+        _ => match spki.algorithm.algorithm.to_id_string().as_str() {
+            "1.3.101.112" => "Ed25519".to_string(),
+            "1.3.101.113" => "Ed448".to_string(),
+            oid => oid.to_string(),
+        },
+    }
+}
+
+fn join_sans(ips: &[IpAddr], dns_names: &[String]) -> String {
+    ips.iter()
+        .map(|ip| ip.to_string())
+        .chain(dns_names.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// DNS names and IP addresses from the subjectAltName extension.
+fn san_entries(cert: &X509Certificate) -> (Vec<String>, Vec<IpAddr>) {
+    let mut dns_names = Vec::new();
+    let mut ips = Vec::new();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(dns) => dns_names.push(dns.to_string()),
+                GeneralName::IPAddress(bytes) => {
+                    if let Ok(v4) = <[u8; 4]>::try_from(*bytes) {
+                        ips.push(IpAddr::from(v4));
+                    } else if let Ok(v6) = <[u8; 16]>::try_from(*bytes) {
+                        ips.push(IpAddr::from(v6));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    (dns_names, ips)
+}
+
+/// Accepts any certificate, but still checks handshake signatures so the peer has to
+/// hold the key for the certificate it shows.
+#[derive(Debug)]
+struct InspectOnly(Arc<rustls::crypto::CryptoProvider>);
+
+impl ServerCertVerifier for InspectOnly {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serving_cert_covers_ips_and_wildcards() {
+        let cert = ServingCert {
+            not_after: chrono::Utc::now(),
+            dns_names: vec!["kubernetes.default".to_string(), "*.k3s.lan".to_string()],
+            ips: vec!["10.0.0.1".parse().unwrap(), "127.0.0.1".parse().unwrap()],
+            details: CertDetails::default(),
+        };
+        assert!(cert.covers("10.0.0.1"));
+        assert!(!cert.covers("10.0.0.2"));
+        assert!(cert.covers("node1.k3s.lan"));
+        assert!(!cert.covers("a.node1.k3s.lan"));
+        assert!(cert.covers("Kubernetes.Default"));
+        assert_eq!(cert.sans(), "10.0.0.1, 127.0.0.1, kubernetes.default, *.k3s.lan");
+    }
+}
@@ -1,10 +1,10 @@
-use crossterm::event::KeyEvent;
-use std::collections::{HashMap, HashSet};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 
 use crate::bitwarden::ServerSource;
 use crate::config::Config;
-use crate::state::ServerRunState;
+use crate::state::{RunStatus, ServerRunState};
 
 // ─── Events ──────────────────────────────────────────────────────────────────
 
@@ -15,13 +15,41 @@ pub const WIZARD_SENTINEL: &str = "__wizard__";
 /// Sentinel used in `in_progress` while a Bitwarden vault unlock is running.
 pub const BITWARDEN_SENTINEL: &str = "__bitwarden__";
 
+/// Sentinel used in `in_progress` while a network discovery scan is running.
+pub const DISCOVER_SENTINEL: &str = "__discover__";
+
 pub enum AppEvent {
     Key(KeyEvent),
+    /// Text pasted into the terminal, delivered whole by bracketed paste.
+    Paste(String),
     Resize(u16, u16),
     Tick,
+    /// Running byte count for an in-flight fetch.
+    FetchProgress {
+        server_name: String,
+        bytes: u64,
+    },
     FetchComplete {
         server_name: String,
         result: Result<(), String>,
+        /// Set when the fetch worked but `verify_api` couldn't reach the API server.
+        unreachable: Option<String>,
+        /// Wall-clock time from spawning the fetch to its result.
+        duration: std::time::Duration,
+    },
+    /// The renewal command finished (and the settle delay passed); a fetch follows on success.
+    RenewComplete {
+        server_name: String,
+        result: Result<(), String>,
+    },
+    /// A fetch stopped by `cancel_fetch`; sent instead of `FetchComplete`.
+    FetchCancelled {
+        server_name: String,
+    },
+    /// The changes a dry-run fetch would have made. Sent after the matching completion event.
+    DryRunDiff {
+        server_name: String,
+        diff: String,
     },
     WizardTestComplete {
         result: Result<(), String>,
@@ -30,10 +58,64 @@ pub enum AppEvent {
         server_name: String,
         result: Result<Option<chrono::DateTime<chrono::Utc>>, String>,
     },
+    /// Time to re-check which servers are reachable.
+    ReachabilityDue,
+    ReachabilityChecked {
+        server_name: String,
+        reachability: Reachability,
+    },
+    ApiProbeComplete {
+        server_name: String,
+        result: Result<crate::tls::ServingCert, String>,
+    },
+    /// One server's result from probing all listed servers (`p` on the dashboard).
+    RemoteCertChecked {
+        server_name: String,
+        result: Result<Option<chrono::DateTime<chrono::Utc>>, String>,
+    },
     StateFileChanged,
+    /// The config file was written or replaced, by an editor or another tool.
+    ConfigFileChanged,
+    /// A file in `local_output_dir` was written or removed outside the TUI.
+    KubeconfigChanged {
+        file_name: String,
+    },
     BitwardenComplete {
         result: Result<(Vec<crate::bitwarden::VaultServer>, Vec<String>), String>,
     },
+    /// A discovery scan of `network` finished.
+    DiscoverComplete {
+        network: String,
+        result: Result<(Vec<crate::import::Candidate>, Vec<crate::import::Skipped>), String>,
+    },
+    /// A fetch or connection test stopped because the host key isn't in known_hosts.
+    /// Sent after the matching completion event.
+    HostKeyUnknown {
+        server_name: String,
+        key: crate::ssh::UnknownHostKey,
+    },
+    /// A connection stopped because the identity file is encrypted and no passphrase
+    /// was found. Sent after the matching completion event.
+    KeyPassphraseRequired {
+        server_name: String,
+        identity_file: String,
+    },
+    /// sudo on the server rejected the stored password. Sent after the matching
+    /// completion event.
+    SudoPasswordRejected {
+        server_name: String,
+    },
+    /// The server asked for a one-time code (keyboard-interactive 2FA). Sent after the
+    /// matching completion event.
+    OtpRequired {
+        server_name: String,
+    },
+    /// A fetch succeeded but its entries clash with another cluster in ~/.kube/config
+    /// and the merge strategy is `prompt`. Sent after the matching completion event.
+    MergeConflict {
+        server_name: String,
+        names: Vec<String>,
+    },
 }
 
 // ─── Probe State ──────────────────────────────────────────────────────────────
@@ -45,6 +127,59 @@ pub enum ProbeState {
     Failed(String),
 }
 
+/// TLS probe of a server's Kubernetes API endpoint, shown in the detail view.
+#[derive(Clone)]
+pub enum ApiProbeState {
+    Probing,
+    Done(crate::tls::ServingCert),
+    Failed(String),
+}
+
+/// Result of the last background reachability check for a server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reachability {
+    /// The SSH port accepted a connection.
+    pub ssh: bool,
+    /// The Kubernetes API port accepted a connection; `None` when not checked.
+    pub api: Option<bool>,
+}
+
+/// Dashboard subset picked with `1`-`4`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    /// Last run failed, was rejected, had no credential or couldn't reach the API.
+    Failing,
+    /// Cached cert expired or within `cert_warning_days`.
+    Expiring,
+    /// No fetch on record.
+    NeverFetched,
+}
+
+impl std::fmt::Display for StatusFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StatusFilter::All => "all",
+            StatusFilter::Failing => "failing",
+            StatusFilter::Expiring => "expiring",
+            StatusFilter::NeverFetched => "never fetched",
+        })
+    }
+}
+
+/// Server counts for the dashboard title bar; each server lands in exactly one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FleetSummary {
+    pub ok: usize,
+    /// Last run fine, but the cert is expired or within `cert_warning_days`.
+    pub expiring: usize,
+    /// Failed, auth rejected or unreachable.
+    pub failed: usize,
+    pub no_credential: usize,
+    pub never_fetched: usize,
+}
+
 // ─── View State Machine ───────────────────────────────────────────────────────
 
 #[allow(clippy::large_enum_variant)]
@@ -56,21 +191,204 @@ pub enum View {
     CredentialMenu(String),  // server name
     CredentialInput(String), // server name
     DeleteConfirm(String),   // server name
-    Help,
+    /// Password prompt applied to every marked server (uses `credential_input`).
+    MarkedCredentialInput(Vec<String>),
+    /// Confirms deleting every marked server.
+    MarkedDeleteConfirm(Vec<String>),
+    Help(HelpState),
     Error {
         message: String,
     },
     /// Shown when the system keyring is unavailable and the user must explicitly
     /// accept or decline file-based credential storage before anything is written.
     KeyringFallbackConsent {
-        server_name: String,
+        server_names: Vec<String>,
         password: String,
         keyring_error: String,
     },
+    /// Masked prompt for the file store's master passphrase (uses `credential_input`),
+    /// shown after consent when the store hasn't been unlocked this session.
+    MasterPassphraseInput {
+        server_names: Vec<String>,
+        password: String,
+    },
     BitwardenUnlock {
         error: Option<String>,
     },
     EditServer(EditServerState),
+    /// Trust-on-first-use prompt for a host key not yet in known_hosts.
+    /// `return_to` is the view that was active when the prompt opened.
+    HostKeyTrust {
+        server_name: String,
+        key: crate::ssh::UnknownHostKey,
+        return_to: Box<View>,
+    },
+    /// Masked prompt for an encrypted identity file's passphrase (uses `credential_input`).
+    KeyPassphraseInput {
+        server_name: String,
+        identity_file: String,
+        return_to: Box<View>,
+    },
+    /// Masked prompt for a new password after sudo rejected the stored one (uses
+    /// `credential_input`).
+    SudoPasswordInput {
+        server_name: String,
+        return_to: Box<View>,
+    },
+    /// Masked prompt for a one-time code, used for the retry (uses `credential_input`).
+    OtpInput {
+        server_name: String,
+        return_to: Box<View>,
+    },
+    /// Asks how to merge a fetched kubeconfig whose names are taken in ~/.kube/config.
+    MergeConflict {
+        server_name: String,
+        names: Vec<String>,
+        return_to: Box<View>,
+    },
+    /// Scrollable view of a server's cached kubeconfig, already redacted.
+    KubeconfigPreview {
+        server_name: String,
+        lines: Vec<String>,
+        scroll: usize,
+    },
+    /// Confirms running the server's `renew_command` before a re-fetch.
+    RenewConfirm {
+        server_name: String,
+        return_to: Box<View>,
+    },
+    /// Overlay with the unified diffs of dry-run fetches; later ones are appended.
+    DryRunDiff {
+        lines: Vec<String>,
+        scroll: usize,
+        return_to: Box<View>,
+    },
+    /// Picks clusters from ~/.kube/config (`I`) or a discovery scan (`A`) to add as servers.
+    Import(ImportState),
+    /// Asks for a new name for a server (`r`).
+    RenameInput {
+        from: String,
+        new_name: String,
+        error: Option<String>,
+    },
+    /// Asks whether to fetch a server the wizard just added.
+    FetchNowConfirm(String), // server name
+    /// Confirms removing what deleted servers left behind (`P`).
+    PruneConfirm(Vec<crate::prune::Orphan>),
+    /// Asks for the network range to scan for nodes (`A`).
+    DiscoverInput {
+        cidr: String,
+        error: Option<String>,
+    },
+    /// Picks a saved version of a server's kubeconfig to roll back to (`h`).
+    History {
+        server_name: String,
+        versions: Vec<crate::history::Version>,
+        cursor: usize,
+    },
+    /// This session's notifications, newest first (`M`).
+    Notifications {
+        scroll: usize,
+        return_to: Box<View>,
+    },
+    /// Scrollable credential audit log, newest first (`L`).
+    AuditLog {
+        events: Vec<crate::audit::Event>,
+        scroll: usize,
+    },
+    /// Editor for the global defaults (`S`).
+    Settings(SettingsState),
+    /// Asks what to do with running fetches before quitting.
+    QuitConfirm {
+        /// Quit once the last of them finishes or is cancelled.
+        waiting: bool,
+        return_to: Box<View>,
+    },
+    /// Confirms force-fetching every listed server (`F`).
+    FetchAllConfirm(Vec<String>), // server names
+    /// What a fetch-all run did, shown once its last server finishes.
+    FetchAllSummary {
+        run: FetchAllRun,
+        scroll: usize,
+        return_to: Box<View>,
+    },
+}
+
+// ─── Help ─────────────────────────────────────────────────────────────────────
+
+/// Scroll position and filter of the help overlay (`?`).
+#[derive(Clone, Debug, Default)]
+pub struct HelpState {
+    /// First line shown.
+    pub scroll: usize,
+    /// Lines that fit at the last render; PgUp/PgDn move by this much.
+    pub page: usize,
+    /// Only rows whose keys or description contain this, ignoring case.
+    pub filter: String,
+    /// True while the filter is being typed.
+    pub filtering: bool,
+}
+
+// ─── Notification History ─────────────────────────────────────────────────────
+
+/// Notifications kept for the `M` view; older ones are dropped first.
+pub const NOTIFICATION_HISTORY: usize = 500;
+
+#[derive(Clone, Debug)]
+pub struct LoggedNotification {
+    pub at: chrono::DateTime<chrono::Local>,
+    pub message: String,
+    /// When it was raised, which tells it apart from the next one with the same text.
+    pub shown: std::time::Instant,
+}
+
+// ─── Fetch Results ────────────────────────────────────────────────────────────
+
+/// How long a finished fetch's ✓/✗ and duration stay beside its status.
+pub const RESULT_SHOWN: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How a server's last fetch in this session ended.
+#[derive(Clone, Copy, Debug)]
+pub struct FetchResult {
+    /// Fetched, and the API server (if checked) was reachable.
+    pub ok: bool,
+    pub duration: std::time::Duration,
+    pub finished: std::time::Instant,
+}
+
+impl FetchResult {
+    pub fn is_recent(&self) -> bool {
+        self.finished.elapsed() < RESULT_SHOWN
+    }
+}
+
+// ─── Fetch All ────────────────────────────────────────────────────────────────
+
+/// A confirmed `F`, followed until every server it started has finished.
+#[derive(Clone, Debug, Default)]
+pub struct FetchAllRun {
+    /// Started and not finished yet.
+    pub pending: HashSet<String>,
+    pub fetched: Vec<String>,
+    /// Server name and error message.
+    pub failed: Vec<(String, String)>,
+    /// Already being fetched when the run started, or cancelled.
+    pub skipped: Vec<String>,
+}
+
+impl FetchAllRun {
+    /// Files a finished fetch of `server_name` (`None` when it was cancelled). Returns
+    /// true once nothing is pending; servers outside the run are ignored.
+    pub fn record(&mut self, server_name: &str, result: Option<Result<(), String>>) -> bool {
+        if self.pending.remove(server_name) {
+            match result {
+                Some(Ok(())) => self.fetched.push(server_name.to_string()),
+                Some(Err(msg)) => self.failed.push((server_name.to_string(), msg)),
+                None => self.skipped.push(server_name.to_string()),
+            }
+        }
+        self.pending.is_empty()
+    }
 }
 
 // ─── Edit Server ──────────────────────────────────────────────────────────────
@@ -80,6 +398,8 @@ pub enum View {
 pub struct EditServerState {
     /// Name of the server being edited (not editable — used as the key).
     pub server_name: String,
+    /// The server as loaded, so fields without an editor row survive the save.
+    pub original: crate::config::Server,
     /// Index of the currently focused field (0-6).
     pub field_idx: usize,
     /// Editable field values: [address, target_cluster_ip, user, file_path, file_name, context_name, identity_file]
@@ -101,6 +421,7 @@ impl EditServerState {
     pub fn from_server(server: &crate::config::Server) -> Self {
         EditServerState {
             server_name: server.name.clone(),
+            original: server.clone(),
             field_idx: 0,
             fields: [
                 server.address.clone(),
@@ -126,10 +447,126 @@ impl EditServerState {
             file_name: opt(&self.fields[4]),
             context_name: opt(&self.fields[5]),
             identity_file: opt(&self.fields[6]),
+            ..self.original.clone()
         }
     }
 }
 
+// ─── Settings ─────────────────────────────────────────────────────────────────
+
+/// In-TUI editor for the global settings, pre-populated from the current config. A
+/// blank field removes the key so its default applies.
+#[derive(Clone, Debug)]
+pub struct SettingsState {
+    /// Index of the currently focused field.
+    pub field_idx: usize,
+    /// Field values in `LABELS` order.
+    pub fields: [String; 9],
+    pub error: Option<String>,
+}
+
+impl SettingsState {
+    pub const LABELS: [&'static str; 9] = [
+        "Output directory",
+        "Default SSH user",
+        "Default remote path",
+        "Default remote file",
+        "Default identity file",
+        "Parallel fetches",
+        "Warn days",
+        "Critical days",
+        "Fetch after add",
+    ];
+
+    /// What each field is, shown while it has the focus.
+    pub const HINTS: [&'static str; 9] = [
+        "local_output_dir: where fetched kubeconfigs are written (required)",
+        "default_user: SSH user for servers without their own",
+        "default_file_path: remote directory (blank: /etc/rancher/k3s)",
+        "default_file_name: remote file name (blank: k3s.yaml)",
+        "default_identity_file: private key for servers without their own",
+        "max_parallel: servers fetched at once (blank: 32)",
+        "cert_warning_days: days left when expiry turns yellow (blank: 30)",
+        "cert_critical_days: days left when expiry turns red (blank: 7)",
+        "fetch_after_add: yes, no, or blank to ask after the wizard saves",
+    ];
+
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: Option<u64>| value.map(|n| n.to_string()).unwrap_or_default();
+        SettingsState {
+            field_idx: 0,
+            fields: [
+                config.local_output_dir.clone(),
+                text(&config.default_user),
+                text(&config.default_file_path),
+                text(&config.default_file_name),
+                text(&config.default_identity_file),
+                number(config.max_parallel.map(|n| n as u64)),
+                number(config.cert_warning_days.map(u64::from)),
+                number(config.cert_critical_days.map(u64::from)),
+                match config.fetch_after_add {
+                    Some(true) => "yes".to_string(),
+                    Some(false) => "no".to_string(),
+                    None => String::new(),
+                },
+            ],
+            error: None,
+        }
+    }
+
+    /// `config` with the edited values, or the index of the first invalid field and why.
+    pub fn apply(&self, config: &crate::config::Config) -> Result<crate::config::Config, (usize, String)> {
+        let text = |i: usize| Some(self.fields[i].trim().to_string()).filter(|v| !v.is_empty());
+        let number = |i: usize| -> Result<Option<u32>, (usize, String)> {
+            match text(i) {
+                None => Ok(None),
+                Some(v) => v
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| (i, format!("{} must be a whole number", Self::LABELS[i]))),
+            }
+        };
+        let local_output_dir = text(0).ok_or((0, "Output directory is required".to_string()))?;
+        let max_parallel = number(5)?;
+        if max_parallel == Some(0) {
+            return Err((5, "Parallel fetches must be at least 1".to_string()));
+        }
+        let fetch_after_add = match text(8).map(|v| v.to_lowercase()).as_deref() {
+            None => None,
+            Some("yes" | "y" | "true") => Some(true),
+            Some("no" | "n" | "false") => Some(false),
+            Some(_) => return Err((8, "Fetch after add is yes, no or blank".to_string())),
+        };
+        Ok(crate::config::Config {
+            local_output_dir,
+            default_user: text(1),
+            default_file_path: text(2),
+            default_file_name: text(3),
+            default_identity_file: text(4),
+            max_parallel: max_parallel.map(|n| n as usize),
+            cert_warning_days: number(6)?,
+            cert_critical_days: number(7)?,
+            fetch_after_add,
+            ..config.clone()
+        })
+    }
+}
+
+// ─── Import ───────────────────────────────────────────────────────────────────
+
+/// Clusters found in ~/.kube/config, or nodes found by a scan, that aren't configured yet.
+#[derive(Clone, Debug)]
+pub struct ImportState {
+    /// Where the candidates came from: `~/.kube/config` or the scanned range.
+    pub source: String,
+    pub candidates: Vec<crate::import::Candidate>,
+    pub skipped: Vec<crate::import::Skipped>,
+    /// Parallel to `candidates`: whether each one is marked for import.
+    pub chosen: Vec<bool>,
+    pub cursor: usize,
+}
+
 // ─── Setup Wizard ─────────────────────────────────────────────────────────────
 
 #[derive(Clone, Default, Debug)]
@@ -139,7 +576,11 @@ pub struct SetupWizardState {
     pub default_user: String,
     pub default_file_path: String,
     pub default_file_name: String,
-    // Bitwarden steps (shown after DefaultFileName)
+    /// Written as `credential_backend`.
+    pub credential_backend: crate::credentials::Backend,
+    /// Why the OS keyring can't be used, found when setup started; None when it answered.
+    pub keyring_error: Option<String>,
+    // Bitwarden steps (shown after CredentialBackend)
     pub bitwarden_enabled: bool,
     pub bitwarden_server_url: String,
     pub bitwarden_item_prefix: String,
@@ -153,6 +594,8 @@ pub enum SetupStep {
     DefaultUser,
     DefaultFilePath,
     DefaultFileName,
+    /// Where credentials are kept: picked from the backends this machine has.
+    CredentialBackend,
     /// "Enable Bitwarden vault integration? [y/N]"
     BitwardenEnabled,
     /// Vault server URL (blank = bitwarden.com / self-hosted otherwise)
@@ -168,9 +611,10 @@ impl SetupStep {
             SetupStep::DefaultUser => 1,
             SetupStep::DefaultFilePath => 2,
             SetupStep::DefaultFileName => 3,
-            SetupStep::BitwardenEnabled => 4,
-            SetupStep::BitwardenServerUrl => 5,
-            SetupStep::BitwardenItemPrefix => 6,
+            SetupStep::CredentialBackend => 4,
+            SetupStep::BitwardenEnabled => 5,
+            SetupStep::BitwardenServerUrl => 6,
+            SetupStep::BitwardenItemPrefix => 7,
         }
     }
 
@@ -180,6 +624,7 @@ impl SetupStep {
             SetupStep::DefaultUser => "Default SSH user",
             SetupStep::DefaultFilePath => "Default remote file path",
             SetupStep::DefaultFileName => "Default remote file name",
+            SetupStep::CredentialBackend => "Credential storage",
             SetupStep::BitwardenEnabled => "Bitwarden vault integration",
             SetupStep::BitwardenServerUrl => "Vault server URL",
             SetupStep::BitwardenItemPrefix => "Vault item prefix",
@@ -193,7 +638,8 @@ impl SetupStep {
             SetupStep::OutputDir => Some(SetupStep::DefaultUser),
             SetupStep::DefaultUser => Some(SetupStep::DefaultFilePath),
             SetupStep::DefaultFilePath => Some(SetupStep::DefaultFileName),
-            SetupStep::DefaultFileName => Some(SetupStep::BitwardenEnabled),
+            SetupStep::DefaultFileName => Some(SetupStep::CredentialBackend),
+            SetupStep::CredentialBackend => Some(SetupStep::BitwardenEnabled),
             SetupStep::BitwardenEnabled => Some(SetupStep::BitwardenServerUrl),
             SetupStep::BitwardenServerUrl => Some(SetupStep::BitwardenItemPrefix),
             SetupStep::BitwardenItemPrefix => None,
@@ -206,7 +652,8 @@ impl SetupStep {
             SetupStep::DefaultUser => Some(SetupStep::OutputDir),
             SetupStep::DefaultFilePath => Some(SetupStep::DefaultUser),
             SetupStep::DefaultFileName => Some(SetupStep::DefaultFilePath),
-            SetupStep::BitwardenEnabled => Some(SetupStep::DefaultFileName),
+            SetupStep::CredentialBackend => Some(SetupStep::DefaultFileName),
+            SetupStep::BitwardenEnabled => Some(SetupStep::CredentialBackend),
             SetupStep::BitwardenServerUrl => Some(SetupStep::BitwardenEnabled),
             SetupStep::BitwardenItemPrefix => Some(SetupStep::BitwardenServerUrl),
         }
@@ -222,7 +669,7 @@ pub struct WizardState {
     pub address: String,
     pub user: String,
     pub file_path: String,
-    pub file_name: String,
+    pub local_file_name: String,
     pub target_cluster_ip: String,
     pub context_name: String,
     pub auth_method: AuthMethod,
@@ -230,35 +677,202 @@ pub struct WizardState {
     pub help_open: bool,
     pub password_input: MaskedInput,
     pub identity_file_input: String,
+    /// Char index of the cursor in the field being typed into (`field_mut`).
+    pub cursor: usize,
+    /// The step was opened from the review step: Enter and Esc go back there.
+    pub reviewing: bool,
+    /// SSH port, when not 22; set by picking a host from `~/.ssh/config`.
+    pub ssh_port: Option<u16>,
+    /// The `~/.ssh/config` host list, while open on the address step (Tab).
+    pub host_picker: Option<HostPicker>,
+    /// The identity file needs a passphrase, as found when the field was last checked.
+    pub key_encrypted: bool,
     pub testing: bool,
     pub test_passed: bool,
     pub error: Option<String>,
+    /// The server being edited (`E`), or None when adding. Its name can't change, and
+    /// fields the wizard doesn't ask about are kept as they are.
+    pub editing: Option<crate::config::Server>,
+    /// The prefilled server when adding a cluster from ~/.kube/config; fields the wizard
+    /// doesn't ask about (such as `target_cluster_port`) come from it.
+    pub imported: Option<crate::config::Server>,
+    /// Clusters still to be imported after this one; each opens the wizard in turn.
+    pub import_queue: Vec<crate::config::Server>,
 }
 
-#[derive(Clone, PartialEq, Default)]
+impl WizardState {
+    /// Wizard pre-filled from an existing server, starting after the (fixed) name step.
+    pub fn for_server(server: &crate::config::Server) -> Self {
+        WizardState {
+            step: WizardStep::Address,
+            name: server.name.clone(),
+            address: server.address.clone(),
+            user: server.user.clone().unwrap_or_default(),
+            file_path: server.file_path.clone().unwrap_or_default(),
+            local_file_name: server.local_file_name.clone().unwrap_or_default(),
+            target_cluster_ip: server.target_cluster_ip.clone(),
+            context_name: server.context_name.clone().unwrap_or_default(),
+            auth_method: if server.identity_file.is_some() {
+                AuthMethod::IdentityFile
+            } else {
+                AuthMethod::Password
+            },
+            identity_file_input: server.identity_file.clone().unwrap_or_default(),
+            ssh_port: server.ssh_port,
+            editing: Some(server.clone()),
+            ..Default::default()
+        }
+        .at_step(WizardStep::Address)
+    }
+
+    /// Wizard for a cluster found in ~/.kube/config, starting at the SSH address with
+    /// the name, cluster IP and context already filled in, and whatever SSH settings
+    /// came from ~/.ssh/config.
+    pub fn for_import(server: &crate::config::Server, import_queue: Vec<crate::config::Server>) -> Self {
+        WizardState {
+            step: WizardStep::Address,
+            name: server.name.clone(),
+            address: server.address.clone(),
+            ssh_port: server.ssh_port,
+            user: server.user.clone().unwrap_or_default(),
+            auth_method: if server.identity_file.is_some() {
+                AuthMethod::IdentityFile
+            } else {
+                AuthMethod::Password
+            },
+            identity_file_input: server.identity_file.clone().unwrap_or_default(),
+            target_cluster_ip: server.target_cluster_ip.clone(),
+            context_name: server.context_name.clone().unwrap_or_default(),
+            imported: Some(server.clone()),
+            import_queue,
+            ..Default::default()
+        }
+        .at_step(WizardStep::Address)
+    }
+
+    fn at_step(mut self, step: WizardStep) -> Self {
+        self.go_to(step);
+        self
+    }
+
+    /// Moves to `step` with the cursor at the end of its field.
+    pub fn go_to(&mut self, step: WizardStep) {
+        if step == WizardStep::Review {
+            self.reviewing = false;
+        }
+        self.step = step;
+        self.cursor = self.field_mut().map_or(0, |field| field.chars().count());
+    }
+
+    /// The text field keys go to: the current step's, or the identity file path on the
+    /// auth step. `None` for the password, which is a `MaskedInput` with its own cursor.
+    pub fn field_mut(&mut self) -> Option<&mut String> {
+        Some(match self.step {
+            WizardStep::Name => &mut self.name,
+            WizardStep::Address => &mut self.address,
+            WizardStep::User => &mut self.user,
+            WizardStep::FilePath => &mut self.file_path,
+            WizardStep::LocalFileName => &mut self.local_file_name,
+            WizardStep::TargetClusterIp => &mut self.target_cluster_ip,
+            WizardStep::ContextName => &mut self.context_name,
+            WizardStep::Auth if self.auth_method == AuthMethod::IdentityFile => &mut self.identity_file_input,
+            WizardStep::Auth | WizardStep::Review => return None,
+        })
+    }
+
+    /// Applies an editing key to the current field; false when the key isn't one.
+    pub fn edit(&mut self, key: &KeyEvent) -> bool {
+        let mut cursor = self.cursor;
+        let Some(field) = self.field_mut() else {
+            return false;
+        };
+        let handled = edit_line(field, &mut cursor, key, usize::MAX);
+        self.cursor = cursor;
+        handled
+    }
+
+    /// Inserts pasted text into the current field at the cursor.
+    pub fn paste(&mut self, text: &str) {
+        let mut cursor = self.cursor;
+        if let Some(field) = self.field_mut() {
+            insert_at_cursor(field, &mut cursor, text, usize::MAX);
+        }
+        self.cursor = cursor;
+    }
+
+    /// The step Esc goes back to; None cancels the wizard.
+    pub fn prev_step(&self) -> Option<WizardStep> {
+        if self.reviewing {
+            return Some(WizardStep::Review);
+        }
+        self.step
+            .prev()
+            .filter(|prev| !(self.editing.is_some() && *prev == WizardStep::Name))
+    }
+
+    /// The step Enter moves on to once the current one is valid.
+    pub fn next_step(&self) -> Option<WizardStep> {
+        if self.reviewing {
+            return Some(WizardStep::Review);
+        }
+        self.step.next()
+    }
+
+    /// Opens `step` from the review step to change it. The name can't change when editing.
+    pub fn revisit(&mut self, step: WizardStep) {
+        if step == WizardStep::Review || (step == WizardStep::Name && self.editing.is_some()) {
+            return;
+        }
+        self.go_to(step);
+        self.reviewing = true;
+    }
+}
+
+#[derive(Clone)]
+pub struct HostPicker {
+    pub hosts: Vec<crate::ssh_config::HostEntry>,
+    pub selected: usize,
+}
+
+#[derive(Clone, PartialEq, Default, Debug)]
 pub enum WizardStep {
     #[default]
     Name,
     Address,
     User,
     FilePath,
-    FileName,
+    LocalFileName,
     TargetClusterIp,
     ContextName,
     Auth,
+    /// Every value at a glance before saving, with a key to go back to each step.
+    Review,
 }
 
 impl WizardStep {
+    pub const ALL: [WizardStep; 9] = [
+        WizardStep::Name,
+        WizardStep::Address,
+        WizardStep::User,
+        WizardStep::FilePath,
+        WizardStep::LocalFileName,
+        WizardStep::TargetClusterIp,
+        WizardStep::ContextName,
+        WizardStep::Auth,
+        WizardStep::Review,
+    ];
+
     pub fn index(&self) -> usize {
         match self {
             WizardStep::Name => 0,
             WizardStep::Address => 1,
             WizardStep::User => 2,
             WizardStep::FilePath => 3,
-            WizardStep::FileName => 4,
+            WizardStep::LocalFileName => 4,
             WizardStep::TargetClusterIp => 5,
             WizardStep::ContextName => 6,
             WizardStep::Auth => 7,
+            WizardStep::Review => 8,
         }
     }
 
@@ -268,10 +882,11 @@ impl WizardStep {
             WizardStep::Address => "Address",
             WizardStep::User => "SSH User",
             WizardStep::FilePath => "File Path",
-            WizardStep::FileName => "File Name",
+            WizardStep::LocalFileName => "Local File",
             WizardStep::TargetClusterIp => "Target Cluster IP",
             WizardStep::ContextName => "Context Name",
             WizardStep::Auth => "Authentication",
+            WizardStep::Review => "Review",
         }
     }
 
@@ -280,11 +895,12 @@ impl WizardStep {
             WizardStep::Name => Some(WizardStep::Address),
             WizardStep::Address => Some(WizardStep::User),
             WizardStep::User => Some(WizardStep::FilePath),
-            WizardStep::FilePath => Some(WizardStep::FileName),
-            WizardStep::FileName => Some(WizardStep::TargetClusterIp),
+            WizardStep::FilePath => Some(WizardStep::LocalFileName),
+            WizardStep::LocalFileName => Some(WizardStep::TargetClusterIp),
             WizardStep::TargetClusterIp => Some(WizardStep::ContextName),
             WizardStep::ContextName => Some(WizardStep::Auth),
-            WizardStep::Auth => None,
+            WizardStep::Auth => Some(WizardStep::Review),
+            WizardStep::Review => None,
         }
     }
 
@@ -294,10 +910,11 @@ impl WizardStep {
             WizardStep::Address => Some(WizardStep::Name),
             WizardStep::User => Some(WizardStep::Address),
             WizardStep::FilePath => Some(WizardStep::User),
-            WizardStep::FileName => Some(WizardStep::FilePath),
-            WizardStep::TargetClusterIp => Some(WizardStep::FileName),
+            WizardStep::LocalFileName => Some(WizardStep::FilePath),
+            WizardStep::TargetClusterIp => Some(WizardStep::LocalFileName),
             WizardStep::ContextName => Some(WizardStep::TargetClusterIp),
             WizardStep::Auth => Some(WizardStep::ContextName),
+            WizardStep::Review => Some(WizardStep::Auth),
         }
     }
 }
@@ -314,26 +931,114 @@ pub enum AuthMethod {
 #[derive(Clone, Default)]
 pub struct MaskedInput {
     pub value: String,
+    /// Char index of the cursor; typing and Backspace happen here.
+    pub cursor: usize,
 }
 
+/// Longest value a `MaskedInput` accepts, in bytes.
+const MASKED_INPUT_MAX: usize = 256;
+
 impl MaskedInput {
     pub fn new() -> Self {
-        MaskedInput { value: String::new() }
+        MaskedInput::default()
     }
     pub fn push(&mut self, c: char) {
-        if self.value.len() < 256 {
-            self.value.push(c);
-        }
+        let mut buf = [0; 4];
+        insert_at_cursor(
+            &mut self.value,
+            &mut self.cursor,
+            c.encode_utf8(&mut buf),
+            MASKED_INPUT_MAX,
+        );
     }
     pub fn pop(&mut self) {
-        self.value.pop();
+        edit_line(
+            &mut self.value,
+            &mut self.cursor,
+            &KeyEvent::from(KeyCode::Backspace),
+            MASKED_INPUT_MAX,
+        );
+    }
+    /// Applies an editing key (cursor movement included); false when the key isn't one.
+    pub fn edit(&mut self, key: &KeyEvent) -> bool {
+        edit_line(&mut self.value, &mut self.cursor, key, MASKED_INPUT_MAX)
+    }
+    pub fn paste(&mut self, text: &str) {
+        insert_at_cursor(&mut self.value, &mut self.cursor, text, MASKED_INPUT_MAX);
     }
     pub fn clear(&mut self) {
-        self.value.clear();
+        zeroize::Zeroize::zeroize(&mut self.value);
+        self.cursor = 0;
     }
     pub fn masked_display(&self) -> String {
-        "*".repeat(self.value.len())
+        "*".repeat(self.value.chars().count())
+    }
+}
+
+impl Drop for MaskedInput {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.value);
+    }
+}
+
+// ─── Line editing ─────────────────────────────────────────────────────────────
+
+fn byte_index(value: &str, cursor: usize) -> usize {
+    value.char_indices().nth(cursor).map_or(value.len(), |(i, _)| i)
+}
+
+/// Applies a line-editing key to `value` with the cursor at char index `cursor`:
+/// Left/Right/Home/End (and Ctrl+A/Ctrl+E) move it, Backspace and Delete remove the
+/// char before or under it, and typed chars are inserted at it as long as `value`
+/// stays within `max_len` bytes. Returns false for keys that don't edit.
+pub fn edit_line(value: &mut String, cursor: &mut usize, key: &KeyEvent, max_len: usize) -> bool {
+    let len = value.chars().count();
+    *cursor = (*cursor).min(len);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+        KeyCode::Right => *cursor = (*cursor + 1).min(len),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = len,
+        KeyCode::Char('a') if ctrl => *cursor = 0,
+        KeyCode::Char('e') if ctrl => *cursor = len,
+        KeyCode::Backspace => {
+            if *cursor > 0 {
+                *cursor -= 1;
+                value.remove(byte_index(value, *cursor));
+            }
+        }
+        KeyCode::Delete => {
+            if *cursor < len {
+                value.remove(byte_index(value, *cursor));
+            }
+        }
+        KeyCode::Char(c) if !ctrl => {
+            let mut buf = [0; 4];
+            insert_at_cursor(value, cursor, c.encode_utf8(&mut buf), max_len);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Inserts `text` at the cursor and moves the cursor past it. Line breaks are dropped
+/// (a copied path often ends with one), and nothing is inserted if `value` would grow
+/// past `max_len` bytes.
+pub fn insert_at_cursor(value: &mut String, cursor: &mut usize, text: &str, max_len: usize) {
+    let text: String = text.chars().filter(|c| !matches!(c, '\n' | '\r')).collect();
+    if value.len() + text.len() > max_len {
+        return;
     }
+    *cursor = (*cursor).min(value.chars().count());
+    value.insert_str(byte_index(value, *cursor), &text);
+    *cursor += text.chars().count();
+}
+
+/// `text` with a `│` cursor drawn before char index `cursor`.
+pub fn with_cursor(text: &str, cursor: usize) -> String {
+    let at = byte_index(text, cursor);
+    format!("{}│{}", &text[..at], &text[at..])
 }
 
 // ─── Spinner ──────────────────────────────────────────────────────────────────
@@ -364,26 +1069,73 @@ pub struct AppState {
     pub config_path: PathBuf,
     pub server_states: HashMap<String, ServerRunState>,
     pub cert_cache: HashMap<String, Option<chrono::DateTime<chrono::Utc>>>,
+    /// Cluster CA expiry per server, read alongside `cert_cache`.
+    pub ca_cache: HashMap<String, Option<chrono::DateTime<chrono::Utc>>>,
     pub cred_cache: HashMap<String, bool>,
     pub in_progress: HashSet<String>,
+    /// Bytes received so far for each in-flight fetch.
+    pub fetch_progress: HashMap<String, u64>,
+    /// Servers waiting for a free fetch slot; they are also listed in `in_progress`.
+    pub fetch_queue: VecDeque<crate::config::Server>,
+    /// Number of fetch threads currently running (bounded by `Config::max_parallel`).
+    pub running_fetches: usize,
+    /// Servers whose renewal command is running; they are also listed in `in_progress`.
+    pub renewing: HashSet<String>,
+    /// Cancel handles for running fetches, removed when the fetch completes.
+    pub fetch_cancels: HashMap<String, std::sync::Arc<crate::ssh::CancelToken>>,
+    /// The fetch-all run in progress, if any.
+    pub fetch_all: Option<FetchAllRun>,
     pub view: View,
     pub prior_view: Option<Box<View>>, // saved when entering Help
     pub dry_run: bool,
     pub table_state: ratatui::widgets::TableState,
+    /// When set, the dashboard only lists servers carrying this tag.
+    pub tag_filter: Option<String>,
+    pub status_filter: StatusFilter,
+    /// Dashboard search query (`/`); only servers whose name, address or context
+    /// contain it are listed.
+    pub search: Option<String>,
+    /// True while the search query is being typed.
+    pub search_editing: bool,
+    /// Servers marked with Space; `f`, `D` and `c` act on all of them when non-empty.
+    pub marked: HashSet<String>,
     pub spinner: SpinnerState,
-    pub flash_rows: HashMap<String, u8>, // server_name → frames remaining
+    /// Recently finished fetches, dropped once `RESULT_SHOWN` has passed.
+    pub last_results: HashMap<String, FetchResult>,
     pub notification: Option<(String, std::time::Instant)>,
+    /// Every notification shown this session, oldest first, up to `NOTIFICATION_HISTORY`.
+    pub notification_log: VecDeque<LoggedNotification>,
     pub credential_input: MaskedInput,
+    /// False with `NO_COLOR`, `--color never` or the `monochrome` theme.
     pub use_color: bool,
+    pub theme: crate::tui::theme::Theme,
+    /// Dashboard and detail view keys, with `[keys]` applied.
+    pub keys: crate::tui::keys::Keymap,
+    /// Dates or time left in the CERT EXPIRES column and the detail view; `T` switches.
+    pub cert_display: crate::config::CertDisplay,
+    /// Terminal color depth; `use_color` is false when this is `ColorDepth::None`.
+    pub color_depth: crate::color::ColorDepth,
     pub last_state_mtime: Option<std::time::SystemTime>,
     /// Cert expiry captured just before a fetch starts (for delta notification).
     pub pre_fetch_expiry: HashMap<String, Option<chrono::DateTime<chrono::Utc>>>,
     /// Current server cert probe result shown in the detail view.
     pub probe: Option<(String, ProbeState)>,
+    /// Current API server cert probe result shown in the detail view.
+    pub api_probe: Option<(String, ApiProbeState)>,
+    /// Expanded certificate section of the detail view (`i`): the server and its client
+    /// cert's details, `None` inside when the cached kubeconfig has no readable cert.
+    pub cert_details: Option<(String, Option<crate::tls::CertDetails>)>,
+    /// Latest reachability result per server, when `reachability_check` is on.
+    pub reachability: HashMap<String, Reachability>,
+    /// Latest probe of each server's remote client cert, from `p` on the dashboard or
+    /// the detail view; drives the REMOTE column.
+    pub remote_certs: HashMap<String, ProbeState>,
     /// Tracks whether each server came from config.toml or Bitwarden vault.
     pub server_sources: HashMap<String, ServerSource>,
     /// Passwords sourced from Bitwarden vault, keyed by server name.
-    pub vault_passwords: HashMap<String, String>,
+    pub vault_passwords: HashMap<String, zeroize::Zeroizing<String>>,
+    /// Passphrases entered this session, keyed by identity file path.
+    pub key_passphrases: HashMap<String, String>,
     /// Bitwarden session key (held in memory only).
     pub bw_session: Option<String>,
 }
@@ -395,43 +1147,263 @@ impl AppState {
         server_states: HashMap<String, ServerRunState>,
         dry_run: bool,
     ) -> Self {
-        let use_color = std::env::var("NO_COLOR").is_err();
+        let color_depth = crate::color::depth();
+        let theme = crate::tui::theme::Theme::from_config(config.theme.as_ref(), color_depth);
+        let use_color = color_depth.enabled() && theme.color;
+        let cert_display = config.cert_display.unwrap_or_default();
         AppState {
             config,
             config_path,
             server_states,
             cert_cache: HashMap::new(),
+            ca_cache: HashMap::new(),
             cred_cache: HashMap::new(),
             in_progress: HashSet::new(),
+            fetch_progress: HashMap::new(),
+            fetch_queue: VecDeque::new(),
+            running_fetches: 0,
+            renewing: HashSet::new(),
+            fetch_cancels: HashMap::new(),
+            fetch_all: None,
             view: View::Dashboard,
             prior_view: None,
             dry_run,
             table_state: ratatui::widgets::TableState::default(),
+            tag_filter: None,
+            status_filter: StatusFilter::All,
+            search: None,
+            search_editing: false,
+            marked: HashSet::new(),
             spinner: SpinnerState::new(),
-            flash_rows: HashMap::new(),
+            last_results: HashMap::new(),
             notification: None,
+            notification_log: VecDeque::new(),
             credential_input: MaskedInput::new(),
             use_color,
+            theme,
+            keys: crate::tui::keys::Keymap::default(),
+            cert_display,
+            color_depth,
             last_state_mtime: None,
             pre_fetch_expiry: HashMap::new(),
             probe: None,
+            api_probe: None,
+            cert_details: None,
+            reachability: HashMap::new(),
+            remote_certs: HashMap::new(),
             server_sources: HashMap::new(),
             vault_passwords: HashMap::new(),
+            key_passphrases: HashMap::new(),
             bw_session: None,
         }
     }
 
+    /// True while anything on screen changes without user input (spinners, fetch results,
+    /// a notification waiting to expire, a running probe). Drives the tick rate.
+    pub fn is_animating(&self) -> bool {
+        let probe_active = self
+            .probe
+            .as_ref()
+            .map(|(_, s)| matches!(s, ProbeState::Probing))
+            .unwrap_or(false)
+            || matches!(self.api_probe, Some((_, ApiProbeState::Probing)))
+            || self.remote_certs.values().any(|s| matches!(s, ProbeState::Probing));
+        !self.in_progress.is_empty() || !self.last_results.is_empty() || self.notification.is_some() || probe_active
+    }
+
+    /// Copies a notification set since the last call into `notification_log`. Called
+    /// once per event, so notifications raised from any view are kept.
+    pub fn log_notification(&mut self) {
+        let Some((message, shown)) = &self.notification else {
+            return;
+        };
+        if self
+            .notification_log
+            .back()
+            .is_some_and(|last| last.shown == *shown && last.message == *message)
+        {
+            return;
+        }
+        if self.notification_log.len() == NOTIFICATION_HISTORY {
+            self.notification_log.pop_front();
+        }
+        self.notification_log.push_back(LoggedNotification {
+            at: chrono::Local::now(),
+            message: message.clone(),
+            shown: *shown,
+        });
+    }
+
+    /// Servers with a fetch or renewal running or queued, sorted. Wizard tests, vault
+    /// unlocks and discovery scans write nothing and aren't included.
+    pub fn busy_servers(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .in_progress
+            .iter()
+            .map(String::as_str)
+            .filter(|name| ![WIZARD_SENTINEL, BITWARDEN_SENTINEL, DISCOVER_SENTINEL].contains(name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Servers listed on the dashboard, in config order; table rows index into this.
+    pub fn visible_servers(&self) -> Vec<&crate::config::Server> {
+        self.config
+            .servers
+            .iter()
+            .filter(|s| self.tag_filter.as_ref().is_none_or(|tag| s.tags.contains(tag)))
+            .filter(|s| self.matches_status_filter(&s.name))
+            .filter(|s| self.search.as_deref().is_none_or(|q| matches_search(s, q)))
+            .collect()
+    }
+
+    fn matches_status_filter(&self, server_name: &str) -> bool {
+        let state = self.server_states.get(server_name);
+        match self.status_filter {
+            StatusFilter::All => true,
+            StatusFilter::Failing => state.is_some_and(|s| {
+                matches!(
+                    s.status,
+                    RunStatus::Failed | RunStatus::AuthRejected | RunStatus::NoCredential | RunStatus::Unreachable
+                )
+            }),
+            StatusFilter::Expiring => self.cert_expiring(server_name),
+            StatusFilter::NeverFetched => state.is_none_or(|s| s.last_updated.is_none()),
+        }
+    }
+
+    /// The cached cert is expired or within `cert_warning_days`.
+    fn cert_expiring(&self, server_name: &str) -> bool {
+        let (warning, _) = self.config.cert_thresholds();
+        self.cert_cache
+            .get(server_name)
+            .and_then(|v| v.as_ref())
+            .is_some_and(|exp| (*exp - chrono::Utc::now()).num_days() <= warning)
+    }
+
+    /// Counts every configured server, ignoring filters and search, by its last run and
+    /// cached cert.
+    pub fn fleet_summary(&self) -> FleetSummary {
+        let mut summary = FleetSummary::default();
+        for server in &self.config.servers {
+            let count = match self.server_states.get(&server.name).map(|s| &s.status) {
+                None => &mut summary.never_fetched,
+                Some(RunStatus::NoCredential) => &mut summary.no_credential,
+                Some(RunStatus::Failed | RunStatus::AuthRejected | RunStatus::Unreachable) => &mut summary.failed,
+                Some(RunStatus::Fetched | RunStatus::Skipped) if self.cert_expiring(&server.name) => {
+                    &mut summary.expiring
+                }
+                Some(RunStatus::Fetched | RunStatus::Skipped) => &mut summary.ok,
+            };
+            *count += 1;
+        }
+        summary
+    }
+
+    /// Switches the status filter and selects the first remaining row.
+    pub fn set_status_filter(&mut self, filter: StatusFilter) {
+        self.status_filter = filter;
+        self.select_first_visible();
+    }
+
+    /// Replaces the search query and selects the first remaining row.
+    pub fn set_search(&mut self, query: Option<String>) {
+        self.search = query;
+        self.select_first_visible();
+    }
+
+    /// Moves to the next (or previous) listed server, wrapping around. While a search is
+    /// active every listed server is a match, so this is how `n` / `N` step through them.
+    pub fn select_match(&mut self, forward: bool) {
+        let total = self.visible_servers().len();
+        if total == 0 {
+            return;
+        }
+        let next = match self.table_state.selected() {
+            Some(sel) if forward => (sel + 1) % total,
+            Some(sel) => (sel + total - 1) % total,
+            None => 0,
+        };
+        self.table_state.select(Some(next));
+    }
+
+    /// Marked servers in table order, or just the selected one when nothing is marked.
+    pub fn marked_or_selected(&self) -> Vec<String> {
+        let visible = self.visible_servers();
+        if self.marked.is_empty() {
+            return self
+                .table_state
+                .selected()
+                .and_then(|i| visible.get(i))
+                .map(|s| vec![s.name.clone()])
+                .unwrap_or_default();
+        }
+        // Marks survive filtering, so also include marked servers that are hidden right now.
+        self.config
+            .servers
+            .iter()
+            .filter(|s| self.marked.contains(&s.name))
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    /// Selects `server_name`'s row, clearing the search and tag filter when they hide it.
+    pub fn select_server(&mut self, server_name: &str) {
+        if !self.visible_servers().iter().any(|s| s.name == server_name) {
+            self.search = None;
+            self.tag_filter = None;
+            self.status_filter = StatusFilter::All;
+        }
+        let row = self.visible_servers().iter().position(|s| s.name == server_name);
+        self.table_state.select(row);
+    }
+
+    fn select_first_visible(&mut self) {
+        let visible = self.visible_servers().len();
+        self.table_state.select(if visible == 0 { None } else { Some(0) });
+    }
+
+    /// Steps the tag filter through every tag in the config, then back to "all".
+    pub fn cycle_tag_filter(&mut self) {
+        let tags = self.config.tags();
+        self.tag_filter = match &self.tag_filter {
+            None => tags.first().cloned(),
+            Some(current) => tags.iter().skip_while(|t| *t != current).nth(1).cloned(),
+        };
+        self.select_first_visible();
+    }
+
     /// Reads cert expiry for every server directly from the cached kubeconfig files.
     /// Called on startup, after any fetch, and when the state file changes.
     pub fn refresh_cert_cache(&mut self) {
-        for server in &self.config.servers {
-            let mut path = PathBuf::from(&self.config.local_output_dir);
-            path.push(&server.name);
-            let expiry = match crate::kube::check_local_cert_expiry(&path) {
-                crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) => Some(exp),
-                _ => None,
-            };
-            self.cert_cache.insert(server.name.clone(), expiry);
+        let names: Vec<String> = self.config.servers.iter().map(|s| s.name.clone()).collect();
+        for name in names {
+            self.refresh_cert_for(&name);
+        }
+    }
+
+    /// Re-reads cert and CA expiry for a single server's cached kubeconfig.
+    pub fn refresh_cert_for(&mut self, server_name: &str) {
+        let path = self.config.local_path(server_name);
+        let expiry = match crate::kube::check_local_cert_expiry(&path) {
+            crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) => Some(exp),
+            _ => None,
+        };
+        self.cert_cache.insert(server_name.to_string(), expiry);
+        self.ca_cache
+            .insert(server_name.to_string(), crate::kube::local_ca_expiry(&path));
+    }
+
+    /// Updates the cache after a credential was stored for `account`. The `_default`
+    /// account covers every server.
+    pub fn note_credential_stored(&mut self, account: &str) {
+        if account == crate::credentials::DEFAULT_ACCOUNT {
+            for server in &self.config.servers {
+                self.cred_cache.insert(server.name.clone(), true);
+            }
+        } else {
+            self.cred_cache.insert(account.to_string(), true);
         }
     }
 
@@ -439,11 +1411,17 @@ impl AppState {
     /// Avoids repeated keyring/D-Bus/process calls on every render frame.
     pub fn refresh_cred_cache(&mut self) {
         for server in &self.config.servers {
-            let stored = matches!(
-                crate::credentials::get_credential(&server.name),
-                crate::credentials::CredentialResult::Found(_)
-            );
+            let stored = crate::credentials::has_credential(&server.name, self.config.credentials_from_env());
             self.cred_cache.insert(server.name.clone(), stored);
         }
     }
 }
+
+/// Case-insensitive substring match on a server's name, address or context name.
+fn matches_search(server: &crate::config::Server, query: &str) -> bool {
+    let query = query.to_lowercase();
+    [Some(&server.name), Some(&server.address), server.context_name.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&query))
+}
@@ -0,0 +1,91 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use super::fg;
+use crate::audit::{Action, Event};
+use crate::tui::app::{AppState, View};
+
+/// Opens the credential audit log (`L`), newest event first.
+pub fn open(app: &mut AppState) {
+    match crate::audit::read() {
+        Ok(mut events) => {
+            events.reverse();
+            app.view = View::AuditLog { events, scroll: 0 };
+        }
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Couldn't read the audit log: {:#}", e),
+            };
+        }
+    }
+}
+
+fn event_line(event: &Event, app: &AppState) -> Line<'static> {
+    let action_style = match event.action {
+        Action::Read => Style::default(),
+        Action::Stored => fg(app.use_color, app.theme.ok),
+        Action::Deleted => fg(app.use_color, app.theme.warning),
+        Action::SshAttempt => fg(app.use_color, app.theme.accent),
+    };
+    let outcome_style = if event.outcome.starts_with("failed") || event.outcome.starts_with("unavailable") {
+        fg(app.use_color, app.theme.error)
+    } else {
+        Style::default()
+    };
+    Line::from(vec![
+        Span::styled(
+            format!(" {}  ", event.at.format("%Y-%m-%d %H:%M:%S")),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+        Span::styled(format!("{:<11}  ", event.action.to_string()), action_style),
+        Span::raw(format!("{:<30}  ", event.account)),
+        Span::styled(event.outcome.clone(), outcome_style),
+    ])
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::AuditLog { events, scroll } = &app.view else {
+        return;
+    };
+    let block = Block::default()
+        .title(" Credential audit log (newest first, UTC) ")
+        .title_bottom(" j/k:scroll  PgUp/PgDn:page  g/G:top/bottom  Esc:back ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text: Vec<Line> = if events.is_empty() {
+        vec![Line::from(format!(
+            " No credential use recorded in {}",
+            crate::audit::audit_file_path().display()
+        ))]
+    } else {
+        events.iter().map(|e| event_line(e, app)).collect()
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(block).scroll((*scroll as u16, 0)),
+        frame.area(),
+    );
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent, page: usize) -> bool {
+    let View::AuditLog { events, scroll } = &mut app.view else {
+        return false;
+    };
+    let last = events.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => *scroll = (*scroll + page).min(last),
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => *scroll = last,
+        KeyCode::Esc | KeyCode::Char('q') => app.view = View::Dashboard,
+        _ => {}
+    }
+    false
+}
@@ -2,7 +2,7 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 
-use super::centered_rect;
+use super::{centered_rect, fg};
 use crate::tui::app::{AppEvent, AppState, View};
 
 pub fn render(frame: &mut ratatui::Frame, app: &AppState) {
@@ -15,7 +15,7 @@ pub fn render(frame: &mut ratatui::Frame, app: &AppState) {
     let block = Block::default()
         .title(" Bitwarden Vault Unlock ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(fg(app.use_color, app.theme.accent));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -36,32 +36,31 @@ pub fn render(frame: &mut ratatui::Frame, app: &AppState) {
         "Enter your Bitwarden master password to unlock the vault."
     };
     frame.render_widget(
-        Paragraph::new(status_text).style(Style::default().fg(Color::White)),
+        Paragraph::new(status_text).style(fg(app.use_color, Color::White)),
         rows[0],
     );
 
     frame.render_widget(
-        Paragraph::new("Master Password:").style(Style::default().fg(Color::Gray)),
+        Paragraph::new("Master Password:").style(fg(app.use_color, Color::Gray)),
         rows[2],
     );
 
     let masked = app.credential_input.masked_display();
     frame.render_widget(
-        Paragraph::new(masked).style(Style::default().fg(Color::Yellow)),
+        Paragraph::new(masked).style(fg(app.use_color, app.theme.warning)),
         rows[3],
     );
 
     if let Some(err) = error {
         frame.render_widget(
             Paragraph::new(err)
-                .style(Style::default().fg(Color::Red))
+                .style(fg(app.use_color, app.theme.error))
                 .wrap(Wrap { trim: false }),
             rows[5],
         );
     } else {
         frame.render_widget(
-            Paragraph::new("[Enter] Unlock  [Esc] Skip (local servers only)")
-                .style(Style::default().fg(Color::DarkGray)),
+            Paragraph::new("[Enter] Unlock  [Esc] Skip (local servers only)").style(fg(app.use_color, app.theme.muted)),
             rows[5],
         );
     }
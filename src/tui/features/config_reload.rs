@@ -0,0 +1,121 @@
+use crate::config::Config;
+use crate::tui::app::AppState;
+
+/// Picks up a config file changed outside the TUI. A file that doesn't load, or whose
+/// `[keys]` don't resolve, leaves the running config as it was and says why. The TUI's
+/// own saves write what is already loaded, so those pass through without a word.
+pub fn on_changed(app: &mut AppState) {
+    let path = app.config_path.to_string_lossy().into_owned();
+    let new_config = match crate::config::load_config(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            let message = format!("{:#}", e).replace('\n', " ");
+            keep_old(app, &message);
+            return;
+        }
+    };
+    let keys = match crate::tui::keys::Keymap::from_config(new_config.keys.as_ref()) {
+        Ok(keys) => keys,
+        Err(problems) => {
+            keep_old(app, &format!("[keys]: {}", problems.join("; ")));
+            return;
+        }
+    };
+
+    // Vault servers never come from the file; compare and carry over everything else.
+    let vault: Vec<crate::config::Server> = app
+        .config
+        .servers
+        .iter()
+        .filter(|s| super::is_vault_server(app, &s.name))
+        .cloned()
+        .collect();
+    let mut current = app.config.clone();
+    current.servers.retain(|s| !super::is_vault_server(app, &s.name));
+    if new_config == current {
+        return;
+    }
+
+    let added = new_config
+        .servers
+        .iter()
+        .filter(|s| !current.servers.iter().any(|c| c.name == s.name))
+        .count();
+    let removed = current
+        .servers
+        .iter()
+        .filter(|c| !new_config.servers.iter().any(|s| s.name == c.name))
+        .count();
+    apply(app, new_config, keys, vault);
+
+    let mut message = format!("Reloaded {}", file_name(app));
+    if added + removed > 0 {
+        message.push_str(&format!(" ({} server(s) added, {} removed)", added, removed));
+    }
+    app.notification = Some((message, std::time::Instant::now()));
+}
+
+fn keep_old(app: &mut AppState, error: &str) {
+    log::warn!("Ignoring changed config file: {}", error);
+    app.notification = Some((
+        format!("{} not reloaded: {}", file_name(app), error),
+        std::time::Instant::now(),
+    ));
+}
+
+fn file_name(app: &AppState) -> String {
+    app.config_path.file_name().map_or_else(
+        || app.config_path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
+
+/// Swaps in `config` along with what the session derives from it, keeping the
+/// selected server selected.
+fn apply(app: &mut AppState, mut config: Config, keys: crate::tui::keys::Keymap, vault: Vec<crate::config::Server>) {
+    let selected = app
+        .table_state
+        .selected()
+        .and_then(|i| app.visible_servers().get(i).map(|s| s.name.clone()));
+
+    for server in &config.servers {
+        app.server_sources
+            .insert(server.name.clone(), crate::bitwarden::ServerSource::Local);
+    }
+    for server in vault {
+        if !config.servers.iter().any(|s| s.name == server.name) {
+            config.servers.push(server);
+        }
+    }
+    // `T` toggles for the session; only a changed setting overrides it
+    if config.cert_display != app.config.cert_display {
+        app.cert_display = config.cert_display.unwrap_or_default();
+    }
+    app.theme = crate::tui::theme::Theme::from_config(config.theme.as_ref(), app.color_depth);
+    app.use_color = app.color_depth.enabled() && app.theme.color;
+    app.keys = keys;
+    app.config = config;
+
+    let names: std::collections::HashSet<&str> = app.config.servers.iter().map(|s| s.name.as_str()).collect();
+    app.marked.retain(|name| names.contains(name.as_str()));
+    if app
+        .tag_filter
+        .as_ref()
+        .is_some_and(|tag| !app.config.servers.iter().any(|s| s.tags.contains(tag)))
+    {
+        app.tag_filter = None;
+    }
+    app.refresh_cert_cache();
+    app.refresh_cred_cache();
+
+    let visible = app.visible_servers();
+    let row = selected
+        .and_then(|name| visible.iter().position(|s| s.name == name))
+        .or_else(|| {
+            app.table_state
+                .selected()
+                .map(|i| i.min(visible.len().saturating_sub(1)))
+        })
+        .filter(|_| !visible.is_empty());
+    app.table_state.select(row);
+}
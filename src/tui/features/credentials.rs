@@ -1,18 +1,18 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
-    style::{Color, Style},
+    style::Color,
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 
-use super::{centered_rect, render_dim_background};
-use crate::tui::app::{AppState, View};
+use super::{centered_rect, fg, render_dim_background};
+use crate::tui::app::{AppEvent, AppState, View};
 
-pub fn render_menu(frame: &mut Frame, _app: &AppState, server_name: &str) {
+pub fn render_menu(frame: &mut Frame, app: &AppState, server_name: &str) {
     render_dim_background(frame, frame.area());
 
-    let area = centered_rect(40, 7, frame.area());
+    let area = centered_rect(40, 8, frame.area());
 
     frame.render_widget(Clear, area);
 
@@ -21,12 +21,13 @@ pub fn render_menu(frame: &mut Frame, _app: &AppState, server_name: &str) {
         .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .style(Style::default().fg(Color::White));
+        .style(fg(app.use_color, Color::White));
 
     let lines = vec![
         Line::from(""),
         Line::from(vec![Span::raw("   [s] Set password")]),
         Line::from(vec![Span::raw("   [d] Delete credential")]),
+        Line::from(vec![Span::raw("   [a] Set shared default password")]),
         Line::from(vec![Span::raw("   [Esc] Cancel")]),
         Line::from(""),
     ];
@@ -47,7 +48,7 @@ pub fn render_input(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .style(Style::default().fg(Color::White));
+        .style(fg(app.use_color, Color::White));
 
     let masked = app.credential_input.masked_display();
     let password_line = format!("   > {}│", masked);
@@ -70,6 +71,10 @@ pub fn handle_key_menu(app: &mut AppState, name: String, key: KeyEvent) -> bool
             app.credential_input.clear();
             app.view = View::CredentialInput(name);
         }
+        KeyCode::Char('a') | KeyCode::Char('A') => {
+            app.credential_input.clear();
+            app.view = View::CredentialInput(crate::credentials::DEFAULT_ACCOUNT.to_string());
+        }
         KeyCode::Char('d') | KeyCode::Char('D') => {
             match crate::credentials::delete_credential(&name) {
                 Ok(()) => {
@@ -103,30 +108,268 @@ pub fn handle_key_input(app: &mut AppState, name: String, key: KeyEvent) -> bool
         KeyCode::Enter => {
             let password = app.credential_input.value.clone();
             app.credential_input.clear();
-            match crate::credentials::set_credential(&name, &password) {
-                Ok(()) => {
-                    app.cred_cache.insert(name.clone(), true);
-                    app.notification = Some((format!("Credential saved for '{}'", name), std::time::Instant::now()));
-                    app.view = View::Dashboard;
-                }
+            save_password(app, vec![name], password);
+        }
+        KeyCode::Esc => {
+            app.credential_input.clear();
+            app.view = View::CredentialMenu(name);
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Password prompt for the marked servers: one password, stored for each of them.
+pub fn handle_key_marked_input(app: &mut AppState, names: Vec<String>, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.credential_input.push(c);
+        }
+        KeyCode::Backspace => {
+            app.credential_input.pop();
+        }
+        KeyCode::Enter => {
+            let password = app.credential_input.value.clone();
+            app.credential_input.clear();
+            app.marked.clear();
+            save_password(app, names, password);
+        }
+        KeyCode::Esc => {
+            app.credential_input.clear();
+            app.view = View::Dashboard;
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Stores `password` in the keyring for every server, falling back to the consent
+/// dialog (for all of them) when the keyring turns out to be unavailable.
+fn save_password(app: &mut AppState, names: Vec<String>, password: String) {
+    for name in &names {
+        if let Err(e) = crate::credentials::set_credential(name, &password) {
+            if crate::credentials::keyring_error_is_unavailable(&e) && crate::credentials::file_fallback_allowed() {
+                app.view = View::KeyringFallbackConsent {
+                    server_names: names,
+                    password,
+                    keyring_error: e,
+                };
+            } else {
+                app.view = View::Error {
+                    message: format!("Couldn't save credential for '{}': {}", name, e),
+                };
+            }
+            return;
+        }
+        app.note_credential_stored(name);
+    }
+    app.notification = Some((
+        format!("Credential saved for '{}'", names.join(", ")),
+        std::time::Instant::now(),
+    ));
+    app.view = View::Dashboard;
+}
+
+// ─── Key passphrase ───────────────────────────────────────────────────────────
+
+pub fn render_passphrase_input(frame: &mut Frame, app: &AppState) {
+    let identity_file = match &app.view {
+        View::KeyPassphraseInput { identity_file, .. } => identity_file.clone(),
+        _ => return,
+    };
+
+    let width = (identity_file.chars().count() as u16 + 8).clamp(50, 72);
+    let area = centered_rect(width, 8, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Key Passphrase ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(fg(app.use_color, Color::White));
+
+    let masked = app.credential_input.masked_display();
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::raw(format!("   {} is encrypted.", identity_file))]),
+        Line::from(vec![Span::raw("   Passphrase:")]),
+        Line::from(vec![Span::raw(format!("   > {}│", masked))]),
+        Line::from(""),
+        Line::from(vec![Span::raw("   Enter: unlock and retry   Esc: cancel")]),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Keeps the passphrase for this session and stores it in the keyring so later CLI
+/// runs can use it. A keyring failure is not fatal: the session copy still works.
+pub fn handle_key_passphrase(app: &mut AppState, key: KeyEvent, tx: &std::sync::mpsc::Sender<AppEvent>) -> bool {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.credential_input.push(c);
+        }
+        KeyCode::Backspace => {
+            app.credential_input.pop();
+        }
+        KeyCode::Enter | KeyCode::Esc => {
+            let passphrase = app.credential_input.value.clone();
+            app.credential_input.clear();
+            let View::KeyPassphraseInput {
+                server_name,
+                identity_file,
+                return_to,
+            } = std::mem::replace(&mut app.view, View::Dashboard)
+            else {
+                return false;
+            };
+            app.view = *return_to;
+            if key.code == KeyCode::Esc || passphrase.is_empty() {
+                return false;
+            }
+            let account = crate::credentials::passphrase_account(&identity_file);
+            let what = match crate::credentials::set_credential(&account, &passphrase) {
+                Ok(()) => "Passphrase saved",
                 Err(e) => {
-                    if crate::credentials::keyring_error_is_unavailable(&e) {
-                        app.view = View::KeyringFallbackConsent {
-                            server_name: name.clone(),
-                            password,
-                            keyring_error: e,
-                        };
-                    } else {
-                        app.view = View::Error {
-                            message: format!("Couldn't save credential: {}", e),
-                        };
-                    }
+                    log::warn!("Could not store key passphrase in keyring: {}", e);
+                    "Passphrase kept for this session"
                 }
+            };
+            app.key_passphrases.insert(identity_file, passphrase);
+            crate::tui::retry_after_prompt(app, &server_name, what, tx);
+        }
+        _ => {}
+    }
+    false
+}
+
+// ─── Sudo password ────────────────────────────────────────────────────────────
+
+pub fn render_sudo_password_input(frame: &mut Frame, app: &AppState) {
+    let server_name = match &app.view {
+        View::SudoPasswordInput { server_name, .. } => server_name.clone(),
+        _ => return,
+    };
+
+    let area = centered_rect(56, 8, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" sudo Password: {} ", server_name))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(fg(app.use_color, Color::White));
+
+    let masked = app.credential_input.masked_display();
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::raw("   sudo rejected the stored password.")]),
+        Line::from(vec![Span::raw("   Password:")]),
+        Line::from(vec![Span::raw(format!("   > {}│", masked))]),
+        Line::from(""),
+        Line::from(vec![Span::raw("   Enter: save and retry   Esc: cancel")]),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Stores the new password for the server and fetches again. When the keyring can't
+/// store it, the usual consent dialog takes over and the retry is left to the user.
+pub fn handle_key_sudo_password(app: &mut AppState, key: KeyEvent, tx: &std::sync::mpsc::Sender<AppEvent>) -> bool {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.credential_input.push(c);
+        }
+        KeyCode::Backspace => {
+            app.credential_input.pop();
+        }
+        KeyCode::Enter | KeyCode::Esc => {
+            let password = app.credential_input.value.clone();
+            app.credential_input.clear();
+            let View::SudoPasswordInput { server_name, return_to } = std::mem::replace(&mut app.view, View::Dashboard)
+            else {
+                return false;
+            };
+            app.view = *return_to;
+            if key.code == KeyCode::Esc || password.is_empty() {
+                return false;
+            }
+            let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+            let separate = app
+                .config
+                .servers
+                .iter()
+                .any(|s| s.name == server_name && s.sudo_password == Some(true));
+            let account = if separate {
+                crate::credentials::sudo_account(&server_name)
+            } else {
+                server_name.clone()
+            };
+            save_password(app, vec![account], password);
+            if matches!(app.view, View::Dashboard) {
+                app.view = return_to;
+                crate::tui::retry_after_prompt(app, &server_name, "Password saved", tx);
             }
         }
-        KeyCode::Esc => {
+        _ => {}
+    }
+    false
+}
+
+// ─── One-time code ────────────────────────────────────────────────────────────
+
+pub fn render_otp_input(frame: &mut Frame, app: &AppState) {
+    let server_name = match &app.view {
+        View::OtpInput { server_name, .. } => server_name.clone(),
+        _ => return,
+    };
+
+    let area = centered_rect(56, 8, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" One-time Code: {} ", server_name))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(fg(app.use_color, Color::White));
+
+    let masked = app.credential_input.masked_display();
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::raw("   The server asks for a second factor.")]),
+        Line::from(vec![Span::raw("   Code:")]),
+        Line::from(vec![Span::raw(format!("   > {}│", masked))]),
+        Line::from(""),
+        Line::from(vec![Span::raw("   Enter: log in and retry   Esc: cancel")]),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Hands the code to the next login to the server and fetches again. Codes are never
+/// stored.
+pub fn handle_key_otp(app: &mut AppState, key: KeyEvent, tx: &std::sync::mpsc::Sender<AppEvent>) -> bool {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.credential_input.push(c);
+        }
+        KeyCode::Backspace => {
+            app.credential_input.pop();
+        }
+        KeyCode::Enter | KeyCode::Esc => {
+            let code = app.credential_input.value.trim().to_string();
             app.credential_input.clear();
-            app.view = View::CredentialMenu(name);
+            let View::OtpInput { server_name, return_to } = std::mem::replace(&mut app.view, View::Dashboard) else {
+                return false;
+            };
+            app.view = *return_to;
+            if key.code == KeyCode::Esc || code.is_empty() {
+                return false;
+            }
+            crate::ssh::provide_otp(&server_name, code);
+            crate::tui::retry_after_prompt(app, &server_name, "Code entered", tx);
         }
         _ => {}
     }
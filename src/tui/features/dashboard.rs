@@ -9,80 +9,164 @@ use ratatui::{
     widgets::{Block, BorderType, Cell, Clear, Paragraph, Row, Table, Wrap},
 };
 
-use super::{centered_rect, cert_color, cert_expires_display, status_color, status_display};
+use super::{
+    centered_rect, cert_color, cert_expires_display, compact_expiry, fg, run_duration, status_color, status_display,
+    status_glyph, truncate,
+};
+use crate::config::Column;
 use crate::state::RunStatus;
-use crate::tui::app::{AppEvent, AppState, View, WizardState};
+use crate::tui::app::{AppEvent, AppState, StatusFilter, View, WizardState};
+use crate::tui::keys::{Action, Scope};
+
+/// Below this width the dashboard switches to the compact layout.
+const COMPACT_WIDTH: u16 = 80;
+const MIN_WIDTH: u16 = 50;
+const MIN_HEIGHT: u16 = 10;
 
 pub fn render(frame: &mut Frame, app: &mut AppState) {
     let area = frame.area();
 
     // Enforce minimum terminal size
-    if area.width < 80 || area.height < 10 {
-        let msg = format!("Terminal too small ({}x{}) - minimum 80x10", area.width, area.height);
-        frame.render_widget(Paragraph::new(msg).alignment(Alignment::Center), area);
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        let msg = format!(
+            "Terminal too small ({}x{}) - minimum {}x{}",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        );
+        frame.render_widget(
+            Paragraph::new(msg)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            area,
+        );
         return;
     }
+    let compact = area.width < COMPACT_WIDTH;
 
     // 3-row vertical layout: title | table | status bar
     let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(1), Constraint::Length(1)]).split(area);
 
-    render_title_bar(frame, app, chunks[0]);
-    render_server_table(frame, app, chunks[1]);
-    render_status_bar(frame, app, chunks[2]);
+    render_title_bar(frame, app, chunks[0], compact);
+    if compact {
+        render_compact_table(frame, app, chunks[1]);
+    } else {
+        render_server_table(frame, app, chunks[1]);
+    }
+    render_status_bar(frame, app, chunks[2], compact);
 }
 
-fn render_title_bar(frame: &mut Frame, app: &AppState, area: ratatui::layout::Rect) {
+fn render_title_bar(frame: &mut Frame, app: &AppState, area: ratatui::layout::Rect, compact: bool) {
     let dry_run_indicator = if app.dry_run {
         Span::styled(
             " [DRY-RUN] ",
             Style::default()
-                .fg(if app.use_color { Color::Yellow } else { Color::Reset })
+                .fg(if app.use_color { app.theme.warning } else { Color::Reset })
                 .add_modifier(Modifier::BOLD),
         )
     } else {
         Span::raw("")
     };
 
+    let summary = Line::from(fleet_summary(app, compact));
     let title_chunks = Layout::horizontal([
         Constraint::Fill(1),
+        Constraint::Length(summary.width() as u16),
         Constraint::Length(if app.dry_run { 11 } else { 0 }),
     ])
     .split(area);
 
-    frame.render_widget(
-        Paragraph::new(Line::from(vec![Span::styled(
-            " kube_config_updater ",
-            Style::default().add_modifier(Modifier::BOLD),
-        )])),
-        title_chunks[0],
-    );
+    let mut title = vec![Span::styled(
+        " kube_config_updater ",
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(tag) = &app.tag_filter {
+        title.push(Span::styled(
+            format!("[tag: {}]", tag),
+            fg(app.use_color, app.theme.accent),
+        ));
+    }
+    if app.status_filter != StatusFilter::All {
+        title.push(Span::styled(
+            format!("[{}]", app.status_filter),
+            fg(app.use_color, app.theme.accent),
+        ));
+    }
+    if let Some(query) = app.search.as_deref().filter(|_| !app.search_editing) {
+        title.push(Span::styled(
+            format!("[search: {}]", query),
+            fg(app.use_color, app.theme.accent),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(title)), title_chunks[0]);
+    frame.render_widget(Paragraph::new(summary), title_chunks[1]);
 
     if app.dry_run {
         frame.render_widget(
             Paragraph::new(Line::from(vec![dry_run_indicator])).alignment(Alignment::Right),
-            title_chunks[1],
+            title_chunks[2],
         );
     }
 }
 
+/// `27 ok · 3 expiring ≤30d · 2 failed · 1 no credential · 4 not run` across every
+/// configured server. Empty groups other than ok are left out, and the compact layout
+/// uses short labels.
+fn fleet_summary(app: &AppState, compact: bool) -> Vec<Span<'static>> {
+    let summary = app.fleet_summary();
+    let (warning, _) = app.config.cert_thresholds();
+    let theme = &app.theme;
+    let expiring = format!("expiring ≤{}d", warning);
+    let groups = [
+        (summary.ok, "ok", "ok", theme.ok),
+        (summary.expiring, "exp", expiring.as_str(), theme.warning),
+        (summary.failed, "fail", "failed", theme.error),
+        (summary.no_credential, "no cred", "no credential", theme.warning),
+        (summary.never_fetched, "new", "not run", theme.muted),
+    ];
+    let mut spans = Vec::new();
+    for (i, (count, short, long, color)) in groups.into_iter().enumerate() {
+        if count == 0 && i > 0 {
+            continue;
+        }
+        if !spans.is_empty() {
+            spans.push(Span::styled(" · ", Style::default().add_modifier(Modifier::DIM)));
+        }
+        let label = if compact { short } else { long };
+        spans.push(Span::styled(format!("{} {}", count, label), fg(app.use_color, color)));
+    }
+    spans.push(Span::raw(" "));
+    spans
+}
+
 fn render_server_table(frame: &mut Frame, app: &mut AppState, area: ratatui::layout::Rect) {
+    let columns = app.config.dashboard_columns();
+    let show_reachability = app.config.reachability_check();
+    let show_remote = !app.remote_certs.is_empty();
     let rows: Vec<Row> = app
-        .config
-        .servers
-        .iter()
+        .visible_servers()
+        .into_iter()
         .map(|server| {
             let state = app.server_states.get(&server.name);
             let is_in_progress = app.in_progress.contains(&server.name);
-            let is_flashing = app.flash_rows.get(&server.name).copied().unwrap_or(0) > 0;
+            let is_queued = app.fetch_queue.iter().any(|q| q.name == server.name);
+            let recent = app.last_results.get(&server.name).filter(|r| r.is_recent());
 
             // STATUS column
-            let (status_text, status_style) = if is_in_progress {
+            let (status_text, status_style) = if is_queued {
+                ("… Queued".to_string(), Style::default().add_modifier(Modifier::DIM))
+            } else if is_in_progress {
+                let progress = match app.fetch_progress.get(&server.name) {
+                    Some(&bytes) => indicatif::HumanBytes(bytes).to_string(),
+                    None if app.renewing.contains(&server.name) => "Renewing...".to_string(),
+                    None => "Fetching...".to_string(),
+                };
                 (
-                    format!("{} Fetching...", app.spinner.current()),
-                    Style::default().fg(if app.use_color { Color::Cyan } else { Color::Reset }),
+                    format!("{} {}", app.spinner.current(), progress),
+                    Style::default().fg(if app.use_color { app.theme.accent } else { Color::Reset }),
                 )
             } else {
                 let text = match state {
+                    // The result beside it says how recent it is
+                    Some(s) if recent.is_some() => status_display(&s.status).to_string(),
                     Some(s) => {
                         let base = status_display(&s.status);
                         if s.status == RunStatus::Fetched {
@@ -98,7 +182,7 @@ fn render_server_table(frame: &mut Frame, app: &mut AppState, area: ratatui::lay
                     None => "· Not run yet".to_string(),
                 };
                 let style = match state {
-                    Some(s) => status_color(&s.status, app.use_color),
+                    Some(s) => status_color(&s.status, app),
                     None => Style::default().add_modifier(Modifier::DIM),
                 };
                 (text, style)
@@ -107,7 +191,7 @@ fn render_server_table(frame: &mut Frame, app: &mut AppState, area: ratatui::lay
             // CERT EXPIRES column — read directly from the cached kubeconfig file
             let (cert_str, cert_style) = {
                 let expires = app.cert_cache.get(&server.name).and_then(|v| v.as_ref());
-                (cert_expires_display(expires), cert_color(expires, app.use_color))
+                (cert_expires_display(expires, app), cert_color(expires, app))
             };
 
             // Source badge — vault servers get a "[vault]" indicator
@@ -117,62 +201,228 @@ fn render_server_table(frame: &mut Frame, app: &mut AppState, area: ratatui::lay
                 server.name.clone()
             };
 
-            // NAME column — bold if row recently updated (flash)
-            let name_style = if is_flashing {
-                Style::default().add_modifier(Modifier::BOLD)
+            // A fetch that just finished: ✓ or ✗ and how long it took, beside the status
+            let status_line = Line::from(match recent {
+                Some(r) if !is_queued && !is_in_progress => vec![
+                    Span::styled(status_text, status_style),
+                    Span::styled(
+                        format!(
+                            " {} {}",
+                            if r.ok { "✓" } else { "✗" },
+                            run_duration(r.duration.as_millis() as u64)
+                        ),
+                        result_style(r.ok, app),
+                    ),
+                ],
+                _ => vec![Span::styled(status_text, status_style)],
+            });
+
+            let marker = if app.marked.contains(&server.name) { "●" } else { " " };
+
+            let mut cells = vec![Cell::from(marker).style(fg(app.use_color, app.theme.mark))];
+            for column in columns {
+                cells.push(match column {
+                    Column::Name => Cell::from(display_name.clone()),
+                    Column::Address => Cell::from(server.address.clone()),
+                    Column::User => Cell::from(server.user(&app.config).unwrap_or("—").to_string()),
+                    Column::Context => Cell::from(server.kube_context().to_string()),
+                    Column::CertExpiry => Cell::from(cert_str.clone()).style(cert_style),
+                    Column::LastUpdated => match state.and_then(|s| s.last_updated) {
+                        Some(t) => Cell::from(relative_age(&t)),
+                        None => Cell::from("—").style(Style::default().add_modifier(Modifier::DIM)),
+                    },
+                    Column::LastDuration => match state.and_then(|s| s.duration_ms) {
+                        Some(ms) => Cell::from(run_duration(ms)),
+                        None => Cell::from("—").style(Style::default().add_modifier(Modifier::DIM)),
+                    },
+                    Column::Status => Cell::from(status_line.clone()),
+                });
+            }
+            if show_reachability {
+                let (text, style) = super::reachability::cell(app, &server.name);
+                cells.push(Cell::from(text).style(style));
+            }
+            if show_remote {
+                let (text, style) = super::remote_cert::cell(app, &server.name);
+                cells.push(Cell::from(text).style(style));
+            }
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut widths = vec![Constraint::Length(1)]; // mark
+    let mut header = vec![""];
+    for column in columns {
+        let (width, title) = match column {
+            Column::Name => (Constraint::Fill(1), "NAME"),
+            Column::Address => (Constraint::Fill(1), "ADDRESS"),
+            Column::User => (Constraint::Length(12), "USER"),
+            Column::Context => (Constraint::Fill(1), "CONTEXT"),
+            // YYYY-MM-DD, or "expired 123d ago", plus padding
+            Column::CertExpiry => (
+                Constraint::Length(match app.cert_display {
+                    crate::config::CertDisplay::Date => 13,
+                    crate::config::CertDisplay::Relative => 18,
+                }),
+                "CERT EXPIRES",
+            ),
+            Column::LastUpdated => (Constraint::Length(9), "UPDATED"), // "just now", "12w ago"
+            Column::LastDuration => (Constraint::Length(8), "TOOK"),   // "12.3s", "2m05s"
+            Column::Status => (Constraint::Length(24), "STATUS"),      // fits "⚠ Auth rejected ✗ 12.3s"
+        };
+        widths.push(width);
+        header.push(title);
+    }
+    if show_reachability {
+        widths.push(Constraint::Length(13)); // REACHABLE (fits "ssh ✓ api ✗")
+        header.push("REACHABLE");
+    }
+    if show_remote {
+        widths.push(Constraint::Length(9)); // REMOTE (fits "✗ failed")
+        header.push("REMOTE");
+    }
+
+    let table = Table::new(rows, widths)
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::DIM | Modifier::BOLD)))
+        .row_highlight_style(highlight_style(app))
+        .highlight_symbol("▶ ");
+
+    frame.render_stateful_widget(table, area, &mut app.table_state);
+}
+
+/// The table for narrow terminals (tmux side panes): names cut to fit, cert expiry as
+/// time left, status as its icon alone, and no REACHABLE or REMOTE column.
+fn render_compact_table(frame: &mut Frame, app: &mut AppState, area: ratatui::layout::Rect) {
+    // highlight symbol + mark + CERT + STATUS, and a space between each of the four columns
+    let name_width = area.width.saturating_sub(2 + 1 + 4 + 1 + 3) as usize;
+    let now = chrono::Utc::now();
+    let rows: Vec<Row> = app
+        .visible_servers()
+        .into_iter()
+        .map(|server| {
+            let state = app.server_states.get(&server.name);
+            let (status_text, status_style) = if app.fetch_queue.iter().any(|q| q.name == server.name) {
+                ("…".to_string(), Style::default().add_modifier(Modifier::DIM))
+            } else if app.in_progress.contains(&server.name) {
+                (app.spinner.current().to_string(), fg(app.use_color, app.theme.accent))
+            } else if let Some(r) = app.last_results.get(&server.name).filter(|r| r.is_recent()) {
+                ((if r.ok { "✓" } else { "✗" }).to_string(), result_style(r.ok, app))
+            } else {
+                match state {
+                    Some(s) => (status_glyph(&s.status).to_string(), status_color(&s.status, app)),
+                    None => ("·".to_string(), Style::default().add_modifier(Modifier::DIM)),
+                }
+            };
+
+            let expires = app.cert_cache.get(&server.name).and_then(|v| v.as_ref());
+            let name = if super::is_vault_server(app, &server.name) {
+                format!("{} [v]", server.name)
             } else {
-                Style::default()
+                server.name.clone()
             };
+            let marker = if app.marked.contains(&server.name) { "●" } else { " " };
 
             Row::new(vec![
-                Cell::from(display_name).style(name_style),
-                Cell::from(cert_str).style(cert_style),
+                Cell::from(marker).style(fg(app.use_color, app.theme.mark)),
+                Cell::from(truncate(&name, name_width)),
+                Cell::from(compact_expiry(expires, now)).style(cert_color(expires, app)),
                 Cell::from(status_text).style(status_style),
             ])
         })
         .collect();
 
     let widths = [
-        Constraint::Fill(1),    // NAME
-        Constraint::Length(13), // CERT EXPIRES (YYYY-MM-DD + padding)
-        Constraint::Length(20), // STATUS (fits "⚠ No credential" + spinner)
+        Constraint::Length(1), // mark
+        Constraint::Fill(1),   // NAME
+        Constraint::Length(4), // CERT: "123d", "5h", "exp"
+        Constraint::Length(1), // STATUS icon
     ];
-
-    let highlight_style = if app.use_color {
-        Style::default()
-            .bg(Color::Blue)
-            .fg(Color::White)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
-    };
-
     let table = Table::new(rows, widths)
         .header(
-            Row::new(["NAME", "CERT EXPIRES", "STATUS"])
-                .style(Style::default().add_modifier(Modifier::DIM | Modifier::BOLD)),
+            Row::new(["", "NAME", "CERT", "S"]).style(Style::default().add_modifier(Modifier::DIM | Modifier::BOLD)),
         )
-        .row_highlight_style(highlight_style)
+        .row_highlight_style(highlight_style(app))
         .highlight_symbol("▶ ");
 
     frame.render_stateful_widget(table, area, &mut app.table_state);
 }
 
-fn render_status_bar(frame: &mut Frame, app: &AppState, area: ratatui::layout::Rect) {
-    // Show notification for 3s, then fall back to key hints
-    let content = if let Some((msg, _)) = &app.notification {
+/// The just-finished ✓/✗: bold, so it stands out from the status beside it.
+fn result_style(ok: bool, app: &AppState) -> Style {
+    fg(app.use_color, if ok { app.theme.ok } else { app.theme.error }).add_modifier(Modifier::BOLD)
+}
+
+fn highlight_style(app: &AppState) -> Style {
+    if app.use_color {
+        Style::default()
+            .bg(app.theme.selection)
+            .fg(app.theme.selection_text)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    }
+}
+
+fn render_status_bar(frame: &mut Frame, app: &AppState, area: ratatui::layout::Rect, compact: bool) {
+    // While searching the query replaces everything else; otherwise show a notification
+    // for 3s, then fall back to key hints
+    let content = if app.search_editing {
+        let total = app.visible_servers().len();
+        Line::from(vec![
+            Span::raw(format!(" /{}│", app.search.as_deref().unwrap_or(""))),
+            Span::styled(
+                format!(
+                    "  {} match{}  Enter:keep  Esc:clear",
+                    total,
+                    if total == 1 { "" } else { "es" }
+                ),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ])
+    } else if let Some((msg, _)) = &app.notification {
         Line::from(vec![Span::styled(
             format!(" {} ", msg),
-            Style::default().fg(if app.use_color { Color::Cyan } else { Color::Reset }),
+            Style::default().fg(if app.use_color { app.theme.accent } else { Color::Reset }),
         )])
     } else {
-        let total = app.config.servers.len();
-        let counter = match app.table_state.selected() {
+        let total = app.visible_servers().len();
+        let mut counter = match app.table_state.selected() {
             Some(sel) => format!(" {}/{} ", sel + 1, total),
             None => format!(" –/{} ", total),
         };
+        if !app.marked.is_empty() {
+            counter.push_str(&format!(
+                "{} marked ({}:clear) ",
+                app.marked.len(),
+                app.keys.label(Action::ClearMarks)
+            ));
+        }
 
-        let hints = " f:force-fetch  F:force-all  c:cred  a:add  D:del  d:dry-run  e:edit  ?:help  q:quit ";
+        let hints = if compact {
+            app.keys.hints(&[
+                (Action::Fetch, "fetch"),
+                (Action::Search, "search"),
+                (Action::Add, "add"),
+                (Action::Help, "help"),
+                (Action::Quit, "quit"),
+            ])
+        } else {
+            app.keys.hints(&[
+                (Action::Mark, "mark"),
+                (Action::Search, "search"),
+                (Action::Fetch, "force-fetch"),
+                (Action::FetchAll, "force-all"),
+                (Action::TagFilter, "tag"),
+                (Action::Credentials, "cred"),
+                (Action::Add, "add"),
+                (Action::Delete, "del"),
+                (Action::DryRun, "dry-run"),
+                (Action::EditConfig, "edit"),
+                (Action::Help, "help"),
+                (Action::Quit, "quit"),
+            ])
+        };
+        let hints = format!(" {} ", hints);
 
         Line::from(vec![
             Span::styled(hints, Style::default().add_modifier(Modifier::DIM)),
@@ -184,7 +434,7 @@ fn render_status_bar(frame: &mut Frame, app: &AppState, area: ratatui::layout::R
 }
 
 /// Error overlay — displays an error message over the dimmed dashboard.
-pub fn render_error_overlay(frame: &mut Frame, message: &str) {
+pub fn render_error_overlay(frame: &mut Frame, message: &str, app: &AppState) {
     let area = frame.area();
     let popup_width = (message.len() as u16 + 6)
         .max(40)
@@ -196,7 +446,7 @@ pub fn render_error_overlay(frame: &mut Frame, message: &str) {
     let block = Block::bordered()
         .border_type(BorderType::Rounded)
         .title(" Error ")
-        .title_style(Style::default().fg(Color::Red));
+        .title_style(fg(app.use_color, app.theme.error));
 
     let inner = block.inner(popup_area);
     frame.render_widget(block, popup_area);
@@ -204,7 +454,34 @@ pub fn render_error_overlay(frame: &mut Frame, message: &str) {
     let content = format!("{}\n\nPress any key to dismiss.", message);
     frame.render_widget(
         Paragraph::new(content)
-            .style(Style::default().fg(Color::Red))
+            .style(fg(app.use_color, app.theme.error))
+            .wrap(Wrap { trim: true }),
+        inner,
+    );
+}
+
+/// Delete confirmation overlay for the marked servers.
+pub fn render_marked_delete_confirm(frame: &mut Frame, server_names: &[String]) {
+    let area = frame.area();
+    let list = server_names.join(", ");
+    let popup_width = (list.chars().count() as u16 + 6).clamp(40, 70).min(area.width - 4);
+    let popup_area = centered_rect(popup_width, 6, area);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title(" Delete Marked Servers ");
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let lines = vec![
+        Line::from(format!("Delete {} servers? [y/N]", server_names.len())),
+        Line::from(Span::styled(list, Style::default().add_modifier(Modifier::DIM))),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
             .wrap(Wrap { trim: true }),
         inner,
     );
@@ -234,47 +511,121 @@ pub fn handle_key(
     tx: &mpsc::Sender<AppEvent>,
     terminal: &mut ratatui::DefaultTerminal,
 ) -> bool {
+    if app.search_editing {
+        handle_search_key(app, key);
+        return false;
+    }
+
     let selected_name: Option<String> = app
         .table_state
         .selected()
-        .and_then(|i| app.config.servers.get(i))
-        .map(|s| s.name.clone());
+        .and_then(|i| app.visible_servers().get(i).map(|s| s.name.clone()));
 
-    match key.code {
-        KeyCode::Char('q') => return true,
-        KeyCode::Char('j') | KeyCode::Down => {
+    let action = match key.code {
+        KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Up => Some(Action::MoveUp),
+        _ => app.keys.action(Scope::Dashboard, &key),
+    };
+    match action {
+        Some(Action::Quit) => return true,
+        Some(Action::MoveDown) => {
             app.table_state.select_next();
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Some(Action::MoveUp) => {
             app.table_state.select_previous();
         }
-        KeyCode::Char('g') => {
+        Some(Action::First) => {
             app.table_state.select_first();
         }
-        KeyCode::Char('G') => {
+        Some(Action::Last) => {
             app.table_state.select_last();
         }
-        KeyCode::Enter => {
+        None if key.code == KeyCode::Enter => {
             if let Some(name) = selected_name {
                 app.view = View::Detail(name);
             }
         }
-        KeyCode::Char('f') => {
-            if let Some(name) = selected_name
-                && !app.in_progress.contains(&name)
-                && let Some(server) = app.config.servers.iter().find(|s| s.name == name).cloned()
-            {
-                crate::tui::start_fetch(app, server, tx);
+        Some(Action::Mark) => {
+            if let Some(name) = selected_name {
+                if !app.marked.remove(&name) {
+                    app.marked.insert(name);
+                }
+                app.table_state.select_next();
             }
         }
-        KeyCode::Char('F') => {
-            for server in app.config.servers.clone() {
-                if !app.in_progress.contains(&server.name) {
+        Some(Action::ClearMarks) => {
+            app.marked.clear();
+        }
+        Some(Action::Fetch) => {
+            for name in app.marked_or_selected() {
+                if !app.in_progress.contains(&name)
+                    && let Some(server) = app.config.servers.iter().find(|s| s.name == name).cloned()
+                {
                     crate::tui::start_fetch(app, server, tx);
                 }
             }
+            app.marked.clear();
         }
-        KeyCode::Char('c') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Some(Action::Renew) => {
+            if let Some(name) = selected_name {
+                super::renew::open_confirm(app, &name);
+            }
+        }
+        Some(Action::OpenCluster) => {
+            if let Some(name) = selected_name {
+                super::open_cluster::open(terminal, app, &name);
+            }
+        }
+        Some(Action::SwitchContext) => {
+            if let Some(name) = selected_name {
+                switch_context(app, &name);
+            }
+        }
+        Some(Action::Cancel) => {
+            let names: Vec<String> = app
+                .marked_or_selected()
+                .into_iter()
+                .filter(|name| crate::tui::cancel_fetch(app, name))
+                .collect();
+            if !names.is_empty() {
+                app.notification = Some((
+                    format!("Cancelling fetch of {}…", names.join(", ")),
+                    std::time::Instant::now(),
+                ));
+            }
+        }
+        Some(Action::FetchAll) => super::fetch_all::open_confirm(app),
+        Some(Action::CredentialsAll) => {
+            let names: Vec<String> = app
+                .visible_servers()
+                .into_iter()
+                .map(|s| s.name.clone())
+                .filter(|name| !super::is_vault_server(app, name))
+                .collect();
+            if names.is_empty() {
+                app.notification = Some((
+                    "No listed servers to set a password for".to_string(),
+                    std::time::Instant::now(),
+                ));
+                return false;
+            }
+            app.credential_input.clear();
+            app.view = View::MarkedCredentialInput(names);
+        }
+        Some(Action::Credentials) if !app.marked.is_empty() => {
+            let names: Vec<String> = app
+                .marked_or_selected()
+                .into_iter()
+                .filter(|name| !super::is_vault_server(app, name))
+                .collect();
+            if names.is_empty() {
+                app.notification = Some(("Credentials managed by vault".to_string(), std::time::Instant::now()));
+                return false;
+            }
+            app.credential_input.clear();
+            app.view = View::MarkedCredentialInput(names);
+        }
+        Some(Action::Credentials) => {
             if let Some(name) = selected_name {
                 if super::is_vault_server(app, &name) {
                     app.notification = Some(("Credentials managed by vault".to_string(), std::time::Instant::now()));
@@ -283,15 +634,79 @@ pub fn handle_key(
                 app.view = View::CredentialMenu(name);
             }
         }
-        KeyCode::Char('d') => {
+        Some(Action::Search) => {
+            app.search_editing = true;
+            if app.search.is_none() {
+                app.set_search(Some(String::new()));
+            }
+        }
+        Some(Action::NextMatch) => app.select_match(true),
+        Some(Action::PrevMatch) => app.select_match(false),
+        None if key.code == KeyCode::Esc && app.search.is_some() => app.set_search(None),
+        Some(Action::CertDisplay) => {
+            app.cert_display = match app.cert_display {
+                crate::config::CertDisplay::Date => crate::config::CertDisplay::Relative,
+                crate::config::CertDisplay::Relative => crate::config::CertDisplay::Date,
+            };
+            let msg = match app.cert_display {
+                crate::config::CertDisplay::Date => "Cert expiry shown as dates",
+                crate::config::CertDisplay::Relative => "Cert expiry shown as time left",
+            };
+            app.notification = Some((msg.to_string(), std::time::Instant::now()));
+        }
+        Some(Action::TagFilter) => {
+            app.cycle_tag_filter();
+            let msg = match &app.tag_filter {
+                Some(tag) => format!("Showing servers tagged '{}'", tag),
+                None if app.config.tags().is_empty() => "No tagged servers — add tags = [...] in config".to_string(),
+                None => "Showing all servers".to_string(),
+            };
+            app.notification = Some((msg, std::time::Instant::now()));
+        }
+        Some(Action::FilterAll) => app.set_status_filter(StatusFilter::All),
+        Some(Action::FilterFailing) => app.set_status_filter(StatusFilter::Failing),
+        Some(Action::FilterExpiring) => app.set_status_filter(StatusFilter::Expiring),
+        Some(Action::FilterNeverFetched) => app.set_status_filter(StatusFilter::NeverFetched),
+        Some(Action::DryRun) => {
             app.dry_run = !app.dry_run;
             let msg = if app.dry_run { "Dry-run ON" } else { "Dry-run OFF" };
             app.notification = Some((msg.to_string(), std::time::Instant::now()));
         }
-        KeyCode::Char('a') => {
+        Some(Action::Add) => {
             app.view = View::Wizard(WizardState::default());
         }
-        KeyCode::Char('D') => {
+        Some(Action::Import) => super::import::open(app),
+        Some(Action::Discover) => super::import::open_discover(app),
+        Some(Action::Prune) => super::prune::open(app),
+        Some(Action::AuditLog) => super::audit::open(app),
+        Some(Action::Notifications) => super::notifications::open(app),
+        Some(Action::Probe) => super::remote_cert::probe_all(app, tx),
+        Some(Action::Rename) => {
+            if let Some(name) = selected_name {
+                super::rename::open(app, name);
+            }
+        }
+        Some(Action::Edit) => {
+            if let Some(name) = selected_name {
+                super::open_edit_wizard(app, &name);
+            }
+        }
+        Some(Action::Delete) if !app.marked.is_empty() => {
+            let names: Vec<String> = app
+                .marked_or_selected()
+                .into_iter()
+                .filter(|name| !super::is_vault_server(app, name))
+                .collect();
+            if names.is_empty() {
+                app.notification = Some((
+                    "Vault servers are managed in Bitwarden".to_string(),
+                    std::time::Instant::now(),
+                ));
+                return false;
+            }
+            app.view = View::MarkedDeleteConfirm(names);
+        }
+        Some(Action::Delete) => {
             if let Some(name) = selected_name {
                 if super::is_vault_server(app, &name) {
                     app.notification = Some((
@@ -303,14 +718,12 @@ pub fn handle_key(
                 app.view = View::DeleteConfirm(name);
             }
         }
-        KeyCode::Char('?') => {
-            app.prior_view = Some(Box::new(View::Dashboard));
-            app.view = View::Help;
-        }
-        KeyCode::Char('e') => {
+        Some(Action::Help) => super::help::open(app, View::Dashboard),
+        Some(Action::EditConfig) => {
             open_editor(terminal, app);
         }
-        KeyCode::Char('B') => {
+        Some(Action::Settings) => super::settings::open(app),
+        Some(Action::Bitwarden) => {
             // Open Bitwarden configuration wizard pre-filled from current config
             use crate::tui::app::{SetupStep, SetupWizardState};
             let bw = app.config.bitwarden.as_ref();
@@ -332,6 +745,32 @@ pub fn handle_key(
     false
 }
 
+/// Keys while the `/` query is being typed: the list narrows on every keystroke,
+/// Enter keeps the filter and returns to navigation, Esc clears it.
+fn handle_search_key(app: &mut AppState, key: KeyEvent) {
+    let mut query = app.search.clone().unwrap_or_default();
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => query.push(c),
+        KeyCode::Backspace => {
+            query.pop();
+        }
+        KeyCode::Enter => {
+            app.search_editing = false;
+            if query.is_empty() {
+                app.set_search(None);
+            }
+            return;
+        }
+        KeyCode::Esc => {
+            app.search_editing = false;
+            app.set_search(None);
+            return;
+        }
+        _ => return,
+    }
+    app.set_search(Some(query));
+}
+
 pub fn handle_key_delete_confirm(app: &mut AppState, name: String, key: KeyEvent) -> bool {
     match key.code {
         KeyCode::Char('y') => {
@@ -345,28 +784,85 @@ pub fn handle_key_delete_confirm(app: &mut AppState, name: String, key: KeyEvent
     false
 }
 
+pub fn handle_key_marked_delete_confirm(app: &mut AppState, names: Vec<String>, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('y') => {
+            for name in &names {
+                if !remove_server(app, name) {
+                    return false;
+                }
+            }
+            app.marked.clear();
+            app.notification = Some((
+                format!("Deleted {} servers: {}", names.len(), names.join(", ")),
+                std::time::Instant::now(),
+            ));
+            app.view = View::Dashboard;
+        }
+        KeyCode::Char('n') | KeyCode::Esc => {
+            app.view = View::Dashboard;
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Sets `current-context` in ~/.kube/config to the server's context (kubectx-style).
+fn switch_context(app: &mut AppState, server_name: &str) {
+    let Some(context) = app
+        .config
+        .servers
+        .iter()
+        .find(|s| s.name == server_name)
+        .map(|s| s.kube_context().to_string())
+    else {
+        return;
+    };
+    let msg = if app.dry_run {
+        format!("DRY-RUN: would switch kubectl context to '{}'", context)
+    } else {
+        match crate::kube::main_kubeconfig_path().and_then(|path| crate::kube::use_context(&path, &context)) {
+            Ok(()) => format!("kubectl context → {}", context),
+            Err(e) => format!("Could not switch context: {}", e),
+        }
+    };
+    app.notification = Some((msg, std::time::Instant::now()));
+}
+
 fn perform_delete(app: &mut AppState, server_name: &str) {
+    if remove_server(app, server_name) {
+        app.notification = Some((format!("Deleted server: {}", server_name), std::time::Instant::now()));
+        app.view = View::Dashboard;
+    }
+}
+
+/// Removes a server from config.toml and every in-memory cache. On failure shows the
+/// error and returns false.
+fn remove_server(app: &mut AppState, server_name: &str) -> bool {
     // Remove from config.toml
     if let Err(e) = crate::config::remove_server(&app.config_path, server_name) {
-        let msg = format!("Couldn't delete server: {}", e);
+        let msg = format!("Couldn't delete server '{}': {}", server_name, e);
         app.view = View::Error { message: msg };
-        return;
+        return false;
     }
 
     // Delete the cached local file if it exists
-    let mut local_path = std::path::PathBuf::from(&app.config.local_output_dir);
-    local_path.push(server_name);
-    let _ = std::fs::remove_file(&local_path); // non-fatal
+    let _ = std::fs::remove_file(app.config.local_path(server_name)); // non-fatal
 
     // Remove from in-memory state
     app.config.servers.retain(|s| s.name != server_name);
     app.server_states.remove(server_name);
     app.cert_cache.remove(server_name);
+    app.ca_cache.remove(server_name);
     app.in_progress.remove(server_name);
-    app.flash_rows.remove(server_name);
+    app.fetch_progress.remove(server_name);
+    app.fetch_queue.retain(|q| q.name != server_name);
+    app.last_results.remove(server_name);
+    app.marked.remove(server_name);
+    super::fetch_all::on_finished(app, server_name, None);
 
     // Clamp selection
-    let total = app.config.servers.len();
+    let total = app.visible_servers().len();
     if total == 0 {
         app.table_state = ratatui::widgets::TableState::default();
     } else if let Some(sel) = app.table_state.selected()
@@ -374,9 +870,7 @@ fn perform_delete(app: &mut AppState, server_name: &str) {
     {
         app.table_state.select_last();
     }
-
-    app.notification = Some((format!("Deleted server: {}", server_name), std::time::Instant::now()));
-    app.view = View::Dashboard;
+    true
 }
 
 fn relative_age(dt: &chrono::DateTime<chrono::Utc>) -> String {
@@ -393,13 +887,13 @@ fn relative_age(dt: &chrono::DateTime<chrono::Utc>) -> String {
 }
 
 fn open_editor(terminal: &mut ratatui::DefaultTerminal, app: &mut AppState) {
-    ratatui::restore();
+    crate::tui::restore_terminal();
 
     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
     let _ = std::process::Command::new(&editor).arg(&app.config_path).status();
 
     // Reinit terminal and overwrite the handle in place
-    *terminal = ratatui::init();
+    *terminal = crate::tui::init_terminal();
 
     // Reload config
     let path_str = app.config_path.to_string_lossy().to_string();
@@ -4,13 +4,16 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Layout},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Paragraph},
 };
 
-use super::{cert_color, cert_expires_display, status_color, status_display};
-use crate::tui::app::{AppEvent, AppState, EditServerState, ProbeState, View};
+use super::{
+    ca_color, cert_color, cert_expires_display, cert_expires_long, run_duration, status_color, status_display,
+};
+use crate::tui::app::{ApiProbeState, AppEvent, AppState, EditServerState, ProbeState, View};
+use crate::tui::keys::{Action, Scope};
 
 pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
     let area = frame.area();
@@ -28,6 +31,7 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
 
     let state = app.server_states.get(server_name).cloned();
     let cert_expires_at = app.cert_cache.get(server_name).and_then(|v| *v);
+    let ca_expires_at = app.ca_cache.get(server_name).and_then(|v| *v);
     let use_color = app.use_color;
     let config = &app.config;
 
@@ -54,22 +58,30 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         .to_string();
 
     let context_name = server.context_name.as_deref().unwrap_or("—").to_string();
+    let (target_ip, api_port) = server.api_endpoint();
 
     // Credential status — read from cache populated at startup and after credential changes
     let cred_stored = app.cred_cache.get(server_name).copied().unwrap_or(false);
     let cred_text = if cred_stored { "Stored" } else { "Not stored" };
     let cred_style = if !cred_stored && use_color {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.warning)
     } else {
         Style::default()
     };
 
     // Cert expiry — read from cert_cache (sourced from the kubeconfig file directly)
-    let cert_value = match cert_expires_at {
-        Some(ref exp) => exp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-        None => cert_expires_display(None),
+    // Token and exec-plugin users have no cert to expire; say so instead of a bare dash.
+    let user_auth = match cert_expires_at {
+        Some(_) => None,
+        None => crate::kube::local_user_auth(&config.local_path(server_name))
+            .filter(|auth| *auth != crate::kube::UserAuth::ClientCert),
+    };
+    let cert_value = match (cert_expires_at, user_auth) {
+        (Some(ref exp), _) => cert_expires_long(exp, app),
+        (None, Some(auth)) => format!("— (authenticates with {})", auth),
+        (None, None) => cert_expires_display(None, app),
     };
-    let cert_style = cert_color(cert_expires_at.as_ref(), use_color);
+    let cert_style = cert_color(cert_expires_at.as_ref(), app);
 
     // Last updated
     let last_updated = state
@@ -77,6 +89,11 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         .and_then(|s| s.last_updated)
         .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
         .unwrap_or_else(|| "—".to_string());
+    let last_duration = state
+        .as_ref()
+        .and_then(|s| s.duration_ms)
+        .map(|ms| format!(" (took {})", run_duration(ms)))
+        .unwrap_or_default();
 
     // Status
     let status_text;
@@ -84,7 +101,7 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
     match state.as_ref() {
         Some(s) => {
             status_text = status_display(&s.status).to_string();
-            status_style = status_color(&s.status, use_color);
+            status_style = status_color(&s.status, app);
         }
         None => {
             status_text = "—".to_string();
@@ -100,7 +117,7 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         .to_string();
     let has_error = state.as_ref().map(|s| s.error.is_some()).unwrap_or(false);
     let error_style = if has_error && use_color {
-        Style::default().fg(Color::Red)
+        Style::default().fg(app.theme.error)
     } else {
         Style::default()
     };
@@ -111,6 +128,10 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
             if name == server_name { Some(state.clone()) } else { None }
         },
     );
+    let api_probe_state = app
+        .api_probe
+        .as_ref()
+        .and_then(|(name, state)| if name == server_name { Some(state.clone()) } else { None });
     let spinner_char = app.spinner.current();
 
     // Separator line (fills available width, capped at content width)
@@ -137,6 +158,10 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
             Span::styled("  Remote filename:  ", label_style),
             Span::raw(file_name),
         ]),
+        Line::from(vec![
+            Span::styled("  Local file:       ", label_style),
+            Span::raw(config.local_path(server_name).display().to_string()),
+        ]),
         Line::from(vec![
             Span::styled("  Cluster IP:       ", label_style),
             Span::raw(server.target_cluster_ip.clone()),
@@ -145,6 +170,10 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
             Span::styled("  Context name:     ", label_style),
             Span::raw(context_name),
         ]),
+        Line::from(vec![
+            Span::styled("  Namespace:        ", label_style),
+            Span::raw(server.namespace.clone().unwrap_or_else(|| "—".to_string())),
+        ]),
         Line::from(vec![
             Span::styled("  Source:           ", label_style),
             Span::raw(if super::is_vault_server(app, server_name) {
@@ -158,9 +187,25 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
             Span::styled("  Cert expires:     ", label_style),
             Span::styled(cert_value, cert_style),
         ]),
+        match ca_expires_at {
+            Some(ref exp) => Line::from(vec![
+                Span::styled("  CA expires:       ", label_style),
+                Span::styled(cert_expires_long(exp, app), ca_color(exp, app)),
+                Span::styled(
+                    if *exp - chrono::Utc::now() <= app.config.ca_warning() {
+                        " — plan the CA rotation (every client needs the new CA)"
+                    } else {
+                        ""
+                    },
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]),
+            None => Line::from(vec![Span::styled("  CA expires:       ", label_style), Span::raw("—")]),
+        },
         Line::from(vec![
             Span::styled("  Last updated:     ", label_style),
             Span::raw(last_updated),
+            Span::styled(last_duration, Style::default().add_modifier(Modifier::DIM)),
         ]),
         Line::from(vec![
             Span::styled("  Credential:       ", label_style),
@@ -194,10 +239,10 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         Some(ProbeState::Done(server_expiry)) => {
             let now = chrono::Utc::now();
             let server_cert_str = match server_expiry {
-                Some(exp) => exp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                Some(exp) => cert_expires_long(&exp, app),
                 None => "—".to_string(),
             };
-            let server_cert_style = cert_color(server_expiry.as_ref(), use_color);
+            let server_cert_style = cert_color(server_expiry.as_ref(), app);
 
             // Comparison note: only highlight when there's a meaningful discrepancy
             let note = match (cert_expires_at, server_expiry) {
@@ -218,7 +263,7 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         }
         Some(ProbeState::Failed(err)) => {
             let err_style = if use_color {
-                Style::default().fg(Color::Red)
+                Style::default().fg(app.theme.error)
             } else {
                 Style::default()
             };
@@ -229,6 +274,84 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
         }
     }
 
+    // ── API server cert probe section ────────────────────────────────────────
+    match &api_probe_state {
+        None => {
+            lines.push(Line::from(vec![
+                Span::styled("  API cert:         ", label_style),
+                Span::styled(
+                    format!("press P to probe :{}", api_port),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]));
+        }
+        Some(ApiProbeState::Probing) => {
+            lines.push(Line::from(vec![
+                Span::styled("  API cert:         ", label_style),
+                Span::raw(format!("{} Probing…", spinner_char)),
+            ]));
+        }
+        Some(ApiProbeState::Done(cert)) => {
+            lines.push(Line::from(vec![
+                Span::styled("  API cert:         ", label_style),
+                Span::styled(
+                    cert_expires_long(&cert.not_after, app),
+                    cert_color(Some(&cert.not_after), app),
+                ),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("  API cert SANs:    ", label_style),
+                Span::raw(cert.sans()),
+            ]));
+            if !cert.covers(&target_ip) {
+                lines.push(Line::from(vec![
+                    Span::styled("                    ", label_style),
+                    Span::styled(
+                        format!("⚠ {} is not in the SANs — kubectl will reject this cert", target_ip),
+                        if use_color {
+                            Style::default().fg(app.theme.warning)
+                        } else {
+                            Style::default()
+                        },
+                    ),
+                ]));
+            }
+        }
+        Some(ApiProbeState::Failed(err)) => {
+            let err_style = if use_color {
+                Style::default().fg(app.theme.error)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled("  API cert:         ", label_style),
+                Span::styled(format!("probe failed: {}", err), err_style),
+            ]));
+        }
+    }
+
+    // ── Certificate details (i) ──────────────────────────────────────────────
+    if let Some((name, client)) = &app.cert_details
+        && name == server_name
+    {
+        lines.push(Line::from(Span::raw(format!("  {}", sep))));
+        match client {
+            Some(details) => push_cert_details(&mut lines, "Client cert", details, app),
+            None => lines.push(Line::from(vec![
+                Span::styled("  Client cert:      ", label_style),
+                Span::styled(
+                    user_auth.map_or("no readable cert in the cached kubeconfig".to_string(), |auth| {
+                        format!("none, the user authenticates with {}", auth)
+                    }),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ])),
+        }
+        if let Some(ApiProbeState::Done(cert)) = &api_probe_state {
+            push_cert_details(&mut lines, "API cert", &cert.details, app);
+        }
+    }
+
     // Outer layout: border block | content | footer
     let title = format!(" Server Detail: {} ", server_name);
     let outer_block = Block::bordered().border_type(BorderType::Rounded).title(title);
@@ -242,31 +365,105 @@ pub fn render(frame: &mut Frame, app: &mut AppState, server_name: &str) {
     let content = Paragraph::new(lines);
     frame.render_widget(content, inner_chunks[0]);
 
-    let footer_text = if super::is_vault_server(app, server_name) {
-        "  f:force-fetch  p:probe  Esc:back  ?:help"
-    } else {
-        "  f:force-fetch  p:probe  c:cred  e:edit config  Esc:back  ?:help"
-    };
+    let mut hints = vec![
+        (Action::Fetch, "force-fetch"),
+        (Action::Probe, "probe"),
+        (Action::ProbeApi, "api-probe"),
+        (Action::CertDetails, "certs"),
+        (Action::Rollback, "history"),
+    ];
+    if !super::is_vault_server(app, server_name) {
+        hints.extend([
+            (Action::Credentials, "cred"),
+            (Action::EditInline, "edit"),
+            (Action::Edit, "wizard"),
+        ]);
+    }
+    hints.push((Action::ViewKubeconfig, "view"));
+    let footer_text = format!(
+        "  {}  Esc:back  {}:help",
+        app.keys.hints(&hints),
+        app.keys.label(Action::Help)
+    );
     let footer = Paragraph::new(Line::from(vec![Span::raw(footer_text)]));
     frame.render_widget(footer, inner_chunks[1]);
 }
 
-pub fn handle_key(app: &mut AppState, name: String, key: KeyEvent, tx: &mpsc::Sender<AppEvent>) -> bool {
+/// Subject, issuer, serial, SANs, key and validity of one cert, under a `title` line.
+fn push_cert_details(lines: &mut Vec<Line>, title: &str, details: &crate::tls::CertDetails, app: &AppState) {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    lines.push(Line::from(Span::styled(
+        format!("  {}", title),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    let mut field = |label: &str, value: String, style: Style| {
+        lines.push(Line::from(vec![
+            Span::raw(format!("    {:<16}", label)),
+            Span::styled(value, style),
+        ]));
+    };
+    field("Subject:", details.subject.clone(), Style::default());
+    field("Issuer:", details.issuer.clone(), Style::default());
+    field("Serial:", details.serial.clone(), dim);
+    if !details.sans.is_empty() {
+        field("SANs:", details.sans.clone(), Style::default());
+    }
+    field("Key:", details.key_algorithm.clone(), Style::default());
+    field(
+        "Not before:",
+        details.not_before.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        Style::default(),
+    );
+    field(
+        "Not after:",
+        cert_expires_long(&details.not_after, app),
+        cert_color(Some(&details.not_after), app),
+    );
+}
+
+pub fn handle_key(
+    app: &mut AppState,
+    name: String,
+    key: KeyEvent,
+    tx: &mpsc::Sender<AppEvent>,
+    terminal: &mut ratatui::DefaultTerminal,
+) -> bool {
     let is_vault = super::is_vault_server(app, &name);
 
-    match key.code {
-        KeyCode::Esc | KeyCode::Char('q') => {
+    let action = match key.code {
+        KeyCode::Esc => Some(Action::Quit),
+        _ => app.keys.action(Scope::Detail, &key),
+    };
+    match action {
+        Some(Action::Quit) => {
             app.probe = None;
+            app.cert_details = None;
             app.view = View::Dashboard;
         }
-        KeyCode::Char('f') => {
+        Some(Action::CertDetails) => {
+            app.cert_details = match &app.cert_details {
+                Some((open, _)) if open == &name => None,
+                _ => {
+                    let path = app.config.local_path(&name);
+                    Some((name.clone(), crate::kube::local_client_cert_details(&path)))
+                }
+            };
+        }
+        Some(Action::Rollback) => super::history::open(app, &name),
+        Some(Action::Fetch) => {
             if !app.in_progress.contains(&name)
                 && let Some(server) = app.config.servers.iter().find(|s| s.name == name).cloned()
             {
                 crate::tui::start_fetch(app, server, tx);
             }
         }
-        KeyCode::Char('p') => {
+        Some(Action::Cancel) => {
+            let cancelled = crate::tui::cancel_fetch(app, &name);
+            if cancelled {
+                app.notification = Some((format!("Cancelling fetch of {}…", name), std::time::Instant::now()));
+            }
+        }
+        Some(Action::Probe) => {
             let already_probing = app
                 .probe
                 .as_ref()
@@ -274,17 +471,29 @@ pub fn handle_key(app: &mut AppState, name: String, key: KeyEvent, tx: &mpsc::Se
                 .unwrap_or(false);
             if !already_probing && let Some(server) = app.config.servers.iter().find(|s| s.name == name).cloned() {
                 app.probe = Some((name.clone(), ProbeState::Probing));
-                spawn_probe(server, app.config.clone(), tx.clone());
+                let key_passphrase = crate::tui::session_key_passphrase(app, &server);
+                spawn_probe(server, app.config.clone(), key_passphrase, tx.clone());
             }
         }
-        KeyCode::Char('c') => {
+        Some(Action::Renew) => {
+            super::renew::open_confirm(app, &name);
+        }
+        Some(Action::ProbeApi) => {
+            let already_probing = matches!(&app.api_probe, Some((n, ApiProbeState::Probing)) if n == &name);
+            if !already_probing && let Some(server) = app.config.servers.iter().find(|s| s.name == name) {
+                app.api_probe = Some((name.clone(), ApiProbeState::Probing));
+                let (host, port) = server.api_endpoint();
+                spawn_api_probe(name.clone(), host, port, tx.clone());
+            }
+        }
+        Some(Action::Credentials) => {
             if is_vault {
                 app.notification = Some(("Credentials managed by vault".to_string(), std::time::Instant::now()));
                 return false;
             }
             app.view = View::CredentialMenu(name);
         }
-        KeyCode::Char('e') => {
+        Some(Action::EditInline) => {
             if is_vault {
                 app.notification = Some((
                     "Vault servers are managed in Bitwarden".to_string(),
@@ -296,44 +505,67 @@ pub fn handle_key(app: &mut AppState, name: String, key: KeyEvent, tx: &mpsc::Se
                 app.view = View::EditServer(EditServerState::from_server(&server));
             }
         }
-        KeyCode::Char('?') => {
-            app.prior_view = Some(Box::new(View::Detail(name)));
-            app.view = View::Help;
+        Some(Action::Edit) => {
+            super::open_edit_wizard(app, &name);
+        }
+        Some(Action::ViewKubeconfig) => {
+            super::preview::open(app, &name);
         }
+        Some(Action::Notifications) => super::notifications::open(app),
+        Some(Action::OpenCluster) => super::open_cluster::open(terminal, app, &name),
+        Some(Action::Help) => super::help::open(app, View::Detail(name)),
         _ => {}
     }
     false
 }
 
-fn spawn_probe(server: crate::config::Server, config: crate::config::Config, tx: mpsc::Sender<AppEvent>) {
+fn spawn_api_probe(server_name: String, host: String, port: u16, tx: mpsc::Sender<AppEvent>) {
     std::thread::spawn(move || {
-        let result = do_probe(&server, &config).map_err(|e| crate::tui::friendly_error(&e));
+        let result = crate::tls::probe_serving_cert(&host, port, std::time::Duration::from_secs(5))
+            .map_err(|e| format!("{:#}", e));
+        tx.send(AppEvent::ApiProbeComplete { server_name, result }).ok();
+    });
+}
+
+fn spawn_probe(
+    server: crate::config::Server,
+    config: crate::config::Config,
+    key_passphrase: Option<String>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    std::thread::spawn(move || {
+        let mut prompt = None;
+        let result = do_probe(&server, &config, key_passphrase.as_deref()).map_err(|e| {
+            prompt = crate::tui::follow_up_prompt(&e, &server.name);
+            crate::tui::friendly_error(&e)
+        });
         tx.send(AppEvent::ProbeComplete {
             server_name: server.name,
             result,
         })
         .ok();
+        if let Some(event) = prompt {
+            tx.send(event).ok();
+        }
     });
 }
 
-fn do_probe(
+pub(super) fn do_probe(
     server: &crate::config::Server,
     config: &crate::config::Config,
+    key_passphrase: Option<&str>,
 ) -> Result<Option<chrono::DateTime<chrono::Utc>>, anyhow::Error> {
-    let user = server.user(config)?;
-    let remote_path_str = server.file_path(config)?;
-    let identity_file = server.identity_file(config);
-    let password = match crate::credentials::get_credential(&server.name) {
+    let password = match crate::credentials::resolve_credential(&server.name, config.credentials_from_env()) {
         crate::credentials::CredentialResult::Found(pw) => Some(pw),
         _ => None,
     };
-    let contents = crate::ssh::fetch_remote_file(
-        &server.name,
-        &server.address,
-        user,
-        &remote_path_str,
-        identity_file,
-        password.as_deref(),
+    let (contents, _) = crate::fetch::fetch_source(
+        server,
+        config,
+        password.as_ref().map(|pw| pw.as_str()),
+        key_passphrase,
+        &|_| {},
+        None,
     )?;
     Ok(crate::kube::parse_cert_expiry_from_bytes(&contents))
 }
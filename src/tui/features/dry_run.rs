@@ -0,0 +1,98 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use super::{centered_rect, fg, render_dim_background};
+use crate::tui::app::{AppState, View};
+
+/// Shows the diff of a finished dry-run fetch. While the overlay is open, diffs from
+/// other servers in the same batch are appended instead of replacing it.
+pub fn on_diff(app: &mut AppState, server_name: &str, diff: &str) {
+    if diff.is_empty() {
+        app.notification = Some((
+            format!("{}: dry run — nothing would change", server_name),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    let new_lines = diff.lines().map(str::to_string);
+    match &mut app.view {
+        View::DryRunDiff { lines, .. } => {
+            lines.push(String::new());
+            lines.extend(new_lines);
+        }
+        View::Dashboard | View::Detail(_) => {
+            let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+            app.view = View::DryRunDiff {
+                lines: new_lines.collect(),
+                scroll: 0,
+                return_to: Box::new(return_to),
+            };
+        }
+        _ => {}
+    }
+}
+
+fn highlight(line: &str, app: &AppState) -> Line<'static> {
+    let style = if line.starts_with("+++") || line.starts_with("---") {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else if line.starts_with('+') {
+        fg(app.use_color, app.theme.ok)
+    } else if line.starts_with('-') {
+        fg(app.use_color, app.theme.error)
+    } else if line.starts_with("@@") {
+        fg(app.use_color, app.theme.accent)
+    } else {
+        Style::default().add_modifier(Modifier::DIM)
+    };
+    Line::from(Span::styled(line.to_string(), style))
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::DryRunDiff { lines, scroll, .. } = &app.view else {
+        return;
+    };
+
+    let area = frame.area();
+    render_dim_background(frame, area);
+    let popup_area = centered_rect(area.width.saturating_sub(4), area.height.saturating_sub(2), area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Dry run — changes a real fetch would write ")
+        .title_bottom(" j/k:scroll  PgUp/PgDn:page  g/G:top/bottom  Esc:close ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text: Vec<Line> = lines.iter().map(|l| highlight(l, app)).collect();
+    frame.render_widget(
+        Paragraph::new(text).block(block).scroll((*scroll as u16, 0)),
+        popup_area,
+    );
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent, page: usize) -> bool {
+    let View::DryRunDiff { lines, scroll, .. } = &mut app.view else {
+        return false;
+    };
+    let last = lines.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => *scroll = (*scroll + page).min(last),
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => *scroll = last,
+        KeyCode::Esc | KeyCode::Char('q') => {
+            if let View::DryRunDiff { return_to, .. } = std::mem::replace(&mut app.view, View::Dashboard) {
+                app.view = *return_to;
+            }
+        }
+        _ => {}
+    }
+    false
+}
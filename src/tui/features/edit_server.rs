@@ -2,7 +2,7 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Clear, Paragraph},
 };
@@ -52,7 +52,7 @@ pub fn render(frame: &mut Frame, app: &AppState, state: &EditServerState) {
             value.clone()
         };
         let value_style = if focused && app.use_color {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD)
         } else if focused {
             Style::default().add_modifier(Modifier::BOLD)
         } else {
@@ -69,7 +69,7 @@ pub fn render(frame: &mut Frame, app: &AppState, state: &EditServerState) {
 
     if let Some(ref err) = state.error {
         let style = if app.use_color {
-            Style::default().fg(Color::Red)
+            Style::default().fg(app.theme.error)
         } else {
             Style::default()
         };
@@ -0,0 +1,207 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use std::sync::mpsc;
+
+use super::{centered_rect, fg};
+use crate::tui::app::{AppEvent, AppState, FetchAllRun, View};
+
+/// Asks before force-fetching every listed server (`F`).
+pub fn open_confirm(app: &mut AppState) {
+    if app.fetch_all.is_some() {
+        app.notification = Some((
+            "Fetch all is still running — x cancels single servers".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    let names: Vec<String> = app.visible_servers().iter().map(|s| s.name.clone()).collect();
+    if names.is_empty() {
+        app.notification = Some(("No listed servers to fetch".to_string(), std::time::Instant::now()));
+        return;
+    }
+    app.view = View::FetchAllConfirm(names);
+}
+
+pub fn render_confirm(frame: &mut Frame, app: &AppState) {
+    let View::FetchAllConfirm(names) = &app.view else {
+        return;
+    };
+    let running = names.iter().filter(|n| app.in_progress.contains(*n)).count();
+    let area = frame.area();
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(64), 8, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered().border_type(BorderType::Rounded).title(" Fetch All ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut lines = vec![Line::from(format!(
+        "Force-fetch {} listed server{}{}?",
+        names.len(),
+        if names.len() == 1 { "" } else { "s" },
+        if app.dry_run { " (dry-run)" } else { "" }
+    ))];
+    lines.push(Line::from(Span::styled(
+        format!("{} at a time over SSH", app.config.max_parallel()),
+        dim,
+    )));
+    if running > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("{} already being fetched will be skipped", running),
+            dim,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("[y/N]"));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false }),
+        inner,
+    );
+}
+
+pub fn handle_key_confirm(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>) -> bool {
+    let confirmed = match key.code {
+        KeyCode::Char('y') => true,
+        KeyCode::Char('n') | KeyCode::Esc => false,
+        _ => return false,
+    };
+    let View::FetchAllConfirm(names) = std::mem::replace(&mut app.view, View::Dashboard) else {
+        return false;
+    };
+    if confirmed {
+        start(app, names, tx);
+    }
+    false
+}
+
+fn start(app: &mut AppState, names: Vec<String>, tx: &mpsc::Sender<AppEvent>) {
+    let mut run = FetchAllRun::default();
+    for name in names {
+        if app.in_progress.contains(&name) {
+            run.skipped.push(name);
+        } else if let Some(server) = app.config.servers.iter().find(|s| s.name == name).cloned() {
+            run.pending.insert(name);
+            crate::tui::start_fetch(app, server, tx);
+        }
+    }
+    if run.pending.is_empty() {
+        app.notification = Some((
+            "Every listed server is already being fetched".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    app.fetch_all = Some(run);
+}
+
+/// Files a finished or cancelled fetch with the running fetch-all, and shows the
+/// summary once it was the last one. From views that can't be covered the summary
+/// is cut down to a notification.
+pub fn on_finished(app: &mut AppState, server_name: &str, result: Option<Result<(), String>>) {
+    let Some(run) = app.fetch_all.as_mut() else {
+        return;
+    };
+    if !run.record(server_name, result) {
+        return;
+    }
+    let Some(run) = app.fetch_all.take() else {
+        return;
+    };
+    if matches!(app.view, View::Dashboard | View::Detail(_)) {
+        let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+        app.view = View::FetchAllSummary {
+            run,
+            scroll: 0,
+            return_to: Box::new(return_to),
+        };
+    } else {
+        app.notification = Some((
+            format!(
+                "Fetch all done: {} fetched, {} failed, {} skipped",
+                run.fetched.len(),
+                run.failed.len(),
+                run.skipped.len()
+            ),
+            std::time::Instant::now(),
+        ));
+    }
+}
+
+pub fn render_summary(frame: &mut Frame, app: &AppState) {
+    let View::FetchAllSummary { run, scroll, .. } = &app.view else {
+        return;
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("✓ {} fetched", run.fetched.len()),
+            fg(app.use_color, app.theme.ok),
+        ),
+        Span::raw("   "),
+        Span::styled(
+            format!("✗ {} failed", run.failed.len()),
+            fg(app.use_color, app.theme.error),
+        ),
+        Span::raw("   "),
+        Span::styled(format!("— {} skipped", run.skipped.len()), dim),
+    ])];
+    if !run.failed.is_empty() {
+        lines.push(Line::from(""));
+        for (name, msg) in &run.failed {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {}", name), bold),
+                Span::styled(format!(": {}", msg), fg(app.use_color, app.theme.error)),
+            ]));
+        }
+    }
+    if !run.skipped.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  Skipped (already running or cancelled): {}", run.skipped.join(", ")),
+            dim,
+        )));
+    }
+
+    let area = frame.area();
+    let height = (lines.len() as u16 + 2).max(5).min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(80), height, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(" Fetch All — Done ")
+        .title_bottom(" j/k:scroll  any other key:close ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((*scroll as u16, 0)),
+        popup_area,
+    );
+}
+
+pub fn handle_key_summary(app: &mut AppState, key: KeyEvent) -> bool {
+    let View::FetchAllSummary { run, scroll, .. } = &mut app.view else {
+        return false;
+    };
+    let last = run.failed.len() + 2;
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        _ => {
+            if let View::FetchAllSummary { return_to, .. } = std::mem::replace(&mut app.view, View::Dashboard) {
+                app.view = *return_to;
+            }
+        }
+    }
+    false
+}
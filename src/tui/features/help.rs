@@ -1,4 +1,4 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     style::{Modifier, Style},
@@ -6,116 +6,366 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 
-use super::{centered_rect, render_dim_background};
-use crate::tui::app::{AppState, View};
+use super::{centered_rect, fg, render_dim_background};
+use crate::tui::app::{AppState, HelpState, View};
+use crate::tui::keys::Action;
 
-pub fn render(frame: &mut Frame, app: &mut AppState) {
-    render_dim_background(frame, frame.area());
+/// A titled group of key rows.
+struct Section {
+    title: &'static str,
+    /// True for the section describing the view help was opened from.
+    current: bool,
+    rows: Vec<(String, &'static str)>,
+}
 
-    let popup_height = (frame.area().height.saturating_sub(4)).min(36);
-    let area = centered_rect(65, popup_height, frame.area());
+/// Opens the help over `from` (`?`), scrolled to the section for that view.
+pub fn open(app: &mut AppState, from: View) {
+    app.prior_view = Some(Box::new(from));
+    let state = HelpState::default();
+    let scroll = help_lines(app, &state).1;
+    app.view = View::Help(HelpState { scroll, ..state });
+}
 
-    frame.render_widget(Clear, area);
+fn sections(app: &AppState) -> Vec<Section> {
+    let k = |action| app.keys.label(action);
+    let from_detail = matches!(app.prior_view.as_deref(), Some(View::Detail(_)));
+    vec![
+        Section {
+            title: "Dashboard",
+            current: !from_detail,
+            rows: vec![
+                (format!("{} / ↓", k(Action::MoveDown)), "Move down"),
+                (format!("{} / ↑", k(Action::MoveUp)), "Move up"),
+                (k(Action::First), "Go to first"),
+                (k(Action::Last), "Go to last"),
+                ("Enter".to_string(), "Open detail view"),
+                (k(Action::Mark), "Mark / unmark server"),
+                (k(Action::ClearMarks), "Clear marks"),
+                (k(Action::Fetch), "Force fetch selected (or marked) servers"),
+                (k(Action::FetchAll), "Force fetch all listed servers (asks first)"),
+                (
+                    k(Action::Probe),
+                    "Probe all listed servers for newer certs (REMOTE column)",
+                ),
+                (k(Action::Cancel), "Cancel fetch of selected (or marked) servers"),
+                (k(Action::Renew), "Renew certs on server (runs renew_command)"),
+                (k(Action::Search), "Search by name, address or context"),
+                (
+                    format!("{} / {}", k(Action::NextMatch), k(Action::PrevMatch)),
+                    "Next / previous match",
+                ),
+                ("Esc".to_string(), "Clear search"),
+                (k(Action::TagFilter), "Cycle tag filter"),
+                (
+                    [
+                        Action::FilterAll,
+                        Action::FilterFailing,
+                        Action::FilterExpiring,
+                        Action::FilterNeverFetched,
+                    ]
+                    .map(k)
+                    .join("/"),
+                    "Show all / failing / expiring / never fetched",
+                ),
+                (k(Action::CertDisplay), "Show cert expiry as dates / time left"),
+                (k(Action::SwitchContext), "Switch kubectl context to selected server"),
+                (
+                    k(Action::OpenCluster),
+                    "Open the cluster (runs open_command, k9s by default)",
+                ),
+                (
+                    k(Action::Credentials),
+                    "Manage credentials (set one password for marked)",
+                ),
+                (k(Action::CredentialsAll), "Set one password for all listed servers"),
+                (k(Action::Add), "Add server (wizard)"),
+                (k(Action::Import), "Import clusters from ~/.kube/config"),
+                (k(Action::Discover), "Scan a network for nodes to add"),
+                (k(Action::Edit), "Edit selected server (wizard)"),
+                (k(Action::Delete), "Delete selected (or marked) servers"),
+                (k(Action::Rename), "Rename selected server"),
+                (k(Action::Prune), "Prune leftovers of removed servers"),
+                (k(Action::AuditLog), "Credential audit log"),
+                (k(Action::Notifications), "Notifications shown this session"),
+                (k(Action::DryRun), "Toggle dry-run mode"),
+                (k(Action::Bitwarden), "Configure Bitwarden vault"),
+                (k(Action::EditConfig), "Edit config in $EDITOR"),
+                (k(Action::Settings), "Settings: edit the global defaults"),
+                (k(Action::Help), "Show this help"),
+                (
+                    format!("{}/^C/^D", k(Action::Quit)),
+                    "Quit (asks first while fetches are running)",
+                ),
+            ],
+        },
+        Section {
+            title: "Detail View",
+            current: from_detail,
+            rows: vec![
+                (format!("Esc / {}", k(Action::Quit)), "Back to dashboard"),
+                (k(Action::Fetch), "Force fetch this server"),
+                (k(Action::Cancel), "Cancel a running fetch"),
+                (k(Action::Renew), "Renew certs on server, then fetch"),
+                (k(Action::Probe), "Probe server cert (read-only SSH check)"),
+                (k(Action::CertDetails), "Show / hide full cert details"),
+                (k(Action::Rollback), "Roll back to a saved kubeconfig version"),
+                (k(Action::ProbeApi), "Probe API server TLS cert on :6443"),
+                (k(Action::Credentials), "Manage credentials"),
+                (k(Action::EditInline), "Edit fields in place"),
+                (k(Action::Edit), "Edit in the wizard"),
+                (k(Action::ViewKubeconfig), "View cached kubeconfig (secrets redacted)"),
+                (k(Action::OpenCluster), "Open the cluster (runs open_command)"),
+                (k(Action::Notifications), "Notifications shown this session"),
+                (k(Action::Help), "Show this help"),
+            ],
+        },
+        Section {
+            title: "Wizard",
+            current: false,
+            rows: vec![
+                ("Enter".to_string(), "Next step / confirm"),
+                ("Esc".to_string(), "Previous step / cancel"),
+                ("q".to_string(), "Cancel wizard"),
+            ],
+        },
+        Section {
+            title: "Authentication Step",
+            current: false,
+            rows: vec![
+                ("p".to_string(), "Password mode"),
+                ("i".to_string(), "Identity file mode"),
+                ("t".to_string(), "Test connection"),
+                ("s".to_string(), "Save server (after test passes)"),
+            ],
+        },
+        Section {
+            title: "Help",
+            current: false,
+            rows: vec![
+                ("j/k ↓/↑".to_string(), "Scroll"),
+                ("PgDn/PgUp".to_string(), "Scroll a page"),
+                ("/".to_string(), "Filter keys and descriptions"),
+                ("Esc".to_string(), "Clear the filter, then close"),
+            ],
+        },
+    ]
+}
 
+/// The help text with `state`'s filter applied, and the line the current view's
+/// section starts on. Sections left without rows are dropped.
+fn help_lines(app: &AppState, state: &HelpState) -> (Vec<Line<'static>>, usize) {
     let bold = if app.use_color {
         Style::default().add_modifier(Modifier::BOLD)
     } else {
         Style::default()
     };
-
     let dim = if app.use_color {
         Style::default().add_modifier(Modifier::DIM)
     } else {
         Style::default()
     };
+    let filter = state.filter.to_lowercase();
 
     let mut lines: Vec<Line> = Vec::new();
-
-    // ── Dashboard ──────────────────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled(" Dashboard", bold)]));
-    for (keys, desc) in &[
-        ("j / ↓      ", "Move down"),
-        ("k / ↑      ", "Move up"),
-        ("g          ", "Go to first"),
-        ("G          ", "Go to last"),
-        ("Enter      ", "Open detail view"),
-        ("f          ", "Force fetch selected server"),
-        ("F          ", "Force fetch all servers"),
-        ("c          ", "Manage credentials"),
-        ("a          ", "Add server (wizard)"),
-        ("D          ", "Delete selected server"),
-        ("d          ", "Toggle dry-run mode"),
-        ("B          ", "Configure Bitwarden vault"),
-        ("e          ", "Edit config in $EDITOR"),
-        ("?          ", "Show this help"),
-        ("q/^C/^D    ", "Quit"),
-    ] {
-        lines.push(Line::from(vec![Span::raw("  "), Span::raw(*keys), Span::raw(*desc)]));
+    let mut current_start = 0;
+    for section in sections(app) {
+        let rows: Vec<_> = section
+            .rows
+            .into_iter()
+            .filter(|(keys, desc)| {
+                filter.is_empty() || keys.to_lowercase().contains(&filter) || desc.to_lowercase().contains(&filter)
+            })
+            .collect();
+        if rows.is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(Line::raw(""));
+        }
+        if section.current {
+            current_start = lines.len();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {}", section.title),
+                    fg(app.use_color, app.theme.accent).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("  (this view)", dim),
+            ]));
+        } else {
+            lines.push(Line::from(Span::styled(format!(" {}", section.title), bold)));
+        }
+        for (keys, desc) in rows {
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::raw(format!("{:<11}", keys)),
+                Span::raw(desc),
+            ]));
+        }
     }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("  No keys match", dim)));
+    }
+    (lines, current_start)
+}
 
-    lines.push(Line::raw(""));
-
-    // ── Detail View ───────────────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled(" Detail View", bold)]));
-    for (keys, desc) in &[
-        ("Esc / q    ", "Back to dashboard"),
-        ("f          ", "Force fetch this server"),
-        ("p          ", "Probe server cert (read-only SSH check)"),
-        ("c          ", "Manage credentials"),
-        ("?          ", "Show this help"),
-    ] {
-        lines.push(Line::from(vec![Span::raw("  "), Span::raw(*keys), Span::raw(*desc)]));
+pub fn render(frame: &mut Frame, app: &mut AppState) {
+    render_dim_background(frame, frame.area());
+    let View::Help(state) = &app.view else {
+        return;
+    };
+    let (lines, _) = help_lines(app, state);
+
+    let popup_height = (lines.len() as u16 + 2).min(frame.area().height.saturating_sub(4));
+    let area = centered_rect(65, popup_height, frame.area());
+    let page = popup_height.saturating_sub(2) as usize;
+    let scroll = state.scroll.min(lines.len().saturating_sub(page));
+
+    frame.render_widget(Clear, area);
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let footer = if state.filtering {
+        Line::from(vec![
+            Span::raw(format!(" /{}│ ", state.filter)),
+            Span::styled("Enter:keep  Esc:clear ", dim),
+        ])
+    } else if !state.filter.is_empty() {
+        Line::from(vec![
+            Span::raw(format!(" /{} ", state.filter)),
+            Span::styled("Esc:clear ", dim),
+        ])
+    } else {
+        Line::from(Span::styled(" j/k:scroll  /:filter  Esc:close ", dim))
+    };
+    let block = Block::default()
+        .title("─ Help ─")
+        .title_bottom(footer)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll as u16, 0));
+    frame.render_widget(paragraph, area);
+
+    if let View::Help(state) = &mut app.view {
+        state.scroll = scroll;
+        state.page = page.max(1);
     }
+}
 
-    lines.push(Line::raw(""));
+/// j/k and PgDn/PgUp scroll, `/` filters as you type. Esc clears a filter first;
+/// with none, it or any other key closes the help.
+pub fn handle_key(app: &mut AppState, key: KeyEvent) {
+    let View::Help(state) = &app.view else {
+        return;
+    };
+    let mut state = state.clone();
+    if state.filtering {
+        match key.code {
+            KeyCode::Char(c) => state.filter.push(c),
+            KeyCode::Backspace => {
+                state.filter.pop();
+            }
+            KeyCode::Enter => state.filtering = false,
+            KeyCode::Esc => {
+                state.filter.clear();
+                state.filtering = false;
+            }
+            _ => {}
+        }
+        state.scroll = 0;
+        app.view = View::Help(state);
+        return;
+    }
 
-    // ── Wizard ────────────────────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled(" Wizard", bold)]));
-    for (keys, desc) in &[
-        ("Enter      ", "Next step / confirm"),
-        ("Esc        ", "Previous step / cancel"),
-        ("q          ", "Cancel wizard"),
-    ] {
-        lines.push(Line::from(vec![Span::raw("  "), Span::raw(*keys), Span::raw(*desc)]));
+    let last = help_lines(app, &state).0.len().saturating_sub(state.page.max(1));
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => state.scroll = (state.scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => state.scroll = state.scroll.saturating_sub(1),
+        KeyCode::PageDown => state.scroll = (state.scroll + state.page.max(1)).min(last),
+        KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(state.page.max(1)),
+        KeyCode::Home | KeyCode::Char('g') => state.scroll = 0,
+        KeyCode::End | KeyCode::Char('G') => state.scroll = last,
+        KeyCode::Char('/') => state.filtering = true,
+        KeyCode::Esc if !state.filter.is_empty() => {
+            state.filter.clear();
+            state.scroll = 0;
+        }
+        _ => {
+            app.view = app.prior_view.take().map(|b| *b).unwrap_or(View::Dashboard);
+            return;
+        }
     }
+    app.view = View::Help(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
 
-    lines.push(Line::raw(""));
-
-    // ── Authentication Step ───────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled(" Authentication Step", bold)]));
-    for (keys, desc) in &[
-        ("p          ", "Password mode"),
-        ("i          ", "Identity file mode"),
-        ("t          ", "Test connection"),
-        ("s          ", "Save server (after test passes)"),
-    ] {
-        lines.push(Line::from(vec![Span::raw("  "), Span::raw(*keys), Span::raw(*desc)]));
+    fn app() -> AppState {
+        AppState::new(
+            crate::config::Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        )
     }
 
-    lines.push(Line::raw(""));
+    fn press(app: &mut AppState, code: KeyCode) {
+        handle_key(app, KeyEvent::new(code, KeyModifiers::NONE));
+    }
 
-    // ── Any view ──────────────────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled(" Any view", bold)]));
-    lines.push(Line::from(vec![
-        Span::raw("  "),
-        Span::raw("?          "),
-        Span::raw("Show this help"),
-    ]));
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
 
-    lines.push(Line::raw(""));
+    #[test]
+    fn test_help_opens_at_the_section_for_its_view() {
+        let mut app = app();
+        open(&mut app, View::Dashboard);
+        let View::Help(state) = &app.view else { panic!() };
+        assert_eq!(state.scroll, 0);
 
-    // ── Footer ────────────────────────────────────────────────────────────
-    lines.push(Line::from(vec![Span::styled("  [press any key to dismiss]", dim)]));
+        open(&mut app, View::Detail("alpha".to_string()));
+        let View::Help(state) = &app.view else { panic!() };
+        let lines = text(&help_lines(&app, state).0);
+        assert!(
+            lines[state.scroll].starts_with(" Detail View"),
+            "{}",
+            lines[state.scroll]
+        );
+        assert!(lines[state.scroll].contains("(this view)"));
+    }
 
-    let block = Block::default()
-        .title("─ Help ─")
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded);
+    #[test]
+    fn test_help_filter_scroll_and_close() {
+        let mut app = app();
+        open(&mut app, View::Dashboard);
+        if let View::Help(state) = &mut app.view {
+            state.page = 10;
+        }
+        press(&mut app, KeyCode::PageDown);
+        let View::Help(state) = &app.view else { panic!() };
+        assert_eq!(state.scroll, 10);
 
-    let paragraph = Paragraph::new(lines).block(block);
-    frame.render_widget(paragraph, area);
-}
+        press(&mut app, KeyCode::Char('/'));
+        for c in "SAVED".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        let View::Help(state) = &app.view else { panic!() };
+        assert_eq!(state.scroll, 0);
+        let lines = text(&help_lines(&app, state).0);
+        assert_eq!(lines.len(), 2, "{:?}", lines);
+        assert!(lines[1].contains("Roll back"));
 
-pub fn handle_key(app: &mut AppState, _key: KeyEvent) {
-    app.view = app.prior_view.take().map(|b| *b).unwrap_or(View::Dashboard);
+        // j/k scroll again once the filter is kept; Esc clears it before closing
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('j'));
+        assert!(matches!(app.view, View::Help(_)));
+        press(&mut app, KeyCode::Esc);
+        let View::Help(state) = &app.view else { panic!() };
+        assert!(state.filter.is_empty());
+        press(&mut app, KeyCode::Esc);
+        assert!(matches!(app.view, View::Dashboard));
+    }
 }
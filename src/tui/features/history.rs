@@ -0,0 +1,149 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use super::{centered_rect, cert_color, cert_expires_display};
+use crate::tui::app::{AppState, View};
+
+/// Lists the saved versions of a server's kubeconfig to roll back to (`h`).
+pub fn open(app: &mut AppState, server_name: &str) {
+    match crate::history::list(&app.config, server_name) {
+        Ok(versions) if versions.len() < 2 => {
+            app.notification = Some((
+                format!("No earlier version of {} saved", server_name),
+                std::time::Instant::now(),
+            ));
+        }
+        Ok(versions) => {
+            app.view = View::History {
+                server_name: server_name.to_string(),
+                versions,
+                cursor: 1,
+            }
+        }
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Couldn't read the history of {}: {}", server_name, e),
+            };
+        }
+    }
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::History {
+        server_name,
+        versions,
+        cursor,
+    } = &app.view
+    else {
+        return;
+    };
+    let area = frame.area();
+    let height = (versions.len() as u16 + 6).min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(72), height, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" History: {} ", server_name))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let current = crate::kube::local_source_hash(&app.config.local_path(server_name));
+    let mut lines = vec![
+        Line::from("  #  SAVED                    HASH          CERT EXPIRES"),
+        Line::from(""),
+    ];
+    for (i, version) in versions.iter().enumerate() {
+        let selected = i == *cursor;
+        let row_style = if selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(
+                    "{} {:>2}  {}  {}  ",
+                    if selected { ">" } else { " " },
+                    i,
+                    version.saved_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    version.short_hash()
+                ),
+                row_style,
+            ),
+            Span::styled(
+                cert_expires_display(version.cert_expires.as_ref(), app),
+                cert_color(version.cert_expires.as_ref(), app),
+            ),
+        ];
+        if current.as_deref() == Some(version.source_hash.as_str()) {
+            spans.push(Span::styled(
+                "  (current)",
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("  j/k:select  Enter:roll back  Esc:cancel"));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let View::History { versions, cursor, .. } = &mut app.view else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => {
+            *cursor = (*cursor + 1).min(versions.len().saturating_sub(1));
+            return false;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            *cursor = cursor.saturating_sub(1);
+            return false;
+        }
+        KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {}
+        _ => return false,
+    }
+    let View::History {
+        server_name,
+        versions,
+        cursor,
+    } = std::mem::replace(&mut app.view, View::Dashboard)
+    else {
+        return false;
+    };
+    app.view = View::Detail(server_name.clone());
+    if key.code != KeyCode::Enter {
+        return false;
+    }
+    let version = &versions[cursor];
+    if app.dry_run {
+        app.notification = Some((
+            format!("DRY-RUN: would roll {} back to {}", server_name, version.short_hash()),
+            std::time::Instant::now(),
+        ));
+        return false;
+    }
+    let Some(server) = app.config.servers.iter().find(|s| s.name == server_name).cloned() else {
+        return false;
+    };
+    match crate::history::rollback(&app.config, &server, version) {
+        Ok(()) => {
+            app.refresh_cert_for(&server_name);
+            app.notification = Some((
+                format!("Rolled {} back to {}", server_name, version.short_hash()),
+                std::time::Instant::now(),
+            ));
+        }
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Rollback of {} failed: {:#}", server_name, e),
+            };
+        }
+    }
+    false
+}
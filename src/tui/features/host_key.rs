@@ -0,0 +1,116 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::{centered_rect, fg, render_dim_background};
+use crate::tui::app::{AppEvent, AppState, View};
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let (server_name, key) = match &app.view {
+        View::HostKeyTrust { server_name, key, .. } => (server_name, key),
+        _ => return,
+    };
+
+    let area = frame.area();
+    render_dim_background(frame, area);
+
+    let popup_area = centered_rect(
+        area.width.saturating_sub(4).min(72),
+        area.height.saturating_sub(4).min(13),
+        area,
+    );
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Unknown Host Key ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rows = Layout::vertical([
+        Constraint::Length(2), // intro
+        Constraint::Length(1), // blank
+        Constraint::Length(2), // key type + fingerprint
+        Constraint::Length(1), // blank
+        Constraint::Fill(1),   // advice
+        Constraint::Length(1), // key hints
+    ])
+    .split(inner);
+
+    let intro = Paragraph::new(format!(
+        "  '{}' ({}) is not in ~/.ssh/known_hosts. Trusting it records the key there.",
+        server_name, key.host
+    ))
+    .wrap(Wrap { trim: true });
+    frame.render_widget(intro, rows[0]);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let fingerprint = Paragraph::new(vec![
+        Line::from(vec![Span::raw("  Key type:    "), Span::styled(key.key_type, bold)]),
+        Line::from(vec![
+            Span::raw("  Fingerprint: "),
+            Span::styled(
+                &key.fingerprint,
+                fg(app.use_color, app.theme.warning).add_modifier(Modifier::BOLD),
+            ),
+        ]),
+    ]);
+    frame.render_widget(fingerprint, rows[2]);
+
+    let advice = Paragraph::new("  Compare with `ssh-keygen -lf /etc/ssh/ssh_host_*_key.pub` on the server if unsure.")
+        .style(Style::default().add_modifier(Modifier::DIM))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(advice, rows[4]);
+
+    let hints = Line::from(vec![
+        Span::raw("  "),
+        Span::styled("[y]", bold),
+        Span::raw(" Trust and continue    "),
+        Span::styled("[n]", bold),
+        Span::raw(" Cancel"),
+    ]);
+    frame.render_widget(Paragraph::new(hints), rows[5]);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent, tx: &std::sync::mpsc::Sender<AppEvent>) -> bool {
+    if !matches!(
+        key.code,
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc
+    ) {
+        return false;
+    }
+    let View::HostKeyTrust {
+        server_name,
+        key: host_key,
+        return_to,
+    } = std::mem::replace(&mut app.view, View::Dashboard)
+    else {
+        return false;
+    };
+    app.view = *return_to;
+
+    if !matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+        app.notification = Some((
+            format!("Host key for '{}' not trusted", server_name),
+            std::time::Instant::now(),
+        ));
+        return false;
+    }
+
+    if let Err(e) = crate::ssh::trust_host_key(&host_key) {
+        app.view = View::Error {
+            message: format!("Could not update known_hosts: {}", e),
+        };
+        return false;
+    }
+
+    crate::tui::retry_after_prompt(app, &server_name, "Host key trusted", tx);
+    false
+}
@@ -0,0 +1,271 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::{centered_rect, fg, render_dim_background};
+use crate::tui::app::{AppEvent, AppState, DISCOVER_SENTINEL, ImportState, View, WizardState};
+
+/// Per-port connect timeout for discovery scans from the TUI.
+const DISCOVER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Lists the clusters in ~/.kube/config that aren't configured yet (`I`). Each one
+/// picked is then added through the wizard, which only asks for SSH details.
+pub fn open(app: &mut AppState) {
+    let found = crate::kube::main_kubeconfig_path().and_then(|path| crate::import::read_candidates(&path, &app.config));
+    match found {
+        Ok((candidates, _)) if candidates.is_empty() => {
+            app.notification = Some((
+                "No clusters in ~/.kube/config that aren't configured already".to_string(),
+                std::time::Instant::now(),
+            ));
+        }
+        Ok((candidates, skipped)) => {
+            app.view = View::Import(ImportState {
+                source: "~/.kube/config".to_string(),
+                chosen: vec![true; candidates.len()],
+                candidates,
+                skipped,
+                cursor: 0,
+            });
+        }
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Couldn't read clusters to import: {}", e),
+            };
+        }
+    }
+}
+
+/// After first-run setup: lists the clusters of every kubeconfig in ~/.kube and the
+/// hosts of ~/.ssh/config, clusters marked. False when there is nothing to offer.
+pub fn open_for_setup(app: &mut AppState) -> bool {
+    let Some(kube_dir) = crate::kube::main_kubeconfig_path()
+        .ok()
+        .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
+    else {
+        return false;
+    };
+    let scan = crate::import::scan_for_setup(&kube_dir, &crate::ssh_config::read(), &app.config);
+    if scan.is_empty() {
+        return false;
+    }
+    let chosen = std::iter::repeat_n(true, scan.clusters.len())
+        .chain(std::iter::repeat_n(false, scan.hosts.len()))
+        .collect();
+    app.view = View::Import(ImportState {
+        source: "~/.kube and ~/.ssh/config".to_string(),
+        candidates: scan.clusters.into_iter().chain(scan.hosts).collect(),
+        skipped: scan.skipped,
+        chosen,
+        cursor: 0,
+    });
+    true
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::Import(state) = &app.view else {
+        return;
+    };
+    let area = frame.area();
+    render_dim_background(frame, area);
+    let height = (state.candidates.len() + state.skipped.len() + 5) as u16;
+    let popup_area = centered_rect(
+        area.width.saturating_sub(4).min(90),
+        height.min(area.height.saturating_sub(2)),
+        area,
+    );
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" Add servers from {} ", state.source))
+        .title_bottom(" j/k:move  Space:toggle  a:all/none  Enter:add marked  Esc:cancel ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, candidate) in state.candidates.iter().enumerate() {
+        let mark = if state.chosen[i] { "[x]" } else { "[ ]" };
+        let style = if i == state.cursor {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} {:<28}", mark, candidate.server.name), style),
+            Span::styled(format!(" {}", candidate.url), fg(app.use_color, app.theme.accent)),
+        ]));
+    }
+    if !state.skipped.is_empty() {
+        lines.push(Line::from(""));
+        for skip in &state.skipped {
+            lines.push(Line::from(Span::styled(
+                format!("  skipped {}: {}", skip.name, skip.reason),
+                dim,
+            )));
+        }
+    }
+    // Keep the cursor in view when the list is taller than the popup.
+    let rows = popup_area.height.saturating_sub(2) as usize;
+    let offset = (state.cursor + 1).saturating_sub(rows) as u16;
+    frame.render_widget(Paragraph::new(lines).block(block).scroll((offset, 0)), popup_area);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let View::Import(state) = &mut app.view else {
+        return false;
+    };
+    let last = state.candidates.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => state.cursor = (state.cursor + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => state.cursor = state.cursor.saturating_sub(1),
+        KeyCode::Char(' ') => state.chosen[state.cursor] = !state.chosen[state.cursor],
+        KeyCode::Char('a') => {
+            let all = !state.chosen.iter().all(|c| *c);
+            state.chosen.iter_mut().for_each(|c| *c = all);
+        }
+        KeyCode::Enter => {
+            let queue: Vec<crate::config::Server> = state
+                .candidates
+                .iter()
+                .zip(&state.chosen)
+                .filter(|(_, chosen)| **chosen)
+                .map(|(candidate, _)| candidate.server.clone())
+                .collect();
+            app.view = match queue.split_first() {
+                Some((first, rest)) => View::Wizard(WizardState::for_import(first, rest.to_vec())),
+                None => View::Dashboard,
+            };
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.view = View::Dashboard,
+        _ => {}
+    }
+    false
+}
+
+// ─── Discovery ────────────────────────────────────────────────────────────────
+
+/// Asks for a range to scan (`A`). Only one scan runs at a time.
+pub fn open_discover(app: &mut AppState) {
+    if app.in_progress.contains(DISCOVER_SENTINEL) {
+        app.notification = Some((
+            "A network scan is already running".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    app.view = View::DiscoverInput {
+        cidr: String::new(),
+        error: None,
+    };
+}
+
+pub fn render_discover_input(frame: &mut Frame, app: &AppState) {
+    let View::DiscoverInput { cidr, error } = &app.view else {
+        return;
+    };
+    let area = centered_rect(60, 8, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Discover Nodes ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let lines = vec![
+        Line::from(""),
+        Line::from("   Range to scan for SSH (:22) and API server (:6443):"),
+        Line::from(format!("   > {}│", cidr)),
+        match error {
+            Some(e) => Line::from(Span::styled(format!("   {}", e), fg(app.use_color, app.theme.error))),
+            None => Line::from(Span::styled(
+                "   e.g. 192.168.1.0/24 (at most 4096 addresses)",
+                Style::default().add_modifier(Modifier::DIM),
+            )),
+        },
+        Line::from(""),
+        Line::from("   Enter: scan   Esc: cancel"),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn handle_key_discover_input(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>) -> bool {
+    let View::DiscoverInput { cidr, error } = &mut app.view else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => cidr.push(c),
+        KeyCode::Backspace => {
+            cidr.pop();
+        }
+        KeyCode::Esc => app.view = View::Dashboard,
+        KeyCode::Enter => match crate::discover::parse_cidr(cidr.trim()) {
+            Err(e) => *error = Some(e.to_string()),
+            Ok(addresses) => {
+                let network = cidr.trim().to_string();
+                app.in_progress.insert(DISCOVER_SENTINEL.to_string());
+                app.notification = Some((
+                    format!("Scanning {} addresses in {}…", addresses.len(), network),
+                    std::time::Instant::now(),
+                ));
+                app.view = View::Dashboard;
+                spawn_discover(app.config.clone(), network, addresses, tx.clone());
+            }
+        },
+        _ => {}
+    }
+    false
+}
+
+fn spawn_discover(
+    config: crate::config::Config,
+    network: String,
+    addresses: Vec<std::net::IpAddr>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    std::thread::spawn(move || {
+        let result = crate::discover::scan(&addresses, DISCOVER_TIMEOUT, true)
+            .map(|hosts| crate::discover::candidates(&hosts, &config))
+            .map_err(|e| e.to_string());
+        tx.send(AppEvent::DiscoverComplete { network, result }).ok();
+    });
+}
+
+/// Opens the picker with the nodes found, or says why there is nothing to add.
+pub fn on_discover_complete(
+    app: &mut AppState,
+    network: String,
+    result: Result<(Vec<crate::import::Candidate>, Vec<crate::import::Skipped>), String>,
+) {
+    app.in_progress.remove(DISCOVER_SENTINEL);
+    let message = match result {
+        Err(e) => format!("Scan of {} failed: {}", network, e),
+        Ok((candidates, skipped)) if candidates.is_empty() => {
+            format!("No new nodes on {} ({} hosts skipped)", network, skipped.len())
+        }
+        // Don't pull the user out of another dialog; the scan can be re-run.
+        Ok((candidates, _)) if !matches!(app.view, View::Dashboard) => {
+            format!(
+                "Found {} new nodes on {}; press A to scan again",
+                candidates.len(),
+                network
+            )
+        }
+        Ok((candidates, skipped)) => {
+            app.view = View::Import(ImportState {
+                source: network,
+                chosen: vec![true; candidates.len()],
+                candidates,
+                skipped,
+                cursor: 0,
+            });
+            return;
+        }
+    };
+    app.notification = Some((message, std::time::Instant::now()));
+}
@@ -1,4 +1,4 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout},
@@ -7,16 +7,16 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 
-use super::{centered_rect, render_dim_background};
+use super::{centered_rect, fg, render_dim_background};
 use crate::tui::app::{AppState, View};
 
-pub fn render(frame: &mut Frame, app: &AppState, server_name: &str, keyring_error: &str) {
+pub fn render(frame: &mut Frame, app: &AppState, server_names: &str, keyring_error: &str) {
     let area = frame.area();
     render_dim_background(frame, area);
 
     let popup_area = centered_rect(
         area.width.saturating_sub(4).min(68),
-        area.height.saturating_sub(4).min(22),
+        area.height.saturating_sub(4).min(23),
         area,
     );
     frame.render_widget(Clear, popup_area);
@@ -32,7 +32,7 @@ pub fn render(frame: &mut Frame, app: &AppState, server_name: &str, keyring_erro
     let rows = Layout::vertical([
         Constraint::Length(3), // error section (up to 3 wrapped lines)
         Constraint::Length(1), // blank
-        Constraint::Length(3), // file path section (up to 3 wrapped lines)
+        Constraint::Length(4), // file path section (up to 4 wrapped lines)
         Constraint::Length(1), // blank
         Constraint::Fill(1),   // explanation
         Constraint::Length(1), // blank
@@ -42,7 +42,7 @@ pub fn render(frame: &mut Frame, app: &AppState, server_name: &str, keyring_erro
 
     // Row 0: keyring error (truncated)
     let warn_style = if app.use_color {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.warning)
     } else {
         Style::default().add_modifier(Modifier::BOLD)
     };
@@ -64,26 +64,25 @@ pub fn render(frame: &mut Frame, app: &AppState, server_name: &str, keyring_erro
             Span::styled(&file_path, Style::default().add_modifier(Modifier::BOLD)),
         ]),
         Line::from(format!("  Permissions: 0600  (only {} can read this file)", whoami())),
+        Line::from("  Encrypted with a master passphrase you'll be asked for"),
     ]);
     frame.render_widget(path_para.wrap(Wrap { trim: false }), rows[2]);
 
     // Row 4: explanation
     let explanation = vec![
-        Line::from("  This is the same security model used by:"),
+        Line::from("  Like an encrypted SSH key, a copy of the file is useless without"),
+        Line::from("  the passphrase. Later runs ask for it once at startup, or read it from"),
         Line::from(vec![
             Span::raw("    "),
-            Span::styled("~/.kube/config", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("   (kubectl credentials)"),
-        ]),
-        Line::from(vec![
-            Span::raw("    "),
-            Span::styled("~/.ssh/id_rsa", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("    (SSH private keys)"),
+            Span::styled(
+                crate::credentials::MASTER_PASSPHRASE_ENV,
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
         ]),
         Line::from(""),
         Line::from("  To use the system keyring instead:"),
         Line::from("    install gnome-keyring or keepassxc (Secret Service plugin)"),
-        Line::from(format!("    and store credential for '{}' with  c", server_name)),
+        Line::from(format!("    and store credential for '{}' with  c", server_names)),
     ];
     frame.render_widget(Paragraph::new(explanation).wrap(Wrap { trim: true }), rows[4]);
 
@@ -100,35 +99,28 @@ pub fn render(frame: &mut Frame, app: &AppState, server_name: &str, keyring_erro
 }
 
 pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
-    let (server_name, password) = match &app.view {
+    let (server_names, password) = match &app.view {
         View::KeyringFallbackConsent {
-            server_name, password, ..
-        } => (server_name.clone(), password.clone()),
+            server_names, password, ..
+        } => (server_names.clone(), password.clone()),
         _ => return false,
     };
 
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
-            match crate::credentials::set_credential_file(&server_name, &password) {
-                Ok(()) => {
-                    app.cred_cache.insert(server_name.clone(), true);
-                    app.notification = Some((
-                        format!("Credential for '{}' stored in file (0600)", server_name),
-                        std::time::Instant::now(),
-                    ));
-                }
-                Err(e) => {
-                    app.view = View::Error {
-                        message: format!("Could not write credentials file: {}", e),
-                    };
-                    return false;
-                }
+            if !crate::credentials::credential_file_unlocked() {
+                app.credential_input.clear();
+                app.view = View::MasterPassphraseInput { server_names, password };
+                return false;
             }
-            app.view = View::Dashboard;
+            store_to_file(app, &server_names, &password);
         }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             app.notification = Some((
-                format!("Credential not stored for '{}'. Use 'c' to add later.", server_name),
+                format!(
+                    "Credential not stored for '{}'. Use 'c' to add later.",
+                    server_names.join(", ")
+                ),
                 std::time::Instant::now(),
             ));
             app.view = View::Dashboard;
@@ -138,6 +130,101 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
     false
 }
 
+/// Writes the credential for each server to the file store and returns to the dashboard,
+/// or shows the first error.
+fn store_to_file(app: &mut AppState, server_names: &[String], password: &str) {
+    for server_name in server_names {
+        if let Err(e) = crate::credentials::set_credential_file(server_name, password) {
+            app.view = View::Error {
+                message: format!("Could not write credentials file: {}", e),
+            };
+            return;
+        }
+        app.note_credential_stored(server_name);
+    }
+    app.notification = Some((
+        format!("Credential for '{}' stored in encrypted file", server_names.join(", ")),
+        std::time::Instant::now(),
+    ));
+    app.view = View::Dashboard;
+}
+
+pub fn render_master_passphrase(frame: &mut Frame, app: &AppState) {
+    let file_path = crate::credentials::credential_file_path();
+    let exists = std::path::Path::new(&file_path).exists();
+
+    let width = (file_path.chars().count() as u16 + 8).clamp(50, 72);
+    let area = centered_rect(width, 9, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Master Passphrase ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(fg(app.use_color, Color::White));
+
+    let intro = if exists {
+        "   Unlock the credentials file:"
+    } else {
+        "   Choose a passphrase to encrypt the credentials file:"
+    };
+    let masked = app.credential_input.masked_display();
+    let lines = vec![
+        Line::from(""),
+        Line::from(intro),
+        Line::from(format!("   {}", file_path)),
+        Line::from(""),
+        Line::from(format!("   > {}│", masked)),
+        Line::from(""),
+        Line::from("   Enter: store credential   Esc: cancel"),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The passphrase is checked against an existing store (or becomes the key of a new one)
+/// and kept for the session, then the pending credential is written.
+pub fn handle_key_master_passphrase(app: &mut AppState, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.credential_input.push(c);
+        }
+        KeyCode::Backspace => {
+            app.credential_input.pop();
+        }
+        KeyCode::Enter | KeyCode::Esc => {
+            let passphrase = app.credential_input.value.clone();
+            app.credential_input.clear();
+            let View::MasterPassphraseInput { server_names, password } =
+                std::mem::replace(&mut app.view, View::Dashboard)
+            else {
+                return false;
+            };
+            if key.code == KeyCode::Esc {
+                app.notification = Some((
+                    format!(
+                        "Credential not stored for '{}'. Use 'c' to add later.",
+                        server_names.join(", ")
+                    ),
+                    std::time::Instant::now(),
+                ));
+                return false;
+            }
+            match crate::credentials::unlock_credential_file(&passphrase) {
+                Ok(()) => store_to_file(app, &server_names, &password),
+                Err(e) => {
+                    app.view = View::Error {
+                        message: format!("Could not unlock credentials file: {}", e),
+                    };
+                }
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
 /// Returns the current Unix username for display in the consent dialog.
 fn whoami() -> String {
     std::env::var("USER")
@@ -0,0 +1,128 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::{centered_rect, fg, render_dim_background};
+use crate::kube::{MergeOutcome, MergeStrategy};
+use crate::tui::app::{AppState, View};
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let (server_name, names) = match &app.view {
+        View::MergeConflict { server_name, names, .. } => (server_name, names),
+        _ => return,
+    };
+
+    let area = frame.area();
+    render_dim_background(frame, area);
+
+    let popup_area = centered_rect(
+        area.width.saturating_sub(4).min(72),
+        area.height.saturating_sub(4).min(11),
+        area,
+    );
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Merge Conflict ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rows = Layout::vertical([
+        Constraint::Length(2), // intro
+        Constraint::Length(1), // blank
+        Constraint::Length(1), // names
+        Constraint::Fill(1),   // advice
+        Constraint::Length(1), // key hints
+    ])
+    .split(inner);
+
+    let intro = Paragraph::new(format!(
+        "  '{}' was fetched, but ~/.kube/config already has a different cluster under the same name.",
+        server_name
+    ))
+    .wrap(Wrap { trim: true });
+    frame.render_widget(intro, rows[0]);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let conflicting = Line::from(vec![
+        Span::raw("  Name: "),
+        Span::styled(
+            names.join(", "),
+            fg(app.use_color, app.theme.warning).add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(conflicting), rows[2]);
+
+    let advice = Paragraph::new("  Set merge_strategy in config.toml to stop being asked.")
+        .style(Style::default().add_modifier(Modifier::DIM))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(advice, rows[3]);
+
+    let hints = Line::from(vec![
+        Span::raw("  "),
+        Span::styled("[o]", bold),
+        Span::raw(" Overwrite    "),
+        Span::styled("[r]", bold),
+        Span::raw(" Rename    "),
+        Span::styled("[s]", bold),
+        Span::raw(" Skip"),
+    ]);
+    frame.render_widget(Paragraph::new(hints), rows[4]);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let strategy = match key.code {
+        KeyCode::Char('o') | KeyCode::Char('O') => MergeStrategy::Overwrite,
+        KeyCode::Char('r') | KeyCode::Char('R') => MergeStrategy::Rename,
+        KeyCode::Char('s') | KeyCode::Char('S') | KeyCode::Esc => MergeStrategy::Skip,
+        _ => return false,
+    };
+    let View::MergeConflict {
+        server_name, return_to, ..
+    } = std::mem::replace(&mut app.view, View::Dashboard)
+    else {
+        return false;
+    };
+    app.view = *return_to;
+
+    // The fetch itself succeeded and wrote the per-server file; only the merge is left.
+    let local_path = app.config.local_path(&server_name);
+    let message = match crate::kube::merge_into_main_kubeconfig(
+        &local_path,
+        &server_name,
+        strategy,
+        app.config.backup_retention(),
+        app.dry_run,
+    ) {
+        Ok(MergeOutcome::Merged) => format!("{}: merged, replacing the existing entries", server_name),
+        Ok(MergeOutcome::Renamed(name)) => format!("{}: merged as '{}'", server_name, name),
+        Ok(MergeOutcome::Skipped) => format!("{}: fetched, ~/.kube/config left unchanged", server_name),
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Could not merge into ~/.kube/config: {}", e),
+            };
+            return false;
+        }
+    };
+
+    let run_state = crate::state::ServerRunState {
+        status: crate::state::RunStatus::Fetched,
+        last_updated: Some(chrono::Utc::now()),
+        error: None,
+        duration_ms: None,
+    };
+    app.server_states.insert(server_name.clone(), run_state.clone());
+    if let Err(e) = crate::state::update_server_state(&server_name, run_state) {
+        log::warn!("Could not write state file: {}", e);
+    }
+    app.notification = Some((message, std::time::Instant::now()));
+    false
+}
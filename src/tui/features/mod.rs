@@ -1,14 +1,32 @@
+pub mod audit;
 pub mod bitwarden;
+pub mod config_reload;
 pub mod credentials;
 pub mod dashboard;
 pub mod detail;
+pub mod dry_run;
 pub mod edit_server;
+pub mod fetch_all;
 pub mod help;
+pub mod history;
+pub mod host_key;
+pub mod import;
 pub mod keyring_fallback;
+pub mod merge_conflict;
+pub mod notifications;
+pub mod open_cluster;
+pub mod preview;
+pub mod prune;
+pub mod quit;
+pub mod reachability;
+pub mod remote_cert;
+pub mod rename;
+pub mod renew;
+pub mod settings;
 pub mod setup;
 pub mod wizard;
 
-use crate::tui::app::AppState;
+use crate::tui::app::{AppState, View};
 use ratatui::{
     Frame,
     layout::{Constraint, Flex, Layout, Rect},
@@ -16,6 +34,7 @@ use ratatui::{
     widgets::Block,
 };
 
+use crate::config::CertDisplay;
 use crate::state::RunStatus;
 
 // ─── Vault Source Helpers ─────────────────────────────────────────────────────
@@ -30,6 +49,21 @@ pub fn is_vault_server(app: &AppState, server_name: &str) -> bool {
         == crate::bitwarden::ServerSource::Vault
 }
 
+/// Opens the add-server wizard pre-filled with `server_name`'s settings (`E`).
+/// Vault servers are edited in Bitwarden instead.
+pub fn open_edit_wizard(app: &mut AppState, server_name: &str) {
+    if is_vault_server(app, server_name) {
+        app.notification = Some((
+            "Vault servers are managed in Bitwarden".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    if let Some(server) = app.config.servers.iter().find(|s| s.name == server_name) {
+        app.view = View::Wizard(crate::tui::app::WizardState::for_server(server));
+    }
+}
+
 // ─── Layout Helpers ───────────────────────────────────────────────────────────
 
 /// Return a rect of the given size centered within `area`.
@@ -44,37 +78,73 @@ pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
 
 // ─── Color Helpers ────────────────────────────────────────────────────────────
 
-/// Returns the style for a server's run status.
-pub fn status_color(status: &RunStatus, use_color: bool) -> Style {
-    if !use_color {
-        return Style::default();
-    }
-    match status {
-        RunStatus::Fetched => Style::default().fg(Color::Green),
-        RunStatus::Skipped => Style::default().fg(Color::DarkGray),
-        RunStatus::NoCredential | RunStatus::AuthRejected => Style::default().fg(Color::Yellow),
-        RunStatus::Failed => Style::default().fg(Color::Red),
+/// Foreground style that falls back to the terminal default when color is disabled.
+pub fn fg(use_color: bool, color: Color) -> Style {
+    if use_color {
+        Style::default().fg(color)
+    } else {
+        Style::default()
     }
 }
 
-/// Returns the style for a cert expiry date.
-pub fn cert_color(expires_at: Option<&chrono::DateTime<chrono::Utc>>, use_color: bool) -> Style {
-    if !use_color {
-        return Style::default();
+/// Picks a 256-color palette entry when the terminal supports it, falling back to
+/// one of the basic 16 colors otherwise.
+pub fn shade(depth: crate::color::ColorDepth, indexed: u8, basic: Color) -> Color {
+    if depth >= crate::color::ColorDepth::Ansi256 {
+        Color::Indexed(indexed)
+    } else {
+        basic
     }
-    match expires_at {
-        None => Style::default().fg(Color::Red),
+}
+
+/// Returns the style for a server's run status.
+pub fn status_color(status: &RunStatus, app: &AppState) -> Style {
+    let theme = &app.theme;
+    fg(
+        app.use_color,
+        match status {
+            RunStatus::Fetched => theme.ok,
+            RunStatus::Skipped => theme.muted,
+            RunStatus::NoCredential | RunStatus::AuthRejected => theme.warning,
+            RunStatus::Failed | RunStatus::Unreachable => theme.error,
+        },
+    )
+}
+
+/// Returns the style for a cert expiry date: the theme's warning color within
+/// `cert_warning_days`, its error color within `cert_critical_days`, once expired, or
+/// when unknown.
+pub fn cert_color(expires_at: Option<&chrono::DateTime<chrono::Utc>>, app: &AppState) -> Style {
+    expiry_color(expires_at, app.config.cert_thresholds(), app)
+}
+
+/// Returns the style for a cluster CA expiry: the warning color within `ca_warning_days`,
+/// the error color within `cert_critical_days` or once expired.
+pub fn ca_color(expires_at: &chrono::DateTime<chrono::Utc>, app: &AppState) -> Style {
+    let (_, critical) = app.config.cert_thresholds();
+    expiry_color(Some(expires_at), (app.config.ca_warning().num_days(), critical), app)
+}
+
+fn expiry_color(
+    expires_at: Option<&chrono::DateTime<chrono::Utc>>,
+    (warning, critical): (i64, i64),
+    app: &AppState,
+) -> Style {
+    let theme = &app.theme;
+    let color = match expires_at {
+        None => theme.error,
         Some(exp) => {
             let days = (*exp - chrono::Utc::now()).num_days();
-            if days > 30 {
-                Style::default().fg(Color::Green)
-            } else if days > 0 {
-                Style::default().fg(Color::Yellow)
+            if days <= critical || *exp <= chrono::Utc::now() {
+                theme.error
+            } else if days <= warning {
+                theme.warning
             } else {
-                Style::default().fg(Color::Red)
+                theme.ok
             }
         }
-    }
+    };
+    fg(app.use_color, color)
 }
 
 // ─── Overlay Dimming ─────────────────────────────────────────────────────────
@@ -96,13 +166,83 @@ pub fn status_display(status: &RunStatus) -> &'static str {
         RunStatus::NoCredential => "⚠ No credential",
         RunStatus::AuthRejected => "⚠ Auth rejected",
         RunStatus::Failed => "✗ Failed",
+        RunStatus::Unreachable => "✗ Unreachable",
     }
 }
 
+/// How long a run took: tenths of a second under a minute, else minutes and seconds.
+pub fn run_duration(ms: u64) -> String {
+    if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}m{:02}s", ms / 60_000, ms / 1000 % 60)
+    }
+}
+
+/// The icon alone, for the compact dashboard.
+pub fn status_glyph(status: &RunStatus) -> &'static str {
+    let display = status_display(status);
+    display.split_once(' ').map_or(display, |(glyph, _)| glyph)
+}
+
 /// Returns a formatted cert expiry string for display in the dashboard table.
-pub fn cert_expires_display(expires_at: Option<&chrono::DateTime<chrono::Utc>>) -> String {
-    match expires_at {
-        None => "—".to_string(),
-        Some(exp) => exp.format("%Y-%m-%d").to_string(),
+pub fn cert_expires_display(expires_at: Option<&chrono::DateTime<chrono::Utc>>, app: &AppState) -> String {
+    match (expires_at, app.cert_display) {
+        (None, _) => "—".to_string(),
+        (Some(exp), CertDisplay::Date) => exp.format("%Y-%m-%d").to_string(),
+        (Some(exp), CertDisplay::Relative) => relative_expiry(exp, chrono::Utc::now()),
+    }
+}
+
+/// Full timestamp for the detail view, followed by the time left in relative mode.
+pub fn cert_expires_long(expires_at: &chrono::DateTime<chrono::Utc>, app: &AppState) -> String {
+    let date = expires_at.format("%Y-%m-%d %H:%M:%S UTC");
+    match app.cert_display {
+        CertDisplay::Date => date.to_string(),
+        CertDisplay::Relative => format!("{} ({})", relative_expiry(expires_at, chrono::Utc::now()), date),
+    }
+}
+
+/// `12d`, `5h` or `exp`: the time left in at most four columns, for the compact dashboard.
+pub fn compact_expiry(
+    expires_at: Option<&chrono::DateTime<chrono::Utc>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let Some(exp) = expires_at else {
+        return "—".to_string();
+    };
+    let left = *exp - now;
+    if left <= chrono::Duration::zero() {
+        "exp".to_string()
+    } else if left.num_days() > 0 {
+        format!("{}d", left.num_days())
+    } else {
+        format!("{}h", left.num_hours())
+    }
+}
+
+/// Cuts `text` to `width` characters, ending in `…` when anything was dropped.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    if width > 0 {
+        cut.push('…');
+    }
+    cut
+}
+
+/// `in 12d`, `in 5h`, `expired 3d ago`: days once at least one is left, hours below.
+pub fn relative_expiry(expires_at: &chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>) -> String {
+    let left = *expires_at - now;
+    let (amount, span) = match left.abs().num_days() {
+        0 => (left.abs().num_hours(), "h"),
+        days => (days, "d"),
+    };
+    if left > chrono::Duration::zero() {
+        format!("in {}{}", amount, span)
+    } else {
+        format!("expired {}{} ago", amount, span)
     }
 }
@@ -0,0 +1,73 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use crate::tui::app::{AppState, View};
+
+/// Opens this session's notifications (`M`), newest first.
+pub fn open(app: &mut AppState) {
+    let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+    app.view = View::Notifications {
+        scroll: 0,
+        return_to: Box::new(return_to),
+    };
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::Notifications { scroll, .. } = &app.view else {
+        return;
+    };
+    let block = Block::default()
+        .title(" Notifications this session (newest first) ")
+        .title_bottom(" j/k:scroll  PgUp/PgDn:page  g/G:top/bottom  Esc:back ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text: Vec<Line> = if app.notification_log.is_empty() {
+        vec![Line::from(" Nothing yet")]
+    } else {
+        app.notification_log
+            .iter()
+            .rev()
+            .map(|n| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {}  ", n.at.format("%H:%M:%S")),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                    Span::raw(n.message.clone()),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(block).scroll((*scroll as u16, 0)),
+        frame.area(),
+    );
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent, page: usize) -> bool {
+    let last = app.notification_log.len().saturating_sub(1);
+    let View::Notifications { scroll, .. } = &mut app.view else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => *scroll = (*scroll + page).min(last),
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => *scroll = last,
+        KeyCode::Esc | KeyCode::Char('q') => {
+            if let View::Notifications { return_to, .. } = std::mem::replace(&mut app.view, View::Dashboard) {
+                app.view = *return_to;
+            }
+        }
+        _ => {}
+    }
+    false
+}
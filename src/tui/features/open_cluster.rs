@@ -0,0 +1,77 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::tui::app::AppState;
+
+/// Suspends the TUI and runs `open_command` for `server_name` (`o`), by default k9s on
+/// its context. The TUI comes back when the command exits.
+pub fn open(terminal: &mut ratatui::DefaultTerminal, app: &mut AppState, server_name: &str) {
+    let Some(server) = app.config.servers.iter().find(|s| s.name == server_name) else {
+        return;
+    };
+    let kubeconfig = app.config.local_path(server_name);
+    if !kubeconfig.exists() {
+        app.notification = Some((
+            format!("{}: no kubeconfig yet — fetch it first", server_name),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    let template = app.config.open_command().to_string();
+    let mut cmd = command(&template, server.kube_context(), &kubeconfig, server_name);
+
+    crate::tui::restore_terminal();
+    let status = cmd.status();
+    *terminal = crate::tui::init_terminal();
+
+    let msg = match status {
+        Ok(status) if status.success() => return,
+        Ok(status) => format!("'{}' exited with {}", template, status),
+        Err(e) => format!("Couldn't run '{}': {}", template, e),
+    };
+    app.notification = Some((msg, std::time::Instant::now()));
+}
+
+/// `template` as a `sh -c` command line. The placeholders become positional parameters
+/// rather than being pasted in, so names with spaces or quotes reach the program intact.
+fn command(template: &str, context: &str, kubeconfig: &Path, name: &str) -> Command {
+    let script = template
+        .replace("{context}", "\"$1\"")
+        .replace("{kubeconfig}", "\"$2\"")
+        .replace("{name}", "\"$3\"");
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(script)
+        .arg("sh")
+        .arg(context)
+        .arg(kubeconfig)
+        .arg(name);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_command_passes_placeholders_as_arguments() {
+        let cmd = command(
+            "kubectl --kubeconfig {kubeconfig} --context {context} get nodes",
+            "prod; rm -rf ~",
+            Path::new("/kube/prod.yaml"),
+            "prod",
+        );
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(
+            args,
+            [
+                "-c",
+                "kubectl --kubeconfig \"$2\" --context \"$1\" get nodes",
+                "sh",
+                "prod; rm -rf ~",
+                "/kube/prod.yaml",
+                "prod",
+            ]
+        );
+    }
+}
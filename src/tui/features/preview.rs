@@ -0,0 +1,157 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use super::fg;
+use crate::kube::{CERT_KEYS, SECRET_KEYS, split_key_value};
+use crate::tui::app::{AppState, View};
+
+/// Opens the cached kubeconfig for `server_name`, or explains why there is none.
+pub fn open(app: &mut AppState, server_name: &str) {
+    let path = app.config.local_path(server_name);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            app.view = View::KubeconfigPreview {
+                server_name: server_name.to_string(),
+                lines: redact_kubeconfig(&content).lines().map(str::to_string).collect(),
+                scroll: 0,
+            };
+        }
+        Err(e) => {
+            let msg = if e.kind() == std::io::ErrorKind::NotFound {
+                format!("No cached kubeconfig for '{}' yet — fetch it with f", server_name)
+            } else {
+                format!("Could not read {}: {}", path.display(), e)
+            };
+            app.notification = Some((msg, std::time::Instant::now()));
+        }
+    }
+}
+
+/// Replaces secret values with `<redacted>` and shortens certificate blobs, line by line.
+/// Kubeconfig values are always single-line scalars, so no YAML parsing is needed.
+pub fn redact_kubeconfig(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines() {
+        match split_key_value(line) {
+            Some((prefix, key, value)) if SECRET_KEYS.contains(&key) && !value.is_empty() => {
+                out.push_str(&format!("{}{}: <redacted>", prefix, key));
+            }
+            Some((prefix, key, value)) if CERT_KEYS.contains(&key) && value.len() > 24 => {
+                let head: String = value.chars().take(16).collect();
+                out.push_str(&format!("{}{}: {}… ({} chars)", prefix, key, head, value.len()));
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn highlight(line: &str, app: &AppState) -> Line<'static> {
+    if line.trim_start().starts_with('#') {
+        return Line::from(Span::styled(
+            line.to_string(),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    let Some((prefix, key, value)) = split_key_value(line) else {
+        return Line::from(line.to_string());
+    };
+    let value_style = if value == "<redacted>" || value.ends_with("chars)") {
+        fg(app.use_color, app.theme.warning).add_modifier(Modifier::DIM)
+    } else if key == "server" || key == "current-context" {
+        fg(app.use_color, app.theme.ok).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    Line::from(vec![
+        Span::styled(prefix.to_string(), Style::default().add_modifier(Modifier::DIM)),
+        Span::styled(format!("{}:", key), fg(app.use_color, app.theme.accent)),
+        Span::styled(
+            if value.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", value)
+            },
+            value_style,
+        ),
+    ])
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let (server_name, lines, scroll) = match &app.view {
+        View::KubeconfigPreview {
+            server_name,
+            lines,
+            scroll,
+        } => (server_name, lines, *scroll),
+        _ => return,
+    };
+
+    let area = frame.area();
+    let block = Block::default()
+        .title(format!(" {} — cached kubeconfig (secrets redacted) ", server_name))
+        .title_bottom(" j/k:scroll  PgUp/PgDn:page  g/G:top/bottom  Esc:back ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text: Vec<Line> = lines.iter().map(|l| highlight(l, app)).collect();
+    frame.render_widget(Paragraph::new(text).block(block).scroll((scroll as u16, 0)), area);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent, page: usize) -> bool {
+    let View::KubeconfigPreview {
+        server_name,
+        lines,
+        scroll,
+    } = &mut app.view
+    else {
+        return false;
+    };
+    let last = lines.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => *scroll = (*scroll + page).min(last),
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => *scroll = last,
+        KeyCode::Esc | KeyCode::Char('q') => {
+            let name = server_name.clone();
+            app.view = View::Detail(name);
+        }
+        _ => {}
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_kubeconfig_hides_secrets_and_keeps_server() {
+        let cert = "LS0tLS1CRUdJTiBDRVJUSUZJQ0FURS0tLS0tCk1JSUJkekNDQVIyZ0F3SUJBZ0lC";
+        let content = format!(
+            "clusters:\n- cluster:\n    certificate-authority-data: {cert}\n    server: https://10.0.0.1:6443\n  name: prod\nusers:\n- name: prod\n  user:\n    client-key-data: c2VjcmV0LWtleQ==\n    token: abc123\n"
+        );
+        let redacted = redact_kubeconfig(&content);
+        assert!(!redacted.contains("c2VjcmV0LWtleQ=="));
+        assert!(!redacted.contains("abc123"));
+        assert!(!redacted.contains(cert));
+        assert!(redacted.contains("    client-key-data: <redacted>"));
+        assert!(redacted.contains("    token: <redacted>"));
+        assert!(redacted.contains(&format!(
+            "certificate-authority-data: {}… ({} chars)",
+            &cert[..16],
+            cert.len()
+        )));
+        assert!(redacted.contains("    server: https://10.0.0.1:6443"));
+        assert!(redacted.contains("- name: prod"));
+    }
+}
@@ -0,0 +1,98 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::centered_rect;
+use crate::tui::app::{AppState, View};
+
+/// Lists what servers removed from the config left behind and asks before deleting it (`P`).
+pub fn open(app: &mut AppState) {
+    match crate::prune::find_orphans(&app.config) {
+        Ok(orphans) if orphans.is_empty() => {
+            app.notification = Some(("Nothing to prune".to_string(), std::time::Instant::now()));
+        }
+        Ok(orphans) => app.view = View::PruneConfirm(orphans),
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Couldn't look for leftovers: {}", e),
+            };
+        }
+    }
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::PruneConfirm(orphans) = &app.view else {
+        return;
+    };
+    let area = frame.area();
+    let height = (orphans.len() as u16 + 6).min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(80), height, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(" Prune Removed Servers ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let mut lines = vec![
+        Line::from("Left behind by servers no longer in the config:"),
+        Line::from(""),
+    ];
+    for orphan in orphans {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {}", orphan.name),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(": {}", orphan.describe())),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Remove all of it? [y/N]"));
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup_area,
+    );
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let confirmed = match key.code {
+        KeyCode::Char('y') => true,
+        KeyCode::Char('n') | KeyCode::Esc => false,
+        _ => return false,
+    };
+    let View::PruneConfirm(orphans) = std::mem::replace(&mut app.view, View::Dashboard) else {
+        return false;
+    };
+    if !confirmed {
+        return false;
+    }
+    if app.dry_run {
+        app.notification = Some((
+            format!("DRY-RUN: would prune {} server name(s)", orphans.len()),
+            std::time::Instant::now(),
+        ));
+        return false;
+    }
+    match crate::prune::remove(&orphans, &app.config) {
+        Ok(()) => {
+            for orphan in &orphans {
+                app.server_states.remove(&orphan.name);
+                app.cred_cache.remove(&orphan.name);
+            }
+            app.notification = Some((
+                format!("Pruned {} server name(s)", orphans.len()),
+                std::time::Instant::now(),
+            ));
+        }
+        Err(e) => {
+            app.view = View::Error {
+                message: format!("Prune didn't finish: {:#}", e),
+            };
+        }
+    }
+    false
+}
@@ -0,0 +1,123 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::{centered_rect, fg};
+use crate::tui::app::{AppState, View};
+
+/// Called when `q`, Ctrl+C or Ctrl+D asks to quit: true to quit now. With fetches
+/// running it asks first instead, since quitting would abandon them mid-write. Asking
+/// again from the dialog quits at once.
+pub fn confirm(app: &mut AppState) -> bool {
+    if matches!(app.view, View::QuitConfirm { .. }) || app.busy_servers().is_empty() {
+        return true;
+    }
+    let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+    app.view = View::QuitConfirm {
+        waiting: false,
+        return_to: Box::new(return_to),
+    };
+    false
+}
+
+/// True once a quit that was waiting for fetches to finish can go ahead.
+pub fn ready(app: &AppState) -> bool {
+    matches!(app.view, View::QuitConfirm { waiting: true, .. }) && app.busy_servers().is_empty()
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::QuitConfirm { waiting, .. } = &app.view else {
+        return;
+    };
+    let busy = app.busy_servers();
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut lines = if *waiting {
+        vec![
+            Line::from(format!(
+                "{} Quitting once {} fetch{} finish{}…",
+                app.spinner.current(),
+                busy.len(),
+                if busy.len() == 1 { "" } else { "es" },
+                if busy.len() == 1 { "es" } else { "" }
+            )),
+            Line::from(""),
+        ]
+    } else {
+        vec![
+            Line::from(format!(
+                "{} server{} still being fetched:",
+                busy.len(),
+                if busy.len() == 1 { " is" } else { "s are" }
+            )),
+            Line::from(""),
+        ]
+    };
+    for name in &busy {
+        let what = if app.fetch_queue.iter().any(|q| q.name == **name) {
+            "queued"
+        } else if app.renewing.contains(*name) {
+            "renewing"
+        } else {
+            "fetching"
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {}", name), Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  {}", what), fg(app.use_color, app.theme.accent)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if *waiting {
+            "c:cancel them  Esc:stay  Ctrl+C:quit now"
+        } else {
+            "w:wait, then quit  c:cancel them and quit  Esc:stay  Ctrl+C:quit now"
+        },
+        dim,
+    )));
+
+    let area = frame.area();
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(76), height, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(" Quit ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup_area,
+    );
+}
+
+/// `w` waits for the fetches to finish, `c` cancels them (queued ones drop at once,
+/// running ones stop without writing anything); either way the TUI quits once none
+/// is left. Esc goes back.
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('w') | KeyCode::Enter => {
+            if let View::QuitConfirm { waiting, .. } = &mut app.view {
+                *waiting = true;
+            }
+        }
+        KeyCode::Char('c') => {
+            let names: Vec<String> = app.busy_servers().into_iter().map(str::to_string).collect();
+            for name in &names {
+                crate::tui::cancel_fetch(app, name);
+            }
+            if let View::QuitConfirm { waiting, .. } = &mut app.view {
+                *waiting = true;
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('n') => {
+            if let View::QuitConfirm { return_to, .. } = std::mem::replace(&mut app.view, View::Dashboard) {
+                app.view = *return_to;
+            }
+        }
+        _ => {}
+    }
+    false
+}
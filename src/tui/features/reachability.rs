@@ -0,0 +1,62 @@
+use ratatui::style::{Modifier, Style};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::fg;
+use crate::tui::app::{AppEvent, AppState, Reachability};
+
+/// Short enough that an offline host doesn't hold a thread for long.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Starts a TCP check of every configured server; each result arrives as its own
+/// `ReachabilityChecked` event. Does nothing once `reachability_check` is turned off.
+pub fn check_all(app: &AppState, tx: &mpsc::Sender<AppEvent>) {
+    if !app.config.reachability_check() {
+        return;
+    }
+    let check_api = app.config.reachability_check_api();
+    for server in &app.config.servers {
+        let server_name = server.name.clone();
+        let (host, port) = match server.source() {
+            crate::config::Source::Ssh => (server.address.clone(), server.ssh_port()),
+            crate::config::Source::K3sToken => (server.address.clone(), crate::tls::API_PORT),
+            crate::config::Source::Rancher => {
+                crate::rancher::host_port(&server.address).unwrap_or_else(|| (server.address.clone(), 443))
+            }
+            // Managed clusters have no host of ours to check.
+            crate::config::Source::Eks | crate::config::Source::Gke | crate::config::Source::Aks => continue,
+        };
+        let (api_host, api_port) = server.api_endpoint();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let reachability = Reachability {
+                ssh: crate::ssh::tcp_reachable(&host, port, CONNECT_TIMEOUT),
+                api: check_api.then(|| crate::ssh::tcp_reachable(&api_host, api_port, CONNECT_TIMEOUT)),
+            };
+            tx.send(AppEvent::ReachabilityChecked {
+                server_name,
+                reachability,
+            })
+            .ok();
+        });
+    }
+}
+
+/// Text and style for a server's REACHABLE cell.
+pub fn cell(app: &AppState, server_name: &str) -> (String, Style) {
+    let Some(r) = app.reachability.get(server_name) else {
+        return ("…".to_string(), Style::default().add_modifier(Modifier::DIM));
+    };
+    let mark = |ok: bool| if ok { "✓" } else { "✗" };
+    let text = match r.api {
+        Some(api) => format!("ssh {} api {}", mark(r.ssh), mark(api)),
+        None if r.ssh => "✓ up".to_string(),
+        None => "✗ offline".to_string(),
+    };
+    let style = match (r.ssh, r.api) {
+        (true, None | Some(true)) => fg(app.use_color, app.theme.ok),
+        (false, None | Some(false)) => fg(app.use_color, app.theme.error),
+        _ => fg(app.use_color, app.theme.warning),
+    };
+    (text, style)
+}
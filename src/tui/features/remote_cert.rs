@@ -0,0 +1,127 @@
+use ratatui::style::{Modifier, Style};
+use std::sync::mpsc;
+
+use super::fg;
+use crate::tui::app::{AppEvent, AppState, ProbeState};
+
+/// Probes the client cert on every listed server (`p`), `max_parallel` at a time; each
+/// result arrives as its own `RemoteCertChecked` event. Servers already being probed
+/// are left alone. Prompts (host key, passphrase) aren't raised for these: a server
+/// that needs one shows as failed and can be probed from its detail view.
+pub fn probe_all(app: &mut AppState, tx: &mpsc::Sender<AppEvent>) {
+    let servers: Vec<_> = app
+        .visible_servers()
+        .into_iter()
+        .filter(|s| !matches!(app.remote_certs.get(&s.name), Some(ProbeState::Probing)))
+        .cloned()
+        .collect();
+    if servers.is_empty() {
+        return;
+    }
+    let jobs: Vec<_> = servers
+        .into_iter()
+        .map(|server| {
+            app.remote_certs.insert(server.name.clone(), ProbeState::Probing);
+            let key_passphrase = crate::tui::session_key_passphrase(app, &server);
+            (server, key_passphrase)
+        })
+        .collect();
+    app.notification = Some((
+        format!("Probing {} server(s) for newer certs", jobs.len()),
+        std::time::Instant::now(),
+    ));
+    let config = app.config.clone();
+    let tx = tx.clone();
+    std::thread::spawn(move || {
+        use rayon::prelude::*;
+        let Ok(pool) = rayon::ThreadPoolBuilder::new()
+            .num_threads(config.max_parallel())
+            .build()
+        else {
+            return;
+        };
+        pool.install(|| {
+            jobs.into_par_iter().for_each(|(server, key_passphrase)| {
+                let result = super::detail::do_probe(&server, &config, key_passphrase.as_deref())
+                    .map_err(|e| crate::tui::friendly_error(&e));
+                tx.send(AppEvent::RemoteCertChecked {
+                    server_name: server.name,
+                    result,
+                })
+                .ok();
+            });
+        });
+    });
+}
+
+/// How a probed remote cert compares with the cached one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Probing,
+    /// Same expiry as the cached cert.
+    Same,
+    /// The server has a cert the cached kubeconfig doesn't: fetch to pick it up.
+    Newer,
+    /// The cached cert expires later than the server's, e.g. after a rollback.
+    Older,
+    /// No client cert on the server to compare.
+    NoCert,
+    Failed,
+}
+
+pub fn compare(cached: Option<&chrono::DateTime<chrono::Utc>>, remote: &ProbeState) -> Comparison {
+    match remote {
+        ProbeState::Probing => Comparison::Probing,
+        ProbeState::Failed(_) => Comparison::Failed,
+        ProbeState::Done(None) => Comparison::NoCert,
+        ProbeState::Done(Some(remote)) => match cached {
+            Some(cached) if cached == remote => Comparison::Same,
+            Some(cached) if cached > remote => Comparison::Older,
+            _ => Comparison::Newer,
+        },
+    }
+}
+
+/// Text and style for a server's REMOTE cell; empty until it has been probed.
+pub fn cell(app: &AppState, server_name: &str) -> (String, Style) {
+    let Some(remote) = app.remote_certs.get(server_name) else {
+        return (String::new(), Style::default());
+    };
+    let cached = app.cert_cache.get(server_name).and_then(|v| v.as_ref());
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    match compare(cached, remote) {
+        Comparison::Probing => (app.spinner.current().to_string(), dim),
+        Comparison::Same => ("✓ same".to_string(), dim),
+        Comparison::Newer => (
+            "★ newer".to_string(),
+            fg(app.use_color, app.theme.warning).add_modifier(Modifier::BOLD),
+        ),
+        Comparison::Older => ("older".to_string(), fg(app.use_color, app.theme.warning)),
+        Comparison::NoCert => ("—".to_string(), dim),
+        Comparison::Failed => ("✗ failed".to_string(), fg(app.use_color, app.theme.error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_remote_with_cached_cert() {
+        let day = |d: u32| {
+            chrono::NaiveDate::from_ymd_opt(2026, 1, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let done = |d| ProbeState::Done(Some(day(d)));
+        assert_eq!(compare(Some(&day(10)), &done(10)), Comparison::Same);
+        assert_eq!(compare(Some(&day(10)), &done(20)), Comparison::Newer);
+        assert_eq!(compare(None, &done(20)), Comparison::Newer);
+        assert_eq!(compare(Some(&day(20)), &done(10)), Comparison::Older);
+        assert_eq!(compare(Some(&day(20)), &ProbeState::Done(None)), Comparison::NoCert);
+        assert_eq!(compare(None, &ProbeState::Failed("x".to_string())), Comparison::Failed);
+        assert_eq!(compare(None, &ProbeState::Probing), Comparison::Probing);
+    }
+}
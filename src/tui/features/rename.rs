@@ -0,0 +1,129 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use super::{centered_rect, fg};
+use crate::tui::app::{AppState, View};
+
+/// Asks for a new name for the selected server (`r`).
+pub fn open(app: &mut AppState, name: String) {
+    if super::is_vault_server(app, &name) {
+        app.notification = Some((
+            "Vault servers are managed in Bitwarden".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    if app.in_progress.contains(&name) {
+        app.notification = Some((
+            format!("Wait for '{}' to finish fetching before renaming it", name),
+            std::time::Instant::now(),
+        ));
+        return;
+    }
+    app.view = View::RenameInput {
+        new_name: name.clone(),
+        from: name,
+        error: None,
+    };
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::RenameInput { from, new_name, error } = &app.view else {
+        return;
+    };
+    let area = centered_rect(60, 8, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!(" Rename {} ", from))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let lines = vec![
+        Line::from(""),
+        Line::from("   New name:"),
+        Line::from(format!("   > {}│", new_name)),
+        match error {
+            Some(e) => Line::from(Span::styled(format!("   {}", e), fg(app.use_color, app.theme.error))),
+            None => Line::from(Span::styled(
+                "   State, credential, cached file and context move along",
+                Style::default().add_modifier(Modifier::DIM),
+            )),
+        },
+        Line::from(""),
+        Line::from("   Enter: rename   Esc: cancel"),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let View::RenameInput { from, new_name, error } = &mut app.view else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            new_name.push(c);
+            *error = None;
+        }
+        KeyCode::Backspace => {
+            new_name.pop();
+            *error = None;
+        }
+        KeyCode::Esc => app.view = View::Dashboard,
+        KeyCode::Enter => {
+            let (from, to) = (from.clone(), new_name.trim().to_string());
+            let planned = crate::rename::plan(&app.config, &from, &to);
+            let result = match planned {
+                Ok(_) if app.dry_run => {
+                    app.notification = Some((
+                        format!("DRY-RUN: would rename '{}' to '{}'", from, to),
+                        std::time::Instant::now(),
+                    ));
+                    app.view = View::Dashboard;
+                    return false;
+                }
+                Ok(rename) => crate::rename::apply(&rename, &app.config, &app.config_path),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => {
+                    rename_in_memory(app, &from, &to);
+                    app.notification = Some((format!("Renamed '{}' → '{}'", from, to), std::time::Instant::now()));
+                    app.view = View::Dashboard;
+                }
+                Err(e) => {
+                    if let View::RenameInput { error, .. } = &mut app.view {
+                        *error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Moves every in-memory entry keyed by the old name to the new one.
+fn rename_in_memory(app: &mut AppState, from: &str, to: &str) {
+    if let Some(server) = app.config.servers.iter_mut().find(|s| s.name == from) {
+        server.name = to.to_string();
+    }
+    if let Some(state) = app.server_states.remove(from) {
+        app.server_states.insert(to.to_string(), state);
+    }
+    if let Some(cert) = app.cert_cache.remove(from) {
+        app.cert_cache.insert(to.to_string(), cert);
+    }
+    if let Some(ca) = app.ca_cache.remove(from) {
+        app.ca_cache.insert(to.to_string(), ca);
+    }
+    if let Some(cred) = app.cred_cache.remove(from) {
+        app.cred_cache.insert(to.to_string(), cred);
+    }
+    if app.marked.remove(from) {
+        app.marked.insert(to.to_string());
+    }
+}
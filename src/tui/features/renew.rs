@@ -0,0 +1,128 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Alignment,
+    text::Line,
+    widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+use std::sync::mpsc;
+
+use super::centered_rect;
+use crate::tui::app::{AppEvent, AppState, View};
+
+/// Asks before running the server's renewal command.
+pub fn open_confirm(app: &mut AppState, server_name: &str) {
+    if app.in_progress.contains(server_name) {
+        return;
+    }
+    let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+    app.view = View::RenewConfirm {
+        server_name: server_name.to_string(),
+        return_to: Box::new(return_to),
+    };
+}
+
+pub fn render_confirm(frame: &mut Frame, app: &AppState) {
+    let View::RenewConfirm { server_name, .. } = &app.view else {
+        return;
+    };
+    let Some(server) = app.config.servers.iter().find(|s| &s.name == server_name) else {
+        return;
+    };
+    let area = frame.area();
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(64), 7, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title(" Renew Certificates ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let lines = vec![
+        Line::from(format!("Run on {} ({}):", server.name, server.address)),
+        Line::from(format!("  {}", server.renew_command(&app.config))),
+        Line::from(""),
+        Line::from("then fetch the new kubeconfig? [y/N]"),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false }),
+        inner,
+    );
+}
+
+pub fn handle_key_confirm(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>) -> bool {
+    let confirmed = match key.code {
+        KeyCode::Char('y') => true,
+        KeyCode::Char('n') | KeyCode::Esc => false,
+        _ => return false,
+    };
+    let View::RenewConfirm { server_name, return_to } = std::mem::replace(&mut app.view, View::Dashboard) else {
+        return false;
+    };
+    app.view = *return_to;
+    if confirmed && let Some(server) = app.config.servers.iter().find(|s| s.name == server_name).cloned() {
+        app.in_progress.insert(server_name.clone());
+        app.renewing.insert(server_name);
+        spawn_renew(app, server, tx.clone());
+    }
+    false
+}
+
+fn spawn_renew(app: &AppState, server: crate::config::Server, tx: mpsc::Sender<AppEvent>) {
+    let config = app.config.clone();
+    let dry_run = app.dry_run;
+    let vault_password = app.vault_passwords.get(&server.name).cloned();
+    let key_passphrase = crate::tui::session_key_passphrase(app, &server);
+    std::thread::spawn(move || {
+        let mut prompt = None;
+        let result = crate::fetch::renew_server(
+            &server,
+            &config,
+            dry_run,
+            vault_password.as_ref().map(|pw| pw.as_str()),
+            key_passphrase.as_deref(),
+        )
+        .map(|_| {
+            if !dry_run {
+                std::thread::sleep(crate::fetch::RENEW_SETTLE);
+            }
+        })
+        .map_err(|e| {
+            prompt = crate::tui::follow_up_prompt(&e, &server.name);
+            crate::tui::friendly_error(&e)
+        });
+        tx.send(AppEvent::RenewComplete {
+            server_name: server.name,
+            result,
+        })
+        .ok();
+        if let Some(event) = prompt {
+            tx.send(event).ok();
+        }
+    });
+}
+
+/// Fetches the renewed kubeconfig, or reports why the renewal failed.
+pub fn on_complete(app: &mut AppState, server_name: String, result: Result<(), String>, tx: &mpsc::Sender<AppEvent>) {
+    app.in_progress.remove(&server_name);
+    app.renewing.remove(&server_name);
+    match result {
+        Ok(()) => {
+            app.notification = Some((
+                format!("{}: renewal command ran — fetching", server_name),
+                std::time::Instant::now(),
+            ));
+            if let Some(server) = app.config.servers.iter().find(|s| s.name == server_name).cloned() {
+                crate::tui::start_fetch(app, server, tx);
+            }
+        }
+        Err(msg) => {
+            app.notification = Some((
+                format!("{}: renewal failed: {}", server_name, msg),
+                std::time::Instant::now(),
+            ));
+        }
+    }
+}
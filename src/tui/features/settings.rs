@@ -0,0 +1,159 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+
+use super::centered_rect;
+use crate::tui::app::{AppState, SettingsState, View};
+
+/// Opens the editor for the global settings (`S`).
+pub fn open(app: &mut AppState) {
+    app.view = View::Settings(SettingsState::from_config(&app.config));
+}
+
+pub fn render(frame: &mut Frame, app: &AppState) {
+    let View::Settings(state) = &app.view else {
+        return;
+    };
+    let field_count = SettingsState::LABELS.len();
+    let area = frame.area();
+    let popup = centered_rect(area.width.saturating_sub(6).min(76), field_count as u16 + 9, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(" Settings ")
+        .borders(ratatui::widgets::Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let rows = Layout::vertical([
+        Constraint::Length(1),                  // header hint
+        Constraint::Length(1),                  // separator
+        Constraint::Length(field_count as u16), // fields
+        Constraint::Length(1),                  // blank
+        Constraint::Length(2),                  // focused field's hint
+        Constraint::Length(1),                  // error
+        Constraint::Length(1),                  // footer
+    ])
+    .split(inner);
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    frame.render_widget(
+        Paragraph::new(format!("  Global defaults in {}", app.config_path.display())).style(dim),
+        rows[0],
+    );
+    frame.render_widget(
+        Paragraph::new("  ──────────────────────────────────────────────────────────"),
+        rows[1],
+    );
+
+    let field_rows = Layout::vertical(vec![Constraint::Length(1); field_count]).split(rows[2]);
+    for (i, (label, value)) in SettingsState::LABELS.iter().zip(state.fields.iter()).enumerate() {
+        let focused = i == state.field_idx;
+        let value_display = if focused {
+            format!("{}│", value)
+        } else {
+            value.clone()
+        };
+        let value_style = if focused && app.use_color {
+            Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD)
+        } else if focused {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(format!("  {:<23}", format!("{}:", label))),
+                Span::styled(value_display, value_style),
+            ])),
+            field_rows[i],
+        );
+    }
+
+    frame.render_widget(
+        Paragraph::new(format!("  {}", SettingsState::HINTS[state.field_idx]))
+            .style(dim)
+            .wrap(Wrap { trim: false }),
+        rows[4],
+    );
+
+    if let Some(ref err) = state.error {
+        let style = if app.use_color {
+            Style::default().fg(app.theme.error)
+        } else {
+            Style::default()
+        };
+        frame.render_widget(Paragraph::new(format!("  {}", err)).style(style), rows[5]);
+    }
+
+    frame.render_widget(
+        Paragraph::new("  Tab/↑↓:move  Enter:save  Esc:cancel  (blank: default)").style(dim),
+        rows[6],
+    );
+}
+
+pub fn handle_key(app: &mut AppState, key: KeyEvent) -> bool {
+    let mut state = match &app.view {
+        View::Settings(s) => s.clone(),
+        _ => return false,
+    };
+    let num_fields = SettingsState::LABELS.len();
+
+    match key.code {
+        KeyCode::Esc => app.view = View::Dashboard,
+        KeyCode::Tab | KeyCode::Down => {
+            state.field_idx = (state.field_idx + 1) % num_fields;
+            app.view = View::Settings(state);
+        }
+        KeyCode::BackTab | KeyCode::Up => {
+            state.field_idx = state.field_idx.checked_sub(1).unwrap_or(num_fields - 1);
+            app.view = View::Settings(state);
+        }
+        KeyCode::Backspace => {
+            state.fields[state.field_idx].pop();
+            state.error = None;
+            app.view = View::Settings(state);
+        }
+        KeyCode::Enter => save(app, state),
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            state.fields[state.field_idx].push(c);
+            state.error = None;
+            app.view = View::Settings(state);
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Writes the settings and applies them to the running session. The config isn't
+/// reloaded from disk, so servers from the vault stay listed.
+fn save(app: &mut AppState, mut state: SettingsState) {
+    let updated = match state.apply(&app.config) {
+        Ok(updated) => updated,
+        Err((field_idx, message)) => {
+            state.field_idx = field_idx;
+            state.error = Some(message);
+            app.view = View::Settings(state);
+            return;
+        }
+    };
+    match crate::config::update_settings(&app.config_path, &updated) {
+        Ok(()) => {
+            app.config = updated;
+            app.refresh_cert_cache();
+            app.notification = Some(("Settings saved".to_string(), std::time::Instant::now()));
+            app.view = View::Dashboard;
+        }
+        Err(e) => {
+            state.error = Some(format!("Save failed: {}", e));
+            app.view = View::Settings(state);
+        }
+    }
+}
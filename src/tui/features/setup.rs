@@ -4,11 +4,13 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout},
-    style::{Color, Style},
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 
 use super::centered_rect;
+use crate::credentials::Backend;
 use crate::tui::app::{AppEvent, AppState, SetupStep, SetupWizardState, View, WizardState};
 
 pub fn render(frame: &mut Frame, app: &AppState, wizard: &SetupWizardState) {
@@ -46,7 +48,7 @@ pub fn render(frame: &mut Frame, app: &AppState, wizard: &SetupWizardState) {
 
     if let Some(ref err) = wizard.error {
         let style = if app.use_color {
-            Style::default().fg(Color::Red)
+            Style::default().fg(app.theme.error)
         } else {
             Style::default()
         };
@@ -60,6 +62,7 @@ pub fn render(frame: &mut Frame, app: &AppState, wizard: &SetupWizardState) {
 
     let hints = match wizard.step {
         SetupStep::OutputDir => "  Enter:next  Ctrl+C:quit  (required)",
+        SetupStep::CredentialBackend => "  ↑/↓:choose  Enter:next  Esc:back",
         SetupStep::BitwardenEnabled => "  y:enable  n:skip  Esc:back  (required)",
         _ => "  Enter:next  Esc:back  (leave blank for no default)",
     };
@@ -73,8 +76,8 @@ fn render_step_indicator(
     bitwarden_enabled: bool,
 ) {
     let current_idx = wizard.step.index();
-    // Show 7 steps if bitwarden is enabled, 5 if not (4 base + bitwarden y/n)
-    let total = if bitwarden_enabled { 7usize } else { 5usize };
+    // Show 8 steps if bitwarden is enabled, 6 if not (5 base + bitwarden y/n)
+    let total = if bitwarden_enabled { 8usize } else { 6usize };
 
     let label = format!("  Step {} of {} — {}   ", current_idx + 1, total, wizard.step.label());
 
@@ -97,7 +100,70 @@ fn render_step_indicator(
     frame.render_widget(Paragraph::new(dots), cols[1]);
 }
 
+/// The backends offered on the credential step, in display order. macOS has no file store.
+fn backend_options() -> Vec<Backend> {
+    let mut options = vec![Backend::Auto, Backend::Keyring];
+    if !cfg!(target_os = "macos") {
+        options.push(Backend::File);
+    }
+    options
+}
+
+fn backend_description(backend: Backend, keyring_error: Option<&str>) -> String {
+    let keyring = crate::credentials::keyring_name();
+    let availability = if keyring_error.is_some() {
+        "not available here"
+    } else {
+        "available"
+    };
+    match backend {
+        Backend::Auto if cfg!(target_os = "macos") => format!("{} ({})", keyring, availability),
+        Backend::Auto => "the keyring, else the encrypted file (asks first)".to_string(),
+        Backend::Keyring => format!("{} only ({})", keyring, availability),
+        Backend::File => "encrypted file, unlocked with a master passphrase".to_string(),
+        Backend::Command => "your own script (set credential_command)".to_string(),
+    }
+}
+
+fn render_backend_choice(frame: &mut Frame, wizard: &SetupWizardState, area: ratatui::layout::Rect) {
+    let mut lines = vec![
+        Line::from("  Where should passwords and key passphrases be stored?"),
+        Line::from(""),
+    ];
+    for backend in backend_options() {
+        let selected = backend == wizard.credential_backend;
+        let style = if selected {
+            Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::raw(if selected { "  > " } else { "    " }),
+            Span::styled(format!("{:<8}", backend.to_string()), style),
+            Span::raw(format!(
+                " {}",
+                backend_description(backend, wizard.keyring_error.as_deref())
+            )),
+        ]));
+    }
+    lines.push(Line::from(""));
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    match (&wizard.keyring_error, wizard.credential_backend) {
+        (Some(e), _) => lines.push(Line::styled(format!("  Keyring: {}", e), dim)),
+        (None, Backend::File) => lines.push(Line::styled(
+            format!("  File: {}", crate::credentials::credential_file_path()),
+            dim,
+        )),
+        _ => {}
+    }
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
 fn render_content(frame: &mut Frame, wizard: &SetupWizardState, area: ratatui::layout::Rect) {
+    if wizard.step == SetupStep::CredentialBackend {
+        render_backend_choice(frame, wizard, area);
+        return;
+    }
     let (field_label, value, hint) = match wizard.step {
         SetupStep::OutputDir => (
             "Local output directory",
@@ -119,6 +185,7 @@ fn render_content(frame: &mut Frame, wizard: &SetupWizardState, area: ratatui::l
             wizard.default_file_name.as_str(),
             "Remote filename unless overridden  (k3s default: k3s.yaml)",
         ),
+        SetupStep::CredentialBackend => unreachable!("rendered by render_backend_choice"),
         SetupStep::BitwardenEnabled => (
             "Enable Bitwarden/Vaultwarden vault?",
             if wizard.bitwarden_enabled { "y" } else { "?" },
@@ -158,6 +225,26 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, _tx: &mpsc::Sender<AppEvent
         _ => return false,
     };
 
+    if ws.step == SetupStep::CredentialBackend
+        && let Some(forward) = match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Some(true),
+            KeyCode::Up | KeyCode::Char('k') => Some(false),
+            _ => None,
+        }
+    {
+        let mut ws = ws;
+        let options = backend_options();
+        let current = options.iter().position(|&b| b == ws.credential_backend).unwrap_or(0);
+        let next = if forward {
+            (current + 1).min(options.len() - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+        ws.credential_backend = options[next];
+        app.view = View::SetupWizard(ws);
+        return false;
+    }
+
     match key.code {
         KeyCode::Esc => {
             match ws.step.prev() {
@@ -208,7 +295,7 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, _tx: &mpsc::Sender<AppEvent
                 SetupStep::DefaultFileName => {
                     ws.default_file_name.pop();
                 }
-                SetupStep::BitwardenEnabled => {} // toggled by y/n, not text
+                SetupStep::CredentialBackend | SetupStep::BitwardenEnabled => {} // chosen, not typed
                 SetupStep::BitwardenServerUrl => {
                     ws.bitwarden_server_url.pop();
                 }
@@ -226,6 +313,7 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, _tx: &mpsc::Sender<AppEvent
                 SetupStep::DefaultUser => ws.default_user.push(c),
                 SetupStep::DefaultFilePath => ws.default_file_path.push(c),
                 SetupStep::DefaultFileName => ws.default_file_name.push(c),
+                SetupStep::CredentialBackend => {}
                 SetupStep::BitwardenEnabled => match c {
                     'y' | 'Y' => {
                         ws.bitwarden_enabled = true;
@@ -281,6 +369,7 @@ fn build_config_toml(ws: &SetupWizardState) -> String {
             toml_escape(ws.default_file_name.trim())
         ));
     }
+    toml.push_str(&format!("credential_backend = \"{}\"\n", ws.credential_backend));
     if ws.bitwarden_enabled {
         toml.push_str("\n[bitwarden]\nenabled = true\n");
         if !ws.bitwarden_server_url.trim().is_empty() {
@@ -311,8 +400,12 @@ fn setup_write(app: &mut AppState, ws: &SetupWizardState) {
 
     match crate::config::load_config(app.config_path.to_str().unwrap_or_default()) {
         Ok(config) => {
+            // Setup runs before any backend was set, so this one holds for the session.
+            crate::credentials::set_backend(config.credential_backend(), config.credential_command.clone());
             app.config = config;
-            app.view = View::Wizard(WizardState::default());
+            if !super::import::open_for_setup(app) {
+                app.view = View::Wizard(WizardState::default());
+            }
         }
         Err(e) => {
             let mut ws_err = ws.clone();
@@ -334,7 +427,8 @@ mod tests {
         assert_eq!(SetupStep::OutputDir.next(), Some(SetupStep::DefaultUser));
         assert_eq!(SetupStep::DefaultUser.next(), Some(SetupStep::DefaultFilePath));
         assert_eq!(SetupStep::DefaultFilePath.next(), Some(SetupStep::DefaultFileName));
-        assert_eq!(SetupStep::DefaultFileName.next(), Some(SetupStep::BitwardenEnabled));
+        assert_eq!(SetupStep::DefaultFileName.next(), Some(SetupStep::CredentialBackend));
+        assert_eq!(SetupStep::CredentialBackend.next(), Some(SetupStep::BitwardenEnabled));
         assert_eq!(SetupStep::BitwardenEnabled.next(), Some(SetupStep::BitwardenServerUrl));
         assert_eq!(
             SetupStep::BitwardenServerUrl.next(),
@@ -349,7 +443,8 @@ mod tests {
         assert_eq!(SetupStep::DefaultUser.prev(), Some(SetupStep::OutputDir));
         assert_eq!(SetupStep::DefaultFilePath.prev(), Some(SetupStep::DefaultUser));
         assert_eq!(SetupStep::DefaultFileName.prev(), Some(SetupStep::DefaultFilePath));
-        assert_eq!(SetupStep::BitwardenEnabled.prev(), Some(SetupStep::DefaultFileName));
+        assert_eq!(SetupStep::CredentialBackend.prev(), Some(SetupStep::DefaultFileName));
+        assert_eq!(SetupStep::BitwardenEnabled.prev(), Some(SetupStep::CredentialBackend));
         assert_eq!(SetupStep::BitwardenServerUrl.prev(), Some(SetupStep::BitwardenEnabled));
         assert_eq!(
             SetupStep::BitwardenItemPrefix.prev(),
@@ -363,9 +458,10 @@ mod tests {
         assert_eq!(SetupStep::DefaultUser.index(), 1);
         assert_eq!(SetupStep::DefaultFilePath.index(), 2);
         assert_eq!(SetupStep::DefaultFileName.index(), 3);
-        assert_eq!(SetupStep::BitwardenEnabled.index(), 4);
-        assert_eq!(SetupStep::BitwardenServerUrl.index(), 5);
-        assert_eq!(SetupStep::BitwardenItemPrefix.index(), 6);
+        assert_eq!(SetupStep::CredentialBackend.index(), 4);
+        assert_eq!(SetupStep::BitwardenEnabled.index(), 5);
+        assert_eq!(SetupStep::BitwardenServerUrl.index(), 6);
+        assert_eq!(SetupStep::BitwardenItemPrefix.index(), 7);
     }
 
     // ── Validation ────────────────────────────────────────────────────────────
@@ -405,6 +501,7 @@ mod tests {
             SetupStep::DefaultUser,
             SetupStep::DefaultFilePath,
             SetupStep::DefaultFileName,
+            SetupStep::CredentialBackend,
             SetupStep::BitwardenEnabled,
             SetupStep::BitwardenServerUrl,
             SetupStep::BitwardenItemPrefix,
@@ -571,6 +668,21 @@ mod tests {
         assert!(config.servers.is_empty(), "fresh config must have no servers");
     }
 
+    #[test]
+    fn test_build_config_toml_writes_chosen_backend() {
+        use tempfile::NamedTempFile;
+
+        let ws = SetupWizardState {
+            output_dir: "/tmp/kube".to_string(),
+            credential_backend: Backend::File,
+            ..Default::default()
+        };
+        let tmp = NamedTempFile::new().expect("temp file");
+        std::fs::write(tmp.path(), build_config_toml(&ws)).expect("write");
+        let config = crate::config::load_config(tmp.path().to_str().unwrap()).expect("parse");
+        assert_eq!(config.credential_backend(), Backend::File);
+    }
+
     #[test]
     fn test_build_config_toml_minimal_round_trips() {
         use tempfile::NamedTempFile;
@@ -1,16 +1,18 @@
 use std::sync::mpsc;
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Layout},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 
 use super::{centered_rect, render_dim_background};
-use crate::tui::app::{AppEvent, AppState, AuthMethod, View, WIZARD_SENTINEL, WizardState, WizardStep};
+use crate::tui::app::{
+    AppEvent, AppState, AuthMethod, HostPicker, View, WIZARD_SENTINEL, WizardState, WizardStep, with_cursor,
+};
 
 pub fn render(frame: &mut Frame, app: &mut AppState, wizard: &WizardState) {
     let area = frame.area();
@@ -23,8 +25,16 @@ pub fn render(frame: &mut Frame, app: &mut AppState, wizard: &WizardState) {
     );
     frame.render_widget(Clear, popup_area);
 
+    let title = match (&wizard.editing, &wizard.imported) {
+        (Some(server), _) => format!(" Edit Server: {} ", server.name),
+        (None, Some(_)) if !wizard.import_queue.is_empty() => {
+            format!(" Import Server ({} more queued) ", wizard.import_queue.len())
+        }
+        (None, Some(_)) => " Import Server ".to_string(),
+        (None, None) => " Add Server ".to_string(),
+    };
     let block = Block::default()
-        .title(" Add Server ")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
@@ -49,10 +59,10 @@ pub fn render(frame: &mut Frame, app: &mut AppState, wizard: &WizardState) {
     frame.render_widget(sep, rows[1]);
 
     // Row 2: content
-    if wizard.step == WizardStep::Auth {
-        render_auth_content(frame, app, wizard, rows[2]);
-    } else {
-        render_text_input_content(frame, wizard, rows[2]);
+    match wizard.step {
+        WizardStep::Auth => render_auth_content(frame, app, wizard, rows[2]),
+        WizardStep::Review => render_review_content(frame, wizard, rows[2]),
+        _ => render_text_input_content(frame, wizard, rows[2]),
     }
 
     // Row 3: error area (always 2 rows)
@@ -69,7 +79,7 @@ pub fn render(frame: &mut Frame, app: &mut AppState, wizard: &WizardState) {
 
 fn render_step_indicator(frame: &mut Frame, wizard: &WizardState, area: ratatui::layout::Rect) {
     let current_idx = wizard.step.index();
-    let total = 8usize;
+    let total = WizardStep::ALL.len();
 
     let label = format!("  Step {} of {} — {}   ", current_idx + 1, total, wizard.step.label());
 
@@ -110,22 +120,22 @@ fn render_text_input_content(frame: &mut Frame, wizard: &WizardState, area: rata
             wizard.file_path.as_str(),
             "e.g. /etc/rancher/k3s/k3s.yaml  (blank = k3s default)",
         ),
-        WizardStep::FileName => (
+        WizardStep::LocalFileName => (
             "Local filename",
-            wizard.file_name.as_str(),
-            "e.g. myserver.yaml  (blank = {name}.yaml)",
+            wizard.local_file_name.as_str(),
+            "e.g. myserver.yaml  (blank = config default, {name}.yaml)",
         ),
         WizardStep::TargetClusterIp => (
             "Cluster IP to write",
             wizard.target_cluster_ip.as_str(),
-            "IP written into the kubeconfig context",
+            "IP or hostname written into the kubeconfig context",
         ),
         WizardStep::ContextName => (
             "Context name",
             wizard.context_name.as_str(),
             "Leave blank to use server name",
         ),
-        WizardStep::Auth => unreachable!("Auth step handled separately"),
+        WizardStep::Auth | WizardStep::Review => unreachable!("Auth and Review steps handled separately"),
     };
 
     let content_rows = Layout::vertical([
@@ -139,13 +149,77 @@ fn render_text_input_content(frame: &mut Frame, wizard: &WizardState, area: rata
     let label_line = Paragraph::new(format!("  {}:", field_label));
     frame.render_widget(label_line, content_rows[0]);
 
-    let input_line = Paragraph::new(format!("  > {}│", value));
+    let input_line = Paragraph::new(format!("  > {}", with_cursor(value, wizard.cursor)));
     frame.render_widget(input_line, content_rows[1]);
 
+    if let (WizardStep::Address, Some(picker)) = (&wizard.step, &wizard.host_picker) {
+        render_host_picker(frame, picker, content_rows[3]);
+        return;
+    }
+    let hint = if wizard.step == WizardStep::Address && wizard.editing.is_none() {
+        format!("{}\n  Tab: pick a host from ~/.ssh/config", hint)
+    } else {
+        hint.to_string()
+    };
     let hint_line = Paragraph::new(format!("  {}", hint)).wrap(Wrap { trim: true });
     frame.render_widget(hint_line, content_rows[3]);
 }
 
+fn render_host_picker(frame: &mut Frame, picker: &HostPicker, area: ratatui::layout::Rect) {
+    let height = area.height.max(1) as usize;
+    let offset = picker.selected.saturating_sub(height - 1);
+    let lines: Vec<Line> = picker
+        .hosts
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(height)
+        .map(|(i, host)| {
+            let mut target = host.address().to_string();
+            if let Some(user) = &host.user {
+                target = format!("{}@{}", user, target);
+            }
+            if let Some(port) = host.port {
+                target = format!("{}:{}", target, port);
+            }
+            let style = if i == picker.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!("  {:<20} {}", host.alias, target), style),
+                Span::styled(
+                    host.identity_file
+                        .as_deref()
+                        .map(|key| format!("  {}", key))
+                        .unwrap_or_default(),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// Fills in what `host` says about connecting: address, port, user and identity file,
+/// and the cluster IP when it is still blank.
+fn pick_host(ws: &mut WizardState, host: &crate::ssh_config::HostEntry) {
+    ws.address = host.address().to_string();
+    ws.ssh_port = host.port.filter(|&port| port != crate::ssh::SSH_PORT);
+    if let Some(user) = &host.user {
+        ws.user = user.clone();
+    }
+    if let Some(identity_file) = &host.identity_file {
+        ws.identity_file_input = identity_file.clone();
+        ws.auth_method = AuthMethod::IdentityFile;
+    }
+    if ws.target_cluster_ip.is_empty() && crate::validate::is_host(host.address()) {
+        ws.target_cluster_ip = host.address().to_string();
+    }
+    ws.host_picker = None;
+}
+
 fn render_auth_content(frame: &mut Frame, app: &AppState, wizard: &WizardState, area: ratatui::layout::Rect) {
     let rows = Layout::vertical([
         Constraint::Length(1), // "Authentication method:"
@@ -197,7 +271,8 @@ fn render_auth_content(frame: &mut Frame, app: &AppState, wizard: &WizardState,
             } else {
                 Style::default()
             };
-            let input_line = Paragraph::new(format!("  > {}│", masked)).style(input_style);
+            let input_line = Paragraph::new(format!("  > {}", with_cursor(&masked, wizard.password_input.cursor)))
+                .style(input_style);
             frame.render_widget(input_line, rows[4]);
         }
         AuthMethod::IdentityFile => {
@@ -213,7 +288,11 @@ fn render_auth_content(frame: &mut Frame, app: &AppState, wizard: &WizardState,
             } else {
                 Style::default()
             };
-            let input_line = Paragraph::new(format!("  > {}│", wizard.identity_file_input)).style(input_style);
+            let input_line = Paragraph::new(format!(
+                "  > {}",
+                with_cursor(&wizard.identity_file_input, wizard.cursor)
+            ))
+            .style(input_style);
             frame.render_widget(input_line, rows[4]);
         }
     }
@@ -224,13 +303,19 @@ fn render_auth_content(frame: &mut Frame, app: &AppState, wizard: &WizardState,
         format!("  {} Testing...", spinner_frame)
     } else if wizard.test_passed {
         "  ✓ Connected".to_string()
+    } else if wizard.auth_method != AuthMethod::IdentityFile {
+        String::new()
+    } else if wizard.key_encrypted && app.key_passphrases.contains_key(&wizard.identity_file_input) {
+        "  Passphrase-protected key (passphrase entered)".to_string()
+    } else if wizard.key_encrypted {
+        "  Passphrase-protected key: the test asks for its passphrase".to_string()
     } else {
         String::new()
     };
 
     let test_style = if wizard.test_passed && !wizard.testing {
         if app.use_color {
-            Style::default().fg(Color::Green)
+            Style::default().fg(app.theme.ok)
         } else {
             Style::default()
         }
@@ -242,10 +327,69 @@ fn render_auth_content(frame: &mut Frame, app: &AppState, wizard: &WizardState,
     frame.render_widget(test_line, rows[5]);
 }
 
+/// What a step's value will be saved as, or what a blank one falls back to.
+fn review_value(wizard: &WizardState, step: &WizardStep) -> (String, bool) {
+    let or_default = |value: &str, default: &str| match value {
+        "" => (default.to_string(), true),
+        value => (value.to_string(), false),
+    };
+    match step {
+        WizardStep::Name => or_default(&wizard.name, ""),
+        WizardStep::Address => match wizard.ssh_port {
+            Some(port) => (format!("{} (port {})", wizard.address, port), false),
+            None => or_default(&wizard.address, ""),
+        },
+        WizardStep::User => or_default(&wizard.user, "(config default)"),
+        WizardStep::FilePath => or_default(&wizard.file_path, "(k3s default)"),
+        WizardStep::LocalFileName => or_default(&wizard.local_file_name, "(config default)"),
+        WizardStep::TargetClusterIp => or_default(&wizard.target_cluster_ip, ""),
+        WizardStep::ContextName => or_default(&wizard.context_name, "(server name)"),
+        WizardStep::Auth => match wizard.auth_method {
+            AuthMethod::IdentityFile => or_default(&wizard.identity_file_input, "identity file (none given)"),
+            AuthMethod::Password if !wizard.password_input.value.is_empty() => {
+                ("password (entered, stored on save)".to_string(), false)
+            }
+            AuthMethod::Password if wizard.editing.is_some() => ("password (keep the stored one)".to_string(), true),
+            AuthMethod::Password => ("password (none entered)".to_string(), true),
+        },
+        WizardStep::Review => (String::new(), true),
+    }
+}
+
+fn render_review_content(frame: &mut Frame, wizard: &WizardState, area: ratatui::layout::Rect) {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut lines = vec![
+        Line::from("  Check the values, press a number to change one:"),
+        Line::from(""),
+    ];
+    for (i, step) in WizardStep::ALL.iter().enumerate().take(WizardStep::Review.index()) {
+        let (value, is_default) = review_value(wizard, step);
+        let locked = *step == WizardStep::Name && wizard.editing.is_some();
+        let key = if locked {
+            "   ".to_string()
+        } else {
+            format!("[{}]", i + 1)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", key), if locked { dim } else { Style::default() }),
+            Span::raw(format!("{:<18} ", step.label())),
+            Span::styled(
+                value,
+                if is_default {
+                    dim
+                } else {
+                    Style::default().add_modifier(Modifier::BOLD)
+                },
+            ),
+        ]));
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
 fn render_error_area(frame: &mut Frame, app: &AppState, wizard: &WizardState, area: ratatui::layout::Rect) {
     if let Some(ref err_msg) = wizard.error {
         let style = if app.use_color {
-            Style::default().fg(Color::Red)
+            Style::default().fg(app.theme.error)
         } else {
             Style::default()
         };
@@ -259,14 +403,14 @@ fn render_error_area(frame: &mut Frame, app: &AppState, wizard: &WizardState, ar
 }
 
 fn render_footer(frame: &mut Frame, wizard: &WizardState, area: ratatui::layout::Rect) {
-    let hints = if wizard.step == WizardStep::Auth {
-        if wizard.auth_input_focused {
-            "  Enter: test  Esc: cancel  Backspace: delete"
-        } else {
-            "  Enter:type  t:test  s:save (after test)  Esc:back  ?:help"
-        }
-    } else {
-        "  Enter: next  Esc: back  q: cancel  ?:help"
+    let hints = match wizard.step {
+        WizardStep::Auth if wizard.auth_input_focused => "  Enter: test  Esc: cancel  ←/→ Home/End: move",
+        WizardStep::Auth if wizard.editing.is_some() => "  Enter:type  t:test  s:review  Esc:back  ?:help",
+        WizardStep::Auth => "  Enter:type  t:test  s:review (after test)  Esc:back  ?:help",
+        WizardStep::Review => "  Enter/s: save  1-8: change  Esc: back  q: cancel  ?:help",
+        WizardStep::Address if wizard.host_picker.is_some() => "  ↑/↓: select  Enter: use host  Esc: close",
+        _ if wizard.reviewing => "  Enter: back to review  ←/→: move  q: cancel  ?:help",
+        _ => "  Enter: next  Esc: back  ←/→: move  q: cancel  ?:help",
     };
     let footer = Paragraph::new(hints);
     frame.render_widget(footer, area);
@@ -303,46 +447,29 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>
             match key.code {
                 KeyCode::Esc => {
                     ws.auth_input_focused = false;
+                    if ws.auth_method == AuthMethod::IdentityFile {
+                        ws.error = None;
+                        check_identity(&mut ws);
+                    }
                     app.view = View::Wizard(ws);
                 }
                 KeyCode::Enter => {
                     ws.auth_input_focused = false;
                     if !ws.testing {
-                        ws.testing = true;
-                        ws.test_passed = false;
-                        ws.error = None;
-                        let ws_snap = ws.clone();
-                        let default_user = app.config.default_user.clone();
-                        app.in_progress.insert(WIZARD_SENTINEL.to_string());
-                        app.view = View::Wizard(ws);
-                        spawn_wizard_test(ws_snap, default_user, tx.clone());
+                        start_test(app, ws, tx);
                     } else {
                         app.view = View::Wizard(ws);
                     }
                 }
-                KeyCode::Backspace => {
-                    match ws.auth_method {
-                        AuthMethod::Password => {
-                            ws.password_input.pop();
-                        }
-                        AuthMethod::IdentityFile => {
-                            ws.identity_file_input.pop();
-                        }
-                    }
-                    app.view = View::Wizard(ws);
-                }
-                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    match ws.auth_method {
-                        AuthMethod::Password => {
-                            ws.password_input.push(c);
-                        }
-                        AuthMethod::IdentityFile => {
-                            ws.identity_file_input.push(c);
-                        }
+                _ => {
+                    let edited = match ws.auth_method {
+                        AuthMethod::Password => ws.password_input.edit(&key),
+                        AuthMethod::IdentityFile => ws.edit(&key),
+                    };
+                    if edited {
+                        app.view = View::Wizard(ws);
                     }
-                    app.view = View::Wizard(ws);
                 }
-                _ => {}
             }
         } else {
             match key.code {
@@ -352,54 +479,87 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>
                 }
                 KeyCode::Char('i') | KeyCode::Char('I') => {
                     ws.auth_method = AuthMethod::IdentityFile;
+                    ws.go_to(WizardStep::Auth);
                     app.view = View::Wizard(ws);
                 }
                 KeyCode::Enter => {
                     ws.auth_input_focused = true;
                     app.view = View::Wizard(ws);
                 }
-                KeyCode::Char('t') | KeyCode::Char('T') => {
-                    if !ws.testing {
-                        ws.testing = true;
-                        ws.test_passed = false;
-                        ws.error = None;
-                        let ws_snap = ws.clone();
-                        let default_user = app.config.default_user.clone();
-                        app.in_progress.insert(WIZARD_SENTINEL.to_string());
-                        app.view = View::Wizard(ws);
-                        spawn_wizard_test(ws_snap, default_user, tx.clone());
-                    }
-                }
-                KeyCode::Char('s') | KeyCode::Char('S') => {
-                    if ws.test_passed {
-                        let ws_snap = ws.clone();
-                        wizard_save(app, &ws_snap);
-                    }
+                KeyCode::Char('t') | KeyCode::Char('T') if !ws.testing => start_test(app, ws, tx),
+                KeyCode::Char('s') | KeyCode::Char('S') if ws.test_passed || ws.editing.is_some() => {
+                    ws.go_to(WizardStep::Review);
+                    app.view = View::Wizard(ws);
                 }
                 KeyCode::Esc => {
-                    if let Some(prev) = ws.step.prev() {
-                        ws.step = prev;
+                    if let Some(prev) = ws.prev_step() {
+                        ws.go_to(prev);
                         app.view = View::Wizard(ws);
                     }
                 }
                 _ => {}
             }
         }
+    } else if ws.step == WizardStep::Review {
+        let mut ws = ws;
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('s') | KeyCode::Char('S') => wizard_save(app, &ws, tx),
+            KeyCode::Char(c @ '1'..='8') => {
+                let index = c as usize - '1' as usize;
+                ws.revisit(WizardStep::ALL[index].clone());
+                app.view = View::Wizard(ws);
+            }
+            KeyCode::Esc => {
+                ws.go_to(WizardStep::Auth);
+                app.view = View::Wizard(ws);
+            }
+            KeyCode::Char('q') => app.view = View::Dashboard,
+            _ => {}
+        }
+    } else if let Some(picker) = &ws.host_picker {
+        let mut ws = ws.clone();
+        let last = picker.hosts.len().saturating_sub(1);
+        let selected = picker.selected;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => set_picker_selection(&mut ws, selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => set_picker_selection(&mut ws, (selected + 1).min(last)),
+            KeyCode::Enter => {
+                if let Some(host) = ws.host_picker.as_ref().and_then(|p| p.hosts.get(selected)).cloned() {
+                    pick_host(&mut ws, &host);
+                    ws.error = None;
+                    ws.go_to(WizardStep::TargetClusterIp);
+                }
+            }
+            KeyCode::Esc => ws.host_picker = None,
+            _ => {}
+        }
+        app.view = View::Wizard(ws);
     } else {
         match key.code {
+            KeyCode::Tab if ws.step == WizardStep::Address && ws.editing.is_none() => {
+                let mut ws = ws;
+                let hosts = crate::ssh_config::read();
+                if hosts.is_empty() {
+                    ws.error = Some("No Host entries found in ~/.ssh/config".to_string());
+                } else {
+                    ws.error = None;
+                    ws.host_picker = Some(HostPicker { hosts, selected: 0 });
+                }
+                app.view = View::Wizard(ws);
+            }
             KeyCode::Char('q') => {
                 app.view = View::Dashboard;
             }
             KeyCode::Esc => {
                 let mut ws = ws;
-                match ws.step.prev() {
+                match ws.prev_step() {
                     Some(prev) => {
-                        ws.step = prev;
+                        ws.go_to(prev);
                         app.view = View::Wizard(ws);
                     }
                     None => {
                         app.view = View::Dashboard;
-                    } // cancel at Name step
+                    } // cancel at the first step
                 }
             }
             KeyCode::Enter => {
@@ -407,46 +567,45 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>
                 if let Some(err) = wizard_validate_current(&ws, &app.config) {
                     ws.error = Some(err);
                     app.view = View::Wizard(ws);
-                } else if let Some(next) = ws.step.next() {
+                } else if let Some(next) = ws.next_step() {
                     ws.error = None;
-                    ws.step = next;
+                    ws.go_to(next);
                     app.view = View::Wizard(ws);
                 }
             }
-            KeyCode::Backspace => {
-                let mut ws = ws;
-                match ws.step {
-                    WizardStep::Name => ws.name.pop(),
-                    WizardStep::Address => ws.address.pop(),
-                    WizardStep::User => ws.user.pop(),
-                    WizardStep::FilePath => ws.file_path.pop(),
-                    WizardStep::FileName => ws.file_name.pop(),
-                    WizardStep::TargetClusterIp => ws.target_cluster_ip.pop(),
-                    WizardStep::ContextName => ws.context_name.pop(),
-                    WizardStep::Auth => None,
-                };
-                app.view = View::Wizard(ws);
-            }
-            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            _ => {
                 let mut ws = ws;
-                match ws.step {
-                    WizardStep::Name => ws.name.push(c),
-                    WizardStep::Address => ws.address.push(c),
-                    WizardStep::User => ws.user.push(c),
-                    WizardStep::FilePath => ws.file_path.push(c),
-                    WizardStep::FileName => ws.file_name.push(c),
-                    WizardStep::TargetClusterIp => ws.target_cluster_ip.push(c),
-                    WizardStep::ContextName => ws.context_name.push(c),
-                    WizardStep::Auth => {}
+                if ws.edit(&key) {
+                    app.view = View::Wizard(ws);
                 }
-                app.view = View::Wizard(ws);
             }
-            _ => {}
         }
     }
     false
 }
 
+fn set_picker_selection(ws: &mut WizardState, selected: usize) {
+    if let Some(picker) = &mut ws.host_picker {
+        picker.selected = selected;
+    }
+}
+
+/// Pasted text goes into the field being edited, at the cursor. On the auth step that
+/// is only once Enter has focused the input, as with typing.
+pub fn handle_paste(app: &mut AppState, text: &str) {
+    let View::Wizard(ws) = &mut app.view else {
+        return;
+    };
+    if ws.help_open || ws.host_picker.is_some() {
+        return;
+    }
+    match (&ws.step, &ws.auth_method) {
+        (WizardStep::Auth, _) if !ws.auth_input_focused => {}
+        (WizardStep::Auth, AuthMethod::Password) => ws.password_input.paste(text),
+        _ => ws.paste(text),
+    }
+}
+
 /// Called by the event loop when a wizard connection test completes.
 /// Keeps wizard-specific result handling in the wizard module, not in mod.rs.
 pub fn on_test_complete(app: &mut AppState, result: Result<(), String>) {
@@ -466,14 +625,76 @@ pub fn on_test_complete(app: &mut AppState, result: Result<(), String>) {
     }
 }
 
-fn spawn_wizard_test(ws: WizardState, default_user: Option<String>, tx: mpsc::Sender<AppEvent>) {
+/// Checks the identity file, then runs the connection test in the background. An
+/// unusable identity file is reported instead, since the test could only fail on it.
+fn start_test(app: &mut AppState, mut ws: WizardState, tx: &mpsc::Sender<AppEvent>) {
+    ws.test_passed = false;
+    ws.error = None;
+    if !check_identity(&mut ws) {
+        app.view = View::Wizard(ws);
+        return;
+    }
+    ws.testing = true;
+    let ws_snap = ws.clone();
+    let default_user = app.config.default_user.clone();
+    app.in_progress.insert(WIZARD_SENTINEL.to_string());
+    app.view = View::Wizard(ws);
+    let key_passphrase = app.key_passphrases.get(&ws_snap.identity_file_input).cloned();
+    spawn_wizard_test(
+        ws_snap,
+        default_user,
+        app.config.host_key_policy(),
+        key_passphrase,
+        tx.clone(),
+    );
+}
+
+/// Checks the identity file when its field loses focus or a test starts: false, with
+/// the reason in `error`, when it can't be used. Passwords have nothing to check.
+fn check_identity(ws: &mut WizardState) -> bool {
+    ws.key_encrypted = false;
+    if ws.auth_method != AuthMethod::IdentityFile || ws.identity_file_input.is_empty() {
+        return true;
+    }
+    match crate::ssh::check_identity_file(std::path::Path::new(&ws.identity_file_input)) {
+        Ok(key) => {
+            ws.key_encrypted = key.encrypted;
+            true
+        }
+        Err(msg) => {
+            ws.error = Some(msg);
+            false
+        }
+    }
+}
+
+fn spawn_wizard_test(
+    ws: WizardState,
+    default_user: Option<String>,
+    host_key_policy: crate::ssh::HostKeyPolicy,
+    key_passphrase: Option<String>,
+    tx: mpsc::Sender<AppEvent>,
+) {
     std::thread::spawn(move || {
-        let result = do_wizard_connection_test(&ws, default_user).map_err(|e| crate::tui::friendly_error(&e));
+        let mut prompt = None;
+        let result =
+            do_wizard_connection_test(&ws, default_user, host_key_policy, key_passphrase.as_deref()).map_err(|e| {
+                prompt = crate::tui::follow_up_prompt(&e, &ws.name);
+                crate::tui::friendly_error(&e)
+            });
         tx.send(AppEvent::WizardTestComplete { result }).ok();
+        if let Some(event) = prompt {
+            tx.send(event).ok();
+        }
     });
 }
 
-fn do_wizard_connection_test(ws: &WizardState, default_user: Option<String>) -> Result<(), anyhow::Error> {
+fn do_wizard_connection_test(
+    ws: &WizardState,
+    default_user: Option<String>,
+    host_key_policy: crate::ssh::HostKeyPolicy,
+    key_passphrase: Option<&str>,
+) -> Result<(), anyhow::Error> {
     let user = if !ws.user.is_empty() {
         ws.user.clone()
     } else if let Some(ref u) = default_user {
@@ -487,7 +708,13 @@ fn do_wizard_connection_test(ws: &WizardState, default_user: Option<String>) ->
         ws.file_path.clone()
     };
     let password = if ws.auth_method == AuthMethod::Password && !ws.password_input.value.is_empty() {
-        Some(ws.password_input.value.clone())
+        Some(zeroize::Zeroizing::new(ws.password_input.value.clone()))
+    } else if ws.auth_method == AuthMethod::Password && ws.editing.is_some() {
+        // Editing without retyping the password: test with the one already stored.
+        match crate::credentials::get_credential(&ws.name) {
+            crate::credentials::CredentialResult::Found(pw) => Some(pw),
+            _ => None,
+        }
     } else {
         None
     };
@@ -496,21 +723,39 @@ fn do_wizard_connection_test(ws: &WizardState, default_user: Option<String>) ->
     } else {
         None
     };
-    crate::ssh::fetch_remote_file(
-        &ws.name,
-        &ws.address,
-        &user,
-        &file_path,
-        identity.as_deref(),
-        password.as_deref(),
-    )
-    .map(|_| ())
+    let key_passphrase = crate::fetch::resolve_key_passphrase(identity.as_deref(), key_passphrase);
+    let sudo_password = match &ws.editing {
+        Some(server) => crate::fetch::resolve_sudo_password(server)?,
+        None => None,
+    };
+    let target = crate::ssh::SshTarget {
+        server_name: &ws.name,
+        address: &ws.address,
+        port: ws.ssh_port.unwrap_or(crate::ssh::SSH_PORT),
+        user: &user,
+        identity_file: identity.as_deref(),
+        key_passphrase: key_passphrase.as_ref().map(|p| p.as_str()),
+        password: password.as_ref().map(|pw| pw.as_str()),
+        sudo_password: sudo_password.as_ref().map(|pw| pw.as_str()),
+        escalation: ws.editing.as_ref().and_then(|s| s.escalation).unwrap_or_default(),
+        escalation_command: ws.editing.as_ref().and_then(|s| s.escalation_command.as_deref()),
+        compress: false,
+        host_key_policy,
+        transport: ws.editing.as_ref().and_then(|s| s.transport).unwrap_or_default(),
+        transfer: ws.editing.as_ref().and_then(|s| s.transfer).unwrap_or_default(),
+        sftp_fallback: true,
+        cancel: None,
+    };
+    let contents = crate::ssh::fetch_remote_file(&target, &file_path, &|_| {})?;
+    crate::kube::validate_fetched(&contents).map(|_| ())
 }
 
-fn wizard_save(app: &mut AppState, ws: &WizardState) {
+fn wizard_save(app: &mut AppState, ws: &WizardState, tx: &mpsc::Sender<AppEvent>) {
+    let base = ws.editing.clone().or_else(|| ws.imported.clone()).unwrap_or_default();
     let server = crate::config::Server {
         name: ws.name.clone(),
         address: ws.address.clone(),
+        ssh_port: ws.ssh_port,
         target_cluster_ip: ws.target_cluster_ip.clone(),
         user: if ws.user.is_empty() {
             None
@@ -522,10 +767,10 @@ fn wizard_save(app: &mut AppState, ws: &WizardState) {
         } else {
             Some(ws.file_path.clone())
         },
-        file_name: if ws.file_name.is_empty() {
+        local_file_name: if ws.local_file_name.is_empty() {
             None
         } else {
-            Some(ws.file_name.clone())
+            Some(ws.local_file_name.clone())
         },
         context_name: if ws.context_name.is_empty() {
             None
@@ -537,8 +782,14 @@ fn wizard_save(app: &mut AppState, ws: &WizardState) {
         } else {
             None
         },
+        ..base
     };
-    if let Err(e) = crate::config::add_server(&app.config_path, &server) {
+    let saved = if ws.editing.is_some() {
+        crate::config::update_server(&app.config_path, &server)
+    } else {
+        crate::config::add_server(&app.config_path, &server)
+    };
+    if let Err(e) = saved {
         app.view = View::Error {
             message: format!("Couldn't save server: {}", e),
         };
@@ -553,10 +804,10 @@ fn wizard_save(app: &mut AppState, ws: &WizardState) {
         if let Ok(new_config) = crate::config::load_config(&path_str) {
             app.config = new_config;
         }
-        if crate::credentials::keyring_error_is_unavailable(&e) {
+        if crate::credentials::keyring_error_is_unavailable(&e) && crate::credentials::file_fallback_allowed() {
             // Offer the file-based fallback; user must explicitly accept before anything is written.
             app.view = View::KeyringFallbackConsent {
-                server_name: ws.name.clone(),
+                server_names: vec![ws.name.clone()],
                 password: ws.password_input.value.clone(),
                 keyring_error: e,
             };
@@ -571,12 +822,27 @@ fn wizard_save(app: &mut AppState, ws: &WizardState) {
         }
         return;
     }
+    if ws.editing.is_some() {
+        match crate::config::load_config(&app.config_path.to_string_lossy()) {
+            Ok(new_config) => app.config = new_config,
+            Err(e) => {
+                app.view = View::Error {
+                    message: format!("Server saved but config reload failed: {}", e),
+                };
+                return;
+            }
+        }
+        app.notification = Some((format!("Saved changes to '{}'", ws.name), std::time::Instant::now()));
+        app.view = View::Dashboard;
+        return;
+    }
     let _ = crate::state::update_server_state(
         &ws.name,
         crate::state::ServerRunState {
             status: crate::state::RunStatus::Fetched,
             last_updated: Some(chrono::Utc::now()),
             error: None,
+            duration_ms: None,
         },
     );
     let path_str = app.config_path.to_string_lossy().to_string();
@@ -592,7 +858,69 @@ fn wizard_save(app: &mut AppState, ws: &WizardState) {
         }
     }
     app.notification = Some((format!("Server '{}' added", ws.name), std::time::Instant::now()));
-    app.view = View::Dashboard;
+    app.select_server(&ws.name);
+    let fetch_after_add = app.config.fetch_after_add;
+    if fetch_after_add == Some(true) {
+        fetch_added(app, &ws.name, tx);
+    }
+    app.view = match ws.import_queue.split_first() {
+        Some((next, rest)) => View::Wizard(WizardState::for_import(next, rest.to_vec())),
+        // Imports still queued aren't held up by the question; `f` fetches them later.
+        None if fetch_after_add.is_none() => View::FetchNowConfirm(ws.name.clone()),
+        None => View::Dashboard,
+    };
+}
+
+fn fetch_added(app: &mut AppState, server_name: &str, tx: &mpsc::Sender<AppEvent>) {
+    if let Some(server) = app.config.servers.iter().find(|s| s.name == server_name).cloned() {
+        crate::tui::start_fetch(app, server, tx);
+    }
+}
+
+// ─── Fetch Now? ───────────────────────────────────────────────────────────────
+
+pub fn render_fetch_confirm(frame: &mut Frame, app: &AppState) {
+    let View::FetchNowConfirm(server_name) = &app.view else {
+        return;
+    };
+    let area = frame.area();
+    let popup_area = centered_rect(area.width.saturating_sub(4).min(56), 6, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title(" Server Added ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let lines = vec![
+        Line::from(format!("Fetch the kubeconfig from '{}' now? [Y/n]", server_name)),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Set fetch_after_add in the config to skip this question.",
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false }),
+        inner,
+    );
+}
+
+pub fn handle_key_fetch_confirm(app: &mut AppState, key: KeyEvent, tx: &mpsc::Sender<AppEvent>) -> bool {
+    let confirmed = match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => true,
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => false,
+        _ => return false,
+    };
+    let View::FetchNowConfirm(server_name) = std::mem::replace(&mut app.view, View::Dashboard) else {
+        return false;
+    };
+    if confirmed {
+        fetch_added(app, &server_name, tx);
+    }
+    false
 }
 
 fn wizard_validate_current(ws: &WizardState, config: &crate::config::Config) -> Option<String> {
@@ -604,7 +932,7 @@ fn wizard_validate_current(ws: &WizardState, config: &crate::config::Config) ->
             if ws.name.contains(' ') {
                 return Some("Name cannot contain spaces".to_string());
             }
-            if config.servers.iter().any(|s| s.name == ws.name) {
+            if ws.editing.is_none() && config.servers.iter().any(|s| s.name == ws.name) {
                 return Some(format!("A server named '{}' already exists", ws.name));
             }
             None
@@ -613,15 +941,22 @@ fn wizard_validate_current(ws: &WizardState, config: &crate::config::Config) ->
             if ws.address.is_empty() {
                 return Some("Address cannot be empty".to_string());
             }
+            // Rancher and token servers keep a URL here.
+            let ssh = ws
+                .editing
+                .as_ref()
+                .is_none_or(|s| s.source() == crate::config::Source::Ssh);
+            if ssh && !crate::validate::is_host(&ws.address) {
+                return Some("Invalid IP address or hostname".to_string());
+            }
             None
         }
         WizardStep::TargetClusterIp => {
             if ws.target_cluster_ip.is_empty() {
                 return Some("Target cluster IP cannot be empty".to_string());
             }
-            let parts: Vec<&str> = ws.target_cluster_ip.split('.').collect();
-            if parts.len() != 4 || !parts.iter().all(|p| p.parse::<u8>().is_ok()) {
-                return Some("Invalid IP address (expected x.x.x.x)".to_string());
+            if !crate::validate::is_host(&ws.target_cluster_ip) {
+                return Some("Invalid IP address or hostname".to_string());
             }
             None
         }
@@ -692,11 +1027,16 @@ fn step_help_lines(step: &WizardStep) -> Vec<Line<'static>> {
             h("  Purpose"),
             t("    The SSH host this tool connects to when"),
             t("    fetching your kubeconfig. Must be"),
-            t("    reachable on port 22 from this machine."),
+            t("    reachable over SSH from this machine."),
             b.clone(),
             h("  What to enter"),
             t("    An IP address or hostname."),
             t("    e.g.  192.168.1.10  or  k3s.local"),
+            b.clone(),
+            h("  Tip"),
+            t("    Tab lists the hosts in ~/.ssh/config;"),
+            t("    picking one fills in the address, port,"),
+            t("    user and identity file."),
         ],
         WizardStep::User => vec![
             b.clone(),
@@ -724,7 +1064,7 @@ fn step_help_lines(step: &WizardStep) -> Vec<Line<'static>> {
             t("    For RKE2:"),
             t("      /etc/rancher/rke2/rke2.yaml"),
         ],
-        WizardStep::FileName => vec![
+        WizardStep::LocalFileName => vec![
             b.clone(),
             h("  Purpose"),
             t("    The filename used when saving this"),
@@ -733,8 +1073,9 @@ fn step_help_lines(step: &WizardStep) -> Vec<Line<'static>> {
             b.clone(),
             h("  What to enter"),
             t("    e.g.  mycluster.yaml"),
-            t("    Leave blank to default to {name}.yaml"),
-            t("    (where {name} is from step 1)."),
+            t("    Leave blank to use local_file_name"),
+            t("    from the config, {name}.yaml unless"),
+            t("    set (where {name} is from step 1)."),
         ],
         WizardStep::TargetClusterIp => vec![
             b.clone(),
@@ -744,7 +1085,8 @@ fn step_help_lines(step: &WizardStep) -> Vec<Line<'static>> {
             t("    so kubectl works from your machine."),
             b.clone(),
             h("  What to enter"),
-            t("    The server's IP reachable from here."),
+            t("    The server's IP or DNS name reachable"),
+            t("    from here."),
             t("    e.g.  192.168.1.10  (often same as step 2)"),
             b.clone(),
             h("  Tip"),
@@ -775,8 +1117,23 @@ fn step_help_lines(step: &WizardStep) -> Vec<Line<'static>> {
             b.clone(),
             h("  Identity file"),
             t("    Path to your SSH private key."),
-            t("    e.g.  ~/.ssh/id_rsa"),
+            t("    e.g.  /home/me/.ssh/id_ed25519"),
             t("    The key must be authorized on the server."),
+            t("    It's checked (mode, format) before a test."),
+        ],
+        WizardStep::Review => vec![
+            b.clone(),
+            h("  Purpose"),
+            t("    A last look at every value before the"),
+            t("    server is written to your config."),
+            b.clone(),
+            h("  Changing a value"),
+            t("    Press its number to open that step; Enter"),
+            t("    there brings you straight back here."),
+            b.clone(),
+            h("  Saving"),
+            t("    Enter or s saves. Dimmed values are blank"),
+            t("    and fall back to the defaults shown."),
         ],
     }
 }
@@ -0,0 +1,363 @@
+//! Rebindable keys of the dashboard and detail view, from the `[keys]` config section.
+//!
+//! Every action is one character. `[keys]` maps an action name to the character that
+//! replaces its default, e.g. `dry-run = "Z"`. Arrow keys, Enter and Esc, Ctrl-C, and
+//! the keys of dialogs, wizards and text fields can't be rebound.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Where an action can be used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Dashboard,
+    Detail,
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Scope::Dashboard => "the dashboard",
+            Scope::Detail => "the detail view",
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    // Dashboard and detail view
+    Fetch,
+    Cancel,
+    Renew,
+    Probe,
+    Credentials,
+    Edit,
+    OpenCluster,
+    Help,
+    /// Quits from the dashboard, goes back to it from the detail view.
+    Quit,
+    // Dashboard
+    MoveDown,
+    MoveUp,
+    First,
+    Last,
+    Mark,
+    ClearMarks,
+    FetchAll,
+    Search,
+    NextMatch,
+    PrevMatch,
+    TagFilter,
+    FilterAll,
+    FilterFailing,
+    FilterExpiring,
+    FilterNeverFetched,
+    CertDisplay,
+    SwitchContext,
+    CredentialsAll,
+    Add,
+    Import,
+    Discover,
+    Delete,
+    Rename,
+    Prune,
+    AuditLog,
+    Notifications,
+    DryRun,
+    Bitwarden,
+    EditConfig,
+    Settings,
+    // Detail view
+    CertDetails,
+    Rollback,
+    ProbeApi,
+    EditInline,
+    ViewKubeconfig,
+}
+
+impl Action {
+    pub const ALL: [Action; 44] = [
+        Action::Fetch,
+        Action::Cancel,
+        Action::Renew,
+        Action::Probe,
+        Action::Credentials,
+        Action::Edit,
+        Action::OpenCluster,
+        Action::Help,
+        Action::Quit,
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::First,
+        Action::Last,
+        Action::Mark,
+        Action::ClearMarks,
+        Action::FetchAll,
+        Action::Search,
+        Action::NextMatch,
+        Action::PrevMatch,
+        Action::TagFilter,
+        Action::FilterAll,
+        Action::FilterFailing,
+        Action::FilterExpiring,
+        Action::FilterNeverFetched,
+        Action::CertDisplay,
+        Action::SwitchContext,
+        Action::CredentialsAll,
+        Action::Add,
+        Action::Import,
+        Action::Discover,
+        Action::Delete,
+        Action::Rename,
+        Action::Prune,
+        Action::AuditLog,
+        Action::Notifications,
+        Action::DryRun,
+        Action::Bitwarden,
+        Action::EditConfig,
+        Action::Settings,
+        Action::CertDetails,
+        Action::Rollback,
+        Action::ProbeApi,
+        Action::EditInline,
+        Action::ViewKubeconfig,
+    ];
+
+    /// The key used unless `[keys]` rebinds it.
+    pub fn default_key(self) -> char {
+        match self {
+            Action::Fetch => 'f',
+            Action::Cancel => 'x',
+            Action::Renew => 'R',
+            Action::Probe => 'p',
+            Action::Credentials => 'c',
+            Action::Edit => 'E',
+            Action::OpenCluster => 'o',
+            Action::Help => '?',
+            Action::Quit => 'q',
+            Action::MoveDown => 'j',
+            Action::MoveUp => 'k',
+            Action::First => 'g',
+            Action::Last => 'G',
+            Action::Mark => ' ',
+            Action::ClearMarks => 'u',
+            Action::FetchAll => 'F',
+            Action::Search => '/',
+            Action::NextMatch => 'n',
+            Action::PrevMatch => 'N',
+            Action::TagFilter => 't',
+            Action::FilterAll => '1',
+            Action::FilterFailing => '2',
+            Action::FilterExpiring => '3',
+            Action::FilterNeverFetched => '4',
+            Action::CertDisplay => 'T',
+            Action::SwitchContext => 's',
+            Action::CredentialsAll => 'C',
+            Action::Add => 'a',
+            Action::Import => 'I',
+            Action::Discover => 'A',
+            Action::Delete => 'D',
+            Action::Rename => 'r',
+            Action::Prune => 'P',
+            Action::AuditLog => 'L',
+            Action::Notifications => 'M',
+            Action::DryRun => 'd',
+            Action::Bitwarden => 'B',
+            Action::EditConfig => 'e',
+            Action::Settings => 'S',
+            Action::CertDetails => 'i',
+            Action::Rollback => 'h',
+            Action::ProbeApi => 'P',
+            Action::EditInline => 'e',
+            Action::ViewKubeconfig => 'v',
+        }
+    }
+
+    pub fn scopes(self) -> &'static [Scope] {
+        match self {
+            Action::Fetch
+            | Action::Cancel
+            | Action::Renew
+            | Action::Probe
+            | Action::Credentials
+            | Action::Edit
+            | Action::OpenCluster
+            | Action::Help
+            | Action::Notifications
+            | Action::Quit => &[Scope::Dashboard, Scope::Detail],
+            Action::CertDetails | Action::Rollback | Action::ProbeApi | Action::EditInline | Action::ViewKubeconfig => {
+                &[Scope::Detail]
+            }
+            _ => &[Scope::Dashboard],
+        }
+    }
+
+    /// The name used in `[keys]`.
+    pub fn name(self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// The key bound to each action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap {
+    keys: HashMap<Action, char>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            keys: Action::ALL.iter().map(|&a| (a, a.default_key())).collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The defaults with `[keys]` applied. Errors list every value that isn't a single
+    /// character and every key bound to two actions of the same view.
+    pub fn from_config(config: Option<&BTreeMap<Action, String>>) -> Result<Keymap, Vec<String>> {
+        let mut keymap = Keymap::default();
+        let mut problems = Vec::new();
+        for (&action, value) in config.into_iter().flatten() {
+            match parse_key(value) {
+                Some(key) => {
+                    keymap.keys.insert(action, key);
+                }
+                None => problems.push(format!(
+                    "keys.{}: '{}' is not a key (use one character, or \"space\")",
+                    action.name(),
+                    value
+                )),
+            }
+        }
+        for scope in [Scope::Dashboard, Scope::Detail] {
+            let mut bound: BTreeMap<char, Vec<Action>> = BTreeMap::new();
+            for action in Action::ALL.into_iter().filter(|a| a.scopes().contains(&scope)) {
+                bound.entry(keymap.key(action)).or_default().push(action);
+            }
+            for (key, actions) in bound.into_iter().filter(|(_, actions)| actions.len() > 1) {
+                let names: Vec<String> = actions.iter().map(|a| a.name()).collect();
+                problems.push(format!(
+                    "keys: {} is bound to {} on {}",
+                    label(key),
+                    names.join(" and "),
+                    scope
+                ));
+            }
+        }
+        if problems.is_empty() { Ok(keymap) } else { Err(problems) }
+    }
+
+    pub fn key(&self, action: Action) -> char {
+        self.keys.get(&action).copied().unwrap_or_else(|| action.default_key())
+    }
+
+    /// The key as shown in hints and help: the character, or `space`.
+    pub fn label(&self, action: Action) -> String {
+        label(self.key(action))
+    }
+
+    /// A hint line such as `f:force-fetch  a:add`, with the keys as bound.
+    pub fn hints(&self, items: &[(Action, &str)]) -> String {
+        items
+            .iter()
+            .map(|&(action, what)| format!("{}:{}", self.label(action), what))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// The action `key` triggers in `scope`. Keys held with Ctrl or Alt trigger none.
+    pub fn action(&self, scope: Scope, key: &KeyEvent) -> Option<Action> {
+        if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return None;
+        }
+        let KeyCode::Char(c) = key.code else {
+            return None;
+        };
+        Action::ALL
+            .into_iter()
+            .find(|&a| a.scopes().contains(&scope) && self.key(a) == c)
+    }
+}
+
+/// Problems with a `[keys]` section, one message each.
+pub fn check(config: &BTreeMap<Action, String>) -> Vec<String> {
+    Keymap::from_config(Some(config)).err().unwrap_or_default()
+}
+
+fn parse_key(value: &str) -> Option<char> {
+    if value.eq_ignore_ascii_case("space") {
+        return Some(' ');
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_control() => Some(c),
+        _ => None,
+    }
+}
+
+fn label(key: char) -> String {
+    match key {
+        ' ' => "space".to_string(),
+        c => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_rebinds_and_reports_conflicts() {
+        let config: BTreeMap<Action, String> = toml::from_str(
+            r#"
+dry-run = "Z"
+mark = "space"
+edit-inline = "m"
+"#,
+        )
+        .unwrap();
+        let keymap = Keymap::from_config(Some(&config)).unwrap();
+        let press = |c| KeyEvent::from(KeyCode::Char(c));
+        assert_eq!(keymap.action(Scope::Dashboard, &press('Z')), Some(Action::DryRun));
+        assert_eq!(keymap.action(Scope::Dashboard, &press('d')), None);
+        assert_eq!(keymap.action(Scope::Dashboard, &press('P')), Some(Action::Prune));
+        assert_eq!(keymap.action(Scope::Detail, &press('P')), Some(Action::ProbeApi));
+        assert_eq!(keymap.action(Scope::Detail, &press('m')), Some(Action::EditInline));
+        assert_eq!(
+            keymap.action(
+                Scope::Dashboard,
+                &KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL)
+            ),
+            None
+        );
+        assert_eq!(keymap.label(Action::Mark), "space");
+
+        let config: BTreeMap<Action, String> = toml::from_str(
+            r#"
+dry-run = "D"
+rollback = "ctrl-h"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Keymap::from_config(Some(&config)).unwrap_err(),
+            [
+                "keys.rollback: 'ctrl-h' is not a key (use one character, or \"space\")",
+                "keys: D is bound to delete and dry-run on the dashboard",
+            ]
+        );
+
+        assert!(toml::from_str::<BTreeMap<Action, String>>("frobnicate = \"z\"").is_err());
+    }
+
+    #[test]
+    fn test_default_keys_have_no_conflicts() {
+        assert_eq!(Keymap::from_config(None), Ok(Keymap::default()));
+    }
+}
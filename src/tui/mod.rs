@@ -6,12 +6,17 @@ use crate::state;
 
 pub mod app;
 pub mod features;
+pub mod keys;
+pub mod theme;
 
-use app::{AppEvent, AppState, ProbeState, SetupWizardState, View};
+use app::{AppEvent, AppState, FetchResult, ProbeState, SetupWizardState, View};
 
 pub fn run_tui(config: Config, config_path: std::path::PathBuf, dry_run: bool) -> anyhow::Result<()> {
+    let keys = keys::Keymap::from_config(config.keys.as_ref())
+        .map_err(|problems| anyhow::anyhow!("[keys] in {}:\n  {}", config_path.display(), problems.join("\n  ")))?;
     let server_states = state::read_state().unwrap_or_default();
     let mut app = AppState::new(config, config_path, server_states, dry_run);
+    app.keys = keys;
     app.refresh_cert_cache();
     app.refresh_cred_cache();
     // Bitwarden vault integration
@@ -80,15 +85,7 @@ pub fn run_tui(config: Config, config_path: std::path::PathBuf, dry_run: bool) -
 }
 
 pub fn run_tui_setup(config_path: std::path::PathBuf, dry_run: bool) -> anyhow::Result<()> {
-    let empty_config = crate::config::Config {
-        default_user: None,
-        default_file_path: None,
-        default_file_name: None,
-        default_identity_file: None,
-        local_output_dir: String::new(),
-        bitwarden: None,
-        servers: vec![],
-    };
+    let empty_config = crate::config::Config::default();
 
     let initial_output_dir = dirs::home_dir()
         .map(|mut p| {
@@ -97,8 +94,17 @@ pub fn run_tui_setup(config_path: std::path::PathBuf, dry_run: bool) -> anyhow::
         })
         .unwrap_or_else(|| String::from("/tmp/kube"));
 
+    let keyring_error = crate::credentials::probe_keyring().err();
+    // Without a keyring, `auto` would only ask to fall back to the file later on.
+    let credential_backend = if keyring_error.is_some() && !cfg!(target_os = "macos") {
+        crate::credentials::Backend::File
+    } else {
+        crate::credentials::Backend::Auto
+    };
     let setup = SetupWizardState {
         output_dir: initial_output_dir,
+        credential_backend,
+        keyring_error,
         ..Default::default()
     };
 
@@ -114,88 +120,448 @@ fn run_app(mut app: AppState) -> anyhow::Result<()> {
     let tx_events = tx.clone();
     std::thread::spawn(move || {
         loop {
-            match crossterm::event::read() {
-                Ok(crossterm::event::Event::Key(k)) => {
-                    if tx_events.send(AppEvent::Key(k)).is_err() {
-                        break;
-                    }
+            let event = match crossterm::event::read() {
+                Ok(crossterm::event::Event::Key(k)) => AppEvent::Key(k),
+                Ok(crossterm::event::Event::Paste(text)) => AppEvent::Paste(text),
+                Ok(crossterm::event::Event::Resize(w, h)) => AppEvent::Resize(w, h),
+                _ => continue,
+            };
+            if tx_events.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    let ticker = Ticker::spawn(tx.clone());
+
+    if app.config.reachability_check() {
+        spawn_reachability_timer(app.config.reachability_interval(), tx.clone());
+    }
+
+    // Held for the lifetime of the TUI; dropping it stops the watcher thread.
+    let _watcher = spawn_file_watcher(&app.config.local_output_dir, &app.config_path, tx.clone());
+
+    let mut terminal = init_terminal();
+    let result = event_loop(&mut terminal, &mut app, &rx, &tx, &ticker);
+    restore_terminal();
+    result
+}
+
+/// `ratatui::init` with bracketed paste on, so a paste arrives as one `AppEvent::Paste`
+/// instead of a burst of keys (any of which might be a shortcut).
+pub(crate) fn init_terminal() -> ratatui::DefaultTerminal {
+    let terminal = ratatui::init();
+    crossterm::execute!(std::io::stdout(), crossterm::event::EnableBracketedPaste).ok();
+    terminal
+}
+
+/// Undoes `init_terminal`.
+pub(crate) fn restore_terminal() {
+    crossterm::execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste).ok();
+    ratatui::restore();
+}
+
+// ─── Ticker ───────────────────────────────────────────────────────────────────
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Drives `AppEvent::Tick`. Ticks every `TICK_INTERVAL` while something on screen is
+/// animating and parks its thread otherwise, so an idle dashboard causes no wakeups.
+struct Ticker {
+    active: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    thread: std::thread::Thread,
+}
+
+impl Ticker {
+    fn spawn(tx: mpsc::Sender<AppEvent>) -> Self {
+        let active = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let thread_active = active.clone();
+        let handle = std::thread::spawn(move || {
+            loop {
+                if !thread_active.load(std::sync::atomic::Ordering::SeqCst) {
+                    // An unpark issued before we get here is not lost: park returns immediately.
+                    std::thread::park();
+                    continue;
                 }
-                Ok(crossterm::event::Event::Resize(w, h)) => {
-                    if tx_events.send(AppEvent::Resize(w, h)).is_err() {
-                        break;
-                    }
+                std::thread::sleep(TICK_INTERVAL);
+                if tx.send(AppEvent::Tick).is_err() {
+                    break;
                 }
-                _ => {}
             }
+        });
+        Ticker {
+            active,
+            thread: handle.thread().clone(),
+        }
+    }
+
+    fn set_active(&self, active: bool) {
+        let was_active = self.active.swap(active, std::sync::atomic::Ordering::SeqCst);
+        if active && !was_active {
+            self.thread.unpark();
         }
+    }
+}
+
+// ─── File watching ────────────────────────────────────────────────────────────
+
+/// How long filesystem events are coalesced before the TUI is notified. A CLI run
+/// rewrites the state file once per server, so this keeps the dashboard from
+/// reloading several times in a row.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+type FileWatcher = notify_debouncer_mini::Debouncer<notify_debouncer_mini::notify::RecommendedWatcher>;
+
+/// Watch the state file, the config file and the cached kubeconfigs so changes made
+/// by an external CLI run or editor show up immediately. Directories are watched
+/// rather than files because all three are replaced via rename. Falls back to polling
+/// the state and config files when the platform watcher cannot be set up.
+fn spawn_file_watcher(
+    output_dir: &str,
+    config_path: &std::path::Path,
+    tx: mpsc::Sender<AppEvent>,
+) -> Option<FileWatcher> {
+    use notify_debouncer_mini::notify::RecursiveMode;
+
+    let state_path = state::state_file_path();
+    let state_dir = state_path
+        .parent()
+        .map(std::path::Path::to_path_buf)
+        .unwrap_or_default();
+    let _ = std::fs::create_dir_all(&state_dir);
+    let state_dir = std::fs::canonicalize(&state_dir).unwrap_or(state_dir);
+    let output_dir = std::fs::canonicalize(output_dir).ok();
+    let state_file = state_dir.join(state_path.file_name().unwrap_or_default());
+    let config_dir = config_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let config_dir = std::fs::canonicalize(config_dir).ok();
+    let config_file = config_dir
+        .as_ref()
+        .map(|dir| dir.join(config_path.file_name().unwrap_or_default()));
+
+    let handler_tx = tx.clone();
+    let handler_output_dir = output_dir.clone();
+    let handler = move |res: notify_debouncer_mini::DebounceEventResult| {
+        let Ok(events) = res else { return };
+        let changes = classify_watch_events(
+            events.iter().map(|e| e.path.as_path()),
+            &state_file,
+            config_file.as_deref(),
+            handler_output_dir.as_deref(),
+        );
+        if changes.state {
+            handler_tx.send(AppEvent::StateFileChanged).ok();
+        }
+        if changes.config {
+            handler_tx.send(AppEvent::ConfigFileChanged).ok();
+        }
+        for file_name in changes.kubeconfigs {
+            handler_tx.send(AppEvent::KubeconfigChanged { file_name }).ok();
+        }
+    };
+
+    let watcher = notify_debouncer_mini::new_debouncer(WATCH_DEBOUNCE, handler).and_then(|mut debouncer| {
+        debouncer.watcher().watch(&state_dir, RecursiveMode::NonRecursive)?;
+        Ok(debouncer)
     });
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            log::warn!(
+                "File watcher unavailable ({}), polling state and config files instead",
+                e
+            );
+            spawn_poller(state_path, config_path.to_path_buf(), tx);
+            return None;
+        }
+    };
+    // The output dir may not exist until the first fetch; cert refreshes then come
+    // from FetchComplete and the state file instead.
+    let mut dirs: Vec<&std::path::PathBuf> = [&output_dir, &config_dir]
+        .into_iter()
+        .flatten()
+        .filter(|dir| **dir != state_dir)
+        .collect();
+    dirs.dedup();
+    for dir in dirs {
+        if let Err(e) = watcher.watcher().watch(dir, RecursiveMode::NonRecursive) {
+            log::warn!("Could not watch {}: {}", dir.display(), e);
+        }
+    }
+    Some(watcher)
+}
 
-    let tx_tick = tx.clone();
+/// What a batch of debounced filesystem events touched.
+#[derive(Debug, Default)]
+struct WatchChanges {
+    state: bool,
+    config: bool,
+    /// Names of the files in the output directory that changed.
+    kubeconfigs: std::collections::BTreeSet<String>,
+}
+
+/// Sort a batch of debounced paths into the state file, the config file and the files
+/// in the output directory.
+fn classify_watch_events<'a>(
+    paths: impl Iterator<Item = &'a std::path::Path>,
+    state_file: &std::path::Path,
+    config_file: Option<&std::path::Path>,
+    output_dir: Option<&std::path::Path>,
+) -> WatchChanges {
+    let mut changes = WatchChanges::default();
+    for path in paths {
+        if path == state_file {
+            changes.state = true;
+        } else if Some(path) == config_file {
+            changes.config = true;
+        } else if output_dir.is_some()
+            && path.parent() == output_dir
+            && let Some(name) = path.file_name()
+        {
+            changes.kubeconfigs.insert(name.to_string_lossy().into_owned());
+        }
+    }
+    changes
+}
+
+/// Send `ReachabilityDue` right away and then every `interval` until the TUI exits.
+fn spawn_reachability_timer(interval: Duration, tx: mpsc::Sender<AppEvent>) {
     std::thread::spawn(move || {
-        loop {
-            std::thread::sleep(Duration::from_millis(100));
-            if tx_tick.send(AppEvent::Tick).is_err() {
-                break;
-            }
+        while tx.send(AppEvent::ReachabilityDue).is_ok() {
+            std::thread::sleep(interval);
         }
     });
+}
 
-    let tx_watcher = tx.clone();
+/// Poll the state and config files' mtimes every two seconds. Only used when no OS
+/// watcher is available.
+fn spawn_poller(state_path: std::path::PathBuf, config_path: std::path::PathBuf, tx: mpsc::Sender<AppEvent>) {
     std::thread::spawn(move || {
-        let state_path = state::state_file_path();
-        let path = state_path.as_path();
-        let mut last_mtime: Option<std::time::SystemTime> = None;
+        let mtime = |path: &std::path::Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut last_state: Option<std::time::SystemTime> = None;
+        let mut last_config = mtime(&config_path);
         loop {
             std::thread::sleep(Duration::from_secs(2));
-            if let Ok(meta) = std::fs::metadata(path)
-                && let Ok(mtime) = meta.modified()
-                && last_mtime.map(|m| m != mtime).unwrap_or(true)
-            {
-                last_mtime = Some(mtime);
-                if tx_watcher.send(AppEvent::StateFileChanged).is_err() {
+            let state = mtime(&state_path);
+            if state.is_some() && state != last_state {
+                last_state = state;
+                if tx.send(AppEvent::StateFileChanged).is_err() {
+                    break;
+                }
+            }
+            let config = mtime(&config_path);
+            if config.is_some() && config != last_config {
+                last_config = config;
+                if tx.send(AppEvent::ConfigFileChanged).is_err() {
                     break;
                 }
             }
         }
     });
-
-    let mut terminal = ratatui::init();
-    let result = event_loop(&mut terminal, &mut app, &rx, &tx);
-    ratatui::restore();
-    result
 }
 
 /// Record pre-fetch cert state, mark server as in-progress, and spawn a forced fetch.
 /// Centralises the three-step setup that every fetch-triggering key handler needs.
+/// When `max_parallel` fetches are already running the server is queued instead and
+/// started by `drain_fetch_queue` once a slot frees up.
 pub(crate) fn start_fetch(app: &mut AppState, server: crate::config::Server, tx: &mpsc::Sender<AppEvent>) {
     let name = server.name.clone();
-    let vault_pw = app.vault_passwords.get(&name).cloned();
     app.pre_fetch_expiry
         .insert(name.clone(), app.cert_cache.get(&name).copied().flatten());
     app.in_progress.insert(name);
-    spawn_fetch(server, app.config.clone(), app.dry_run, true, vault_pw, tx.clone());
+    if app.running_fetches >= app.config.max_parallel() {
+        app.fetch_queue.push_back(server);
+        return;
+    }
+    launch_fetch(app, server, tx);
+}
+
+fn launch_fetch(app: &mut AppState, server: crate::config::Server, tx: &mpsc::Sender<AppEvent>) {
+    let vault_pw = app.vault_passwords.get(&server.name).cloned();
+    let key_passphrase = session_key_passphrase(app, &server);
+    let cancel = std::sync::Arc::new(crate::ssh::CancelToken::default());
+    app.fetch_cancels.insert(server.name.clone(), cancel.clone());
+    app.running_fetches += 1;
+    spawn_fetch(
+        server,
+        app.config.clone(),
+        app.dry_run,
+        true,
+        vault_pw,
+        key_passphrase,
+        cancel,
+        tx.clone(),
+    );
+}
+
+/// Stops a queued or running fetch. A queued one is dropped at once; a running one ends
+/// with `FetchCancelled` when its thread notices, which takes at most the 10-second
+/// connect timeout. Returns false when `server_name` isn't being fetched.
+pub(crate) fn cancel_fetch(app: &mut AppState, server_name: &str) -> bool {
+    if let Some(pos) = app.fetch_queue.iter().position(|s| s.name == server_name) {
+        app.fetch_queue.remove(pos);
+        app.in_progress.remove(server_name);
+        app.pre_fetch_expiry.remove(server_name);
+        features::fetch_all::on_finished(app, server_name, None);
+        return true;
+    }
+    match app.fetch_cancels.get(server_name) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Passphrase typed into the TUI earlier this session for the server's identity file.
+pub(crate) fn session_key_passphrase(app: &AppState, server: &crate::config::Server) -> Option<String> {
+    server
+        .identity_file(&app.config)
+        .and_then(|path| app.key_passphrases.get(path))
+        .cloned()
+}
+
+/// Start queued fetches until the concurrency limit is reached again.
+fn drain_fetch_queue(app: &mut AppState, tx: &mpsc::Sender<AppEvent>) {
+    while app.running_fetches < app.config.max_parallel()
+        && let Some(server) = app.fetch_queue.pop_front()
+    {
+        launch_fetch(app, server, tx);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn spawn_fetch(
     server: crate::config::Server,
     config: crate::config::Config,
     dry_run: bool,
     force: bool,
-    vault_password: Option<String>,
+    vault_password: Option<zeroize::Zeroizing<String>>,
+    key_passphrase: Option<String>,
+    cancel: std::sync::Arc<crate::ssh::CancelToken>,
     tx: mpsc::Sender<AppEvent>,
 ) {
     std::thread::spawn(move || {
-        let result = crate::fetch::process_server(&server, &config, dry_run, force, vault_password.as_deref())
-            .map(|_| ())
-            .map_err(|e| friendly_error(&e));
+        let on_progress = |bytes: u64| {
+            tx.send(AppEvent::FetchProgress {
+                server_name: server.name.clone(),
+                bytes,
+            })
+            .ok();
+        };
+        let started = std::time::Instant::now();
+        let mut prompt = None;
+        let mut unreachable = None;
+        let result = crate::fetch::process_server(
+            &server,
+            &config,
+            dry_run,
+            force,
+            vault_password.as_ref().map(|pw| pw.as_str()),
+            key_passphrase.as_deref(),
+            &on_progress,
+            Some(&cancel),
+        );
+        if let Err(e) = &result
+            && e.downcast_ref::<crate::ssh::Cancelled>().is_some()
+        {
+            tx.send(AppEvent::FetchCancelled {
+                server_name: server.name,
+            })
+            .ok();
+            return;
+        }
+        let result = result
+            .map(|outcome| match outcome {
+                crate::fetch::ServerResult::DryRun(diff) => {
+                    prompt = Some(AppEvent::DryRunDiff {
+                        server_name: server.name.clone(),
+                        diff,
+                    });
+                }
+                crate::fetch::ServerResult::Unreachable(e) => unreachable = Some(e),
+                _ => {}
+            })
+            .map_err(|e| {
+                prompt = follow_up_prompt(&e, &server.name);
+                friendly_error(&e)
+            });
         tx.send(AppEvent::FetchComplete {
             server_name: server.name,
             result,
+            unreachable,
+            duration: started.elapsed(),
         })
         .ok();
+        if let Some(event) = prompt {
+            tx.send(event).ok();
+        }
     });
 }
 
+/// For failures the user can resolve on the spot (an unknown host key, a missing key
+/// passphrase, a merge conflict), returns the event that opens the matching prompt.
+/// Worker threads send it after their completion event.
+pub(crate) fn follow_up_prompt(e: &anyhow::Error, server_name: &str) -> Option<AppEvent> {
+    if let Some(crate::ssh::HostKeyError::Unknown(key)) = e.downcast_ref::<crate::ssh::HostKeyError>() {
+        return Some(AppEvent::HostKeyUnknown {
+            server_name: server_name.to_string(),
+            key: key.clone(),
+        });
+    }
+    if let Some(conflict) = e.downcast_ref::<crate::kube::MergeConflict>() {
+        return Some(AppEvent::MergeConflict {
+            server_name: server_name.to_string(),
+            names: conflict.names.clone(),
+        });
+    }
+    if e.downcast_ref::<crate::ssh::SudoPasswordRejected>().is_some() {
+        return Some(AppEvent::SudoPasswordRejected {
+            server_name: server_name.to_string(),
+        });
+    }
+    if e.downcast_ref::<crate::ssh::OtpRequired>().is_some() {
+        return Some(AppEvent::OtpRequired {
+            server_name: server_name.to_string(),
+        });
+    }
+    e.downcast_ref::<crate::ssh::PassphraseRequired>()
+        .map(|p| AppEvent::KeyPassphraseRequired {
+            server_name: server_name.to_string(),
+            identity_file: p.identity_file.clone(),
+        })
+}
+
+/// Opens a modal prompt on top of the current view, but only from views that start
+/// connections; elsewhere the row's error message is enough and the prompt
+/// reappears on the next attempt.
+fn open_prompt(app: &mut AppState, make_view: impl FnOnce(Box<View>) -> View) {
+    if matches!(app.view, View::Dashboard | View::Detail(_) | View::Wizard(_)) {
+        let return_to = std::mem::replace(&mut app.view, View::Dashboard);
+        app.view = make_view(Box::new(return_to));
+    }
+}
+
+/// After a prompt is answered, fetch a configured server again, or ask the user to
+/// re-run the wizard's connection test.
+pub(crate) fn retry_after_prompt(app: &mut AppState, server_name: &str, what: &str, tx: &mpsc::Sender<AppEvent>) {
+    match app.config.servers.iter().find(|s| s.name == server_name).cloned() {
+        Some(server) if !matches!(app.view, View::Wizard(_)) => {
+            app.notification = Some((
+                format!("{} — fetching '{}'", what, server_name),
+                std::time::Instant::now(),
+            ));
+            start_fetch(app, server, tx);
+        }
+        _ => {
+            app.notification = Some((format!("{} — press t to test again", what), std::time::Instant::now()));
+        }
+    }
+}
+
 /// Build a fetch completion notification that shows whether the cert changed.
 fn build_fetch_notification(
     server_name: &str,
@@ -227,48 +593,132 @@ fn build_fetch_notification(
     }
 }
 
+/// Shows the bytes read so far in the server's row. Progress arriving after the fetch
+/// finished or was cancelled is dropped.
+fn on_fetch_progress(app: &mut AppState, server_name: String, bytes: u64) {
+    if app.in_progress.contains(&server_name) {
+        app.fetch_progress.insert(server_name, bytes);
+    }
+}
+
+/// Takes in a state file written by another process (a CLI run, the daemon). The TUI's
+/// own writes after each fetch hold what it already shows, so they change nothing and
+/// leave that fetch's notification in place.
+fn on_state_file_changed(
+    app: &mut AppState,
+    read: Result<std::collections::HashMap<String, state::ServerRunState>, anyhow::Error>,
+) {
+    match read {
+        Ok(new_states) if new_states == app.server_states => {}
+        Ok(new_states) => {
+            app.server_states = new_states;
+            app.refresh_cert_cache();
+            app.notification = Some(("State refreshed".to_string(), std::time::Instant::now()));
+        }
+        Err(_) => {
+            app.notification = Some((
+                "State file unreadable — showing cached data".to_string(),
+                std::time::Instant::now(),
+            ));
+        }
+    }
+}
+
+/// Records a finished fetch in memory: the server's run state, the ✓ or ✗ and the
+/// duration shown beside it until `RESULT_SHOWN` has passed, and the notification.
+/// Returns the run state for the caller to write to the state file.
+fn record_fetch_result(
+    app: &mut AppState,
+    server_name: &str,
+    result: &Result<(), String>,
+    unreachable: Option<&str>,
+    duration: Duration,
+) -> state::ServerRunState {
+    let mut run_state = match result {
+        Ok(()) => state::ServerRunState {
+            status: match unreachable {
+                Some(_) => state::RunStatus::Unreachable,
+                None => state::RunStatus::Fetched,
+            },
+            last_updated: Some(chrono::Utc::now()),
+            error: unreachable.map(str::to_string),
+            duration_ms: None,
+        },
+        Err(msg) => {
+            let status = if crate::state::is_auth_error(msg) {
+                state::RunStatus::AuthRejected
+            } else {
+                state::RunStatus::Failed
+            };
+            state::ServerRunState {
+                status,
+                last_updated: Some(chrono::Utc::now()),
+                error: Some(msg.clone()),
+                duration_ms: None,
+            }
+        }
+    };
+    run_state.duration_ms = Some(duration.as_millis() as u64);
+    // Refresh cert cache directly from the kube file
+    app.refresh_cert_for(server_name);
+    let new_expiry = app.cert_cache.get(server_name).copied().flatten();
+    // Build delta notification before consuming pre_fetch_expiry
+    let pre = app.pre_fetch_expiry.remove(server_name);
+    let notif = match unreachable {
+        Some(_) => format!("{}: fetched, but the API server is unreachable", server_name),
+        None => build_fetch_notification(server_name, pre, new_expiry, result.is_ok()),
+    };
+    app.last_results.insert(
+        server_name.to_string(),
+        FetchResult {
+            ok: result.is_ok() && unreachable.is_none(),
+            duration,
+            finished: std::time::Instant::now(),
+        },
+    );
+    app.server_states.insert(server_name.to_string(), run_state.clone());
+    app.notification = Some((notif, std::time::Instant::now()));
+    run_state
+}
+
 fn event_loop(
     terminal: &mut ratatui::DefaultTerminal,
     app: &mut AppState,
     rx: &mpsc::Receiver<AppEvent>,
     tx: &mpsc::Sender<AppEvent>,
+    ticker: &Ticker,
 ) -> anyhow::Result<()> {
     loop {
         // Render
         terminal.draw(|frame| render_app(frame, app))?;
+        ticker.set_active(app.is_animating());
 
         // Process next event
         match rx.recv() {
             Ok(AppEvent::Key(key)) => {
-                if handle_key(app, key, tx, terminal) {
+                if handle_key(app, key, tx, terminal) && features::quit::confirm(app) {
                     break; // quit
                 }
             }
+            Ok(AppEvent::Paste(text)) => {
+                if matches!(app.view, View::Wizard(_)) {
+                    features::wizard::handle_paste(app, &text);
+                }
+            }
             Ok(AppEvent::Resize(_, _)) => {
                 // ratatui handles resize automatically on next draw
             }
             Ok(AppEvent::Tick) => {
                 app.spinner.tick();
-                app.flash_rows.retain(|_, v| {
-                    *v = v.saturating_sub(1);
-                    *v > 0
-                });
+                app.last_results.retain(|_, r| r.is_recent());
                 if let Some((_, ts)) = &app.notification
                     && ts.elapsed() > Duration::from_secs(3)
                 {
                     app.notification = None;
                 }
                 // Skip redraw if nothing needs animating
-                let probe_active = app
-                    .probe
-                    .as_ref()
-                    .map(|(_, s)| matches!(s, ProbeState::Probing))
-                    .unwrap_or(false);
-                if app.in_progress.is_empty()
-                    && app.flash_rows.is_empty()
-                    && app.notification.is_none()
-                    && !probe_active
-                {
+                if !app.is_animating() {
+                    ticker.set_active(false);
                     continue;
                 }
             }
@@ -277,68 +727,130 @@ fn event_loop(
                     Ok(expiry) => ProbeState::Done(expiry),
                     Err(msg) => ProbeState::Failed(msg),
                 };
+                app.remote_certs.insert(server_name.clone(), probe_state.clone());
                 app.probe = Some((server_name, probe_state));
             }
-            Ok(AppEvent::FetchComplete { server_name, result }) => {
-                app.in_progress.remove(&server_name);
-                let run_state = match &result {
-                    Ok(()) => state::ServerRunState {
-                        status: state::RunStatus::Fetched,
-                        last_updated: Some(chrono::Utc::now()),
-                        error: None,
-                    },
-                    Err(msg) => {
-                        let status = if crate::state::is_auth_error(msg) {
-                            state::RunStatus::AuthRejected
-                        } else {
-                            state::RunStatus::Failed
-                        };
-                        state::ServerRunState {
-                            status,
-                            last_updated: Some(chrono::Utc::now()),
-                            error: Some(msg.clone()),
-                        }
-                    }
-                };
-                // Refresh cert cache directly from the kube file
-                let mut local_path = std::path::PathBuf::from(&app.config.local_output_dir);
-                local_path.push(&server_name);
-                let new_expiry = match crate::kube::check_local_cert_expiry(&local_path) {
-                    crate::kube::CertStatus::Valid(exp) | crate::kube::CertStatus::Expired(exp) => Some(exp),
-                    _ => None,
+            Ok(AppEvent::ApiProbeComplete { server_name, result }) => {
+                let probe_state = match result {
+                    Ok(cert) => app::ApiProbeState::Done(cert),
+                    Err(msg) => app::ApiProbeState::Failed(msg),
                 };
-                app.cert_cache.insert(server_name.clone(), new_expiry);
-                // Build delta notification before consuming pre_fetch_expiry
-                let pre = app.pre_fetch_expiry.remove(&server_name);
-                let notif = build_fetch_notification(&server_name, pre, new_expiry, result.is_ok());
-                app.flash_rows.insert(server_name.clone(), 3);
-                app.server_states.insert(server_name.clone(), run_state.clone());
-                app.notification = Some((notif, std::time::Instant::now()));
+                app.api_probe = Some((server_name, probe_state));
+            }
+            Ok(AppEvent::FetchProgress { server_name, bytes }) => on_fetch_progress(app, server_name, bytes),
+            Ok(AppEvent::RenewComplete { server_name, result }) => {
+                features::renew::on_complete(app, server_name, result, tx);
+            }
+            Ok(AppEvent::FetchCancelled { server_name }) => {
+                app.in_progress.remove(&server_name);
+                app.fetch_progress.remove(&server_name);
+                app.fetch_cancels.remove(&server_name);
+                app.pre_fetch_expiry.remove(&server_name);
+                app.running_fetches = app.running_fetches.saturating_sub(1);
+                drain_fetch_queue(app, tx);
+                app.notification = Some((format!("{}: fetch cancelled", server_name), std::time::Instant::now()));
+                features::fetch_all::on_finished(app, &server_name, None);
+            }
+            Ok(AppEvent::FetchComplete {
+                server_name,
+                result,
+                unreachable,
+                duration,
+            }) => {
+                app.in_progress.remove(&server_name);
+                app.fetch_cancels.remove(&server_name);
+                app.fetch_progress.remove(&server_name);
+                app.running_fetches = app.running_fetches.saturating_sub(1);
+                drain_fetch_queue(app, tx);
+                let run_state = record_fetch_result(app, &server_name, &result, unreachable.as_deref(), duration);
                 if let Err(e) = state::update_server_state(&server_name, run_state) {
                     log::warn!("Could not write state file: {}", e);
                 }
+                let outcome = match unreachable {
+                    Some(e) => Err(format!("fetched, but the API server is unreachable: {}", e)),
+                    None => result,
+                };
+                features::fetch_all::on_finished(app, &server_name, Some(outcome));
+            }
+            Ok(AppEvent::HostKeyUnknown { server_name, key }) => {
+                open_prompt(app, |return_to| View::HostKeyTrust {
+                    server_name,
+                    key,
+                    return_to,
+                });
+            }
+            Ok(AppEvent::KeyPassphraseRequired {
+                server_name,
+                identity_file,
+            }) => {
+                app.credential_input.clear();
+                open_prompt(app, |return_to| View::KeyPassphraseInput {
+                    server_name,
+                    identity_file,
+                    return_to,
+                });
+            }
+            // The wizard's password field is the one to fix there, not the keyring.
+            Ok(AppEvent::SudoPasswordRejected { server_name }) if !matches!(app.view, View::Wizard(_)) => {
+                app.credential_input.clear();
+                open_prompt(app, |return_to| View::SudoPasswordInput { server_name, return_to });
+            }
+            Ok(AppEvent::SudoPasswordRejected { .. }) => {}
+            Ok(AppEvent::OtpRequired { server_name }) => {
+                app.credential_input.clear();
+                open_prompt(app, |return_to| View::OtpInput { server_name, return_to });
+            }
+            Ok(AppEvent::MergeConflict { server_name, names }) => {
+                open_prompt(app, |return_to| View::MergeConflict {
+                    server_name,
+                    names,
+                    return_to,
+                });
+            }
+            Ok(AppEvent::ReachabilityDue) => {
+                features::reachability::check_all(app, tx);
+            }
+            Ok(AppEvent::RemoteCertChecked { server_name, result }) => {
+                let probe_state = match result {
+                    Ok(expiry) => ProbeState::Done(expiry),
+                    Err(msg) => ProbeState::Failed(msg),
+                };
+                app.remote_certs.insert(server_name, probe_state);
+            }
+            Ok(AppEvent::ReachabilityChecked {
+                server_name,
+                reachability,
+            }) => {
+                app.reachability.insert(server_name, reachability);
+            }
+            Ok(AppEvent::DryRunDiff { server_name, diff }) => {
+                features::dry_run::on_diff(app, &server_name, &diff);
             }
             Ok(AppEvent::BitwardenComplete { result }) => {
                 features::bitwarden::on_complete(app, result);
             }
+            Ok(AppEvent::DiscoverComplete { network, result }) => {
+                features::import::on_discover_complete(app, network, result);
+            }
             Ok(AppEvent::WizardTestComplete { result }) => {
                 features::wizard::on_test_complete(app, result);
             }
-            Ok(AppEvent::StateFileChanged) => match state::read_state() {
-                Ok(new_states) => {
-                    app.server_states = new_states;
-                    app.refresh_cert_cache();
-                    app.notification = Some(("State refreshed".to_string(), std::time::Instant::now()));
-                }
-                Err(_) => {
-                    app.notification = Some((
-                        "State file unreadable — showing cached data".to_string(),
-                        std::time::Instant::now(),
-                    ));
+            Ok(AppEvent::KubeconfigChanged { file_name }) => {
+                // In-flight fetches refresh their own entry on completion.
+                if let Some(server_name) = app.config.server_for_local_file(&file_name).map(|s| s.name.clone())
+                    && !app.in_progress.contains(&server_name)
+                {
+                    app.refresh_cert_for(&server_name);
                 }
-            },
+            }
+            Ok(AppEvent::ConfigFileChanged) => features::config_reload::on_changed(app),
+            Ok(AppEvent::StateFileChanged) => on_state_file_changed(app, state::read_state()),
             Err(_) => break, // channel closed
         }
+        app.log_notification();
+        if features::quit::ready(app) {
+            break;
+        }
     }
     Ok(())
 }
@@ -357,8 +869,31 @@ fn render_app(frame: &mut ratatui::Frame, app: &mut AppState) {
         CredentialMenu(String),
         CredentialInput(String),
         DeleteConfirm(String),
+        MarkedCredentialInput(String), // server count label
+        MarkedDeleteConfirm(Vec<String>),
         KeyringFallbackConsent(String, String), // (server_name, keyring_error)
+        MasterPassphraseInput,
         BitwardenUnlock,
+        HostKeyTrust,
+        KeyPassphraseInput,
+        SudoPasswordInput,
+        OtpInput,
+        MergeConflict,
+        KubeconfigPreview,
+        DryRunDiff,
+        RenewConfirm,
+        FetchNowConfirm,
+        Import,
+        DiscoverInput,
+        PruneConfirm,
+        RenameInput,
+        History(String),
+        AuditLog,
+        Notifications,
+        Settings,
+        QuitConfirm,
+        FetchAllConfirm,
+        FetchAllSummary(Option<String>), // detail view underneath
     }
 
     let kind = match &app.view {
@@ -367,17 +902,43 @@ fn render_app(frame: &mut ratatui::Frame, app: &mut AppState) {
         View::EditServer(_) => ViewKind::EditServer,
         View::Wizard(_) => ViewKind::Wizard,
         View::SetupWizard(_) => ViewKind::SetupWizard,
-        View::Help => ViewKind::Help,
+        View::Help(_) => ViewKind::Help,
         View::Error { message } => ViewKind::ErrorView(message.clone()),
         View::CredentialMenu(name) => ViewKind::CredentialMenu(name.clone()),
         View::CredentialInput(name) => ViewKind::CredentialInput(name.clone()),
         View::DeleteConfirm(name) => ViewKind::DeleteConfirm(name.clone()),
+        View::MarkedCredentialInput(names) => ViewKind::MarkedCredentialInput(format!("{} servers", names.len())),
+        View::MarkedDeleteConfirm(names) => ViewKind::MarkedDeleteConfirm(names.clone()),
         View::KeyringFallbackConsent {
-            server_name,
+            server_names,
             keyring_error,
             ..
-        } => ViewKind::KeyringFallbackConsent(server_name.clone(), keyring_error.clone()),
+        } => ViewKind::KeyringFallbackConsent(server_names.join(", "), keyring_error.clone()),
+        View::MasterPassphraseInput { .. } => ViewKind::MasterPassphraseInput,
         View::BitwardenUnlock { .. } => ViewKind::BitwardenUnlock,
+        View::HostKeyTrust { .. } => ViewKind::HostKeyTrust,
+        View::KeyPassphraseInput { .. } => ViewKind::KeyPassphraseInput,
+        View::SudoPasswordInput { .. } => ViewKind::SudoPasswordInput,
+        View::OtpInput { .. } => ViewKind::OtpInput,
+        View::MergeConflict { .. } => ViewKind::MergeConflict,
+        View::KubeconfigPreview { .. } => ViewKind::KubeconfigPreview,
+        View::DryRunDiff { .. } => ViewKind::DryRunDiff,
+        View::RenewConfirm { .. } => ViewKind::RenewConfirm,
+        View::FetchNowConfirm(_) => ViewKind::FetchNowConfirm,
+        View::Import(_) => ViewKind::Import,
+        View::DiscoverInput { .. } => ViewKind::DiscoverInput,
+        View::PruneConfirm(_) => ViewKind::PruneConfirm,
+        View::RenameInput { .. } => ViewKind::RenameInput,
+        View::History { server_name, .. } => ViewKind::History(server_name.clone()),
+        View::AuditLog { .. } => ViewKind::AuditLog,
+        View::Notifications { .. } => ViewKind::Notifications,
+        View::Settings(_) => ViewKind::Settings,
+        View::QuitConfirm { .. } => ViewKind::QuitConfirm,
+        View::FetchAllConfirm(_) => ViewKind::FetchAllConfirm,
+        View::FetchAllSummary { return_to, .. } => ViewKind::FetchAllSummary(match return_to.as_ref() {
+            View::Detail(name) => Some(name.clone()),
+            _ => None,
+        }),
     };
 
     match kind {
@@ -413,7 +974,7 @@ fn render_app(frame: &mut ratatui::Frame, app: &mut AppState) {
         ViewKind::ErrorView(message) => {
             features::dashboard::render(frame, app);
             features::render_dim_background(frame, frame.area());
-            features::dashboard::render_error_overlay(frame, &message);
+            features::dashboard::render_error_overlay(frame, &message, app);
         }
         ViewKind::CredentialMenu(name) => {
             features::dashboard::render(frame, app);
@@ -430,11 +991,113 @@ fn render_app(frame: &mut ratatui::Frame, app: &mut AppState) {
             features::render_dim_background(frame, frame.area());
             features::dashboard::render_delete_confirm(frame, app, &name);
         }
+        ViewKind::MarkedCredentialInput(label) => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::credentials::render_input(frame, app, &label);
+        }
+        ViewKind::MarkedDeleteConfirm(names) => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::dashboard::render_marked_delete_confirm(frame, &names);
+        }
         ViewKind::KeyringFallbackConsent(server_name, keyring_error) => {
             features::dashboard::render(frame, app);
             features::keyring_fallback::render(frame, app, &server_name, &keyring_error);
         }
+        ViewKind::MasterPassphraseInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::keyring_fallback::render_master_passphrase(frame, app);
+        }
         ViewKind::BitwardenUnlock => features::bitwarden::render(frame, app),
+        ViewKind::HostKeyTrust => {
+            features::dashboard::render(frame, app);
+            features::host_key::render(frame, app);
+        }
+        ViewKind::KeyPassphraseInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::credentials::render_passphrase_input(frame, app);
+        }
+        ViewKind::SudoPasswordInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::credentials::render_sudo_password_input(frame, app);
+        }
+        ViewKind::OtpInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::credentials::render_otp_input(frame, app);
+        }
+        ViewKind::MergeConflict => {
+            features::dashboard::render(frame, app);
+            features::merge_conflict::render(frame, app);
+        }
+        ViewKind::KubeconfigPreview => features::preview::render(frame, app),
+        ViewKind::DryRunDiff => {
+            features::dashboard::render(frame, app);
+            features::dry_run::render(frame, app);
+        }
+        ViewKind::RenewConfirm => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::renew::render_confirm(frame, app);
+        }
+        ViewKind::FetchNowConfirm => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::wizard::render_fetch_confirm(frame, app);
+        }
+        ViewKind::Import => {
+            features::dashboard::render(frame, app);
+            features::import::render(frame, app);
+        }
+        ViewKind::DiscoverInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::import::render_discover_input(frame, app);
+        }
+        ViewKind::PruneConfirm => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::prune::render(frame, app);
+        }
+        ViewKind::RenameInput => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::rename::render(frame, app);
+        }
+        ViewKind::History(name) => {
+            features::detail::render(frame, app, &name);
+            features::render_dim_background(frame, frame.area());
+            features::history::render(frame, app);
+        }
+        ViewKind::AuditLog => features::audit::render(frame, app),
+        ViewKind::Notifications => features::notifications::render(frame, app),
+        ViewKind::Settings => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::settings::render(frame, app);
+        }
+        ViewKind::QuitConfirm => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::quit::render(frame, app);
+        }
+        ViewKind::FetchAllConfirm => {
+            features::dashboard::render(frame, app);
+            features::render_dim_background(frame, frame.area());
+            features::fetch_all::render_confirm(frame, app);
+        }
+        ViewKind::FetchAllSummary(detail) => {
+            match detail {
+                Some(name) => features::detail::render(frame, app, &name),
+                None => features::dashboard::render(frame, app),
+            }
+            features::render_dim_background(frame, frame.area());
+            features::fetch_all::render_summary(frame, app);
+        }
     }
 }
 
@@ -446,17 +1109,18 @@ fn handle_key(
 ) -> bool {
     use crossterm::event::{KeyCode, KeyModifiers};
 
-    // Global: Ctrl+C and Ctrl+D always quit regardless of the active view.
+    // Global: Ctrl+C and Ctrl+D quit regardless of the active view (asking first while
+    // fetches are running).
     if key.modifiers.contains(KeyModifiers::CONTROL) && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d')) {
         return true;
     }
 
     match &app.view {
         View::Dashboard => features::dashboard::handle_key(app, key, tx, terminal),
-        View::Detail(name) => features::detail::handle_key(app, name.clone(), key, tx),
+        View::Detail(name) => features::detail::handle_key(app, name.clone(), key, tx, terminal),
         View::EditServer(_) => features::edit_server::handle_key(app, key),
         View::DeleteConfirm(name) => features::dashboard::handle_key_delete_confirm(app, name.clone(), key),
-        View::Help => {
+        View::Help(_) => {
             features::help::handle_key(app, key);
             false
         }
@@ -466,20 +1130,100 @@ fn handle_key(
         }
         View::CredentialMenu(name) => features::credentials::handle_key_menu(app, name.clone(), key),
         View::CredentialInput(name) => features::credentials::handle_key_input(app, name.clone(), key),
+        View::MarkedCredentialInput(names) => features::credentials::handle_key_marked_input(app, names.clone(), key),
+        View::MarkedDeleteConfirm(names) => {
+            features::dashboard::handle_key_marked_delete_confirm(app, names.clone(), key)
+        }
         View::Wizard(_) => features::wizard::handle_key(app, key, tx),
         View::SetupWizard(_) => features::setup::handle_key(app, key, tx),
         View::KeyringFallbackConsent { .. } => features::keyring_fallback::handle_key(app, key),
+        View::MasterPassphraseInput { .. } => features::keyring_fallback::handle_key_master_passphrase(app, key),
+        View::HostKeyTrust { .. } => features::host_key::handle_key(app, key, tx),
+        View::KeyPassphraseInput { .. } => features::credentials::handle_key_passphrase(app, key, tx),
+        View::SudoPasswordInput { .. } => features::credentials::handle_key_sudo_password(app, key, tx),
+        View::OtpInput { .. } => features::credentials::handle_key_otp(app, key, tx),
+        View::MergeConflict { .. } => features::merge_conflict::handle_key(app, key),
+        View::KubeconfigPreview { .. } => {
+            let page = terminal
+                .size()
+                .map(|s| s.height.saturating_sub(3) as usize)
+                .unwrap_or(20);
+            features::preview::handle_key(app, key, page)
+        }
+        View::DryRunDiff { .. } => {
+            let page = terminal
+                .size()
+                .map(|s| s.height.saturating_sub(6) as usize)
+                .unwrap_or(20);
+            features::dry_run::handle_key(app, key, page)
+        }
+        View::RenewConfirm { .. } => features::renew::handle_key_confirm(app, key, tx),
+        View::FetchNowConfirm(_) => features::wizard::handle_key_fetch_confirm(app, key, tx),
+        View::Import(_) => features::import::handle_key(app, key),
+        View::DiscoverInput { .. } => features::import::handle_key_discover_input(app, key, tx),
+        View::PruneConfirm(_) => features::prune::handle_key(app, key),
+        View::RenameInput { .. } => features::rename::handle_key(app, key),
+        View::History { .. } => features::history::handle_key(app, key),
+        View::AuditLog { .. } => {
+            let page = terminal
+                .size()
+                .map(|s| s.height.saturating_sub(3) as usize)
+                .unwrap_or(20);
+            features::audit::handle_key(app, key, page)
+        }
+        View::Notifications { .. } => {
+            let page = terminal
+                .size()
+                .map(|s| s.height.saturating_sub(3) as usize)
+                .unwrap_or(20);
+            features::notifications::handle_key(app, key, page)
+        }
         View::BitwardenUnlock { .. } => features::bitwarden::handle_key(app, key, tx),
+        View::Settings(_) => features::settings::handle_key(app, key),
+        View::QuitConfirm { .. } => features::quit::handle_key(app, key),
+        View::FetchAllConfirm(_) => features::fetch_all::handle_key_confirm(app, key, tx),
+        View::FetchAllSummary { .. } => features::fetch_all::handle_key_summary(app, key),
     }
 }
 
 /// Map an anyhow error to a human-readable, actionable message (NFR-7).
 pub fn friendly_error(e: &anyhow::Error) -> String {
+    match e.downcast_ref::<crate::ssh::HostKeyError>() {
+        Some(crate::ssh::HostKeyError::Unknown(key)) => {
+            return format!("Host key for {} not trusted yet ({}).", key.host, key.fingerprint);
+        }
+        Some(mismatch) => return mismatch.to_string(),
+        None => {}
+    }
+    if let Some(p) = e.downcast_ref::<crate::ssh::PassphraseRequired>() {
+        return format!("Identity file {} needs a passphrase.", p.identity_file);
+    }
+    if let Some(t) = e.downcast_ref::<crate::fetch::TimedOut>() {
+        return format!(
+            "Fetch timed out after {}s — the node may be hung. Raise fetch_timeout_seconds if it is just slow.",
+            t.0.as_secs()
+        );
+    }
+    if e.downcast_ref::<crate::ssh::SudoPasswordRejected>().is_some() {
+        return "sudo rejected the stored password. Update it with 'c'.".to_string();
+    }
+    if e.downcast_ref::<crate::ssh::OtpRequired>().is_some() {
+        return "The server asks for a one-time code; fetch again to enter one.".to_string();
+    }
+    if let Some(c) = e.downcast_ref::<crate::kube::MergeConflict>() {
+        return format!(
+            "Fetched, but ~/.kube/config already has a different cluster named {}.",
+            c.names.join(", ")
+        );
+    }
     let s = format!("{:#}", e);
     let lower = s.to_lowercase();
     if lower.contains("connection refused") || lower.contains("timed out") || lower.contains("no route") {
         return "Could not reach host — is it up and reachable from this machine?".to_string();
     }
+    if lower.contains("wrong passphrase") {
+        return "Key passphrase rejected, or the identity file isn't a valid private key.".to_string();
+    }
     if lower.contains("authentication failed") || lower.contains("auth rejected") {
         return "Password rejected by server. Check credentials with 'c'.".to_string();
     }
@@ -508,3 +1252,751 @@ pub fn friendly_error(e: &anyhow::Error) -> String {
     // Fallback: return original
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_is_animating_tracks_activity() {
+        let mut app = AppState::new(
+            Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        assert!(!app.is_animating(), "idle dashboard should let the ticker park");
+        app.in_progress.insert("alpha".to_string());
+        assert!(app.is_animating());
+        app.in_progress.clear();
+        app.notification = Some(("hi".to_string(), std::time::Instant::now()));
+        assert!(app.is_animating());
+        app.notification = None;
+        app.probe = Some(("alpha".to_string(), ProbeState::Probing));
+        assert!(app.is_animating());
+        app.probe = None;
+        let result = FetchResult {
+            ok: true,
+            duration: Duration::from_millis(2140),
+            finished: std::time::Instant::now(),
+        };
+        assert!(result.is_recent());
+        app.last_results.insert("alpha".to_string(), result);
+        assert!(app.is_animating(), "the ✓ beside a finished fetch has to expire");
+    }
+
+    #[test]
+    fn test_wizard_line_editing() {
+        use crossterm::event::{KeyCode, KeyEvent};
+        let key = |code| KeyEvent::from(code);
+
+        let server = crate::config::Server {
+            name: "prod".to_string(),
+            address: "10.0.0.1".to_string(),
+            ..Default::default()
+        };
+        let mut ws = app::WizardState::for_server(&server);
+        assert_eq!(ws.cursor, 8, "prefilled fields start with the cursor at the end");
+        for code in [
+            KeyCode::Home,
+            KeyCode::Delete,
+            KeyCode::Char('9'),
+            KeyCode::End,
+            KeyCode::Left,
+        ] {
+            assert!(ws.edit(&key(code)));
+        }
+        ws.paste("23\n");
+        assert_eq!(ws.address, "90.0.0.231");
+        assert!(!ws.edit(&key(KeyCode::Enter)));
+
+        ws.go_to(app::WizardStep::Auth);
+        assert!(ws.field_mut().is_none(), "the password has its own input");
+        let mut input = app::MaskedInput::new();
+        input.paste("hunter2");
+        input.edit(&key(KeyCode::Left));
+        input.edit(&key(KeyCode::Backspace));
+        input.push('X');
+        assert_eq!(input.value, "hunteX2");
+        assert_eq!(app::with_cursor(&input.masked_display(), input.cursor), "******│*");
+        input.clear();
+        assert_eq!((input.value.as_str(), input.cursor), ("", 0));
+    }
+
+    #[test]
+    fn test_wizard_review_revisits_one_step() {
+        use app::WizardStep;
+
+        let server = crate::config::Server {
+            name: "prod".to_string(),
+            ..Default::default()
+        };
+        let mut ws = app::WizardState::for_server(&server);
+        ws.go_to(WizardStep::Review);
+        ws.revisit(WizardStep::Name);
+        assert_eq!(ws.step, WizardStep::Review, "the name can't change when editing");
+
+        ws.revisit(WizardStep::FilePath);
+        assert_eq!(ws.step, WizardStep::FilePath);
+        assert_eq!(ws.next_step(), Some(WizardStep::Review));
+        assert_eq!(ws.prev_step(), Some(WizardStep::Review));
+        ws.go_to(WizardStep::Review);
+        assert!(!ws.reviewing);
+        assert_eq!(ws.prev_step(), Some(WizardStep::Auth));
+        assert_eq!(WizardStep::Auth.next(), Some(WizardStep::Review));
+    }
+
+    #[test]
+    fn test_relative_expiry() {
+        use crate::tui::features::relative_expiry;
+        let now = chrono::Utc::now();
+        assert_eq!(
+            relative_expiry(&(now + chrono::Duration::hours(12 * 24 + 3)), now),
+            "in 12d"
+        );
+        assert_eq!(relative_expiry(&(now + chrono::Duration::hours(5)), now), "in 5h");
+        assert_eq!(
+            relative_expiry(&(now - chrono::Duration::days(3)), now),
+            "expired 3d ago"
+        );
+        assert_eq!(
+            relative_expiry(&(now - chrono::Duration::minutes(30)), now),
+            "expired 0h ago"
+        );
+    }
+
+    #[test]
+    fn test_compact_dashboard_helpers() {
+        use crate::tui::features::{compact_expiry, run_duration, status_glyph, truncate};
+        let now = chrono::Utc::now();
+        assert_eq!(
+            compact_expiry(Some(&(now + chrono::Duration::hours(12 * 24 + 3))), now),
+            "12d"
+        );
+        assert_eq!(compact_expiry(Some(&(now + chrono::Duration::hours(5))), now), "5h");
+        assert_eq!(compact_expiry(Some(&(now - chrono::Duration::days(3))), now), "exp");
+        assert_eq!(compact_expiry(None, now), "—");
+
+        assert_eq!(status_glyph(&state::RunStatus::Fetched), "✓");
+        assert_eq!(status_glyph(&state::RunStatus::NoCredential), "⚠");
+
+        assert_eq!(truncate("prod", 4), "prod");
+        assert_eq!(truncate("production-east", 8), "product…");
+        assert_eq!(truncate("prod", 0), "");
+
+        assert_eq!(run_duration(2140), "2.1s");
+        assert_eq!(run_duration(125_000), "2m05s");
+    }
+
+    #[test]
+    fn test_dashboard_renders_compact_in_narrow_terminal() {
+        let config = Config {
+            servers: vec![crate::config::Server {
+                name: "production-cluster-east-1".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let screen = |app: &mut AppState, width, height| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+            terminal.draw(|frame| features::dashboard::render(frame, app)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer.content().iter().map(|c| c.symbol()).collect::<String>()
+        };
+
+        let narrow = screen(&mut app, 50, 12);
+        assert!(!narrow.contains("too small"));
+        assert!(narrow.contains("CERT"));
+        assert!(narrow.contains("production-cluster-east-1"));
+        assert!(!narrow.contains("STATUS"));
+
+        let tiny = screen(&mut app, 20, 12);
+        assert!(tiny.contains("too small"));
+    }
+
+    #[test]
+    fn test_fetch_progress_updates_the_row() {
+        let config = Config {
+            servers: vec![crate::config::Server {
+                name: "alpha".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let screen = |app: &mut AppState| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 12)).unwrap();
+            terminal.draw(|frame| features::dashboard::render(frame, app)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer.content().iter().map(|c| c.symbol()).collect::<String>()
+        };
+
+        on_fetch_progress(&mut app, "alpha".to_string(), 512);
+        assert!(app.fetch_progress.is_empty(), "no fetch running, nothing to show");
+
+        app.in_progress.insert("alpha".to_string());
+        assert!(screen(&mut app).contains("Fetching..."));
+        on_fetch_progress(&mut app, "alpha".to_string(), 8192);
+        assert!(screen(&mut app).contains("8.00 KiB"));
+        on_fetch_progress(&mut app, "alpha".to_string(), 16384);
+        let shown = screen(&mut app);
+        assert!(shown.contains("16.00 KiB"));
+        assert!(!shown.contains("8.00 KiB"));
+    }
+
+    #[test]
+    fn test_own_state_write_keeps_the_fetch_notification() {
+        let mut app = AppState::new(
+            Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let fetched = state::ServerRunState {
+            status: state::RunStatus::Fetched,
+            last_updated: Some(chrono::Utc::now()),
+            error: None,
+            duration_ms: Some(2100),
+        };
+        app.server_states.insert("alpha".to_string(), fetched.clone());
+        app.notification = Some((
+            "alpha: cert renewed → 2027-01-01".to_string(),
+            std::time::Instant::now(),
+        ));
+
+        // What the TUI wrote after the fetch, read back through the file's JSON.
+        let written = serde_json::to_string(&app.server_states).unwrap();
+        on_state_file_changed(&mut app, Ok(serde_json::from_str(&written).unwrap()));
+        assert_eq!(
+            app.notification.as_ref().map(|(n, _)| n.as_str()),
+            Some("alpha: cert renewed → 2027-01-01")
+        );
+
+        let mut elsewhere = app.server_states.clone();
+        elsewhere.insert(
+            "beta".to_string(),
+            state::ServerRunState {
+                status: state::RunStatus::Failed,
+                error: Some("connection refused".to_string()),
+                ..fetched
+            },
+        );
+        on_state_file_changed(&mut app, Ok(elsewhere));
+        assert_eq!(
+            app.notification.as_ref().map(|(n, _)| n.as_str()),
+            Some("State refreshed")
+        );
+        assert!(app.server_states.contains_key("beta"));
+    }
+
+    #[test]
+    fn test_fetch_result_and_duration_show_then_expire() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let server = |name: &str| crate::config::Server {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![server("alpha"), server("beta")],
+            local_output_dir: dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let screen = |app: &mut AppState| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 12)).unwrap();
+            terminal.draw(|frame| features::dashboard::render(frame, app)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer.content().iter().map(|c| c.symbol()).collect::<String>()
+        };
+
+        let fetched = record_fetch_result(&mut app, "alpha", &Ok(()), None, Duration::from_millis(2100));
+        assert_eq!(fetched.status, state::RunStatus::Fetched);
+        assert_eq!(fetched.duration_ms, Some(2100));
+        let failed = record_fetch_result(
+            &mut app,
+            "beta",
+            &Err("connection refused".to_string()),
+            None,
+            Duration::from_millis(400),
+        );
+        assert_eq!(failed.status, state::RunStatus::Failed);
+        assert_eq!(app.server_states.get("beta"), Some(&failed));
+        assert!(app.last_results["alpha"].ok);
+        assert!(!app.last_results["beta"].ok);
+        assert_eq!(app.last_results["beta"].duration, Duration::from_millis(400));
+
+        let shown = screen(&mut app);
+        assert!(shown.contains("✓ 2.1s"), "{}", shown);
+        assert!(shown.contains("✗ 0.4s"), "{}", shown);
+
+        let long_ago = std::time::Instant::now() - app::RESULT_SHOWN - Duration::from_secs(1);
+        for result in app.last_results.values_mut() {
+            result.finished = long_ago;
+        }
+        assert!(app.last_results.values().all(|r| !r.is_recent()));
+        let shown = screen(&mut app);
+        assert!(!shown.contains("✓ 2.1s"));
+        assert!(!shown.contains("✗ 0.4s"));
+    }
+
+    #[test]
+    fn test_cancel_fetch_drops_queued_and_signals_running() {
+        let mut app = AppState::new(
+            Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let queued = crate::config::Server {
+            name: "queued".to_string(),
+            ..Default::default()
+        };
+        app.in_progress.insert("queued".to_string());
+        app.fetch_queue.push_back(queued);
+        let token = std::sync::Arc::new(crate::ssh::CancelToken::default());
+        app.in_progress.insert("running".to_string());
+        app.fetch_cancels.insert("running".to_string(), token.clone());
+
+        assert!(cancel_fetch(&mut app, "queued"));
+        assert!(app.fetch_queue.is_empty());
+        assert!(!app.in_progress.contains("queued"));
+
+        assert!(cancel_fetch(&mut app, "running"));
+        assert!(token.is_cancelled());
+        // The row stays busy until the worker thread reports back.
+        assert!(app.in_progress.contains("running"));
+
+        assert!(!cancel_fetch(&mut app, "idle"));
+    }
+
+    #[test]
+    fn test_quit_asks_while_fetches_run_and_waits_for_cancellation() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let mut app = AppState::new(
+            Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        app.in_progress.insert(app::WIZARD_SENTINEL.to_string());
+        assert!(app.busy_servers().is_empty());
+        assert!(
+            features::quit::confirm(&mut app),
+            "a wizard test doesn't hold up quitting"
+        );
+
+        app.in_progress.insert("queued".to_string());
+        app.fetch_queue.push_back(crate::config::Server {
+            name: "queued".to_string(),
+            ..Default::default()
+        });
+        app.in_progress.insert("running".to_string());
+        let token = std::sync::Arc::new(crate::ssh::CancelToken::default());
+        app.fetch_cancels.insert("running".to_string(), token.clone());
+        assert_eq!(app.busy_servers(), ["queued", "running"]);
+
+        assert!(!features::quit::confirm(&mut app));
+        assert!(matches!(app.view, View::QuitConfirm { waiting: false, .. }));
+        features::quit::handle_key(&mut app, KeyEvent::from(KeyCode::Char('c')));
+        assert!(token.is_cancelled());
+        assert_eq!(app.busy_servers(), ["running"]);
+        assert!(!features::quit::ready(&app), "quits only once the running fetch stops");
+
+        app.in_progress.remove("running");
+        assert!(features::quit::ready(&app));
+        assert!(features::quit::confirm(&mut app), "asking again from the dialog quits");
+    }
+
+    #[test]
+    fn test_notifications_are_logged_once_and_capped() {
+        let mut app = AppState::new(
+            Config::default(),
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        app.log_notification();
+        assert!(app.notification_log.is_empty());
+
+        app.notification = Some(("alpha: fetched".to_string(), std::time::Instant::now()));
+        app.log_notification();
+        app.log_notification();
+        assert_eq!(app.notification_log.len(), 1, "the same notification is kept once");
+        app.notification = None;
+        app.log_notification();
+        assert_eq!(app.notification_log.len(), 1, "expiring doesn't drop it from the log");
+
+        for i in 0..app::NOTIFICATION_HISTORY {
+            app.notification = Some((format!("n{}", i), std::time::Instant::now()));
+            app.log_notification();
+        }
+        assert_eq!(app.notification_log.len(), app::NOTIFICATION_HISTORY);
+        assert_eq!(app.notification_log.front().unwrap().message, "n0");
+    }
+
+    #[test]
+    fn test_cycle_tag_filter_narrows_visible_servers() {
+        let server = |name: &str, tags: &[&str]| crate::config::Server {
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![server("a", &["prod"]), server("b", &["homelab"]), server("c", &[])],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let names = |app: &AppState| app.visible_servers().iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+
+        assert_eq!(names(&app), ["a", "b", "c"]);
+        app.cycle_tag_filter();
+        assert_eq!(app.tag_filter.as_deref(), Some("homelab"));
+        assert_eq!(names(&app), ["b"]);
+        app.cycle_tag_filter();
+        assert_eq!(names(&app), ["a"]);
+        app.cycle_tag_filter();
+        assert_eq!(app.tag_filter, None);
+        assert_eq!(names(&app).len(), 3);
+    }
+
+    #[test]
+    fn test_status_filter_narrows_visible_servers() {
+        use crate::state::{RunStatus, ServerRunState};
+        use crate::tui::app::StatusFilter;
+
+        let server = |name: &str| crate::config::Server {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![server("ok"), server("failed"), server("rejected"), server("new")],
+            ..Default::default()
+        };
+        let run = |status| ServerRunState {
+            status,
+            last_updated: Some(chrono::Utc::now()),
+            error: None,
+            duration_ms: None,
+        };
+        let states = std::collections::HashMap::from([
+            ("ok".to_string(), run(RunStatus::Fetched)),
+            ("failed".to_string(), run(RunStatus::Failed)),
+            ("rejected".to_string(), run(RunStatus::AuthRejected)),
+        ]);
+        let mut app = AppState::new(config, std::path::PathBuf::new(), states, false);
+        let now = chrono::Utc::now();
+        app.cert_cache
+            .insert("ok".to_string(), Some(now + chrono::Duration::days(300)));
+        app.cert_cache
+            .insert("failed".to_string(), Some(now + chrono::Duration::days(3)));
+        let names = |app: &AppState| app.visible_servers().iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+
+        app.set_status_filter(StatusFilter::Failing);
+        assert_eq!(names(&app), ["failed", "rejected"]);
+        assert_eq!(app.table_state.selected(), Some(0));
+        app.set_status_filter(StatusFilter::Expiring);
+        assert_eq!(names(&app), ["failed"]);
+        app.set_status_filter(StatusFilter::NeverFetched);
+        assert_eq!(names(&app), ["new"]);
+
+        app.select_server("ok");
+        assert_eq!(
+            app.status_filter,
+            StatusFilter::All,
+            "selecting a hidden row drops the filter"
+        );
+        assert_eq!(app.table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_fleet_summary_counts_every_server_once() {
+        use crate::state::{RunStatus, ServerRunState};
+        use crate::tui::app::FleetSummary;
+
+        let server = |name: &str, tags: &[&str]| crate::config::Server {
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![
+                server("ok", &["prod"]),
+                server("expiring", &[]),
+                server("failed", &[]),
+                server("unreachable", &[]),
+                server("no-cred", &[]),
+                server("new", &[]),
+            ],
+            ..Default::default()
+        };
+        let run = |status| ServerRunState {
+            status,
+            last_updated: Some(chrono::Utc::now()),
+            error: None,
+            duration_ms: None,
+        };
+        let states = std::collections::HashMap::from([
+            ("ok".to_string(), run(RunStatus::Fetched)),
+            ("expiring".to_string(), run(RunStatus::Skipped)),
+            ("failed".to_string(), run(RunStatus::Failed)),
+            ("unreachable".to_string(), run(RunStatus::Unreachable)),
+            ("no-cred".to_string(), run(RunStatus::NoCredential)),
+        ]);
+        let mut app = AppState::new(config, std::path::PathBuf::new(), states, false);
+        app.cert_cache.insert(
+            "expiring".to_string(),
+            Some(chrono::Utc::now() + chrono::Duration::days(2)),
+        );
+        app.tag_filter = Some("prod".to_string());
+
+        assert_eq!(
+            app.fleet_summary(),
+            FleetSummary {
+                ok: 1,
+                expiring: 1,
+                failed: 2,
+                no_credential: 1,
+                never_fetched: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fetch_all_run_finishes_with_last_server() {
+        let mut run = app::FetchAllRun {
+            pending: ["a", "b", "c"].map(String::from).into(),
+            skipped: vec!["busy".to_string()],
+            ..Default::default()
+        };
+        assert!(!run.record("a", Some(Ok(()))));
+        assert!(
+            !run.record("outside", Some(Ok(()))),
+            "servers outside the run are ignored"
+        );
+        assert!(!run.record("b", Some(Err("auth rejected".to_string()))));
+        assert!(run.record("c", None));
+        assert_eq!(run.fetched, ["a"]);
+        assert_eq!(run.failed, [("b".to_string(), "auth rejected".to_string())]);
+        assert_eq!(run.skipped, ["busy", "c"]);
+    }
+
+    #[test]
+    fn test_search_filters_by_name_address_and_context() {
+        let server = |name: &str, address: &str, context: Option<&str>| crate::config::Server {
+            name: name.to_string(),
+            address: address.to_string(),
+            context_name: context.map(str::to_string),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![
+                server("prod-k3s", "10.0.0.1", None),
+                server("lab", "pi.home.lan", Some("homelab")),
+                server("staging", "10.0.0.2", Some("STAGE")),
+            ],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        let names = |app: &AppState| app.visible_servers().iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+
+        app.set_search(Some("10.0".to_string()));
+        assert_eq!(names(&app), ["prod-k3s", "staging"]);
+        assert_eq!(app.table_state.selected(), Some(0));
+        app.select_match(true);
+        assert_eq!(app.table_state.selected(), Some(1));
+        app.select_match(true);
+        assert_eq!(app.table_state.selected(), Some(0));
+        app.select_match(false);
+        assert_eq!(app.table_state.selected(), Some(1));
+
+        app.set_search(Some("home".to_string()));
+        assert_eq!(names(&app), ["lab"]);
+        app.set_search(Some("stage".to_string()));
+        assert_eq!(names(&app), ["staging"]);
+        app.set_search(Some("nothing".to_string()));
+        assert!(names(&app).is_empty());
+        assert_eq!(app.table_state.selected(), None);
+        app.set_search(None);
+        assert_eq!(names(&app).len(), 3);
+    }
+
+    #[test]
+    fn test_select_server_clears_filters_hiding_it() {
+        let server = |name: &str| crate::config::Server {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![server("a"), server("b"), server("new")],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        app.set_search(Some("b".to_string()));
+        app.select_server("b");
+        assert_eq!(app.search.as_deref(), Some("b"), "a visible row keeps the search");
+        assert_eq!(app.table_state.selected(), Some(0));
+
+        app.select_server("new");
+        assert_eq!(app.search, None);
+        assert_eq!(app.table_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_marked_servers_outrank_selection() {
+        let server = |name: &str, tags: &[&str]| crate::config::Server {
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        let config = Config {
+            servers: vec![server("a", &["prod"]), server("b", &[]), server("c", &["prod"])],
+            ..Default::default()
+        };
+        let mut app = AppState::new(
+            config,
+            std::path::PathBuf::new(),
+            std::collections::HashMap::new(),
+            false,
+        );
+        app.table_state.select(Some(1));
+        assert_eq!(app.marked_or_selected(), ["b"]);
+
+        app.marked.insert("c".to_string());
+        app.marked.insert("a".to_string());
+        assert_eq!(app.marked_or_selected(), ["a", "c"]);
+        // Marks hidden by a filter still count.
+        app.set_search(Some("b".to_string()));
+        assert_eq!(app.marked_or_selected(), ["a", "c"]);
+    }
+
+    #[test]
+    fn test_edit_wizard_prefills_and_starts_after_name() {
+        let server = crate::config::Server {
+            name: "prod".to_string(),
+            address: "10.0.0.1".to_string(),
+            identity_file: Some("~/.ssh/id_ed25519".to_string()),
+            tags: vec!["homelab".to_string()],
+            ..Default::default()
+        };
+        let ws = crate::tui::app::WizardState::for_server(&server);
+        assert!(ws.step == crate::tui::app::WizardStep::Address);
+        assert!(ws.prev_step().is_none());
+        assert!(ws.auth_method == crate::tui::app::AuthMethod::IdentityFile);
+        assert_eq!(ws.identity_file_input, "~/.ssh/id_ed25519");
+        assert_eq!(ws.editing.as_ref().map(|s| s.tags.clone()), Some(server.tags));
+        assert!(crate::tui::app::WizardState::default().prev_step().is_none());
+    }
+
+    #[test]
+    fn test_classify_watch_events_separates_state_config_and_kubeconfigs() {
+        let paths = [
+            Path::new("/data/kcu/state.json"),
+            Path::new("/home/u/.kube/alpha"),
+            Path::new("/home/u/.kube/alpha"),
+            Path::new("/home/u/.kube/beta"),
+            Path::new("/elsewhere/gamma"),
+            Path::new("/home/u/.config/kcu/config.toml"),
+        ];
+        let changes = classify_watch_events(
+            paths.into_iter(),
+            Path::new("/data/kcu/state.json"),
+            Some(Path::new("/home/u/.config/kcu/config.toml")),
+            Some(Path::new("/home/u/.kube")),
+        );
+        assert!(changes.state);
+        assert!(changes.config);
+        assert_eq!(
+            changes.kubeconfigs.into_iter().collect::<Vec<_>>(),
+            vec!["alpha", "beta"]
+        );
+    }
+
+    #[test]
+    fn test_classify_watch_events_without_output_dir() {
+        let changes = classify_watch_events(
+            [
+                Path::new("/home/u/.kube/alpha"),
+                Path::new("/home/u/.config/kcu/servers.toml"),
+            ]
+            .into_iter(),
+            Path::new("/data/kcu/state.json"),
+            Some(Path::new("/home/u/.config/kcu/config.toml")),
+            None,
+        );
+        assert!(!changes.state);
+        assert!(!changes.config);
+        assert!(changes.kubeconfigs.is_empty());
+    }
+
+    #[test]
+    fn test_config_changed_on_disk_is_reloaded_unless_broken() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "local_output_dir = \"/tmp\"\n").unwrap();
+        let config = crate::config::load_config(&file.path().to_string_lossy()).unwrap();
+        let mut app = AppState::new(
+            config,
+            file.path().to_path_buf(),
+            std::collections::HashMap::new(),
+            false,
+        );
+
+        // Unchanged, e.g. the TUI's own save: nothing to say.
+        features::config_reload::on_changed(&mut app);
+        assert!(app.notification.is_none());
+
+        std::fs::write(
+            file.path(),
+            "local_output_dir = \"/tmp\"\nmax_parallel = 3\n\n[[server]]\nname = \"beta\"\naddress = \"10.0.0.2\"\n",
+        )
+        .unwrap();
+        features::config_reload::on_changed(&mut app);
+        assert_eq!(app.config.max_parallel(), 3);
+        assert_eq!(app.config.servers.len(), 1);
+        let (msg, _) = app.notification.take().unwrap();
+        assert!(msg.contains("1 server(s) added"), "{}", msg);
+
+        std::fs::write(file.path(), "max_parallel = [").unwrap();
+        features::config_reload::on_changed(&mut app);
+        assert_eq!(app.config.max_parallel(), 3, "a broken file keeps the loaded config");
+        let (msg, _) = app.notification.take().unwrap();
+        assert!(msg.contains("not reloaded"), "{}", msg);
+
+        std::fs::write(file.path(), "local_output_dir = \"/tmp\"\n\n[keys]\nfetch = \"q\"\n").unwrap();
+        features::config_reload::on_changed(&mut app);
+        assert_eq!(app.config.max_parallel(), 3, "clashing keys keep the loaded config");
+        assert!(app.notification.unwrap().0.contains("[keys]"));
+    }
+}
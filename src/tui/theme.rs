@@ -0,0 +1,188 @@
+//! TUI colors: a preset from the `[theme]` config section, with single colors
+//! overridden by name.
+//!
+//! Colors are roles rather than hues: `ok`, `warning` and `error` color both run
+//! statuses and cert expiry, `accent` covers in-progress rows, notifications and
+//! filters. `monochrome` turns color off as `NO_COLOR` does and leaves bold, dim
+//! and underline to tell things apart.
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::color::ColorDepth;
+
+/// The `[theme]` section of config.toml.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Colors to start from; `default` unless set.
+    pub preset: Option<Preset>,
+    /// Fetched servers, certs well ahead of expiry, reachable ports.
+    pub ok: Option<String>,
+    /// Missing credentials, certs within `cert_warning_days`, dry-run.
+    pub warning: Option<String>,
+    /// Failures, expired certs and errors.
+    pub error: Option<String>,
+    /// Fetches in progress, notifications, the tag and search filters.
+    pub accent: Option<String>,
+    /// Skipped servers and secondary text.
+    pub muted: Option<String>,
+    /// The marker of servers marked with Space.
+    pub mark: Option<String>,
+    /// Background of the selected row.
+    pub selection: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    #[default]
+    Default,
+    /// Bright colors, and black on yellow for the selected row.
+    HighContrast,
+    /// No color at all.
+    Monochrome,
+}
+
+/// Resolved colors for the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub ok: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub accent: Color,
+    pub muted: Color,
+    pub mark: Color,
+    pub selection: Color,
+    /// Text of the selected row, chosen to stay readable on `selection`.
+    pub selection_text: Color,
+    /// False for the `monochrome` preset.
+    pub color: bool,
+}
+
+impl Theme {
+    pub fn preset(preset: Preset, depth: ColorDepth) -> Theme {
+        match preset {
+            Preset::Default | Preset::Monochrome => Theme {
+                ok: Color::Green,
+                warning: Color::Yellow,
+                error: Color::Red,
+                accent: Color::Cyan,
+                muted: Color::DarkGray,
+                mark: Color::Magenta,
+                selection: crate::tui::features::shade(depth, 24, Color::Blue),
+                selection_text: Color::White,
+                color: preset != Preset::Monochrome,
+            },
+            Preset::HighContrast => Theme {
+                ok: Color::LightGreen,
+                warning: Color::LightYellow,
+                error: Color::LightRed,
+                accent: Color::LightCyan,
+                muted: Color::Gray,
+                mark: Color::LightMagenta,
+                selection: Color::Yellow,
+                selection_text: Color::Black,
+                color: true,
+            },
+        }
+    }
+
+    /// The configured preset with its overrides applied. Colors that don't parse are
+    /// ignored with a warning; `validate` reports them.
+    pub fn from_config(config: Option<&ThemeConfig>, depth: ColorDepth) -> Theme {
+        let Some(config) = config else {
+            return Theme::preset(Preset::Default, depth);
+        };
+        let mut theme = Theme::preset(config.preset.unwrap_or_default(), depth);
+        for (field, name, slot) in [
+            ("ok", &config.ok, &mut theme.ok),
+            ("warning", &config.warning, &mut theme.warning),
+            ("error", &config.error, &mut theme.error),
+            ("accent", &config.accent, &mut theme.accent),
+            ("muted", &config.muted, &mut theme.muted),
+            ("mark", &config.mark, &mut theme.mark),
+            ("selection", &config.selection, &mut theme.selection),
+        ] {
+            let Some(name) = name else { continue };
+            match parse_color(name) {
+                Ok(color) => *slot = color,
+                Err(e) => log::warn!("theme.{}: {}", field, e),
+            }
+        }
+        theme
+    }
+}
+
+/// Problems with the colors of a `[theme]` section, one message each.
+pub fn check(config: &ThemeConfig) -> Vec<String> {
+    [
+        ("ok", &config.ok),
+        ("warning", &config.warning),
+        ("error", &config.error),
+        ("accent", &config.accent),
+        ("muted", &config.muted),
+        ("mark", &config.mark),
+        ("selection", &config.selection),
+    ]
+    .into_iter()
+    .filter_map(|(field, name)| {
+        let e = parse_color(name.as_deref()?).err()?;
+        Some(format!("theme.{}: {}", field, e))
+    })
+    .collect()
+}
+
+/// A color name (`red`, `light-blue`, `dark-gray`), a 256-color index (`208`) or
+/// `#rrggbb`.
+fn parse_color(name: &str) -> Result<Color, String> {
+    name.trim().parse::<Color>().map_err(|_| {
+        format!(
+            "'{}' is not a color (use a name like red or light-blue, a number 0-255, or #rrggbb)",
+            name
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_overrides_preset() {
+        let config: ThemeConfig = toml::from_str(
+            r##"
+preset = "high-contrast"
+ok = "blue"
+accent = "#ff8800"
+error = "208"
+mark = "not-a-color"
+"##,
+        )
+        .unwrap();
+        let theme = Theme::from_config(Some(&config), ColorDepth::Ansi256);
+        assert_eq!(theme.ok, Color::Blue);
+        assert_eq!(theme.accent, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.error, Color::Indexed(208));
+        assert_eq!(theme.warning, Color::LightYellow, "unset colors come from the preset");
+        assert_eq!(theme.mark, Color::LightMagenta, "a bad color falls back to the preset");
+        assert!(theme.color);
+
+        assert_eq!(
+            check(&config),
+            [
+                "theme.mark: 'not-a-color' is not a color (use a name like red or light-blue, a number 0-255, or #rrggbb)"
+            ]
+        );
+
+        let default = Theme::from_config(None, ColorDepth::Ansi256);
+        assert_eq!(default.selection, Color::Indexed(24));
+        assert_eq!(Theme::from_config(None, ColorDepth::Ansi16).selection, Color::Blue);
+
+        let mono = ThemeConfig {
+            preset: Some(Preset::Monochrome),
+            ..Default::default()
+        };
+        assert!(!Theme::from_config(Some(&mono), ColorDepth::TrueColor).color);
+    }
+}
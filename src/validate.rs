@@ -0,0 +1,410 @@
+//! `validate` subcommand: checks a parsed config for problems that only show up at
+//! fetch time, such as a missing identity file or a malformed cluster IP.
+
+use crate::config::{Config, Server, Source};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Returns one message per problem found; an empty list means the config is usable.
+/// Unknown keys and type errors are already rejected when the config is loaded.
+pub fn check(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if config.local_output_dir.is_empty() {
+        problems.push("local_output_dir is empty".to_string());
+    } else {
+        let dir = Path::new(&config.local_output_dir);
+        if dir.exists() && !dir.is_dir() {
+            problems.push(format!("local_output_dir '{}' is not a directory", dir.display()));
+        }
+    }
+    if let Some(path) = &config.default_identity_file {
+        check_file(&mut problems, "default_identity_file", path);
+    }
+    if let Some(bitwarden) = &config.bitwarden
+        && let Some(path) = &bitwarden.password_file
+    {
+        check_file(&mut problems, "bitwarden.password_file", &path.to_string_lossy());
+    }
+    if let Some(notifications) = &config.notifications {
+        for webhook in &notifications.webhooks {
+            if !webhook.url.starts_with("https://") && !webhook.url.starts_with("http://") {
+                problems.push(format!("webhook url '{}' is not an http(s) URL", webhook.url));
+            }
+        }
+    }
+
+    if let Some(theme) = &config.theme {
+        problems.extend(crate::tui::theme::check(theme));
+    }
+    if let Some(keys) = &config.keys {
+        problems.extend(crate::tui::keys::check(keys));
+    }
+
+    if let Some(template) = &config.local_file_name {
+        if !template.contains("{name}") {
+            problems.push(format!(
+                "local_file_name '{}' has no {{name}}, so every server would write the same file",
+                template
+            ));
+        }
+        check_local_file_name(&mut problems, "local_file_name", template);
+    }
+
+    match config.credential_backend() {
+        crate::credentials::Backend::Command if config.credential_command.is_none() => {
+            problems.push("credential_backend is \"command\" but credential_command is not set".to_string());
+        }
+        crate::credentials::Backend::File if cfg!(target_os = "macos") => {
+            problems.push("credential_backend \"file\" is not available on macOS; the Keychain is used".to_string());
+        }
+        _ => {}
+    }
+    if config.credential_command.is_some() && config.credential_backend() != crate::credentials::Backend::Command {
+        problems.push("credential_command is set but credential_backend is not \"command\"".to_string());
+    }
+
+    let (warning, critical) = config.cert_thresholds();
+    if critical > warning {
+        problems.push(format!(
+            "cert_critical_days ({}) is larger than cert_warning_days ({})",
+            critical, warning
+        ));
+    }
+
+    let mut names = HashSet::new();
+    let mut local_files = std::collections::HashMap::new();
+    for server in &config.servers {
+        if server.name.is_empty() {
+            problems.push(format!("a server with address '{}' has no name", server.address));
+        } else if !names.insert(server.name.as_str()) {
+            problems.push(format!("server name '{}' is used more than once", server.name));
+        } else if let Some(other) = local_files.insert(server.local_file_name(config), server.name.as_str()) {
+            problems.push(format!(
+                "[{}] local file '{}' is also written by '{}'",
+                server.name,
+                server.local_file_name(config),
+                other
+            ));
+        }
+        check_server(&mut problems, config, server);
+    }
+    problems
+}
+
+/// A local file name has to stay inside `local_output_dir` and clear of the
+/// dot-directories kept there, such as `.history`.
+fn check_local_file_name(problems: &mut Vec<String>, field: &str, name: &str) {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        problems.push(format!(
+            "{} '{}' must be a plain file name that doesn't start with '.'",
+            field, name
+        ));
+    }
+}
+
+fn check_server(problems: &mut Vec<String>, config: &Config, server: &Server) {
+    let mut problem = |message: String| problems.push(format!("[{}] {}", server.name, message));
+
+    if !server.target_cluster_ip.is_empty() && !is_host(&server.target_cluster_ip) {
+        problem(format!(
+            "target_cluster_ip '{}' is not an IP address or hostname",
+            server.target_cluster_ip
+        ));
+    }
+    if let Some(url) = &server.target_server_url
+        && crate::rancher::host_port(url).is_none()
+    {
+        problem(format!("target_server_url '{}' is not a URL (https://host:port)", url));
+    }
+    if server.target_cluster_port == Some(0) {
+        problem("target_cluster_port can't be 0".to_string());
+    }
+    if let Some(schedule) = &server.schedule {
+        if let Err(e) = crate::daemon::Schedule::for_server(server, config) {
+            problem(format!("schedule '{}': {}", schedule, e));
+        } else if server.interval_minutes.is_some() {
+            problem("schedule and interval_minutes are both set; schedule is used".to_string());
+        }
+    }
+    if let Some(local) = &server.local_file_name {
+        let mut local_problems = Vec::new();
+        check_local_file_name(&mut local_problems, "local_file_name", local);
+        local_problems.into_iter().for_each(&mut problem);
+    }
+    match server.source() {
+        Source::Ssh => {
+            if server.address.is_empty() {
+                problem("address is required".to_string());
+            } else if !is_host(&server.address) {
+                problem(format!("address '{}' is not an IP address or hostname", server.address));
+            }
+            if server.target_cluster_ip.is_empty() && server.target_server_url.is_none() {
+                problem("target_cluster_ip or target_server_url is required".to_string());
+            }
+            if server.user(config).is_err() {
+                problem("no user: set user or default_user".to_string());
+            }
+            if let Err(e) = server.file_path(config) {
+                problem(
+                    e.to_string()
+                        .trim_start_matches(&format!("[{}] ", server.name))
+                        .to_string(),
+                );
+            }
+            if let Some(template) = server.escalation_command(config)
+                && !template.contains("{command}")
+            {
+                problem(format!(
+                    "escalation_command '{}' has no {{command}} placeholder",
+                    template
+                ));
+            }
+            if server.transport(config) == crate::ssh::Transport::Openssh
+                && server.transfer(config) == crate::ssh::Transfer::Sftp
+            {
+                problem(
+                    "transfer \"sftp\" isn't available with transport \"openssh\", which reads with cat".to_string(),
+                );
+            }
+            if let Some(path) = &server.identity_file {
+                let mut file_problems = Vec::new();
+                check_file(&mut file_problems, "identity_file", path);
+                file_problems.into_iter().for_each(&mut problem);
+            }
+        }
+        Source::Rancher => {
+            if crate::rancher::host_port(&server.address).is_none() {
+                problem(format!(
+                    "address '{}' is not a Rancher URL (https://...)",
+                    server.address
+                ));
+            }
+            if server.rancher_cluster_id.is_none() {
+                problem("rancher_cluster_id is required".to_string());
+            }
+        }
+        Source::K3sToken => {
+            if server.address.is_empty() {
+                problem("address is required".to_string());
+            }
+        }
+        source @ (Source::Eks | Source::Gke | Source::Aks) => {
+            let cloud = server.cloud.clone().unwrap_or_default();
+            let required: &[(&str, &Option<String>)] = match source {
+                Source::Eks => &[("region", &cloud.region)],
+                Source::Gke => &[("region", &cloud.region), ("project", &cloud.project)],
+                _ => &[("resource_group", &cloud.resource_group)],
+            };
+            for (field, value) in required {
+                if value.is_none() {
+                    problem(format!("cloud.{} is required for {:?}", field, source));
+                }
+            }
+        }
+    }
+}
+
+/// An IP address, or a DNS name of letter/digit/hyphen labels. A name whose last label
+/// is all digits is a mistyped IP (`10.0.0.300`), not a hostname.
+pub(crate) fn is_host(host: &str) -> bool {
+    if bare_host(host).parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    let labels: Vec<&str> = host.strip_suffix('.').unwrap_or(host).split('.').collect();
+    host.len() <= 253
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+        && labels
+            .last()
+            .is_some_and(|last| !last.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// `host` without the brackets of a URL-style IPv6 literal (`[fd00::1]`).
+pub(crate) fn bare_host(host: &str) -> &str {
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host)
+}
+
+/// Reports `path` if it doesn't exist or isn't a readable file.
+fn check_file(problems: &mut Vec<String>, field: &str, path: &str) {
+    match std::fs::File::open(path) {
+        Ok(file) if file.metadata().is_ok_and(|m| m.is_file()) => {}
+        Ok(_) => problems.push(format!("{} '{}' is not a file", field, path)),
+        Err(e) => problems.push(format!("{} '{}' can't be read: {}", field, path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_each_problem() {
+        let server = |name: &str, ip: &str| Server {
+            name: name.to_string(),
+            address: "host".to_string(),
+            target_cluster_ip: ip.to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            local_output_dir: "/tmp".to_string(),
+            default_user: Some("ubuntu".to_string()),
+            servers: vec![
+                server("ok", "10.0.0.1"),
+                server("bad-ip", "10.0.0.300"),
+                Server {
+                    identity_file: Some("/nonexistent/id_ed25519".to_string()),
+                    ..server("no-key", "10.0.0.2")
+                },
+                server("ok", "10.0.0.3"),
+                Server {
+                    source: Some(Source::Gke),
+                    ..server("gke", "")
+                },
+            ],
+            ..Default::default()
+        };
+        let problems = check(&config);
+        assert_eq!(problems.len(), 5, "{:#?}", problems);
+        assert!(problems[0].starts_with("[bad-ip] target_cluster_ip '10.0.0.300' is not an IP address or hostname"));
+        assert!(problems[1].starts_with("[no-key] identity_file '/nonexistent/id_ed25519' can't be read"));
+        assert_eq!(problems[2], "server name 'ok' is used more than once");
+        assert_eq!(problems[3], "[gke] cloud.region is required for Gke");
+        assert_eq!(problems[4], "[gke] cloud.project is required for Gke");
+    }
+
+    #[test]
+    fn test_check_schedules() {
+        let server = |name: &str, schedule: &str| Server {
+            name: name.to_string(),
+            address: "host".to_string(),
+            target_cluster_ip: "10.0.0.1".to_string(),
+            schedule: Some(schedule.to_string()),
+            ..Default::default()
+        };
+        let config = Config {
+            local_output_dir: "/tmp".to_string(),
+            default_user: Some("ubuntu".to_string()),
+            servers: vec![
+                server("cron", "0 6 * * mon-fri"),
+                server("every", "6h"),
+                server("typo", "0 6 * *"),
+                Server {
+                    interval_minutes: Some(30),
+                    ..server("both", "30m")
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&config),
+            [
+                "[typo] schedule '0 6 * *': expected 5 fields (minute hour day-of-month month day-of-week), got 4",
+                "[both] schedule and interval_minutes are both set; schedule is used",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_openssh_transfer() {
+        use crate::ssh::{Transfer, Transport};
+        let server = |name: &str, transport, transfer| Server {
+            name: name.to_string(),
+            address: "host".to_string(),
+            target_cluster_ip: "10.0.0.1".to_string(),
+            transport: Some(transport),
+            transfer: Some(transfer),
+            ..Default::default()
+        };
+        let config = Config {
+            local_output_dir: "/tmp".to_string(),
+            default_user: Some("ubuntu".to_string()),
+            servers: vec![
+                server("sftp", Transport::Libssh2, Transfer::Sftp),
+                server("ssh", Transport::Openssh, Transfer::Exec),
+                server("both", Transport::Openssh, Transfer::Sftp),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&config),
+            ["[both] transfer \"sftp\" isn't available with transport \"openssh\", which reads with cat"]
+        );
+    }
+
+    #[test]
+    fn test_check_credential_backend() {
+        let config = |backend, command: Option<&str>| Config {
+            local_output_dir: "/tmp".to_string(),
+            credential_backend: Some(backend),
+            credential_command: command.map(str::to_string),
+            ..Default::default()
+        };
+        use crate::credentials::Backend;
+        assert!(check(&config(Backend::Command, Some("pass-helper"))).is_empty());
+        assert_eq!(
+            check(&config(Backend::Command, None)),
+            ["credential_backend is \"command\" but credential_command is not set"]
+        );
+        assert_eq!(
+            check(&config(Backend::Keyring, Some("pass-helper"))),
+            ["credential_command is set but credential_backend is not \"command\""]
+        );
+    }
+
+    #[test]
+    fn test_check_local_file_names() {
+        let server = |name: &str, local: Option<&str>| Server {
+            name: name.to_string(),
+            source: Some(Source::K3sToken),
+            address: "host".to_string(),
+            local_file_name: local.map(str::to_string),
+            ..Default::default()
+        };
+        let config = Config {
+            local_output_dir: "/tmp".to_string(),
+            local_file_name: Some("{name}.conf".to_string()),
+            servers: vec![
+                server("a", None),
+                server("b", Some("a.conf")),
+                server("c", Some("../c.yaml")),
+            ],
+            ..Default::default()
+        };
+        let problems = check(&config);
+        assert_eq!(
+            problems,
+            [
+                "[b] local file 'a.conf' is also written by 'a'",
+                "[c] local_file_name '../c.yaml' must be a plain file name that doesn't start with '.'",
+            ]
+        );
+
+        let config = Config {
+            local_output_dir: "/tmp".to_string(),
+            local_file_name: Some("kubeconfig.yaml".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(check(&config).len(), 1);
+    }
+
+    #[test]
+    fn test_is_host() {
+        assert!(is_host("10.0.0.1"));
+        assert!(is_host("fd00::1"));
+        assert!(is_host("[fd00::1]"));
+        assert_eq!(bare_host("[fd00::1]"), "fd00::1");
+        assert_eq!(bare_host("10.0.0.1"), "10.0.0.1");
+        assert!(is_host("k3s.internal.lan"));
+        assert!(is_host("node-1"));
+        assert!(!is_host("10.0.0.300"));
+        assert!(!is_host("bad_name.lan"));
+        assert!(!is_host("-node.lan"));
+        assert!(!is_host("a..b"));
+    }
+}